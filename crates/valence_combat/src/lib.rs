@@ -0,0 +1,694 @@
+#![doc = include_str!("../README.md")]
+
+pub mod weapon;
+
+use std::collections::HashSet;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use valence_health::{DamageAppliedEvent, DamageEvent, DamageSet, DamageSource, Dead, FireTicks};
+use valence_inventory::durability::DamageItemEvent;
+use valence_inventory::{HeldItem, Inventory, UpdateInventoriesSet, UpdateSelectedSlotEvent};
+use valence_server::client::{Client, SpawnClientsSet, VisibleChunkLayer, VisibleEntityLayers};
+use valence_server::enchantment;
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::Flags;
+use valence_server::entity::hitbox::Hitbox;
+use valence_server::entity::living::Health;
+use valence_server::entity::{
+    EntityAnimation, EntityAnimations, EntityKind, EntityLayerId, Look, OnGround, Position,
+    Velocity,
+};
+use valence_server::interact_entity::{EntityInteraction, InteractEntityEvent};
+use valence_server::layer::UpdateLayersPreClientSet;
+use valence_server::math::{Aabb, DVec3, Vec3};
+use valence_server::movement::MovementEvent;
+use valence_server::protocol::packets::play::particle_s2c::Particle;
+use valence_server::protocol::sound::SoundCategory;
+use valence_server::protocol::Sound;
+use valence_server::{ident, ChunkLayer, EventLoopUpdate, GameMode, ItemStack, Server};
+use weapon::WeaponStats;
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CombatSettings>()
+            .init_resource::<PendingAttacks>()
+            .init_resource::<PendingHits>()
+            .add_event::<AttackEvent>()
+            .add_event::<CancelAttackEvent>()
+            .add_systems(PreUpdate, init_new_clients.after(SpawnClientsSet))
+            .add_systems(
+                EventLoopUpdate,
+                (
+                    track_fall_distance,
+                    reset_cooldown_on_slot_change,
+                    handle_attacks,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    apply_attacks.before(DamageSet),
+                    apply_hit_effects
+                        .after(DamageSet)
+                        .before(UpdateLayersPreClientSet)
+                        .before(UpdateInventoriesSet),
+                ),
+            );
+    }
+}
+
+/// Global configuration for melee combat.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct CombatSettings {
+    /// Whether attacks are weakened until the attack cooldown has recharged.
+    /// When disabled, every attack deals full damage.
+    pub attack_cooldown: bool,
+    /// Whether attacks made while falling deal 50% extra damage.
+    pub critical_hits: bool,
+    /// Whether fully charged attacks with a sword also hit entities next to
+    /// the target.
+    pub sweep_attacks: bool,
+    /// The maximum distance from the eyes of an attacker to the hitbox of
+    /// the target. Attacks from further away are ignored. This is a bit
+    /// larger than the reach of the vanilla client to account for latency.
+    pub max_reach: f64,
+    /// Like [`CombatSettings::max_reach`], but for attackers in creative mode.
+    pub creative_max_reach: f64,
+    /// Whether weapons and tools lose durability when they hurt an entity.
+    pub damage_weapons: bool,
+}
+
+impl CombatSettings {
+    /// Settings resembling combat before Minecraft 1.9, without an attack
+    /// cooldown or sweep attacks.
+    pub fn legacy() -> Self {
+        Self {
+            attack_cooldown: false,
+            sweep_attacks: false,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for CombatSettings {
+    fn default() -> Self {
+        Self {
+            attack_cooldown: true,
+            critical_hits: true,
+            sweep_attacks: true,
+            max_reach: 4.0,
+            creative_max_reach: 6.0,
+            damage_weapons: true,
+        }
+    }
+}
+
+/// The combat state of a client.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct CombatState {
+    /// The server tick at which the attack cooldown was last reset.
+    pub last_attack_tick: i64,
+    /// The distance the client has fallen since it last touched the ground.
+    pub fall_distance: f64,
+}
+
+impl CombatState {
+    /// Returns how far the attack cooldown has recharged, from `0.0` to `1.0`.
+    pub fn cooldown_progress(&self, current_tick: i64, attack_speed: f64) -> f32 {
+        let ticks_per_attack = 20.0 / attack_speed;
+        let elapsed = (current_tick - self.last_attack_tick) as f64 + 0.5;
+
+        (elapsed / ticks_per_attack).clamp(0.0, 1.0) as f32
+    }
+}
+
+/// Sent when a client lands a valid melee attack on an entity. Every entity
+/// hit by a sweep attack gets its own event.
+///
+/// The damage, knockback and sounds of the attack are applied in
+/// [`PostUpdate`], unless the attack is cancelled with a [`CancelAttackEvent`]
+/// before then.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct AttackEvent {
+    pub attacker: Entity,
+    pub target: Entity,
+    /// The damage dealt to the target before armor and other reductions.
+    pub damage: f32,
+    /// How far the attack cooldown had recharged, from `0.0` to `1.0`.
+    pub cooldown_progress: f32,
+    pub critical: bool,
+    /// Whether this was a sprint attack dealing extra knockback.
+    pub sprint: bool,
+    /// Whether this attack is part of a sweep attack, which also hits
+    /// entities next to the entity that was attacked.
+    pub sweep: bool,
+}
+
+/// Prevents an attack of the current tick from dealing damage. Cancelling the
+/// attack on the entity the client attacked also cancels the sweep attack on
+/// the entities next to it.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use valence_combat::{AttackEvent, CancelAttackEvent};
+/// #[derive(Component, PartialEq)]
+/// struct Team(u8);
+///
+/// fn no_friendly_fire(
+///     mut attacks: EventReader<AttackEvent>,
+///     mut cancel: EventWriter<CancelAttackEvent>,
+///     teams: Query<&Team>,
+/// ) {
+///     for attack in attacks.read() {
+///         if let Ok([a, b]) = teams.get_many([attack.attacker, attack.target]) {
+///             if a == b {
+///                 cancel.send(CancelAttackEvent {
+///                     attacker: attack.attacker,
+///                     target: attack.target,
+///                 });
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CancelAttackEvent {
+    pub attacker: Entity,
+    pub target: Entity,
+}
+
+/// Redirects attacks on this entity to another living entity. This is meant
+/// for entities standing in for another entity, such as copies of a player
+/// shown to some clients in a different entity layer.
+///
+/// Attacks on a proxy are validated against the position of the proxy, which
+/// must be in one of the attacker's [`VisibleEntityLayers`].
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CombatProxy(pub Entity);
+
+/// Attacks of the current tick which have not been cancelled yet.
+#[derive(Resource, Default)]
+struct PendingAttacks(Vec<PendingAttack>);
+
+struct PendingAttack {
+    /// The target of the attack this hit is part of. Differs from the target
+    /// of the hit for entities hit by a sweep attack.
+    primary_target: Entity,
+    damage: f32,
+    hit: PendingHit,
+    sound: Option<AttackSound>,
+}
+
+/// The sound and particles of an attack, played once for every attack.
+struct AttackSound {
+    chunk_layer: Entity,
+    sound: Sound,
+    pos: DVec3,
+    /// Where to show the sweep particle, if this was a sweep attack.
+    sweep_particle: Option<DVec3>,
+}
+
+/// Knockback and effects to apply once the damage of an attack has been
+/// applied.
+#[derive(Resource, Default)]
+struct PendingHits(Vec<PendingHit>);
+
+struct PendingHit {
+    attacker: Entity,
+    target: Entity,
+    /// Where the attacker was standing.
+    origin: DVec3,
+    /// The yaw of the attacker, in degrees.
+    yaw: f32,
+    /// Extra knockback strength along the attacker's yaw.
+    knockback: f64,
+    /// The number of ticks to set the target on fire for.
+    fire_ticks: u32,
+    /// The slot of the weapon and the durability it loses if the hit lands.
+    weapon_damage: Option<(u16, u32)>,
+    critical: bool,
+    enchanted: bool,
+}
+
+/// The base knockback strength of every hit.
+const BASE_KNOCKBACK: f64 = 0.4;
+
+fn init_new_clients(
+    clients: Query<Entity, Added<Client>>,
+    mut commands: Commands,
+    server: Res<Server>,
+) {
+    for entity in &clients {
+        commands.entity(entity).insert(CombatState {
+            last_attack_tick: server.current_tick(),
+            ..Default::default()
+        });
+    }
+}
+
+fn track_fall_distance(
+    mut events: EventReader<MovementEvent>,
+    mut clients: Query<&mut CombatState>,
+) {
+    for event in events.read() {
+        let Ok(mut state) = clients.get_mut(event.client) else {
+            continue;
+        };
+
+        if event.on_ground {
+            state.fall_distance = 0.0;
+        } else if event.position.y < event.old_position.y {
+            state.fall_distance += event.old_position.y - event.position.y;
+        }
+    }
+}
+
+fn reset_cooldown_on_slot_change(
+    mut events: EventReader<UpdateSelectedSlotEvent>,
+    mut clients: Query<&mut CombatState>,
+    server: Res<Server>,
+) {
+    for event in events.read() {
+        if let Ok(mut state) = clients.get_mut(event.client) {
+            state.last_attack_tick = server.current_tick();
+        }
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct AttackerQuery {
+    position: &'static Position,
+    look: &'static Look,
+    flags: &'static Flags,
+    on_ground: &'static OnGround,
+    game_mode: &'static GameMode,
+    layer_id: &'static EntityLayerId,
+    chunk_layer: &'static VisibleChunkLayer,
+    visible_layers: &'static VisibleEntityLayers,
+    attributes: Option<&'static EntityAttributes>,
+    inventory: Option<&'static Inventory>,
+    held_item: Option<&'static HeldItem>,
+    state: &'static mut CombatState,
+    is_dead: Has<Dead>,
+}
+
+#[derive(QueryData)]
+struct TargetQuery {
+    entity: Entity,
+    position: &'static Position,
+    kind: &'static EntityKind,
+    layer_id: &'static EntityLayerId,
+    hitbox: Option<&'static Hitbox>,
+    proxy: Option<&'static CombatProxy>,
+    is_dead: Has<Dead>,
+}
+
+impl TargetQueryItem<'_> {
+    fn hitbox(&self) -> Aabb {
+        self.hitbox
+            .map_or_else(|| Aabb::new_point(self.position.0), |hitbox| hitbox.get())
+    }
+}
+
+fn handle_attacks(
+    mut events: EventReader<InteractEntityEvent>,
+    mut attackers: Query<AttackerQuery>,
+    targets: Query<TargetQuery, Or<(With<Health>, With<CombatProxy>)>>,
+    mut attack_events: EventWriter<AttackEvent>,
+    mut pending: ResMut<PendingAttacks>,
+    settings: Res<CombatSettings>,
+    server: Res<Server>,
+) {
+    for event in events.read() {
+        if event.interact != EntityInteraction::Attack || event.client == event.entity {
+            continue;
+        }
+
+        let Ok(mut attacker) = attackers.get_mut(event.client) else {
+            continue;
+        };
+
+        let Ok(clicked) = targets.get(event.entity) else {
+            continue;
+        };
+
+        // Proxies are in a different layer than the entity they stand in for, so
+        // they only need to be visible to the attacker.
+        let in_layer = if clicked.proxy.is_some() {
+            attacker.visible_layers.0.contains(&clicked.layer_id.0)
+        } else {
+            attacker.layer_id.0 == clicked.layer_id.0
+        };
+        let clicked_box = clicked.hitbox();
+
+        let target = match clicked.proxy {
+            Some(proxy) => targets.get(proxy.0),
+            None => Ok(clicked),
+        };
+
+        let Ok(target) = target else {
+            continue;
+        };
+
+        if !in_layer
+            || target.proxy.is_some()
+            || target.entity == event.client
+            || attacker.is_dead
+            || target.is_dead
+            || *attacker.game_mode == GameMode::Spectator
+        {
+            continue;
+        }
+
+        let eye_height = if attacker.flags.sneaking() {
+            1.27
+        } else {
+            1.62
+        };
+        let eye_pos = attacker.position.0 + DVec3::new(0.0, eye_height, 0.0);
+        let max_reach = if *attacker.game_mode == GameMode::Creative {
+            settings.creative_max_reach
+        } else {
+            settings.max_reach
+        };
+
+        if clicked_box.distance_to_point(eye_pos) > max_reach {
+            continue;
+        }
+
+        let weapon = match (attacker.inventory, attacker.held_item) {
+            (Some(inventory), Some(held_item)) => inventory.slot(held_item.slot()).clone(),
+            _ => ItemStack::EMPTY,
+        };
+
+        let stats = WeaponStats::of(weapon.item);
+
+        let attribute = |attr, default| {
+            attacker
+                .attributes
+                .and_then(|attrs| attrs.get_compute_value(attr))
+                .unwrap_or(default)
+        };
+
+        let attack_damage = attribute(EntityAttribute::GenericAttackDamage, 1.0)
+            + stats.map_or(0.0, |s| s.attack_damage);
+        let attack_speed = attribute(EntityAttribute::GenericAttackSpeed, 4.0)
+            + stats.map_or(0.0, |s| s.attack_speed);
+
+        let progress = if settings.attack_cooldown {
+            attacker
+                .state
+                .cooldown_progress(server.current_tick(), attack_speed)
+        } else {
+            1.0
+        };
+
+        attacker.state.last_attack_tick = server.current_tick();
+
+        let mut damage = attack_damage as f32 * (0.2 + progress * progress * 0.8);
+        let enchant_bonus = enchantment::attack_damage_bonus(&weapon, *target.kind) * progress;
+
+        let strong = progress > 0.9;
+        let sprinting = attacker.flags.sprinting();
+        let sprint = strong && sprinting;
+
+        let critical = settings.critical_hits
+            && strong
+            && attacker.state.fall_distance > 0.0
+            && !attacker.on_ground.0
+            && !sprinting;
+
+        if critical {
+            damage *= 1.5;
+        }
+
+        let sweep = settings.sweep_attacks
+            && strong
+            && !critical
+            && !sprint
+            && attacker.on_ground.0
+            && weapon::is_sword(weapon.item);
+
+        let knockback_level = enchantment::knockback_bonus(&weapon) + u32::from(sprint);
+
+        let sound = if sprint {
+            Sound::EntityPlayerAttackKnockback
+        } else if critical {
+            Sound::EntityPlayerAttackCrit
+        } else if sweep {
+            Sound::EntityPlayerAttackSweep
+        } else if strong {
+            Sound::EntityPlayerAttackStrong
+        } else {
+            Sound::EntityPlayerAttackWeak
+        };
+
+        let yaw = attacker.look.yaw.to_radians();
+
+        pending.0.push(PendingAttack {
+            primary_target: target.entity,
+            damage: damage + enchant_bonus,
+            hit: PendingHit {
+                attacker: event.client,
+                target: target.entity,
+                origin: attacker.position.0,
+                yaw: attacker.look.yaw,
+                knockback: f64::from(knockback_level) * 0.5,
+                fire_ticks: enchantment::fire_aspect_ticks(&weapon),
+                weapon_damage: attacker
+                    .held_item
+                    .filter(|_| settings.damage_weapons)
+                    .map(|held_item| (held_item.slot(), weapon::durability_cost(weapon.item))),
+                critical,
+                enchanted: enchant_bonus > 0.0,
+            },
+            sound: Some(AttackSound {
+                chunk_layer: attacker.chunk_layer.0,
+                sound,
+                pos: attacker.position.0,
+                sweep_particle: sweep.then(|| {
+                    attacker.position.0
+                        + DVec3::new(-f64::from(yaw.sin()), 0.9, f64::from(yaw.cos()))
+                }),
+            }),
+        });
+
+        attack_events.send(AttackEvent {
+            attacker: event.client,
+            target: target.entity,
+            damage: damage + enchant_bonus,
+            cooldown_progress: progress,
+            critical,
+            sprint,
+            sweep,
+        });
+
+        if sweep {
+            let sweep_damage = 1.0 + weapon::sweeping_damage_ratio(&weapon) * damage;
+            let target_box = target.hitbox();
+            let sweep_box = Aabb::new(
+                target_box.min() - DVec3::new(1.0, 0.25, 1.0),
+                target_box.max() + DVec3::new(1.0, 0.25, 1.0),
+            );
+
+            for other in &targets {
+                if other.entity == event.client
+                    || other.entity == target.entity
+                    || other.proxy.is_some()
+                    || other.is_dead
+                    || other.layer_id.0 != attacker.layer_id.0
+                    || !other.hitbox().intersects(sweep_box)
+                    || other.position.0.distance_squared(attacker.position.0) >= 9.0
+                {
+                    continue;
+                }
+
+                pending.0.push(PendingAttack {
+                    primary_target: target.entity,
+                    damage: sweep_damage,
+                    hit: PendingHit {
+                        attacker: event.client,
+                        target: other.entity,
+                        origin: attacker.position.0,
+                        yaw: attacker.look.yaw,
+                        knockback: BASE_KNOCKBACK,
+                        fire_ticks: 0,
+                        weapon_damage: None,
+                        critical: false,
+                        enchanted: false,
+                    },
+                    sound: None,
+                });
+
+                attack_events.send(AttackEvent {
+                    attacker: event.client,
+                    target: other.entity,
+                    damage: sweep_damage,
+                    cooldown_progress: progress,
+                    critical: false,
+                    sprint: false,
+                    sweep: true,
+                });
+            }
+        }
+    }
+}
+
+fn apply_attacks(
+    mut cancel_events: EventReader<CancelAttackEvent>,
+    mut attacks: ResMut<PendingAttacks>,
+    mut hits: ResMut<PendingHits>,
+    mut chunk_layers: Query<&mut ChunkLayer>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let cancelled: HashSet<_> = cancel_events
+        .read()
+        .map(|event| (event.attacker, event.target))
+        .collect();
+
+    for attack in attacks.0.drain(..) {
+        let attacker = attack.hit.attacker;
+
+        if cancelled.contains(&(attacker, attack.primary_target))
+            || cancelled.contains(&(attacker, attack.hit.target))
+        {
+            continue;
+        }
+
+        if let Some(sound) = attack.sound {
+            if let Ok(mut layer) = chunk_layers.get_mut(sound.chunk_layer) {
+                layer.play_sound(sound.sound, SoundCategory::Player, sound.pos, 1.0, 1.0);
+
+                if let Some(pos) = sound.sweep_particle {
+                    layer.play_particle(&Particle::SweepAttack, false, pos, Vec3::ZERO, 0.0, 0);
+                }
+            }
+        }
+
+        damage_events.send(DamageEvent {
+            target: attack.hit.target,
+            amount: attack.damage,
+            source: DamageSource::new(ident!("player_attack")).with_attacker(attacker),
+        });
+
+        hits.0.push(attack.hit);
+    }
+}
+
+/// Applies vanilla knockback to a velocity in blocks per tick. `dir_x` and
+/// `dir_z` point from the entity being knocked back towards the source of the
+/// knockback.
+fn knockback(velocity: Vec3, strength: f64, dir_x: f64, dir_z: f64, on_ground: bool) -> Vec3 {
+    let dir = DVec3::new(dir_x, 0.0, dir_z).normalize_or_zero() * strength;
+    let velocity = velocity.as_dvec3();
+
+    DVec3::new(
+        velocity.x / 2.0 - dir.x,
+        if on_ground {
+            (velocity.y / 2.0 + strength).min(0.4)
+        } else {
+            velocity.y
+        },
+        velocity.z / 2.0 - dir.z,
+    )
+    .as_vec3()
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_hit_effects(
+    mut events: EventReader<DamageAppliedEvent>,
+    mut targets: Query<(
+        &Position,
+        &OnGround,
+        &mut Velocity,
+        &mut EntityAnimations,
+        Option<&EntityAttributes>,
+        Option<&mut Client>,
+        Option<&mut FireTicks>,
+    )>,
+    mut pending: ResMut<PendingHits>,
+    mut item_damage_events: EventWriter<DamageItemEvent>,
+) {
+    for event in events.read() {
+        let Some(idx) = pending.0.iter().position(|hit| {
+            hit.target == event.target && event.source.attacker == Some(hit.attacker)
+        }) else {
+            continue;
+        };
+
+        let hit = pending.0.swap_remove(idx);
+
+        if let Some((slot, amount)) = hit.weapon_damage.filter(|&(_, amount)| amount > 0) {
+            item_damage_events.send(DamageItemEvent {
+                client: hit.attacker,
+                slot,
+                amount,
+            });
+        }
+
+        if !event.full_hit {
+            continue;
+        }
+
+        let Ok((pos, on_ground, mut velocity, mut animations, attributes, client, fire)) =
+            targets.get_mut(event.target)
+        else {
+            continue;
+        };
+
+        let resistance = attributes
+            .and_then(|attrs| attrs.get_compute_value(EntityAttribute::GenericKnockbackResistance))
+            .unwrap_or(0.0);
+
+        // Velocity is stored in blocks per second, but knockback is computed in
+        // blocks per tick.
+        let mut new_velocity = velocity.0 / 20.0;
+
+        new_velocity = knockback(
+            new_velocity,
+            BASE_KNOCKBACK * (1.0 - resistance),
+            hit.origin.x - pos.0.x,
+            hit.origin.z - pos.0.z,
+            on_ground.0,
+        );
+
+        if hit.knockback > 0.0 {
+            let yaw = f64::from(hit.yaw.to_radians());
+
+            new_velocity = knockback(
+                new_velocity,
+                hit.knockback * (1.0 - resistance),
+                yaw.sin(),
+                -yaw.cos(),
+                on_ground.0,
+            );
+        }
+
+        let new_velocity = new_velocity * 20.0;
+
+        match client {
+            Some(mut client) => client.set_velocity(new_velocity),
+            None => velocity.0 = new_velocity,
+        }
+
+        if let Some(mut fire) = fire.filter(|_| hit.fire_ticks > 0) {
+            fire.ignite(hit.fire_ticks);
+        }
+
+        if hit.critical {
+            animations.trigger(EntityAnimation::Crit);
+        }
+
+        if hit.enchanted {
+            animations.trigger(EntityAnimation::EnchantedHit);
+        }
+    }
+
+    pending.0.clear();
+}
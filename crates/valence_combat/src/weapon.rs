@@ -0,0 +1,87 @@
+//! Weapon stats and enchantments that affect melee attacks.
+
+use valence_server::enchantment::Enchantment;
+use valence_server::{ItemKind, ItemStack};
+
+/// The attribute modifiers an item grants while held in the main hand.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct WeaponStats {
+    /// Added to the `generic.attack_damage` attribute.
+    pub attack_damage: f64,
+    /// Added to the `generic.attack_speed` attribute.
+    pub attack_speed: f64,
+}
+
+impl WeaponStats {
+    /// Returns the vanilla stats of an item, or `None` if the item is not a
+    /// weapon or tool.
+    pub fn of(item: ItemKind) -> Option<Self> {
+        // The total attack damage and speed as shown in the item tooltip.
+        let (damage, speed) = match item {
+            ItemKind::WoodenSword | ItemKind::GoldenSword => (4.0, 1.6),
+            ItemKind::StoneSword => (5.0, 1.6),
+            ItemKind::IronSword => (6.0, 1.6),
+            ItemKind::DiamondSword => (7.0, 1.6),
+            ItemKind::NetheriteSword => (8.0, 1.6),
+            ItemKind::WoodenAxe => (7.0, 0.8),
+            ItemKind::GoldenAxe => (7.0, 1.0),
+            ItemKind::StoneAxe => (9.0, 0.8),
+            ItemKind::IronAxe => (9.0, 0.9),
+            ItemKind::DiamondAxe => (9.0, 1.0),
+            ItemKind::NetheriteAxe => (10.0, 1.0),
+            ItemKind::WoodenPickaxe | ItemKind::GoldenPickaxe => (2.0, 1.2),
+            ItemKind::StonePickaxe => (3.0, 1.2),
+            ItemKind::IronPickaxe => (4.0, 1.2),
+            ItemKind::DiamondPickaxe => (5.0, 1.2),
+            ItemKind::NetheritePickaxe => (6.0, 1.2),
+            ItemKind::WoodenShovel | ItemKind::GoldenShovel => (2.5, 1.0),
+            ItemKind::StoneShovel => (3.5, 1.0),
+            ItemKind::IronShovel => (4.5, 1.0),
+            ItemKind::DiamondShovel => (5.5, 1.0),
+            ItemKind::NetheriteShovel => (6.5, 1.0),
+            ItemKind::WoodenHoe | ItemKind::GoldenHoe => (1.0, 1.0),
+            ItemKind::StoneHoe => (1.0, 2.0),
+            ItemKind::IronHoe => (1.0, 3.0),
+            ItemKind::DiamondHoe | ItemKind::NetheriteHoe => (1.0, 4.0),
+            ItemKind::Trident => (9.0, 1.1),
+            _ => return None,
+        };
+
+        Some(Self {
+            attack_damage: damage - 1.0,
+            attack_speed: speed - 4.0,
+        })
+    }
+}
+
+/// Returns `true` if the item can perform sweep attacks.
+pub fn is_sword(item: ItemKind) -> bool {
+    matches!(
+        item,
+        ItemKind::WoodenSword
+            | ItemKind::StoneSword
+            | ItemKind::GoldenSword
+            | ItemKind::IronSword
+            | ItemKind::DiamondSword
+            | ItemKind::NetheriteSword
+    )
+}
+
+/// Returns the durability an item loses when it is used to hit an entity.
+pub fn durability_cost(item: ItemKind) -> u32 {
+    if is_sword(item) || item == ItemKind::Trident {
+        1
+    } else if item.mineable_tag().is_some() {
+        2
+    } else {
+        0
+    }
+}
+
+/// Returns the fraction of the attack damage dealt to entities hit by a sweep
+/// attack, determined by the Sweeping Edge enchantment.
+pub fn sweeping_damage_ratio(weapon: &ItemStack) -> f32 {
+    let level = weapon.enchantment_level(Enchantment::Sweeping) as f32;
+
+    level / (level + 1.0)
+}
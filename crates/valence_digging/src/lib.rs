@@ -0,0 +1,510 @@
+#![doc = include_str!("../README.md")]
+
+mod server_mining;
+pub mod tool;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+pub use server_mining::{BlockHardness, ServerMining};
+use tool::Tool;
+use valence_inventory::durability::DamageItemEvent;
+use valence_inventory::player_inventory::PlayerInventory;
+use valence_inventory::{HeldItem, Inventory};
+use valence_server::action::{DiggingEvent, DiggingState};
+use valence_server::block::{BlockKind, PropName, PropValue};
+use valence_server::client::{Client, FlushPacketsSet, UpdateClientsSet, VisibleChunkLayer};
+use valence_server::enchantment::{self, Enchantment};
+use valence_server::entity::active_status_effects::ActiveStatusEffects;
+use valence_server::entity::entity::Flags;
+use valence_server::entity::{EntityId, OnGround, Position};
+use valence_server::layer::UpdateLayersPreClientSet;
+use valence_server::math::DVec3;
+use valence_server::protocol::packets::play::{BlockBreakingProgressS2c, BlockUpdateS2c};
+use valence_server::protocol::status_effects::StatusEffect;
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::registry::TagsRegistry;
+use valence_server::{
+    BlockPos, BlockState, ChunkLayer, EventLoopUpdate, GameMode, ItemStack, Layer, Server,
+};
+
+pub struct DiggingPlugin;
+
+impl Plugin for DiggingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiggingSettings>()
+            .init_resource::<BlockHardness>()
+            .add_event::<BlockMinedEvent>()
+            .add_event::<DiggingViolationEvent>()
+            .add_systems(
+                EventLoopUpdate,
+                (
+                    handle_digging,
+                    server_mining::handle_server_mining,
+                    damage_tools
+                        .after(handle_digging)
+                        .after(server_mining::handle_server_mining),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    broadcast_digging_progress,
+                    server_mining::advance_server_mining,
+                )
+                    .before(UpdateLayersPreClientSet),
+            )
+            .add_systems(
+                PostUpdate,
+                server_mining::hide_client_progress
+                    .after(UpdateClientsSet)
+                    .before(FlushPacketsSet),
+            );
+    }
+}
+
+/// Global configuration for block breaking.
+///
+/// The checks only decide which [`BlockMinedEvent`]s are sent. A
+/// [`DiggingEvent`] is still sent for every action of the client without being
+/// validated, so blocks should only be broken in response to
+/// [`BlockMinedEvent`]s.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct DiggingSettings {
+    /// Reject blocks that are broken faster than the client's tool,
+    /// enchantments, status effects and surroundings allow. When disabled,
+    /// every block the client claims to have broken is mined.
+    pub check_break_speed: bool,
+    /// The fraction of the expected break time a client must have spent
+    /// digging for the block to be mined. This leaves room for latency.
+    pub min_progress: f32,
+    /// Whether the crack animation of blocks being broken is shown to other
+    /// players.
+    pub broadcast_progress: bool,
+    /// Whether the tool a client is holding loses durability for every
+    /// [`BlockMinedEvent`]. Disable this if you don't always break the mined
+    /// blocks.
+    pub damage_tools: bool,
+    /// The maximum distance from the eyes of a client to the center of the
+    /// blocks it digs. Digging further away is a
+    /// [`DiggingViolation::TooFar`].
+    pub max_reach: f64,
+}
+
+impl Default for DiggingSettings {
+    fn default() -> Self {
+        Self {
+            check_break_speed: true,
+            // Same as vanilla.
+            min_progress: 0.7,
+            broadcast_progress: true,
+            damage_tools: true,
+            // Same as vanilla.
+            max_reach: 6.0,
+        }
+    }
+}
+
+/// The block a client is currently breaking. Inserted when the client starts
+/// digging and removed when it stops.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Digging {
+    /// The position of the block being broken.
+    pub position: BlockPos,
+    /// The tick the client started digging on.
+    pub start_tick: i64,
+    /// The fraction of the block broken so far, with [`ServerMining`].
+    progress: f32,
+    /// The last tick a client with [`ServerMining`] started digging the block.
+    last_start_tick: i64,
+    /// The crack stage last shown to other players.
+    stage: Option<u8>,
+}
+
+impl Digging {
+    fn new(position: BlockPos, tick: i64) -> Self {
+        Self {
+            position,
+            start_tick: tick,
+            progress: 0.0,
+            last_start_tick: tick,
+            stage: None,
+        }
+    }
+}
+
+/// Sent when a client breaks a block in a way that passes the checks enabled
+/// in [`DiggingSettings`], or when a client with [`ServerMining`] has dug long
+/// enough. In creative mode, this is sent as soon as the client starts digging.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct BlockMinedEvent {
+    pub client: Entity,
+    pub position: BlockPos,
+    /// The block that was broken.
+    pub block: BlockState,
+}
+
+/// Sent when a client claims to have broken a block it wasn't able to break.
+/// The block is sent to the client again to undo the break on its side.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct DiggingViolationEvent {
+    pub client: Entity,
+    pub position: BlockPos,
+    pub violation: DiggingViolation,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DiggingViolation {
+    /// The client finished breaking the block too quickly.
+    TooFast,
+    /// The client finished breaking a block it never started breaking.
+    NotStarted,
+    /// The block is out of the reach set in [`DiggingSettings::max_reach`].
+    TooFar,
+}
+
+/// Any crack stage outside of `0..=9` removes the animation.
+const NO_STAGE: u8 = 10;
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct DiggerQuery {
+    client: &'static mut Client,
+    entity_id: &'static EntityId,
+    position: &'static Position,
+    on_ground: &'static OnGround,
+    game_mode: &'static GameMode,
+    chunk_layer: &'static VisibleChunkLayer,
+    digging: Option<&'static mut Digging>,
+    flags: Option<&'static Flags>,
+    effects: Option<&'static ActiveStatusEffects>,
+    inventory: Option<&'static Inventory>,
+    held_item: Option<&'static HeldItem>,
+}
+
+impl DiggerQueryItem<'_> {
+    fn held_stack(&self) -> &ItemStack {
+        match (self.inventory, self.held_item) {
+            (Some(inventory), Some(held_item)) => inventory.slot(held_item.slot()),
+            _ => &ItemStack::EMPTY,
+        }
+    }
+
+    fn effect_amplifier(&self, effect: StatusEffect) -> Option<u8> {
+        self.effects?
+            .get_current_effect(effect)
+            .map(|effect| effect.amplifier())
+    }
+
+    fn eye_pos(&self) -> DVec3 {
+        let sneaking = self.flags.is_some_and(|flags| flags.sneaking());
+        let eye_height = if sneaking { 1.27 } else { 1.62 };
+
+        self.position.0 + DVec3::new(0.0, eye_height, 0.0)
+    }
+
+    fn can_reach(&self, pos: BlockPos, max_reach: f64) -> bool {
+        let center = DVec3::new(
+            f64::from(pos.x) + 0.5,
+            f64::from(pos.y) + 0.5,
+            f64::from(pos.z) + 0.5,
+        );
+
+        self.eye_pos().distance_squared(center) <= max_reach * max_reach
+    }
+
+    /// Rejects digging out of reach. The block is sent to the client again
+    /// in case it already broke it on its side.
+    fn check_reach(
+        &mut self,
+        event: &DiggingEvent,
+        block: BlockState,
+        settings: &DiggingSettings,
+        violation_events: &mut EventWriter<DiggingViolationEvent>,
+    ) -> bool {
+        if event.state == DiggingState::Abort || self.can_reach(event.position, settings.max_reach)
+        {
+            return true;
+        }
+
+        self.client.write_packet(&BlockUpdateS2c {
+            position: event.position,
+            block_id: block,
+        });
+
+        violation_events.send(DiggingViolationEvent {
+            client: event.client,
+            position: event.position,
+            violation: DiggingViolation::TooFar,
+        });
+
+        false
+    }
+
+    fn is_in_water(&self, layer: &ChunkLayer) -> bool {
+        let eyes = BlockPos::from(self.eye_pos());
+
+        layer.block(eyes).is_some_and(|block| {
+            block.state.to_kind() == BlockKind::Water
+                || block.state.get(PropName::Waterlogged) == Some(PropValue::True)
+        })
+    }
+
+    /// Returns the fraction of the block the client breaks every tick, the
+    /// same way the vanilla client computes it.
+    fn break_delta(
+        &self,
+        block: BlockState,
+        hardness: f32,
+        layer: &ChunkLayer,
+        tags: &TagsRegistry,
+    ) -> f32 {
+        if hardness < 0.0 {
+            return 0.0;
+        }
+
+        let stack = self.held_stack();
+        let tool = Tool::of(stack.item);
+
+        let mut speed = tool.map_or(1.0, |tool| tool.mining_speed(block, tags));
+
+        if speed > 1.0 {
+            speed += enchantment::dig_speed_bonus(stack);
+        }
+
+        let haste = self
+            .effect_amplifier(StatusEffect::Haste)
+            .max(self.effect_amplifier(StatusEffect::ConduitPower));
+
+        if let Some(amplifier) = haste {
+            speed *= 1.0 + (f32::from(amplifier) + 1.0) * 0.2;
+        }
+
+        if let Some(amplifier) = self.effect_amplifier(StatusEffect::MiningFatigue) {
+            speed *= match amplifier {
+                0 => 0.3,
+                1 => 0.09,
+                2 => 0.0027,
+                _ => 8.1e-4,
+            };
+        }
+
+        if self.is_in_water(layer) {
+            let aqua_affinity = self.inventory.is_some_and(|inventory| {
+                inventory
+                    .slot(PlayerInventory::SLOT_HEAD)
+                    .enchantment_level(Enchantment::AquaAffinity)
+                    > 0
+            });
+
+            if !aqua_affinity {
+                speed /= 5.0;
+            }
+        }
+
+        if !self.on_ground.0 {
+            speed /= 5.0;
+        }
+
+        let harvestable =
+            !block.requires_tool() || tool.is_some_and(|tool| tool.is_suitable_for(block, tags));
+
+        speed / hardness / if harvestable { 30.0 } else { 100.0 }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_digging(
+    mut events: EventReader<DiggingEvent>,
+    mut clients: Query<DiggerQuery, Without<ServerMining>>,
+    mut layers: Query<&mut ChunkLayer>,
+    mut mined_events: EventWriter<BlockMinedEvent>,
+    mut violation_events: EventWriter<DiggingViolationEvent>,
+    settings: Res<DiggingSettings>,
+    tags: Res<TagsRegistry>,
+    server: Res<Server>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Ok(mut digger) = clients.get_mut(event.client) else {
+            continue;
+        };
+
+        let Ok(mut layer) = layers.get_mut(digger.chunk_layer.0) else {
+            continue;
+        };
+
+        // Whatever the client was digging before is no longer being dug.
+        if let Some(digging) = digger.digging.as_deref() {
+            if digging.stage.is_some() {
+                layer
+                    .view_except_writer(digging.position, event.client)
+                    .write_packet(&BlockBreakingProgressS2c {
+                        entity_id: VarInt(digger.entity_id.get()),
+                        position: digging.position,
+                        destroy_stage: NO_STAGE,
+                    });
+            }
+
+            commands.entity(event.client).remove::<Digging>();
+        }
+
+        if *digger.game_mode == GameMode::Spectator {
+            continue;
+        }
+
+        let Some(block) = layer.block(event.position).map(|block| block.state) else {
+            continue;
+        };
+
+        if !digger.check_reach(event, block, &settings, &mut violation_events) {
+            continue;
+        }
+
+        match event.state {
+            DiggingState::Start => {
+                if block.is_air() {
+                    continue;
+                }
+
+                if *digger.game_mode == GameMode::Creative
+                    || digger.break_delta(block, block.hardness(), &layer, &tags) >= 1.0
+                {
+                    mined_events.send(BlockMinedEvent {
+                        client: event.client,
+                        position: event.position,
+                        block,
+                    });
+                } else {
+                    commands
+                        .entity(event.client)
+                        .insert(Digging::new(event.position, server.current_tick()));
+                }
+            }
+            DiggingState::Abort => {}
+            DiggingState::Stop => {
+                let violation = match digger.digging.as_deref() {
+                    _ if !settings.check_break_speed => None,
+                    Some(digging) if digging.position == event.position => {
+                        let ticks = server.current_tick() - digging.start_tick + 1;
+                        let progress = digger.break_delta(block, block.hardness(), &layer, &tags)
+                            * ticks as f32;
+
+                        (progress < settings.min_progress).then_some(DiggingViolation::TooFast)
+                    }
+                    _ => Some(DiggingViolation::NotStarted),
+                };
+
+                if let Some(violation) = violation {
+                    // The client already removed the block on its side.
+                    digger.client.write_packet(&BlockUpdateS2c {
+                        position: event.position,
+                        block_id: block,
+                    });
+
+                    violation_events.send(DiggingViolationEvent {
+                        client: event.client,
+                        position: event.position,
+                        violation,
+                    });
+                } else if !block.is_air() {
+                    mined_events.send(BlockMinedEvent {
+                        client: event.client,
+                        position: event.position,
+                        block,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Wears down the tools used to break blocks.
+fn damage_tools(
+    mut events: EventReader<BlockMinedEvent>,
+    clients: Query<(&Inventory, &HeldItem, Has<ServerMining>)>,
+    mut damage_events: EventWriter<DamageItemEvent>,
+    hardness: Res<BlockHardness>,
+    settings: Res<DiggingSettings>,
+) {
+    for event in events.read() {
+        if !settings.damage_tools {
+            continue;
+        }
+
+        let Ok((inventory, held_item, server_mining)) = clients.get(event.client) else {
+            continue;
+        };
+
+        let Some(tool) = Tool::of(inventory.slot(held_item.slot()).item) else {
+            continue;
+        };
+
+        // Hardness overrides only apply to clients with `ServerMining`.
+        let hardness = if server_mining {
+            hardness.get(event.block.to_kind())
+        } else {
+            event.block.hardness()
+        };
+        let amount = tool.durability_cost(hardness);
+
+        if amount > 0 {
+            damage_events.send(DamageItemEvent {
+                client: event.client,
+                slot: held_item.slot(),
+                amount,
+            });
+        }
+    }
+}
+
+fn broadcast_digging_progress(
+    mut clients: Query<(Entity, DiggerQuery), Without<ServerMining>>,
+    mut layers: Query<&mut ChunkLayer>,
+    settings: Res<DiggingSettings>,
+    tags: Res<TagsRegistry>,
+    server: Res<Server>,
+    mut commands: Commands,
+) {
+    if !settings.broadcast_progress {
+        return;
+    }
+
+    for (entity, mut digger) in &mut clients {
+        let Some(mut digging) = digger.digging.take() else {
+            continue;
+        };
+
+        let Ok(mut layer) = layers.get_mut(digger.chunk_layer.0) else {
+            continue;
+        };
+
+        let block = layer
+            .block(digging.position)
+            .map_or(BlockState::AIR, |block| block.state);
+
+        // The block was removed by something else.
+        let stage = if block.is_air() {
+            commands.entity(entity).remove::<Digging>();
+            NO_STAGE
+        } else {
+            let ticks = server.current_tick() - digging.start_tick + 1;
+            let progress =
+                digger.break_delta(block, block.hardness(), &layer, &tags) * ticks as f32;
+
+            ((progress * 10.0) as u8).min(9)
+        };
+
+        if digging.stage.unwrap_or(NO_STAGE) != stage {
+            digging.stage = (stage != NO_STAGE).then_some(stage);
+
+            layer
+                .view_except_writer(digging.position, entity)
+                .write_packet(&BlockBreakingProgressS2c {
+                    entity_id: VarInt(digger.entity_id.get()),
+                    position: digging.position,
+                    destroy_stage: stage,
+                });
+        }
+    }
+}
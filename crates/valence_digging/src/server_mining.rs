@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use valence_server::action::{DiggingEvent, DiggingState};
+use valence_server::block::BlockKind;
+use valence_server::client::{Client, VisibleChunkLayer};
+use valence_server::entity::active_status_effects::ActiveStatusEffects;
+use valence_server::protocol::packets::play::{
+    entity_status_effect_s2c, BlockBreakingProgressS2c, BlockUpdateS2c, EntityStatusEffectS2c,
+    RemoveEntityStatusEffectS2c,
+};
+use valence_server::protocol::status_effects::StatusEffect;
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::registry::TagsRegistry;
+use valence_server::{BlockState, ChunkLayer, GameMode, Layer, Server};
+
+use crate::{
+    BlockMinedEvent, DiggerQuery, Digging, DiggingSettings, DiggingViolationEvent, NO_STAGE,
+};
+
+/// Makes the server decide how long blocks take to break for a client,
+/// instead of the client.
+///
+/// The client is given a hidden Mining Fatigue effect so it can't break blocks
+/// on its own. While the client is digging, the server advances the break
+/// progress every tick using the hardness from [`BlockHardness`] and sends a
+/// [`BlockMinedEvent`] once the block is broken. The crack animation is shown
+/// to the client and, if [`DiggingSettings::broadcast_progress`] is set, to
+/// other players.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct ServerMining;
+
+/// The hardness of blocks mined by clients with [`ServerMining`]. Blocks
+/// without an override use their vanilla [hardness](BlockKind::hardness).
+///
+/// Overrides can make blocks that normally break instantly, like flowers and
+/// torches, take time to break. The client breaks those blocks on its own
+/// regardless of Mining Fatigue, so they are sent back to the client and have
+/// to be dug continuously until they break.
+#[derive(Resource, Clone, PartialEq, Default, Debug)]
+pub struct BlockHardness {
+    overrides: HashMap<BlockKind, f32>,
+}
+
+impl BlockHardness {
+    /// Returns the hardness of the block kind.
+    pub fn get(&self, kind: BlockKind) -> f32 {
+        self.overrides
+            .get(&kind)
+            .copied()
+            .unwrap_or_else(|| kind.hardness())
+    }
+
+    /// Overrides the hardness of the block kind. A negative hardness makes
+    /// the block unbreakable.
+    pub fn set(&mut self, kind: BlockKind, hardness: f32) {
+        self.overrides.insert(kind, hardness);
+    }
+
+    /// Restores the vanilla hardness of the block kind.
+    pub fn reset(&mut self, kind: BlockKind) {
+        self.overrides.remove(&kind);
+    }
+}
+
+/// The number of ticks without a new start after which digging a block that
+/// the client breaks instantly is considered aborted. The client doesn't
+/// report aborting those.
+const INSTANT_BREAK_TIMEOUT: i64 = 20;
+
+/// The Mining Fatigue amplifier which slows the client down the most.
+const MAX_FATIGUE: u8 = u8::MAX;
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn handle_server_mining(
+    mut events: EventReader<DiggingEvent>,
+    mut clients: Query<DiggerQuery, With<ServerMining>>,
+    mut layers: Query<&mut ChunkLayer>,
+    mut mined_events: EventWriter<BlockMinedEvent>,
+    mut violation_events: EventWriter<DiggingViolationEvent>,
+    settings: Res<DiggingSettings>,
+    hardness: Res<BlockHardness>,
+    tags: Res<TagsRegistry>,
+    server: Res<Server>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Ok(mut digger) = clients.get_mut(event.client) else {
+            continue;
+        };
+
+        let Ok(mut layer) = layers.get_mut(digger.chunk_layer.0) else {
+            continue;
+        };
+
+        if *digger.game_mode == GameMode::Spectator {
+            continue;
+        }
+
+        let Some(block) = layer.block(event.position).map(|block| block.state) else {
+            continue;
+        };
+
+        if !digger.check_reach(event, block, &settings, &mut violation_events) {
+            continue;
+        }
+
+        let client_broke_block = match event.state {
+            DiggingState::Start => block.hardness() == 0.0,
+            DiggingState::Abort => false,
+            // The client can only get here if it didn't have Mining Fatigue yet.
+            DiggingState::Stop => true,
+        };
+
+        if client_broke_block {
+            digger.client.write_packet(&BlockUpdateS2c {
+                position: event.position,
+                block_id: block,
+            });
+        }
+
+        match event.state {
+            DiggingState::Start => {
+                if let Some(digging) = digger.digging.as_deref_mut() {
+                    if digging.position == event.position {
+                        digging.last_start_tick = server.current_tick();
+                        continue;
+                    }
+
+                    clear_progress(&mut layer, digging, digger.entity_id.get());
+                    commands.entity(event.client).remove::<Digging>();
+                }
+
+                if block.is_air() {
+                    continue;
+                }
+
+                let hardness = hardness.get(block.to_kind());
+
+                if *digger.game_mode == GameMode::Creative
+                    || digger.break_delta(block, hardness, &layer, &tags) >= 1.0
+                {
+                    mined_events.send(BlockMinedEvent {
+                        client: event.client,
+                        position: event.position,
+                        block,
+                    });
+                } else {
+                    commands
+                        .entity(event.client)
+                        .insert(Digging::new(event.position, server.current_tick()));
+                }
+            }
+            DiggingState::Abort => {
+                if let Some(digging) = digger.digging.as_deref() {
+                    clear_progress(&mut layer, digging, digger.entity_id.get());
+                    commands.entity(event.client).remove::<Digging>();
+                }
+            }
+            DiggingState::Stop => {}
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn advance_server_mining(
+    mut clients: Query<(Entity, DiggerQuery), With<ServerMining>>,
+    mut layers: Query<&mut ChunkLayer>,
+    mut mined_events: EventWriter<BlockMinedEvent>,
+    settings: Res<DiggingSettings>,
+    hardness: Res<BlockHardness>,
+    tags: Res<TagsRegistry>,
+    server: Res<Server>,
+    mut commands: Commands,
+) {
+    for (entity, mut digger) in &mut clients {
+        let Some(mut digging) = digger.digging.take() else {
+            continue;
+        };
+
+        let Ok(mut layer) = layers.get_mut(digger.chunk_layer.0) else {
+            continue;
+        };
+
+        let entity_id = digger.entity_id.get();
+
+        let block = layer
+            .block(digging.position)
+            .map_or(BlockState::AIR, |block| block.state);
+
+        let timed_out = block.hardness() == 0.0
+            && server.current_tick() - digging.last_start_tick > INSTANT_BREAK_TIMEOUT;
+
+        // The block was removed by something else or the client stopped digging.
+        if block.is_air() || timed_out {
+            clear_progress(&mut layer, &digging, entity_id);
+            commands.entity(entity).remove::<Digging>();
+            continue;
+        }
+
+        digging.progress += digger.break_delta(block, hardness.get(block.to_kind()), &layer, &tags);
+
+        if digging.progress >= 1.0 {
+            mined_events.send(BlockMinedEvent {
+                client: entity,
+                position: digging.position,
+                block,
+            });
+
+            clear_progress(&mut layer, &digging, entity_id);
+            commands.entity(entity).remove::<Digging>();
+            continue;
+        }
+
+        let stage = ((digging.progress * 10.0) as u8).min(9);
+
+        if digging.stage != Some(stage) {
+            digging.stage = Some(stage);
+
+            let pkt = BlockBreakingProgressS2c {
+                entity_id: VarInt(entity_id),
+                position: digging.position,
+                destroy_stage: stage,
+            };
+
+            if settings.broadcast_progress {
+                layer.view_writer(digging.position).write_packet(&pkt);
+            } else {
+                digger.client.write_packet(&pkt);
+            }
+        }
+    }
+}
+
+/// Removes the crack animation from everyone who could have seen it.
+fn clear_progress(layer: &mut ChunkLayer, digging: &Digging, entity_id: i32) {
+    if digging.stage.is_some() {
+        layer
+            .view_writer(digging.position)
+            .write_packet(&BlockBreakingProgressS2c {
+                entity_id: VarInt(entity_id),
+                position: digging.position,
+                destroy_stage: NO_STAGE,
+            });
+    }
+}
+
+/// Gives clients with [`ServerMining`] the hidden Mining Fatigue effect. The
+/// effect is sent again whenever the client's status effects or layer change,
+/// since either can clear it.
+#[allow(clippy::type_complexity)]
+pub(super) fn hide_client_progress(
+    mut clients: Query<(
+        &mut Client,
+        Ref<ServerMining>,
+        Ref<VisibleChunkLayer>,
+        Option<Ref<ActiveStatusEffects>>,
+    )>,
+    mut removed_clients: Query<(&mut Client, Option<&ActiveStatusEffects>), Without<ServerMining>>,
+    mut removed: RemovedComponents<ServerMining>,
+) {
+    let effect_id = VarInt(i32::from(StatusEffect::MiningFatigue.to_raw()));
+
+    for (mut client, server_mining, chunk_layer, effects) in &mut clients {
+        if server_mining.is_added()
+            || chunk_layer.is_changed()
+            || effects.is_some_and(|effects| effects.is_changed())
+        {
+            // We reserve ID 0 for clients.
+            client.write_packet(&EntityStatusEffectS2c {
+                entity_id: VarInt(0),
+                effect_id,
+                amplifier: MAX_FATIGUE,
+                duration: VarInt(-1),
+                flags: entity_status_effect_s2c::Flags::new(),
+                factor_codec: None,
+            });
+        }
+    }
+
+    for entity in removed.read() {
+        let Ok((mut client, effects)) = removed_clients.get_mut(entity) else {
+            continue;
+        };
+
+        // Give the client back its real Mining Fatigue, if any.
+        match effects.and_then(|effects| effects.get_current_effect(StatusEffect::MiningFatigue)) {
+            Some(effect) => client.write_packet(&EntityStatusEffectS2c {
+                entity_id: VarInt(0),
+                effect_id,
+                amplifier: effect.amplifier(),
+                duration: VarInt(effect.remaining_duration().unwrap_or(-1)),
+                flags: entity_status_effect_s2c::Flags::new()
+                    .with_is_ambient(effect.ambient())
+                    .with_show_particles(effect.show_particles())
+                    .with_show_icon(effect.show_icon()),
+                factor_codec: None,
+            }),
+            None => client.write_packet(&RemoveEntityStatusEffectS2c {
+                entity_id: VarInt(0),
+                effect_id,
+            }),
+        }
+    }
+}
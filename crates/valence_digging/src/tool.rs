@@ -0,0 +1,114 @@
+//! Vanilla mining tools and how they affect breaking blocks.
+
+use valence_server::block::BlockKind;
+use valence_server::item::ToolTier;
+use valence_server::protocol::VarInt;
+use valence_server::registry::TagsRegistry;
+use valence_server::{BlockState, ItemKind};
+
+/// A kind of item that is faster at breaking some blocks.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Tool {
+    /// A pickaxe, axe, shovel or hoe, which is effective on the blocks in its
+    /// `mineable` tag.
+    Mining {
+        tier: ToolTier,
+        mineable: &'static str,
+    },
+    Sword,
+    Shears,
+}
+
+impl Tool {
+    /// Returns the tool an item is, or `None` if the item is not a tool.
+    pub fn of(item: ItemKind) -> Option<Self> {
+        match (item.tool_tier(), item.mineable_tag()) {
+            (Some(tier), Some(mineable)) => Some(Self::Mining { tier, mineable }),
+            (Some(_), None) => Some(Self::Sword),
+            _ if item == ItemKind::Shears => Some(Self::Shears),
+            _ => None,
+        }
+    }
+
+    /// Returns the multiplier the tool applies to the speed of breaking the
+    /// block, before enchantments and status effects.
+    pub fn mining_speed(self, block: BlockState, tags: &TagsRegistry) -> f32 {
+        let kind = block.to_kind();
+
+        match self {
+            Self::Mining { tier, mineable } => {
+                if in_tag(tags, mineable, kind) {
+                    tier.mining_speed()
+                } else {
+                    1.0
+                }
+            }
+            Self::Sword => {
+                if kind == BlockKind::Cobweb {
+                    15.0
+                } else if in_tag(tags, "minecraft:sword_efficient", kind) {
+                    1.5
+                } else {
+                    1.0
+                }
+            }
+            Self::Shears => {
+                if kind == BlockKind::Cobweb || in_tag(tags, "minecraft:leaves", kind) {
+                    15.0
+                } else if in_tag(tags, "minecraft:wool", kind) {
+                    5.0
+                } else if matches!(kind, BlockKind::Vine | BlockKind::GlowLichen) {
+                    2.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+
+    /// Returns the durability the tool loses when it is used to break a block
+    /// with the given hardness.
+    pub fn durability_cost(self, hardness: f32) -> u32 {
+        match self {
+            Self::Shears => 1,
+            _ if hardness == 0.0 => 0,
+            Self::Mining { .. } => 1,
+            Self::Sword => 2,
+        }
+    }
+
+    /// Returns whether the tool can harvest a block which
+    /// [requires a tool](BlockState::requires_tool).
+    pub fn is_suitable_for(self, block: BlockState, tags: &TagsRegistry) -> bool {
+        let kind = block.to_kind();
+
+        let (tier, tag) = match self {
+            Self::Mining { tier, mineable } => (tier, mineable),
+            Self::Sword => return kind == BlockKind::Cobweb,
+            Self::Shears => {
+                return matches!(
+                    kind,
+                    BlockKind::Cobweb | BlockKind::RedstoneWire | BlockKind::Tripwire
+                )
+            }
+        };
+
+        let too_low = [
+            (3, "minecraft:needs_diamond_tool"),
+            (2, "minecraft:needs_iron_tool"),
+            (1, "minecraft:needs_stone_tool"),
+        ]
+        .into_iter()
+        .any(|(level, needs)| tier.mining_level() < level && in_tag(tags, needs, kind));
+
+        !too_low && in_tag(tags, tag, kind)
+    }
+}
+
+/// Returns whether the block kind is in the block tag with the given ID.
+pub fn in_tag(tags: &TagsRegistry, tag: &str, kind: BlockKind) -> bool {
+    tags.registries
+        .get("minecraft:block")
+        .and_then(|blocks| blocks.get(tag))
+        .is_some_and(|ids| ids.contains(&VarInt(kind.to_raw().into())))
+}
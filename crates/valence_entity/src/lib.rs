@@ -379,6 +379,20 @@ impl EntityAnimations {
 #[derive(Component, Default, Debug, Deref, DerefMut)]
 pub struct ObjectData(pub i32);
 
+/// **For internal use only.**
+///
+/// The protocol IDs of the passengers riding an entity and of the vehicle it is
+/// riding. These are sent when the entity is spawned for a client so that
+/// mounted entities appear mounted regardless of the order they are spawned in.
+#[doc(hidden)]
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct PassengerIds {
+    /// The passengers riding this entity.
+    pub passengers: Vec<VarInt>,
+    /// The vehicle this entity is riding and all passengers of that vehicle.
+    pub vehicle: Option<(VarInt, Vec<VarInt>)>,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct VillagerData {
     pub kind: VillagerKind,
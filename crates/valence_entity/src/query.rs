@@ -1,14 +1,13 @@
-use std::mem;
-
 use bevy_ecs::prelude::DetectChanges;
 use bevy_ecs::query::QueryData;
 use bevy_ecs::world::Ref;
 use valence_math::DVec3;
 use valence_protocol::encode::WritePacket;
 use valence_protocol::packets::play::{
-    EntityAnimationS2c, EntityAttributesS2c, EntityPositionS2c, EntitySetHeadYawS2c,
-    EntitySpawnS2c, EntityStatusS2c, EntityTrackerUpdateS2c, EntityVelocityUpdateS2c,
-    ExperienceOrbSpawnS2c, MoveRelativeS2c, PlayerSpawnS2c, RotateAndMoveRelativeS2c, RotateS2c,
+    EntityAnimationS2c, EntityAttributesS2c, EntityPassengersSetS2c, EntityPositionS2c,
+    EntitySetHeadYawS2c, EntitySpawnS2c, EntityStatusS2c, EntityTrackerUpdateS2c,
+    EntityVelocityUpdateS2c, ExperienceOrbSpawnS2c, MoveRelativeS2c, PlayerSpawnS2c,
+    RotateAndMoveRelativeS2c, RotateS2c,
 };
 use valence_protocol::var_int::VarInt;
 use valence_protocol::ByteAngle;
@@ -18,7 +17,7 @@ use crate::attributes::TrackedEntityAttributes;
 use crate::tracked_data::TrackedData;
 use crate::{
     EntityAnimations, EntityId, EntityKind, EntityLayerId, EntityStatuses, HeadYaw, Look,
-    ObjectData, OldEntityLayerId, OldPosition, OnGround, Position, Velocity,
+    ObjectData, OldEntityLayerId, OldPosition, OnGround, PassengerIds, Position, Velocity,
 };
 
 #[derive(QueryData)]
@@ -32,6 +31,7 @@ pub struct EntityInitQuery {
     pub object_data: &'static ObjectData,
     pub velocity: &'static Velocity,
     pub tracked_data: &'static TrackedData,
+    pub passenger_ids: Option<&'static PassengerIds>,
 }
 
 impl EntityInitQueryItem<'_> {
@@ -82,6 +82,22 @@ impl EntityInitQueryItem<'_> {
                 tracked_values: init_data.into(),
             });
         }
+
+        if let Some(ids) = self.passenger_ids {
+            if !ids.passengers.is_empty() {
+                writer.write_packet(&EntityPassengersSetS2c {
+                    entity_id: self.entity_id.get().into(),
+                    passengers: ids.passengers.as_slice().into(),
+                });
+            }
+
+            if let Some((vehicle_id, passengers)) = &ids.vehicle {
+                writer.write_packet(&EntityPassengersSetS2c {
+                    entity_id: *vehicle_id,
+                    passengers: passengers.as_slice().into(),
+                });
+            }
+        }
     }
 }
 
@@ -172,7 +188,7 @@ impl UpdateEntityQueryItem<'_> {
         }
 
         if self.statuses.0 != 0 {
-            for i in 0..mem::size_of_val(self.statuses) {
+            for i in 0..u64::BITS {
                 if (self.statuses.0 >> i) & 1 == 1 {
                     writer.write_packet(&EntityStatusS2c {
                         entity_id: entity_id.0,
@@ -183,7 +199,7 @@ impl UpdateEntityQueryItem<'_> {
         }
 
         if self.animations.0 != 0 {
-            for i in 0..mem::size_of_val(self.animations) {
+            for i in 0..u8::BITS {
                 if (self.animations.0 >> i) & 1 == 1 {
                     writer.write_packet(&EntityAnimationS2c {
                         entity_id,
@@ -20,6 +20,8 @@ struct Block {
     wall_variant_id: Option<u16>,
     translation_key: String,
     name: String,
+    hardness: f32,
+    requires_tool: bool,
     properties: Vec<Property>,
     default_state_id: u16,
     states: Vec<State>,
@@ -91,6 +93,28 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
         })
         .collect::<TokenStream>();
 
+    let kind_to_hardness_arms = blocks
+        .iter()
+        .map(|b| {
+            let kind = ident(b.name.to_pascal_case());
+            let hardness = b.hardness;
+            quote! {
+                Self::#kind => #hardness,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let kind_requires_tool_arms = blocks
+        .iter()
+        .filter(|b| b.requires_tool)
+        .map(|b| {
+            let kind = ident(b.name.to_pascal_case());
+            quote! {
+                Self::#kind => true,
+            }
+        })
+        .collect::<TokenStream>();
+
     let state_to_kind_arms = blocks
         .iter()
         .map(|b| {
@@ -713,6 +737,16 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 }
             }
 
+            #[doc = "Returns the hardness of this block state. See [`BlockKind::hardness`]."]
+            pub const fn hardness(self) -> f32 {
+                self.to_kind().hardness()
+            }
+
+            #[doc = "Returns whether this block state only drops items when broken with the right tool. See [`BlockKind::requires_tool`]."]
+            pub const fn requires_tool(self) -> bool {
+                self.to_kind().requires_tool()
+            }
+
             pub const fn block_entity_kind(self) -> Option<BlockEntityKind> {
                 let kind = match self.0 {
                     #state_to_block_entity_type_arms
@@ -771,6 +805,23 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 }
             }
 
+            #[doc = "Returns how long this block takes to break. A hardness of `0.0` breaks"]
+            #[doc = "instantly and a negative hardness means the block can't be broken."]
+            pub const fn hardness(self) -> f32 {
+                match self {
+                    #kind_to_hardness_arms
+                }
+            }
+
+            #[doc = "Returns whether this block only drops items and breaks at the normal"]
+            #[doc = "speed when mined with a suitable tool."]
+            pub const fn requires_tool(self) -> bool {
+                match self {
+                    #kind_requires_tool_arms
+                    _ => false,
+                }
+            }
+
             #[doc = "Converts a block kind to its corresponding item kind."]
             #[doc = ""]
             #[doc = "[`ItemKind::Air`] is used to indicate the absence of an item."]
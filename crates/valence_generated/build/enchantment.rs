@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+
+use heck::ToPascalCase;
+use proc_macro2::TokenStream;
+use quote::quote;
+use serde::Deserialize;
+use valence_build_utils::{ident, rerun_if_changed};
+
+#[derive(Deserialize, Debug)]
+pub(crate) enum EnchantmentRarity {
+    Common,
+    Uncommon,
+    Rare,
+    VeryRare,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) enum EnchantmentTarget {
+    Armor,
+    ArmorFeet,
+    ArmorLegs,
+    ArmorChest,
+    ArmorHead,
+    Weapon,
+    Digger,
+    FishingRod,
+    Trident,
+    Breakable,
+    Bow,
+    Wearable,
+    Crossbow,
+    Vanishable,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Enchantment {
+    id: u16,
+    name: String,
+    translation_key: String,
+    max_level: u32,
+    rarity: EnchantmentRarity,
+    target: EnchantmentTarget,
+    treasure: bool,
+    curse: bool,
+    incompatible: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Enchants {
+    enchantments: Vec<Enchantment>,
+    /// The names of the items in each target.
+    targets: BTreeMap<EnchantmentTarget, Vec<String>>,
+}
+
+pub(crate) fn build() -> anyhow::Result<TokenStream> {
+    rerun_if_changed(["extracted/enchants.json"]);
+
+    let Enchants {
+        enchantments,
+        targets,
+    } = serde_json::from_str(include_str!("../extracted/enchants.json"))?;
+
+    let enchantment_count = enchantments.len();
+
+    let enchantment_from_raw_id_arms = enchantments
+        .iter()
+        .map(|enchantment| {
+            let id = &enchantment.id;
+            let name = ident(enchantment.name.to_pascal_case());
+
+            quote! {
+                #id => Some(Self::#name),
+            }
+        })
+        .collect::<TokenStream>();
+
+    let enchantment_to_raw_id_arms = enchantments
+        .iter()
+        .map(|enchantment| {
+            let id = &enchantment.id;
+            let name = ident(enchantment.name.to_pascal_case());
+
+            quote! {
+                Self::#name => #id,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let enchantment_from_ident_arms = enchantments
+        .iter()
+        .map(|enchantment| {
+            let ident_name = format!("minecraft:{}", &enchantment.name);
+            let name = ident(enchantment.name.to_pascal_case());
+
+            quote! {
+                #ident_name => Some(Self::#name),
+            }
+        })
+        .collect::<TokenStream>();
+
+    let enchantment_to_ident_arms = enchantments
+        .iter()
+        .map(|enchantment| {
+            let str_name = &enchantment.name;
+            let name = ident(str_name.to_pascal_case());
+
+            quote! {
+                Self::#name => ident!(#str_name),
+            }
+        })
+        .collect::<TokenStream>();
+
+    let enchantment_to_translation_key_arms = enchantments
+        .iter()
+        .map(|enchantment| {
+            let translation_key = &enchantment.translation_key;
+            let name = ident(enchantment.name.to_pascal_case());
+
+            quote! {
+                Self::#name => #translation_key,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let enchantment_to_max_level_arms = enchantments
+        .iter()
+        .map(|enchantment| {
+            let max_level = enchantment.max_level;
+            let name = ident(enchantment.name.to_pascal_case());
+
+            quote! {
+                Self::#name => #max_level,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let enchantment_to_rarity_arms = enchantments
+        .iter()
+        .map(|enchantment| {
+            let rarity = ident(format!("{:?}", enchantment.rarity));
+            let name = ident(enchantment.name.to_pascal_case());
+
+            quote! {
+                Self::#name => EnchantmentRarity::#rarity,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let enchantment_to_target_arms = enchantments
+        .iter()
+        .map(|enchantment| {
+            let target = ident(format!("{:?}", enchantment.target));
+            let name = ident(enchantment.name.to_pascal_case());
+
+            quote! {
+                Self::#name => EnchantmentTarget::#target,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let enchantment_treasure_names = enchantments
+        .iter()
+        .filter(|enchantment| enchantment.treasure)
+        .map(|enchantment| ident(enchantment.name.to_pascal_case()))
+        .collect::<Vec<_>>();
+
+    let enchantment_curse_names = enchantments
+        .iter()
+        .filter(|enchantment| enchantment.curse)
+        .map(|enchantment| ident(enchantment.name.to_pascal_case()))
+        .collect::<Vec<_>>();
+
+    let enchantment_to_incompatible_arms = enchantments
+        .iter()
+        .filter(|enchantment| !enchantment.incompatible.is_empty())
+        .map(|enchantment| {
+            let name = ident(enchantment.name.to_pascal_case());
+            let incompatible = enchantment
+                .incompatible
+                .iter()
+                .map(|other| ident(other.to_pascal_case()));
+
+            quote! {
+                Self::#name => &[#(Self::#incompatible,)*],
+            }
+        })
+        .collect::<TokenStream>();
+
+    let target_includes_arms = targets
+        .iter()
+        .map(|(target, items)| {
+            let target = ident(format!("{target:?}"));
+            let items = items.iter().map(|item| ident(item.to_pascal_case()));
+
+            quote! {
+                Self::#target => matches!(item, #(ItemKind::#items)|*),
+            }
+        })
+        .collect::<TokenStream>();
+
+    let enchantment_variants = enchantments
+        .iter()
+        .map(|enchantment| ident(enchantment.name.to_pascal_case()))
+        .collect::<Vec<_>>();
+
+    Ok(quote! {
+        use valence_ident::{Ident, ident};
+
+        #[doc = "How often an enchantment is offered by enchanting tables and found in loot."]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum EnchantmentRarity {
+            Common,
+            Uncommon,
+            Rare,
+            VeryRare,
+        }
+
+        impl EnchantmentRarity {
+            #[doc = "Gets the weight of this rarity when picking random enchantments."]
+            pub const fn weight(self) -> u32 {
+                match self {
+                    Self::Common => 10,
+                    Self::Uncommon => 5,
+                    Self::Rare => 2,
+                    Self::VeryRare => 1,
+                }
+            }
+        }
+
+        #[doc = "The category of items an enchantment can be applied to."]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum EnchantmentTarget {
+            Armor,
+            ArmorFeet,
+            ArmorLegs,
+            ArmorChest,
+            ArmorHead,
+            Weapon,
+            Digger,
+            FishingRod,
+            Trident,
+            Breakable,
+            Bow,
+            Wearable,
+            Crossbow,
+            Vanishable,
+        }
+
+        impl EnchantmentTarget {
+            #[doc = "Returns whether an item is in this category."]
+            pub const fn includes(self, item: ItemKind) -> bool {
+                match self {
+                    #target_includes_arms
+                }
+            }
+        }
+
+        #[doc = "Represents an enchantment from the game"]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum Enchantment {
+            #(#enchantment_variants,)*
+        }
+
+        impl Enchantment {
+            #[doc = "Constructs an enchantment from a raw enchantment ID."]
+            #[doc = ""]
+            #[doc = "If the given ID is invalid, `None` is returned."]
+            pub const fn from_raw(id: u16) -> Option<Self> {
+                match id {
+                    #enchantment_from_raw_id_arms
+                    _ => None
+                }
+            }
+
+            #[doc = "Gets the raw enchantment ID from the enchantment"]
+            pub const fn to_raw(self) -> u16 {
+                match self {
+                    #enchantment_to_raw_id_arms
+                }
+            }
+
+            #[doc = "Constructs an enchantment from its identifier (e.g. `minecraft:sharpness`)."]
+            #[doc = ""]
+            #[doc = "Returns `None` if the identifier is invalid."]
+            pub fn from_ident(id: Ident<&str>) -> Option<Self> {
+                match id.as_str() {
+                    #enchantment_from_ident_arms
+                    _ => None
+                }
+            }
+
+            #[doc = "Gets the identifier of this enchantment."]
+            pub const fn to_ident(self) -> Ident<&'static str> {
+                match self {
+                    #enchantment_to_ident_arms
+                }
+            }
+
+            #[doc = "Gets the translation key of this enchantment."]
+            pub const fn translation_key(self) -> &'static str {
+                match self {
+                    #enchantment_to_translation_key_arms
+                }
+            }
+
+            #[doc = "Gets the highest level of this enchantment obtainable in survival."]
+            pub const fn max_level(self) -> u32 {
+                match self {
+                    #enchantment_to_max_level_arms
+                }
+            }
+
+            #[doc = "Gets the rarity of this enchantment."]
+            pub const fn rarity(self) -> EnchantmentRarity {
+                match self {
+                    #enchantment_to_rarity_arms
+                }
+            }
+
+            #[doc = "Gets the category of items this enchantment is made for."]
+            pub const fn target(self) -> EnchantmentTarget {
+                match self {
+                    #enchantment_to_target_arms
+                }
+            }
+
+            #[doc = "Gets whether this enchantment is only found as treasure and never"]
+            #[doc = "offered by enchanting tables."]
+            pub const fn is_treasure(self) -> bool {
+                matches!(self, #(Self::#enchantment_treasure_names)|*)
+            }
+
+            #[doc = "Gets whether this enchantment is a curse."]
+            pub const fn is_curse(self) -> bool {
+                matches!(self, #(Self::#enchantment_curse_names)|*)
+            }
+
+            #[doc = "Gets the enchantments that can't be on the same item as this one."]
+            pub const fn incompatible(self) -> &'static [Self] {
+                match self {
+                    #enchantment_to_incompatible_arms
+                    _ => &[],
+                }
+            }
+
+            #[doc = "An array of all enchantments."]
+            pub const ALL: [Self; #enchantment_count] = [#(Self::#enchantment_variants,)*];
+        }
+    })
+}
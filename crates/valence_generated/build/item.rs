@@ -14,6 +14,12 @@ struct Item {
     max_durability: u16,
     enchantability: u8,
     fireproof: bool,
+    rarity: String,
+    armor_slot: Option<String>,
+    tool_tier: Option<String>,
+    mining_speed: Option<f32>,
+    mineable: Option<String>,
+    fuel_time: Option<u16>,
     food: Option<FoodComponent>,
 }
 
@@ -24,7 +30,15 @@ struct FoodComponent {
     always_edible: bool,
     meat: bool,
     snack: bool,
-    // TODO: effects
+    effects: Vec<FoodEffect>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct FoodEffect {
+    id: String,
+    duration: i32,
+    amplifier: u8,
+    chance: f32,
 }
 
 pub(crate) fn build() -> anyhow::Result<TokenStream> {
@@ -118,6 +132,25 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 let always_edible = food_component.always_edible;
                 let meat = food_component.meat;
                 let snack = food_component.snack;
+                let effects = food_component
+                    .effects
+                    .iter()
+                    .map(|effect| {
+                        let id = ident(effect.id.to_pascal_case());
+                        let duration = effect.duration;
+                        let amplifier = effect.amplifier;
+                        let chance = effect.chance;
+
+                        quote! {
+                            FoodEffect {
+                                effect: StatusEffect::#id,
+                                duration: #duration,
+                                amplifier: #amplifier,
+                                chance: #chance,
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>();
 
                 quote! {
                     Self::#name => Some(FoodComponent {
@@ -126,6 +159,7 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                         always_edible: #always_edible,
                         meat: #meat,
                         snack: #snack,
+                        effects: &[#(#effects,)*],
                     }
                 ),
                 }
@@ -172,7 +206,82 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
         })
         .collect::<TokenStream>();
 
+    let item_kind_to_rarity_arms = items
+        .iter()
+        .filter(|item| item.rarity != "common")
+        .map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let rarity = ident(item.rarity.to_pascal_case());
+
+            quote! {
+                Self::#name => ItemRarity::#rarity,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let item_kind_to_armor_slot_arms = items
+        .iter()
+        .filter_map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let slot = ident(item.armor_slot.as_ref()?.to_pascal_case());
+
+            Some(quote! {
+                Self::#name => Some(ArmorSlot::#slot),
+            })
+        })
+        .collect::<TokenStream>();
+
+    let item_kind_to_tool_tier_arms = items
+        .iter()
+        .filter_map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let tier = ident(item.tool_tier.as_ref()?.to_pascal_case());
+
+            Some(quote! {
+                Self::#name => Some(ToolTier::#tier),
+            })
+        })
+        .collect::<TokenStream>();
+
+    let item_kind_to_mining_speed_arms = items
+        .iter()
+        .filter_map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let speed = item.mining_speed?;
+
+            Some(quote! {
+                Self::#name => #speed,
+            })
+        })
+        .collect::<TokenStream>();
+
+    let item_kind_to_mineable_tag_arms = items
+        .iter()
+        .filter_map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let tag = item.mineable.as_ref()?;
+
+            Some(quote! {
+                Self::#name => Some(#tag),
+            })
+        })
+        .collect::<TokenStream>();
+
+    let item_kind_to_fuel_time_arms = items
+        .iter()
+        .filter_map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let fuel_time = item.fuel_time?;
+
+            Some(quote! {
+                Self::#name => #fuel_time,
+            })
+        })
+        .collect::<TokenStream>();
+
     Ok(quote! {
+        use crate::status_effects::StatusEffect;
+
         #[doc = "Represents an item from the game"]
         #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
         #[repr(u16)]
@@ -191,6 +300,112 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
             pub always_edible: bool,
             pub meat: bool,
             pub snack: bool,
+            #[doc = "The status effects the item may give when it is eaten."]
+            pub effects: &'static [FoodEffect],
+        }
+
+        #[doc = "A status effect given to the entity eating some food."]
+        #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+        pub struct FoodEffect {
+            pub effect: StatusEffect,
+            #[doc = "The duration of the effect in ticks."]
+            pub duration: i32,
+            pub amplifier: u8,
+            #[doc = "The probability of the effect being given, from `0.0` to `1.0`."]
+            pub chance: f32,
+        }
+
+        #[doc = "How rare an item is. Determines the color of the item's name."]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+        pub enum ItemRarity {
+            #[default]
+            Common,
+            Uncommon,
+            Rare,
+            Epic,
+        }
+
+        impl ItemRarity {
+            #[doc = "Gets the rarity of an enchanted item of this rarity. Enchanting"]
+            #[doc = "makes common and uncommon items rare."]
+            pub const fn enchanted(self) -> Self {
+                match self {
+                    Self::Common | Self::Uncommon => Self::Rare,
+                    rarity => rarity,
+                }
+            }
+        }
+
+        #[doc = "The armor slot of a player's inventory an item can be worn in."]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum ArmorSlot {
+            Head,
+            Chest,
+            Legs,
+            Feet,
+        }
+
+        #[doc = "The material of a tiered tool or sword."]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum ToolTier {
+            Wood,
+            Stone,
+            Iron,
+            Diamond,
+            Gold,
+            Netherite,
+        }
+
+        impl ToolTier {
+            #[doc = "Gets the mining level. Blocks in the `needs_stone_tool`,"]
+            #[doc = "`needs_iron_tool` and `needs_diamond_tool` tags need a level of at"]
+            #[doc = "least 1, 2 and 3 respectively."]
+            pub const fn mining_level(self) -> u8 {
+                match self {
+                    Self::Wood | Self::Gold => 0,
+                    Self::Stone => 1,
+                    Self::Iron => 2,
+                    Self::Diamond => 3,
+                    Self::Netherite => 4,
+                }
+            }
+
+            #[doc = "Gets the mining speed of tools of this tier on the blocks they are"]
+            #[doc = "effective on."]
+            pub const fn mining_speed(self) -> f32 {
+                match self {
+                    Self::Wood => 2.0,
+                    Self::Stone => 4.0,
+                    Self::Iron => 6.0,
+                    Self::Diamond => 8.0,
+                    Self::Gold => 12.0,
+                    Self::Netherite => 9.0,
+                }
+            }
+
+            #[doc = "Gets the maximum durability of tools of this tier."]
+            pub const fn durability(self) -> u16 {
+                match self {
+                    Self::Wood => 59,
+                    Self::Stone => 131,
+                    Self::Iron => 250,
+                    Self::Diamond => 1561,
+                    Self::Gold => 32,
+                    Self::Netherite => 2031,
+                }
+            }
+
+            #[doc = "Gets the attack damage tools of this tier add on top of their"]
+            #[doc = "base damage."]
+            pub const fn attack_damage_bonus(self) -> f32 {
+                match self {
+                    Self::Wood | Self::Gold => 0.0,
+                    Self::Stone => 1.0,
+                    Self::Iron => 2.0,
+                    Self::Diamond => 3.0,
+                    Self::Netherite => 4.0,
+                }
+            }
         }
 
         impl ItemKind {
@@ -253,9 +468,11 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 }
             }
 
-            #[doc = "Returns the maximum durability before the item will break."]
+            #[doc = "Returns the maximum durability before the item will break. This is"]
+            #[doc = "the highest value the `Damage` tag can have."]
             #[doc = ""]
             #[doc = "If the item doesn't have durability, `0` is returned."]
+            #[doc(alias = "max_damage")]
             pub const fn max_durability(self) -> u16 {
                 match self {
                     #item_kind_to_max_durability_arms
@@ -282,6 +499,68 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 }
             }
 
+            #[doc = "Returns the rarity of the item kind, before it is enchanted."]
+            pub const fn rarity(self) -> ItemRarity {
+                match self {
+                    #item_kind_to_rarity_arms
+                    _ => ItemRarity::Common,
+                }
+            }
+
+            #[doc = "Returns the armor slot the item can be worn in. This includes mob"]
+            #[doc = "heads, carved pumpkins and elytras."]
+            #[doc = ""]
+            #[doc = "If the item can't be worn, `None` is returned."]
+            pub const fn armor_slot(self) -> Option<ArmorSlot> {
+                match self {
+                    #item_kind_to_armor_slot_arms
+                    _ => None,
+                }
+            }
+
+            #[doc = "Returns the tier of a tiered tool or sword."]
+            #[doc = ""]
+            #[doc = "If the item kind is not a tiered tool, `None` is returned."]
+            pub const fn tool_tier(self) -> Option<ToolTier> {
+                match self {
+                    #item_kind_to_tool_tier_arms
+                    _ => None,
+                }
+            }
+
+            #[doc = "Returns the mining speed of a pickaxe, axe, shovel or hoe on the"]
+            #[doc = "blocks in its [`mineable_tag`](Self::mineable_tag)."]
+            #[doc = ""]
+            #[doc = "For all other items, `1.0` is returned."]
+            pub const fn mining_speed(self) -> f32 {
+                match self {
+                    #item_kind_to_mining_speed_arms
+                    _ => 1.0,
+                }
+            }
+
+            #[doc = "Returns the block tag of the blocks a pickaxe, axe, shovel or hoe is"]
+            #[doc = "effective on, e.g. `minecraft:mineable/pickaxe`."]
+            #[doc = ""]
+            #[doc = "If the item kind is not one of these tools, `None` is returned."]
+            pub const fn mineable_tag(self) -> Option<&'static str> {
+                match self {
+                    #item_kind_to_mineable_tag_arms
+                    _ => None,
+                }
+            }
+
+            #[doc = "Returns the number of ticks the item burns for when used as fuel in a"]
+            #[doc = "furnace."]
+            #[doc = ""]
+            #[doc = "If the item can't be used as fuel, `0` is returned."]
+            pub const fn fuel_time(self) -> u16 {
+                match self {
+                    #item_kind_to_fuel_time_arms
+                    _ => 0,
+                }
+            }
+
             /*
             #[doc = "Constructs an item kind from a block kind."]
             #[doc = ""]
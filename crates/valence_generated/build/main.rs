@@ -3,6 +3,7 @@ use valence_build_utils::write_generated_file;
 mod attributes;
 mod block;
 mod chunk_view;
+mod enchantment;
 mod item;
 mod packet_id;
 mod sound;
@@ -11,6 +12,7 @@ mod status_effects;
 pub fn main() -> anyhow::Result<()> {
     write_generated_file(attributes::build()?, "attributes.rs")?;
     write_generated_file(block::build()?, "block.rs")?;
+    write_generated_file(enchantment::build()?, "enchantment.rs")?;
     write_generated_file(item::build()?, "item.rs")?;
     write_generated_file(sound::build()?, "sound.rs")?;
     write_generated_file(packet_id::build()?, "packet_id.rs")?;
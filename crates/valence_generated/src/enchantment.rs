@@ -0,0 +1,46 @@
+use crate::item::ItemKind;
+
+include!(concat!(env!("OUT_DIR"), "/enchantment.rs"));
+
+impl Enchantment {
+    /// Returns whether this enchantment can be applied to an item in an
+    /// anvil. This is usually whether the item is in the enchantment's
+    /// [target](Self::target), with a few exceptions like Sharpness on axes.
+    pub fn can_enchant(self, item: ItemKind) -> bool {
+        match self {
+            Self::Sharpness | Self::Smite | Self::BaneOfArthropods => {
+                self.target().includes(item)
+                    || item.mineable_tag() == Some("minecraft:mineable/axe")
+            }
+            Self::Efficiency => self.target().includes(item) || item == ItemKind::Shears,
+            Self::Thorns => EnchantmentTarget::Armor.includes(item),
+            _ => self.target().includes(item),
+        }
+    }
+
+    /// Returns whether this enchantment can be on the same item as another
+    /// enchantment.
+    pub fn is_compatible_with(self, other: Self) -> bool {
+        self != other && !self.incompatible().contains(&other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_items() {
+        assert!(EnchantmentTarget::ArmorHead.includes(ItemKind::TurtleHelmet));
+        assert!(!EnchantmentTarget::Armor.includes(ItemKind::IronHorseArmor));
+        assert!(EnchantmentTarget::Wearable.includes(ItemKind::CreeperHead));
+        assert!(EnchantmentTarget::Vanishable.includes(ItemKind::Compass));
+        assert!(!EnchantmentTarget::Digger.includes(ItemKind::WoodenSword));
+    }
+
+    #[test]
+    fn sharpness_on_axes() {
+        assert!(Enchantment::Sharpness.can_enchant(ItemKind::IronAxe));
+        assert!(!Enchantment::Sharpness.can_enchant(ItemKind::IronPickaxe));
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::unseparated_literal_suffix)]
 
 pub mod block;
+pub mod enchantment;
 
 pub mod attributes {
     include!(concat!(env!("OUT_DIR"), "/attributes.rs"));
@@ -0,0 +1,389 @@
+//! The damage pipeline.
+//!
+//! Damage is dealt by sending a [`DamageEvent`]. Events are processed in
+//! [`DamageSet`](crate::DamageSet), where the damage is scaled by difficulty,
+//! filtered by [`DamageCooldown`] and reduced by armor, protection
+//! enchantments and [`Absorption`] before being subtracted from the target's
+//! [`Health`]. A
+//! [`DamageAppliedEvent`] is sent for every event that actually hurt its
+//! target.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use valence_inventory::player_inventory::PlayerInventory;
+use valence_inventory::Inventory;
+use valence_server::client::Client;
+use valence_server::enchantment::{self, ProtectedDamage};
+use valence_server::entity::active_status_effects::ActiveStatusEffects;
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::living::{Absorption, Health, LivingEntity};
+use valence_server::entity::player::PlayerEntity;
+use valence_server::entity::{EntityId, EntityLayerId, Look, Position};
+use valence_server::math::DVec3;
+use valence_server::protocol::packets::play::{DamageTiltS2c, EntityDamageS2c};
+use valence_server::protocol::status_effects::StatusEffect;
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::registry::damage_type::{DamageScaling, DamageTypeId};
+use valence_server::registry::{DamageTypeRegistry, TagsRegistry};
+use valence_server::{Difficulty, EntityLayer, GameMode, Ident, Layer};
+
+use crate::death::Dead;
+use crate::hunger::Exhaustion;
+use crate::HealthSettings;
+
+/// Describes what caused some damage.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DamageSource {
+    /// The name of the damage type in the [`DamageTypeRegistry`], such as
+    /// `minecraft:player_attack`.
+    pub damage_type: Ident<String>,
+    /// The entity ultimately responsible for the damage, e.g. the player who
+    /// shot an arrow.
+    pub attacker: Option<Entity>,
+    /// The entity that directly dealt the damage, e.g. the arrow itself. If
+    /// this is `None`, the attacker is assumed to have dealt the damage
+    /// directly.
+    pub direct: Option<Entity>,
+    /// An explicit position the damage came from. Takes precedence over the
+    /// positions of the attacker and direct entities.
+    pub position: Option<DVec3>,
+}
+
+impl DamageSource {
+    pub fn new<I: Into<Ident<String>>>(damage_type: I) -> Self {
+        Self {
+            damage_type: damage_type.into(),
+            attacker: None,
+            direct: None,
+            position: None,
+        }
+    }
+
+    pub fn with_attacker(mut self, attacker: Entity) -> Self {
+        self.attacker = Some(attacker);
+        self
+    }
+
+    pub fn with_direct(mut self, direct: Entity) -> Self {
+        self.direct = Some(direct);
+        self
+    }
+
+    pub fn with_position<P: Into<DVec3>>(mut self, position: P) -> Self {
+        self.position = Some(position.into());
+        self
+    }
+
+    /// Returns the entity that directly dealt the damage.
+    pub fn direct_entity(&self) -> Option<Entity> {
+        self.direct.or(self.attacker)
+    }
+}
+
+/// Send this event to damage a living entity.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    /// The amount of damage before any reductions, in half hearts.
+    pub amount: f32,
+    pub source: DamageSource,
+}
+
+/// Sent after a [`DamageEvent`] has been applied to its target.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct DamageAppliedEvent {
+    pub target: Entity,
+    /// The amount of health that was actually removed from the target after
+    /// all reductions.
+    pub amount: f32,
+    pub source: DamageSource,
+    /// Whether this was a full hit. Damage that only tops up a previous hit
+    /// during the [`DamageCooldown`] is not a full hit and does not play the
+    /// hurt animation.
+    pub full_hit: bool,
+}
+
+/// The invulnerability frames of a living entity.
+///
+/// After taking a full hit, an entity only takes damage for the portion of
+/// later hits that exceeds the last hit, until the cooldown has elapsed. The
+/// length of the cooldown is [`HealthSettings::invulnerability_ticks`].
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct DamageCooldown {
+    /// The number of ticks left until the entity can take full hits again
+    /// (counting down to zero).
+    pub ticks: u32,
+    /// The amount of damage of the last hit, before reductions.
+    pub last_amount: f32,
+}
+
+impl DamageCooldown {
+    /// Returns `true` if only damage exceeding the last hit would be applied
+    /// right now.
+    pub fn is_active(&self) -> bool {
+        self.ticks > 0
+    }
+}
+
+/// The source of the last damage an entity took. Used to attribute deaths.
+#[derive(Component, Clone, PartialEq, Default, Debug)]
+pub struct LastDamageSource(pub Option<DamageSource>);
+
+/// Computes the damage left after armor has absorbed its share, using the
+/// vanilla formula.
+pub fn damage_after_armor(amount: f32, armor: f32, toughness: f32) -> f32 {
+    let toughness_factor = 2.0 + toughness / 4.0;
+    let effective_armor = (armor - amount / toughness_factor).clamp(armor * 0.2, 20.0);
+
+    amount * (1.0 - effective_armor / 25.0)
+}
+
+/// Scales damage dealt to a player by the difficulty.
+pub fn scale_damage_with_difficulty(amount: f32, difficulty: Difficulty) -> f32 {
+    match difficulty {
+        Difficulty::Peaceful => 0.0,
+        Difficulty::Easy => (amount / 2.0 + 1.0).min(amount),
+        Difficulty::Normal => amount,
+        Difficulty::Hard => amount * 1.5,
+    }
+}
+
+/// Returns `true` if the damage type is in the given `minecraft:damage_type`
+/// tag, e.g. `minecraft:bypasses_armor`.
+pub fn damage_type_has_tag(tags: &TagsRegistry, damage_type: DamageTypeId, tag: &str) -> bool {
+    tags.registries
+        .get("minecraft:damage_type")
+        .and_then(|tags| tags.get(tag))
+        .is_some_and(|ids| ids.contains(&VarInt(damage_type.to_raw())))
+}
+
+pub(crate) fn tick_damage_cooldowns(mut cooldowns: Query<&mut DamageCooldown>) {
+    for mut cooldown in &mut cooldowns {
+        if cooldown.ticks > 0 {
+            cooldown.ticks -= 1;
+        }
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct DamageTargetQuery {
+    entity_id: &'static EntityId,
+    health: &'static mut Health,
+    absorption: Option<&'static mut Absorption>,
+    position: &'static Position,
+    look: &'static Look,
+    layer_id: &'static EntityLayerId,
+    attributes: Option<&'static EntityAttributes>,
+    effects: Option<&'static ActiveStatusEffects>,
+    inventory: Option<&'static Inventory>,
+    cooldown: Option<&'static mut DamageCooldown>,
+    last_source: Option<&'static mut LastDamageSource>,
+    exhaustion: Option<&'static mut Exhaustion>,
+    game_mode: Option<&'static GameMode>,
+    client: Option<&'static mut Client>,
+    is_player: Has<PlayerEntity>,
+    is_dead: Has<Dead>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_damage(
+    mut events: EventReader<DamageEvent>,
+    mut targets: Query<DamageTargetQuery>,
+    sources: Query<(&EntityId, &Position, Has<LivingEntity>, Has<PlayerEntity>)>,
+    mut layers: Query<&mut EntityLayer>,
+    mut applied_events: EventWriter<DamageAppliedEvent>,
+    damage_types: Res<DamageTypeRegistry>,
+    tags: Res<TagsRegistry>,
+    settings: Res<HealthSettings>,
+) {
+    for event in events.read() {
+        let Ok(mut target) = targets.get_mut(event.target) else {
+            continue;
+        };
+
+        if target.is_dead || target.health.0 <= 0.0 {
+            continue;
+        }
+
+        let Some(type_id) = damage_types.index_of(event.source.damage_type.as_str_ident()) else {
+            continue;
+        };
+
+        let damage_type = &damage_types[type_id];
+
+        let bypasses_invulnerability =
+            damage_type_has_tag(&tags, type_id, "minecraft:bypasses_invulnerability");
+
+        if !bypasses_invulnerability
+            && matches!(
+                target.game_mode,
+                Some(GameMode::Creative | GameMode::Spectator)
+            )
+        {
+            continue;
+        }
+
+        let is_fire = damage_type_has_tag(&tags, type_id, "minecraft:is_fire");
+
+        if is_fire
+            && target
+                .effects
+                .is_some_and(|effects| effects.has_effect(StatusEffect::FireResistance))
+        {
+            continue;
+        }
+
+        let attacker = event.source.attacker.and_then(|e| sources.get(e).ok());
+
+        let mut amount = event.amount;
+
+        if target.is_player {
+            let scaled = match damage_type.scaling {
+                DamageScaling::Never => false,
+                DamageScaling::WhenCausedByLivingNonPlayer => {
+                    attacker.is_some_and(|(_, _, is_living, is_player)| is_living && !is_player)
+                }
+                DamageScaling::Always => true,
+            };
+
+            if scaled {
+                amount = scale_damage_with_difficulty(amount, settings.difficulty);
+            }
+        }
+
+        if amount <= 0.0 {
+            continue;
+        }
+
+        // Apply invulnerability frames.
+        let (mut dealt, mut full_hit) = (amount, true);
+
+        if let Some(cooldown) = &mut target.cooldown {
+            if cooldown.is_active() && !bypasses_invulnerability {
+                if amount <= cooldown.last_amount {
+                    continue;
+                }
+
+                dealt = amount - cooldown.last_amount;
+                cooldown.last_amount = amount;
+                full_hit = false;
+            } else {
+                cooldown.last_amount = amount;
+                cooldown.ticks = settings.invulnerability_ticks;
+            }
+        }
+
+        if !damage_type_has_tag(&tags, type_id, "minecraft:bypasses_armor") {
+            if let Some(attributes) = target.attributes {
+                let armor = attributes
+                    .get_compute_value(EntityAttribute::GenericArmor)
+                    .unwrap_or(0.0);
+                let toughness = attributes
+                    .get_compute_value(EntityAttribute::GenericArmorToughness)
+                    .unwrap_or(0.0);
+
+                dealt = damage_after_armor(dealt, armor as f32, toughness as f32);
+            }
+        }
+
+        if !bypasses_invulnerability
+            && !damage_type_has_tag(&tags, type_id, "minecraft:bypasses_enchantments")
+        {
+            if let Some(inventory) = target.inventory.filter(|_| target.is_player) {
+                let armor = [
+                    PlayerInventory::SLOT_HEAD,
+                    PlayerInventory::SLOT_CHEST,
+                    PlayerInventory::SLOT_LEGS,
+                    PlayerInventory::SLOT_FEET,
+                ]
+                .map(|slot| inventory.slot(slot));
+
+                let protection = enchantment::protection(
+                    armor,
+                    ProtectedDamage {
+                        fire: is_fire,
+                        fall: damage_type_has_tag(&tags, type_id, "minecraft:is_fall"),
+                        explosion: damage_type_has_tag(&tags, type_id, "minecraft:is_explosion"),
+                        projectile: damage_type_has_tag(&tags, type_id, "minecraft:is_projectile"),
+                    },
+                );
+
+                dealt = enchantment::damage_after_protection(dealt, protection);
+            }
+        }
+
+        if let Some(absorption) = &mut target.absorption {
+            let absorbed = dealt.min(absorption.0.max(0.0));
+
+            if absorbed > 0.0 {
+                absorption.0 -= absorbed;
+                dealt -= absorbed;
+            }
+        }
+
+        target.health.0 = (target.health.0 - dealt).max(0.0);
+
+        if let Some(exhaustion) = &mut target.exhaustion {
+            exhaustion.add(damage_type.exhaustion);
+        }
+
+        if let Some(last_source) = &mut target.last_source {
+            last_source.0 = Some(event.source.clone());
+        }
+
+        if full_hit {
+            let direct = event
+                .source
+                .direct_entity()
+                .and_then(|e| sources.get(e).ok());
+
+            let source_pos = event
+                .source
+                .position
+                .or_else(|| direct.map(|(_, pos, _, _)| pos.0));
+
+            let to_id = |entity: Option<(&EntityId, &Position, bool, bool)>| {
+                VarInt(entity.map_or(0, |(id, _, _, _)| id.get() + 1))
+            };
+
+            let pkt = EntityDamageS2c {
+                entity_id: VarInt(target.entity_id.get()),
+                source_type_id: VarInt(type_id.to_raw()),
+                source_cause_id: to_id(attacker),
+                source_direct_id: to_id(direct),
+                source_pos: event.source.position,
+            };
+
+            if let Ok(mut layer) = layers.get_mut(target.layer_id.0) {
+                layer
+                    .view_except_writer(target.position.0, event.target)
+                    .write_packet(&pkt);
+            }
+
+            if let Some(client) = &mut target.client {
+                client.write_packet(&EntityDamageS2c {
+                    entity_id: VarInt(0),
+                    ..pkt
+                });
+
+                if let Some(source_pos) = source_pos {
+                    let delta = source_pos - target.position.0;
+                    let yaw = delta.z.atan2(delta.x).to_degrees() as f32 - target.look.yaw;
+
+                    client.write_packet(&DamageTiltS2c {
+                        entity_id: VarInt(0),
+                        yaw,
+                    });
+                }
+            }
+        }
+
+        applied_events.send(DamageAppliedEvent {
+            target: event.target,
+            amount: dealt,
+            source: event.source.clone(),
+            full_hit,
+        });
+    }
+}
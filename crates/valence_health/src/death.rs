@@ -0,0 +1,248 @@
+//! Death and respawning.
+//!
+//! A living entity dies once its [`Health`] reaches zero. Dead clients are
+//! shown the respawn screen and are respawned when they press the respawn
+//! button.
+
+use bevy_ecs::prelude::*;
+use valence_server::client::{Client, Username, VisibleChunkLayer};
+use valence_server::entity::active_status_effects::ActiveStatusEffects;
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::living::Health;
+use valence_server::entity::player::{Food, Saturation};
+use valence_server::entity::query::EntityInitQuery;
+use valence_server::entity::{
+    EntityKind, EntityLayerId, EntityStatus, EntityStatuses, Look, OldPosition, Position,
+};
+use valence_server::message::SendMessage;
+use valence_server::protocol::packets::play::EntitiesDestroyS2c;
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::registry::damage_type::DeathMessageType;
+use valence_server::registry::DamageTypeRegistry;
+use valence_server::spawn::RespawnPosition;
+use valence_server::status::RequestRespawnEvent;
+use valence_server::{EntityLayer, Layer, Text};
+
+use crate::damage::{DamageSource, LastDamageSource};
+use crate::hunger::{Exhaustion, FoodTickTimer};
+use crate::HealthSettings;
+
+/// Marker component for living entities that have died and have not been
+/// respawned yet. Dead entities do not take damage, regenerate or starve.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Dead;
+
+/// Sent when a living entity dies.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    /// The source of the last damage the entity took, if known.
+    pub source: Option<DamageSource>,
+}
+
+/// Sent after a dead client has been respawned.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct RespawnEvent {
+    pub entity: Entity,
+}
+
+/// The saturation players respawn with.
+const RESPAWN_SATURATION: f32 = 5.0;
+
+pub(crate) fn detect_deaths(
+    mut dying: Query<
+        (
+            Entity,
+            &Health,
+            &Position,
+            &EntityLayerId,
+            Option<&mut EntityStatuses>,
+            Option<&LastDamageSource>,
+            Option<&mut Client>,
+        ),
+        (Changed<Health>, Without<Dead>),
+    >,
+    names: Query<(Option<&Username>, Option<&EntityKind>)>,
+    mut layers: Query<&mut EntityLayer>,
+    mut death_events: EventWriter<DeathEvent>,
+    mut commands: Commands,
+    damage_types: Res<DamageTypeRegistry>,
+    settings: Res<HealthSettings>,
+) {
+    for (entity, health, pos, layer_id, statuses, last_source, client) in &mut dying {
+        if health.0 > 0.0 {
+            continue;
+        }
+
+        commands.entity(entity).insert(Dead);
+
+        if let Some(mut statuses) = statuses {
+            statuses.trigger(EntityStatus::PlayDeathSoundOrAddProjectileHitParticles);
+        }
+
+        let source = last_source.and_then(|s| s.0.clone());
+
+        if let Some(mut client) = client {
+            let message = death_message(entity, source.as_ref(), &names, &damage_types);
+
+            if settings.show_death_messages {
+                if let Ok(mut layer) = layers.get_mut(layer_id.0) {
+                    layer
+                        .view_except_writer(pos.0, entity)
+                        .send_chat_message(message.clone());
+                }
+            }
+
+            client.kill(message);
+        }
+
+        death_events.send(DeathEvent { entity, source });
+    }
+}
+
+/// Builds the vanilla death message for `victim`.
+fn death_message(
+    victim: Entity,
+    source: Option<&DamageSource>,
+    names: &Query<(Option<&Username>, Option<&EntityKind>)>,
+    damage_types: &DamageTypeRegistry,
+) -> Text {
+    let name_of = |entity: Entity| -> Option<Text> {
+        match names.get(entity).ok()? {
+            (Some(username), _) => Some(Text::text(username.0.clone())),
+            (None, Some(kind)) => Some(Text::translate(kind.translation_key()?, [])),
+            (None, None) => None,
+        }
+    };
+
+    let victim_name = name_of(victim).unwrap_or_default();
+
+    let Some(source) = source else {
+        return Text::translate("death.attack.generic", [victim_name]);
+    };
+
+    let damage_type = damage_types
+        .index_of(source.damage_type.as_str_ident())
+        .map(|id| &damage_types[id]);
+
+    if damage_type.and_then(|ty| ty.death_message_type) == Some(DeathMessageType::FallVariants) {
+        return Text::translate("death.fell.accident.generic", [victim_name]);
+    }
+
+    let message_id = damage_type.map_or("generic", |ty| ty.message_id.as_str());
+
+    match source.attacker.and_then(name_of) {
+        Some(attacker_name) => Text::translate(
+            format!("death.attack.{message_id}.player"),
+            [victim_name, attacker_name],
+        ),
+        None => Text::translate(format!("death.attack.{message_id}"), [victim_name]),
+    }
+}
+
+pub(crate) fn handle_respawn_requests(
+    mut events: EventReader<RequestRespawnEvent>,
+    mut clients: Query<
+        (
+            &mut Health,
+            &mut Food,
+            &mut Saturation,
+            Option<&mut Exhaustion>,
+            Option<&mut FoodTickTimer>,
+            Option<&mut LastDamageSource>,
+            Option<&mut ActiveStatusEffects>,
+            Option<&EntityAttributes>,
+            &mut Position,
+            &mut Look,
+            &RespawnPosition,
+            &mut VisibleChunkLayer,
+        ),
+        With<Dead>,
+    >,
+    mut respawn_events: EventWriter<RespawnEvent>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Ok((
+            mut health,
+            mut food,
+            mut saturation,
+            exhaustion,
+            timer,
+            last_source,
+            effects,
+            attributes,
+            mut pos,
+            mut look,
+            respawn_pos,
+            mut visible_chunk_layer,
+        )) = clients.get_mut(event.client)
+        else {
+            continue;
+        };
+
+        health.0 = attributes
+            .and_then(|attrs| attrs.get_compute_value(EntityAttribute::GenericMaxHealth))
+            .unwrap_or(20.0) as f32;
+        food.0 = 20;
+        saturation.0 = RESPAWN_SATURATION;
+
+        if let Some(mut exhaustion) = exhaustion {
+            exhaustion.0 = 0.0;
+        }
+
+        if let Some(mut timer) = timer {
+            timer.0 = 0;
+        }
+
+        if let Some(mut last_source) = last_source {
+            last_source.0 = None;
+        }
+
+        if let Some(mut effects) = effects {
+            effects.remove_all();
+        }
+
+        pos.set([
+            f64::from(respawn_pos.pos.x) + 0.5,
+            f64::from(respawn_pos.pos.y),
+            f64::from(respawn_pos.pos.z) + 0.5,
+        ]);
+        look.yaw = respawn_pos.yaw;
+        look.pitch = 0.0;
+
+        // Sends the respawn packet to the client.
+        visible_chunk_layer.set_changed();
+
+        commands.entity(event.client).remove::<Dead>();
+
+        respawn_events.send(RespawnEvent {
+            entity: event.client,
+        });
+    }
+}
+
+/// Replaces the corpse seen by other clients with the respawned entity.
+pub(crate) fn respawn_for_viewers(
+    mut events: EventReader<RespawnEvent>,
+    entities: Query<(EntityInitQuery, &Position, &OldPosition, &EntityLayerId)>,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for event in events.read() {
+        let Ok((init, pos, old_pos, layer_id)) = entities.get(event.entity) else {
+            continue;
+        };
+
+        let Ok(mut layer) = layers.get_mut(layer_id.0) else {
+            continue;
+        };
+
+        layer
+            .view_except_writer(old_pos.get(), event.entity)
+            .write_packet(&EntitiesDestroyS2c {
+                entity_ids: vec![VarInt(init.entity_id.get())].into(),
+            });
+
+        init.write_init_packets(pos.0, layer.view_except_writer(pos.0, event.entity));
+    }
+}
@@ -0,0 +1,502 @@
+//! Server-side behavior of status effects.
+//!
+//! [`ActiveStatusEffects`] only keeps track of which effects an entity has.
+//! The [`EffectBehaviorPlugin`] gives those effects meaning by running a
+//! [`StatusEffectBehavior`] whenever an effect is applied, ticks or is removed.
+//! The behaviors of all vanilla effects with server-side logic are registered
+//! by default, and can be replaced or extended through the
+//! [`StatusEffectBehaviors`] resource.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_health::effect::{StatusEffectBehavior, StatusEffectBehaviors};
+//! # use valence_server::entity::active_status_effects::ActiveStatusEffect;
+//! # use valence_server::protocol::status_effects::StatusEffect;
+//! struct Levitation;
+//!
+//! impl StatusEffectBehavior for Levitation {
+//!     fn on_applied(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+//!         // ...
+//!     }
+//! }
+//!
+//! fn setup(mut behaviors: ResMut<StatusEffectBehaviors>) {
+//!     behaviors.insert(StatusEffect::Levitation, Levitation);
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemState;
+use valence_server::entity::active_status_effects::{ActiveStatusEffect, ActiveStatusEffects};
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::Flags;
+use valence_server::entity::living::{Absorption, Health};
+use valence_server::entity::player::AbsorptionAmount;
+use valence_server::protocol::status_effects::{AttributeModifier, StatusEffect};
+use valence_server::status_effect::{StatusEffectAdded, StatusEffectRemoved};
+use valence_server::{ident, Ident};
+
+use crate::damage::{DamageEvent, DamageSource};
+use crate::death::Dead;
+use crate::hunger::Exhaustion;
+use crate::DamageSet;
+
+pub struct EffectBehaviorPlugin;
+
+impl Plugin for EffectBehaviorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StatusEffectBehaviors>()
+            .add_systems(PostUpdate, run_effect_behaviors.before(DamageSet));
+    }
+}
+
+/// The server-side logic of a [`StatusEffect`].
+///
+/// All methods do nothing by default.
+pub trait StatusEffectBehavior: Send + Sync + 'static {
+    /// Called when the effect is added to an entity, or when the strongest
+    /// instance of the effect changes. In the latter case,
+    /// [`on_removed`](Self::on_removed) is called for the previous instance
+    /// first.
+    ///
+    /// Instant effects such as [`StatusEffect::InstantHealth`] only ever have
+    /// this method called.
+    fn on_applied(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        let _ = (world, entity, effect);
+    }
+
+    /// Returns whether [`on_tick`](Self::on_tick) should run this tick.
+    fn should_tick(&self, effect: &ActiveStatusEffect) -> bool {
+        let _ = effect;
+        false
+    }
+
+    /// Called every tick for which [`should_tick`](Self::should_tick) returns
+    /// `true` while the effect is active.
+    fn on_tick(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        let _ = (world, entity, effect);
+    }
+
+    /// Called when the effect is removed from an entity or expires.
+    fn on_removed(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        let _ = (world, entity, effect);
+    }
+}
+
+/// The [`StatusEffectBehavior`] of every status effect with server-side logic.
+///
+/// The default value contains the vanilla behaviors.
+#[derive(Resource)]
+pub struct StatusEffectBehaviors {
+    behaviors: HashMap<StatusEffect, Box<dyn StatusEffectBehavior>>,
+}
+
+impl StatusEffectBehaviors {
+    /// Creates an empty set of behaviors, so that status effects do nothing.
+    pub fn empty() -> Self {
+        Self {
+            behaviors: HashMap::new(),
+        }
+    }
+
+    /// Sets the behavior of a status effect, returning the previous behavior.
+    pub fn insert<B: StatusEffectBehavior>(
+        &mut self,
+        effect: StatusEffect,
+        behavior: B,
+    ) -> Option<Box<dyn StatusEffectBehavior>> {
+        self.behaviors.insert(effect, Box::new(behavior))
+    }
+
+    /// Removes the behavior of a status effect, returning it.
+    pub fn remove(&mut self, effect: StatusEffect) -> Option<Box<dyn StatusEffectBehavior>> {
+        self.behaviors.remove(&effect)
+    }
+
+    /// Gets the behavior of a status effect.
+    pub fn get(&self, effect: StatusEffect) -> Option<&dyn StatusEffectBehavior> {
+        self.behaviors.get(&effect).map(|b| b.as_ref())
+    }
+}
+
+impl Default for StatusEffectBehaviors {
+    fn default() -> Self {
+        let mut behaviors = Self::empty();
+
+        for effect in StatusEffect::ALL {
+            if !effect.attribute_modifiers().is_empty() {
+                behaviors.insert(effect, AttributeModifiers);
+            }
+        }
+
+        behaviors.insert(StatusEffect::Regeneration, Regeneration);
+        behaviors.insert(StatusEffect::Poison, Poison);
+        behaviors.insert(StatusEffect::Wither, Wither);
+        behaviors.insert(StatusEffect::Hunger, Hunger);
+        behaviors.insert(StatusEffect::InstantHealth, InstantHealth);
+        behaviors.insert(StatusEffect::InstantDamage, InstantDamage);
+        behaviors.insert(StatusEffect::Absorption, AbsorptionEffect);
+        behaviors.insert(StatusEffect::Invisibility, Invisibility);
+
+        behaviors
+    }
+}
+
+/// The instances of status effects whose behavior has been applied to an
+/// entity, used to undo them when the effects change.
+#[derive(Component, Default, Debug)]
+pub struct AppliedStatusEffects(HashMap<StatusEffect, ActiveStatusEffect>);
+
+impl AppliedStatusEffects {
+    /// Gets the applied instance of an effect.
+    pub fn get(&self, effect: StatusEffect) -> Option<&ActiveStatusEffect> {
+        self.0.get(&effect)
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn run_effect_behaviors(
+    world: &mut World,
+    state: &mut SystemState<(
+        EventReader<StatusEffectAdded>,
+        EventReader<StatusEffectRemoved>,
+        Query<(Entity, &ActiveStatusEffects), Without<Dead>>,
+    )>,
+) {
+    let (mut added_events, mut removed_events, entities) = state.get_mut(world);
+
+    let added: Vec<_> = added_events
+        .read()
+        .map(|event| (event.entity, event.status_effect))
+        .collect();
+
+    let removed: Vec<_> = removed_events
+        .read()
+        .map(|event| (event.entity, event.status_effect.clone()))
+        .collect();
+
+    let active: Vec<_> = entities
+        .iter()
+        .flat_map(|(entity, effects)| {
+            effects
+                .get_current_effects()
+                .into_iter()
+                .map(move |effect| (entity, effect.clone()))
+        })
+        .collect();
+
+    world.resource_scope(|world, behaviors: Mut<StatusEffectBehaviors>| {
+        for (entity, status_effect) in added {
+            let Some(current) = world
+                .get::<ActiveStatusEffects>(entity)
+                .and_then(|effects| effects.get_current_effect(status_effect))
+                .cloned()
+            else {
+                // Instant effects have already expired by now. They are applied
+                // when their removal is handled below.
+                continue;
+            };
+
+            let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+                continue;
+            };
+
+            let previous = match entity_mut.get_mut::<AppliedStatusEffects>() {
+                Some(mut applied) => applied.0.insert(status_effect, current.clone()),
+                None => {
+                    let mut applied = AppliedStatusEffects::default();
+                    applied.0.insert(status_effect, current.clone());
+                    entity_mut.insert(applied);
+                    None
+                }
+            };
+
+            if let Some(behavior) = behaviors.get(status_effect) {
+                if let Some(previous) = previous {
+                    behavior.on_removed(world, entity, &previous);
+                }
+
+                behavior.on_applied(world, entity, &current);
+            }
+        }
+
+        for (entity, effect) in removed {
+            let status_effect = effect.status_effect();
+
+            let previous = world
+                .get_mut::<AppliedStatusEffects>(entity)
+                .and_then(|mut applied| applied.0.remove(&status_effect));
+
+            let Some(behavior) = behaviors.get(status_effect) else {
+                continue;
+            };
+
+            if let Some(previous) = previous {
+                behavior.on_removed(world, entity, &previous);
+            } else if status_effect.instant() {
+                behavior.on_applied(world, entity, &effect);
+            }
+        }
+
+        for (entity, effect) in active {
+            if let Some(behavior) = behaviors.get(effect.status_effect()) {
+                if behavior.should_tick(&effect) && world.get_entity(entity).is_some() {
+                    behavior.on_tick(world, entity, &effect);
+                }
+            }
+        }
+    });
+}
+
+/// Returns `true` on the ticks a periodic effect with the given base interval
+/// should act. Higher amplifiers shorten the interval.
+fn is_periodic_tick(effect: &ActiveStatusEffect, base_interval: i32) -> bool {
+    let interval = base_interval >> effect.amplifier().min(31);
+
+    if interval <= 0 {
+        return true;
+    }
+
+    let ticks = effect
+        .remaining_duration()
+        .unwrap_or_else(|| effect.active_ticks());
+
+    ticks % interval == 0
+}
+
+fn max_health(world: &World, entity: Entity) -> f32 {
+    world
+        .get::<EntityAttributes>(entity)
+        .and_then(|attrs| attrs.get_compute_value(EntityAttribute::GenericMaxHealth))
+        .unwrap_or(20.0) as f32
+}
+
+/// Heals a living entity without exceeding its maximum health. Dead entities
+/// are not healed.
+fn heal(world: &mut World, entity: Entity, amount: f32) {
+    let max_health = max_health(world, entity);
+
+    if let Some(mut health) = world.get_mut::<Health>(entity) {
+        if health.0 > 0.0 && health.0 < max_health {
+            health.0 = (health.0 + amount).min(max_health);
+        }
+    }
+}
+
+fn damage(world: &mut World, entity: Entity, amount: f32, damage_type: Ident<&'static str>) {
+    world.send_event(DamageEvent {
+        target: entity,
+        amount,
+        source: DamageSource::new(damage_type),
+    });
+}
+
+/// Applies the [attribute modifiers](StatusEffect::attribute_modifiers) of an
+/// effect, such as the movement speed bonus of [`StatusEffect::Speed`]. The
+/// modifier amount is multiplied by the level of the effect.
+pub struct AttributeModifiers;
+
+impl AttributeModifiers {
+    fn amount(effect: &ActiveStatusEffect, modifier: &AttributeModifier) -> f64 {
+        let base = match effect.status_effect() {
+            // The damage modifiers have a different amount per level than the
+            // generated modifier.
+            StatusEffect::Strength => 3.0,
+            StatusEffect::Weakness => -4.0,
+            _ => modifier.value,
+        };
+
+        base * f64::from(effect.amplifier() + 1)
+    }
+}
+
+impl StatusEffectBehavior for AttributeModifiers {
+    fn on_applied(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        let Some(mut attributes) = world.get_mut::<EntityAttributes>(entity) else {
+            return;
+        };
+
+        for modifier in effect.status_effect().attribute_modifiers() {
+            attributes.set_modifier(
+                modifier.attribute,
+                modifier.uuid,
+                Self::amount(effect, &modifier),
+                modifier.operation,
+            );
+        }
+    }
+
+    fn on_removed(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        let Some(mut attributes) = world.get_mut::<EntityAttributes>(entity) else {
+            return;
+        };
+
+        for modifier in effect.status_effect().attribute_modifiers() {
+            attributes.remove_modifier(modifier.attribute, modifier.uuid);
+        }
+
+        // Losing health boost lowers the maximum health.
+        let max_health = max_health(world, entity);
+
+        if let Some(mut health) = world.get_mut::<Health>(entity) {
+            if health.0 > max_health {
+                health.0 = max_health;
+            }
+        }
+    }
+}
+
+/// Heals one point of health every 50 ticks, halved for every level.
+pub struct Regeneration;
+
+impl StatusEffectBehavior for Regeneration {
+    fn should_tick(&self, effect: &ActiveStatusEffect) -> bool {
+        is_periodic_tick(effect, 50)
+    }
+
+    fn on_tick(&self, world: &mut World, entity: Entity, _effect: &ActiveStatusEffect) {
+        heal(world, entity, 1.0);
+    }
+}
+
+/// Deals one point of magic damage every 25 ticks, halved for every level.
+/// Poison never kills.
+pub struct Poison;
+
+impl StatusEffectBehavior for Poison {
+    fn should_tick(&self, effect: &ActiveStatusEffect) -> bool {
+        is_periodic_tick(effect, 25)
+    }
+
+    fn on_tick(&self, world: &mut World, entity: Entity, _effect: &ActiveStatusEffect) {
+        if world.get::<Health>(entity).is_some_and(|h| h.0 > 1.0) {
+            damage(world, entity, 1.0, ident!("magic"));
+        }
+    }
+}
+
+/// Deals one point of wither damage every 40 ticks, halved for every level.
+pub struct Wither;
+
+impl StatusEffectBehavior for Wither {
+    fn should_tick(&self, effect: &ActiveStatusEffect) -> bool {
+        is_periodic_tick(effect, 40)
+    }
+
+    fn on_tick(&self, world: &mut World, entity: Entity, _effect: &ActiveStatusEffect) {
+        damage(world, entity, 1.0, ident!("wither"));
+    }
+}
+
+/// Adds exhaustion to players every tick.
+pub struct Hunger;
+
+impl StatusEffectBehavior for Hunger {
+    fn should_tick(&self, _effect: &ActiveStatusEffect) -> bool {
+        true
+    }
+
+    fn on_tick(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        if let Some(mut exhaustion) = world.get_mut::<Exhaustion>(entity) {
+            exhaustion.add(0.005 * f32::from(effect.amplifier() + 1));
+        }
+    }
+}
+
+/// Instantly heals `4 << level` health.
+pub struct InstantHealth;
+
+impl StatusEffectBehavior for InstantHealth {
+    fn on_applied(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        heal(world, entity, (4 << effect.amplifier().min(29)) as f32);
+    }
+}
+
+/// Instantly deals `6 << level` magic damage.
+pub struct InstantDamage;
+
+impl StatusEffectBehavior for InstantDamage {
+    fn on_applied(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        damage(
+            world,
+            entity,
+            (6 << effect.amplifier().min(28)) as f32,
+            ident!("magic"),
+        );
+    }
+}
+
+/// Grants four points of [`Absorption`] per level, which are taken away again
+/// when the effect ends.
+pub struct AbsorptionEffect;
+
+impl AbsorptionEffect {
+    fn amount(effect: &ActiveStatusEffect) -> f32 {
+        4.0 * f32::from(effect.amplifier() + 1)
+    }
+}
+
+impl StatusEffectBehavior for AbsorptionEffect {
+    fn on_applied(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        if let Some(mut absorption) = world.get_mut::<Absorption>(entity) {
+            absorption.0 += Self::amount(effect);
+        }
+    }
+
+    fn on_removed(&self, world: &mut World, entity: Entity, effect: &ActiveStatusEffect) {
+        if let Some(mut absorption) = world.get_mut::<Absorption>(entity) {
+            absorption.0 = (absorption.0 - Self::amount(effect)).max(0.0);
+        }
+    }
+}
+
+/// Makes the entity invisible while the effect is active. Entities that were
+/// already invisible when the effect was applied stay invisible when it ends.
+pub struct Invisibility;
+
+/// Marks entities whose invisible flag was set by [`Invisibility`].
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct InvisibleFromEffect;
+
+impl StatusEffectBehavior for Invisibility {
+    fn on_applied(&self, world: &mut World, entity: Entity, _effect: &ActiveStatusEffect) {
+        let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+            return;
+        };
+
+        if let Some(mut flags) = entity_mut.get_mut::<Flags>() {
+            if !flags.invisible() {
+                flags.set_invisible(true);
+                entity_mut.insert(InvisibleFromEffect);
+            }
+        }
+    }
+
+    fn on_removed(&self, world: &mut World, entity: Entity, _effect: &ActiveStatusEffect) {
+        let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+            return;
+        };
+
+        if entity_mut.take::<InvisibleFromEffect>().is_some() {
+            if let Some(mut flags) = entity_mut.get_mut::<Flags>() {
+                flags.set_invisible(false);
+            }
+        }
+    }
+}
+
+/// Copies the [`Absorption`] of players to their [`AbsorptionAmount`], which
+/// is what clients display as golden hearts.
+pub(crate) fn sync_player_absorption(
+    mut players: Query<(&Absorption, &mut AbsorptionAmount), Changed<Absorption>>,
+) {
+    for (absorption, mut amount) in &mut players {
+        if amount.0 != absorption.0 {
+            amount.0 = absorption.0;
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Burning entities.
+//!
+//! An entity with [`FireTicks`] above zero is on fire. It takes one point of
+//! `minecraft:on_fire` damage every second until the ticks run out, and is
+//! shown burning to clients through its [`Flags`].
+
+use bevy_ecs::prelude::*;
+use valence_server::entity::entity::Flags;
+use valence_server::ident;
+
+use crate::damage::{DamageEvent, DamageSource};
+use crate::death::Dead;
+
+/// The number of ticks a living entity keeps burning for (counting down to
+/// zero). Entities without this component can't be set on fire.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct FireTicks(pub u32);
+
+impl FireTicks {
+    /// Sets the entity on fire for at least `ticks` ticks. Does not shorten a
+    /// longer fire that is already burning.
+    pub fn ignite(&mut self, ticks: u32) {
+        self.0 = self.0.max(ticks);
+    }
+
+    pub fn extinguish(&mut self) {
+        self.0 = 0;
+    }
+
+    pub fn is_burning(&self) -> bool {
+        self.0 > 0
+    }
+}
+
+pub(crate) fn tick_fire(
+    mut entities: Query<(Entity, &mut FireTicks, &mut Flags, Has<Dead>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, mut fire, mut flags, is_dead) in &mut entities {
+        if is_dead && fire.is_burning() {
+            fire.extinguish();
+        }
+
+        if fire.is_burning() {
+            if fire.0 % 20 == 0 {
+                damage_events.send(DamageEvent {
+                    target: entity,
+                    amount: 1.0,
+                    source: DamageSource::new(ident!("on_fire")),
+                });
+            }
+
+            fire.0 -= 1;
+        }
+
+        if flags.on_fire() != fire.is_burning() {
+            flags.set_on_fire(fire.is_burning());
+        }
+    }
+}
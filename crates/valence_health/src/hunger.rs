@@ -0,0 +1,159 @@
+//! Exhaustion, natural regeneration and starvation for players.
+//!
+//! This follows the vanilla hunger rules: exhaustion accumulates from actions
+//! such as sprinting, jumping and taking damage, and drains saturation and
+//! then food. Players with a nearly full food bar slowly regenerate health,
+//! while players with an empty food bar starve.
+
+use bevy_ecs::prelude::*;
+use derive_more::{Deref, DerefMut};
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::Flags;
+use valence_server::entity::living::Health;
+use valence_server::entity::player::{Food, Saturation};
+use valence_server::movement::MovementEvent;
+use valence_server::{ident, Difficulty, GameMode, Server};
+
+use crate::damage::{DamageEvent, DamageSource};
+use crate::death::Dead;
+use crate::HealthSettings;
+
+/// The exhaustion level of a player. Every time this exceeds
+/// [`Exhaustion::THRESHOLD`], one point of saturation (or food, if there is no
+/// saturation left) is consumed.
+#[derive(Component, Copy, Clone, PartialEq, PartialOrd, Default, Debug, Deref, DerefMut)]
+pub struct Exhaustion(pub f32);
+
+impl Exhaustion {
+    pub const THRESHOLD: f32 = 4.0;
+    pub const MAX: f32 = 40.0;
+
+    /// Adds exhaustion, capped at [`Exhaustion::MAX`].
+    pub fn add(&mut self, amount: f32) {
+        self.0 = (self.0 + amount).min(Self::MAX);
+    }
+}
+
+/// Counts the ticks until the next regeneration or starvation step.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug, Deref, DerefMut)]
+pub struct FoodTickTimer(pub u32);
+
+const SPRINT_EXHAUSTION_PER_METER: f32 = 0.1;
+const JUMP_EXHAUSTION: f32 = 0.05;
+const SPRINT_JUMP_EXHAUSTION: f32 = 0.2;
+
+pub(crate) fn add_movement_exhaustion(
+    mut events: EventReader<MovementEvent>,
+    mut clients: Query<(&mut Exhaustion, &Flags, &GameMode), Without<Dead>>,
+) {
+    for event in events.read() {
+        let Ok((mut exhaustion, flags, game_mode)) = clients.get_mut(event.client) else {
+            continue;
+        };
+
+        if !matches!(game_mode, GameMode::Survival | GameMode::Adventure) {
+            continue;
+        }
+
+        let delta = event.position - event.old_position;
+
+        if event.old_on_ground && !event.on_ground && delta.y > 0.0 {
+            exhaustion.add(if flags.sprinting() {
+                SPRINT_JUMP_EXHAUSTION
+            } else {
+                JUMP_EXHAUSTION
+            });
+        } else if event.old_on_ground && flags.sprinting() {
+            let distance = delta.x.hypot(delta.z) as f32;
+            exhaustion.add(SPRINT_EXHAUSTION_PER_METER * distance);
+        }
+    }
+}
+
+pub(crate) fn tick_hunger(
+    mut players: Query<
+        (
+            Entity,
+            &mut Food,
+            &mut Saturation,
+            &mut Health,
+            &mut Exhaustion,
+            &mut FoodTickTimer,
+            Option<&EntityAttributes>,
+        ),
+        Without<Dead>,
+    >,
+    mut damage_events: EventWriter<DamageEvent>,
+    settings: Res<HealthSettings>,
+    server: Res<Server>,
+) {
+    let difficulty = settings.difficulty;
+
+    for (entity, mut food, mut saturation, mut health, mut exhaustion, mut timer, attributes) in
+        &mut players
+    {
+        let max_health = attributes
+            .and_then(|attrs| attrs.get_compute_value(EntityAttribute::GenericMaxHealth))
+            .unwrap_or(20.0) as f32;
+
+        if exhaustion.0 > Exhaustion::THRESHOLD {
+            exhaustion.0 -= Exhaustion::THRESHOLD;
+
+            if saturation.0 > 0.0 {
+                saturation.0 = (saturation.0 - 1.0).max(0.0);
+            } else if difficulty != Difficulty::Peaceful && food.0 > 0 {
+                food.0 -= 1;
+            }
+        }
+
+        let can_heal = health.0 > 0.0 && health.0 < max_health;
+
+        if difficulty == Difficulty::Peaceful && settings.natural_regeneration {
+            if can_heal && server.current_tick() % 20 == 0 {
+                health.0 = (health.0 + 1.0).min(max_health);
+            }
+
+            if food.0 < 20 && server.current_tick() % 10 == 0 {
+                food.0 += 1;
+            }
+        }
+
+        if settings.natural_regeneration && saturation.0 > 0.0 && can_heal && food.0 >= 20 {
+            timer.0 += 1;
+
+            if timer.0 >= 10 {
+                let amount = saturation.0.min(6.0);
+                health.0 = (health.0 + amount / 6.0).min(max_health);
+                exhaustion.add(amount);
+                timer.0 = 0;
+            }
+        } else if settings.natural_regeneration && food.0 >= 18 && can_heal {
+            timer.0 += 1;
+
+            if timer.0 >= 80 {
+                health.0 = (health.0 + 1.0).min(max_health);
+                exhaustion.add(6.0);
+                timer.0 = 0;
+            }
+        } else if food.0 <= 0 {
+            timer.0 += 1;
+
+            if timer.0 >= 80 {
+                if health.0 > 10.0
+                    || difficulty == Difficulty::Hard
+                    || (health.0 > 1.0 && difficulty == Difficulty::Normal)
+                {
+                    damage_events.send(DamageEvent {
+                        target: entity,
+                        amount: 1.0,
+                        source: DamageSource::new(ident!("starve")),
+                    });
+                }
+
+                timer.0 = 0;
+            }
+        } else if timer.0 != 0 {
+            timer.0 = 0;
+        }
+    }
+}
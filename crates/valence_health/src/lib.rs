@@ -0,0 +1,103 @@
+#![doc = include_str!("../README.md")]
+
+pub mod damage;
+pub mod death;
+pub mod effect;
+pub mod fire;
+pub mod hunger;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+pub use damage::{DamageAppliedEvent, DamageCooldown, DamageEvent, DamageSource, LastDamageSource};
+pub use death::{Dead, DeathEvent, RespawnEvent};
+pub use effect::{EffectBehaviorPlugin, StatusEffectBehavior, StatusEffectBehaviors};
+pub use fire::FireTicks;
+pub use hunger::{Exhaustion, FoodTickTimer};
+use valence_server::client::{Client, SpawnClientsSet, UpdateClientsSet};
+use valence_server::entity::UpdateTrackedDataSet;
+use valence_server::layer::UpdateLayersPreClientSet;
+use valence_server::{Difficulty, EventLoopUpdate};
+
+pub struct HealthPlugin;
+
+/// The [`SystemSet`] in [`PostUpdate`] where [`DamageEvent`]s are applied and
+/// deaths are detected. Systems that send [`DamageEvent`]s should run _before_
+/// this. Systems that read [`DamageAppliedEvent`]s or [`DeathEvent`]s in the
+/// same tick should run _after_ this.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DamageSet;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HealthSettings>()
+            .add_event::<DamageEvent>()
+            .add_event::<DamageAppliedEvent>()
+            .add_event::<DeathEvent>()
+            .add_event::<RespawnEvent>()
+            .configure_sets(
+                PostUpdate,
+                DamageSet
+                    .before(UpdateTrackedDataSet)
+                    .before(UpdateLayersPreClientSet)
+                    .before(UpdateClientsSet),
+            )
+            .add_systems(PreUpdate, init_new_clients.after(SpawnClientsSet))
+            .add_systems(EventLoopUpdate, hunger::add_movement_exhaustion)
+            .add_systems(
+                PostUpdate,
+                (
+                    damage::tick_damage_cooldowns,
+                    hunger::tick_hunger,
+                    fire::tick_fire,
+                    damage::apply_damage,
+                    effect::sync_player_absorption,
+                    death::detect_deaths,
+                    death::handle_respawn_requests,
+                    death::respawn_for_viewers,
+                )
+                    .chain()
+                    .in_set(DamageSet),
+            );
+    }
+}
+
+/// Global configuration for health, hunger and damage.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct HealthSettings {
+    /// Whether players with enough food regenerate health over time. This is
+    /// the `naturalRegeneration` game rule.
+    pub natural_regeneration: bool,
+    /// The difficulty used to scale damage taken by players and to decide how
+    /// far starvation can lower their health.
+    pub difficulty: Difficulty,
+    /// Whether death messages are broadcast to everyone who can see the dying
+    /// player. This is the `showDeathMessages` game rule. The dying player
+    /// always sees the message on their respawn screen.
+    pub show_death_messages: bool,
+    /// The number of ticks after a full hit during which an entity only takes
+    /// damage exceeding that hit. See [`DamageCooldown`].
+    pub invulnerability_ticks: u32,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            natural_regeneration: true,
+            difficulty: Difficulty::Normal,
+            show_death_messages: true,
+            invulnerability_ticks: 10,
+        }
+    }
+}
+
+fn init_new_clients(clients: Query<Entity, Added<Client>>, mut commands: Commands) {
+    for entity in &clients {
+        commands.entity(entity).insert((
+            Exhaustion::default(),
+            FoodTickTimer::default(),
+            DamageCooldown::default(),
+            LastDamageSource::default(),
+            FireTicks::default(),
+        ));
+    }
+}
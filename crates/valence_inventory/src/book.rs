@@ -0,0 +1,244 @@
+//! Written books and books and quills.
+//!
+//! Send an [`OpenBookEvent`] to show a [`WrittenBook`] to a client without
+//! the book being in their inventory. When a client edits or signs a book and
+//! quill, the item is updated and a [`BookEditEvent`] is sent.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_inventory::book::*;
+//! # use valence_server::client::Client;
+//! fn show_rules(clients: Query<Entity, Added<Client>>, mut events: EventWriter<OpenBookEvent>) {
+//!     for client in &clients {
+//!         events.send(OpenBookEvent {
+//!             client,
+//!             book: WrittenBook::new(vec!["Be nice!".into(), "Have fun!".into()]),
+//!         });
+//!     }
+//! }
+//! ```
+
+use std::borrow::Cow;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_server::client::{Client, FlushPacketsSet, Username};
+use valence_server::event_loop::{EventLoopPreUpdate, PacketEvent};
+use valence_server::nbt::{compound, List};
+use valence_server::protocol::packets::play::{
+    BookUpdateC2s, OpenWrittenBookS2c, ScreenHandlerSlotUpdateS2c,
+};
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::{Hand, ItemKind, ItemStack, Text};
+
+use crate::player_inventory::PlayerInventory;
+use crate::{ClientInventoryState, HeldItem, Inventory, UpdateInventoriesSet};
+
+pub(crate) struct BookPlugin;
+
+impl Plugin for BookPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BookSettings>()
+            .add_event::<OpenBookEvent>()
+            .add_event::<BookEditEvent>()
+            .add_systems(EventLoopPreUpdate, handle_book_update)
+            .add_systems(
+                PostUpdate,
+                open_books
+                    .after(UpdateInventoriesSet)
+                    .before(FlushPacketsSet),
+            );
+    }
+}
+
+/// The limits of books edited by clients. Edits breaking them are rejected.
+/// The defaults are the limits of the vanilla client.
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct BookSettings {
+    pub max_pages: usize,
+    /// The maximum number of characters on a page.
+    pub max_page_chars: usize,
+    /// The maximum number of characters in the title of a signed book.
+    pub max_title_chars: usize,
+}
+
+impl Default for BookSettings {
+    fn default() -> Self {
+        Self {
+            max_pages: 100,
+            max_page_chars: 1024,
+            max_title_chars: 16,
+        }
+    }
+}
+
+/// The contents of a written book.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct WrittenBook {
+    pub title: String,
+    pub author: String,
+    pub pages: Vec<Text>,
+}
+
+impl WrittenBook {
+    pub fn new(pages: Vec<Text>) -> Self {
+        Self {
+            title: String::new(),
+            author: String::new(),
+            pages,
+        }
+    }
+
+    /// Returns a written book item with the contents of this book.
+    pub fn to_item_stack(&self) -> ItemStack {
+        let pages = self.pages.iter().cloned().map(String::from).collect();
+
+        ItemStack::new(
+            ItemKind::WrittenBook,
+            1,
+            Some(compound! {
+                "title" => self.title.clone(),
+                "author" => self.author.clone(),
+                "pages" => List::String(pages),
+                "resolved" => true,
+            }),
+        )
+    }
+}
+
+/// Shows a [`WrittenBook`] to a client.
+///
+/// The book is put into the client's main hand for a moment and the item in
+/// the hand is restored after the book is opened. The client's inventory
+/// isn't changed.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct OpenBookEvent {
+    pub client: Entity,
+    pub book: WrittenBook,
+}
+
+/// An event sent when a client edits or signs a book and quill. The item has
+/// already been updated.
+#[derive(Event, Clone, PartialEq, Eq, Debug)]
+pub struct BookEditEvent {
+    pub client: Entity,
+    /// The slot of the book in the player's inventory.
+    pub slot: u16,
+    pub pages: Vec<String>,
+    /// The title of the book if it was signed, which turns it into a written
+    /// book.
+    pub title: Option<String>,
+}
+
+fn open_books(
+    mut events: EventReader<OpenBookEvent>,
+    mut clients: Query<(&mut Client, &Inventory, &HeldItem, &ClientInventoryState)>,
+) {
+    for event in events.read() {
+        let Ok((mut client, inventory, held_item, inv_state)) = clients.get_mut(event.client)
+        else {
+            continue;
+        };
+
+        let slot_update = |stack| ScreenHandlerSlotUpdateS2c {
+            window_id: 0,
+            state_id: VarInt(inv_state.state_id.0),
+            slot_idx: held_item.slot() as i16,
+            slot_data: stack,
+        };
+
+        client.write_packet(&slot_update(Cow::Owned(event.book.to_item_stack())));
+        client.write_packet(&OpenWrittenBookS2c { hand: Hand::Main });
+        client.write_packet(&slot_update(Cow::Borrowed(
+            inventory.slot(held_item.slot()),
+        )));
+    }
+}
+
+fn handle_book_update(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Inventory, &mut ClientInventoryState, &Username)>,
+    settings: Res<BookSettings>,
+    mut events: EventWriter<BookEditEvent>,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<BookUpdateC2s>() else {
+            continue;
+        };
+
+        let Ok((mut inventory, mut inv_state, username)) = clients.get_mut(packet.client) else {
+            continue;
+        };
+
+        let slot = match pkt.slot.0 {
+            0..=8 => PlayerInventory::hotbar_to_slot(pkt.slot.0 as u8),
+            40 => PlayerInventory::SLOT_OFFHAND,
+            _ => continue,
+        };
+
+        let stack = inventory.slot(slot);
+
+        if stack.item != ItemKind::WritableBook {
+            continue;
+        }
+
+        let title = pkt.title.map(|title| title.trim().to_owned());
+
+        let valid = !inventory.readonly
+            && pkt.entries.len() <= settings.max_pages
+            && pkt
+                .entries
+                .iter()
+                .all(|page| page.chars().count() <= settings.max_page_chars)
+            && title.as_ref().is_none_or(|title| {
+                !title.is_empty() && title.chars().count() <= settings.max_title_chars
+            });
+
+        if !valid {
+            // The client has already changed its copy of the book.
+            inventory.changed |= 1 << slot;
+            continue;
+        }
+
+        let pages: Vec<String> = pkt.entries.iter().map(|page| page.to_string()).collect();
+        let mut nbt = stack.nbt.clone().unwrap_or_default();
+
+        let new_stack = match &title {
+            Some(title) => {
+                let pages = pages
+                    .iter()
+                    .map(|page| Text::text(page.clone()).into())
+                    .collect();
+
+                nbt.insert("title", title.clone());
+                nbt.insert("author", username.0.clone());
+                nbt.insert("pages", List::String(pages));
+
+                ItemStack::new(ItemKind::WrittenBook, 1, Some(nbt))
+            }
+            None => {
+                if pages.is_empty() {
+                    nbt.remove("pages");
+                } else {
+                    nbt.insert("pages", List::String(pages.clone()));
+                }
+
+                let nbt = (!nbt.is_empty()).then_some(nbt);
+
+                // The client already shows the new pages.
+                inv_state.slots_changed |= 1 << slot;
+
+                ItemStack::new(ItemKind::WritableBook, stack.count, nbt)
+            }
+        };
+
+        inventory.set_slot(slot, new_stack);
+
+        events.send(BookEditEvent {
+            client: packet.client,
+            slot,
+            pages,
+            title,
+        });
+    }
+}
@@ -0,0 +1,177 @@
+//! Durability of tools, weapons and armor.
+//!
+//! Send a [`DamageItemEvent`] to wear down an item in a client's inventory.
+//! The Unbreaking enchantment prevents some of the damage, and items with the
+//! `Unbreakable` tag or without durability are not affected. Clients in
+//! creative mode never damage their items.
+//!
+//! An item breaks once its damage reaches [`ItemKind::max_durability`]. It is
+//! then removed from the inventory, the break sound and animation are played
+//! and an [`ItemBreakEvent`] is sent.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_inventory::durability::DamageItemEvent;
+//! # use valence_inventory::HeldItem;
+//! # use valence_server::client::Client;
+//! // Wears down the held item of every client by one point each tick.
+//! fn wear_down(
+//!     clients: Query<(Entity, &HeldItem), With<Client>>,
+//!     mut events: EventWriter<DamageItemEvent>,
+//! ) {
+//!     for (client, held_item) in &clients {
+//!         events.send(DamageItemEvent {
+//!             client,
+//!             slot: held_item.slot(),
+//!             amount: 1,
+//!         });
+//!     }
+//! }
+//! ```
+//!
+//! [`ItemKind::max_durability`]: valence_server::ItemKind::max_durability
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use rand::Rng;
+use valence_server::client::Client;
+use valence_server::enchantment;
+use valence_server::entity::{EntityStatus, EntityStatuses};
+use valence_server::{GameMode, ItemStack};
+
+use crate::player_inventory::PlayerInventory;
+use crate::{update_player_inventories, HeldItem, Inventory, UpdateInventoriesSet};
+
+pub(crate) struct DurabilityPlugin;
+
+impl Plugin for DurabilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageItemEvent>()
+            .add_event::<ItemBreakEvent>()
+            .add_systems(
+                PostUpdate,
+                damage_items
+                    .in_set(UpdateInventoriesSet)
+                    .before(update_player_inventories),
+            );
+    }
+}
+
+/// Send this event to damage the item in a slot of a client's player
+/// inventory.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DamageItemEvent {
+    pub client: Entity,
+    pub slot: u16,
+    /// The amount of durability to remove, before Unbreaking.
+    pub amount: u32,
+}
+
+/// Sent when an item in a client's player inventory runs out of durability
+/// and breaks.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct ItemBreakEvent {
+    pub client: Entity,
+    pub slot: u16,
+    /// The item that broke, as it was before breaking.
+    pub stack: ItemStack,
+}
+
+/// Adds `amount` damage to an item stack, of which the Unbreaking enchantment
+/// prevents some. Returns `true` if the item ran out of durability and should
+/// break.
+///
+/// Unbreakable items and items without durability are never damaged.
+pub fn damage_stack<R: Rng + ?Sized>(stack: &mut ItemStack, amount: u32, rng: &mut R) -> bool {
+    let max_durability = i32::from(stack.item.max_durability());
+
+    if max_durability == 0 || stack.is_unbreakable() {
+        return false;
+    }
+
+    let lost = (0..amount)
+        .filter(|_| !enchantment::prevents_durability_loss(stack, rng))
+        .count() as i32;
+
+    if lost == 0 {
+        return false;
+    }
+
+    let damage = stack.damage() + lost;
+    stack.set_damage(damage);
+
+    damage >= max_durability
+}
+
+/// Returns the status which plays the break animation of an item in a slot
+/// of the player inventory, if the item is visible on the player.
+fn break_status(slot: u16, held_item: Option<&HeldItem>) -> Option<EntityStatus> {
+    Some(match slot {
+        PlayerInventory::SLOT_HEAD => EntityStatus::BreakHead,
+        PlayerInventory::SLOT_CHEST => EntityStatus::BreakChest,
+        PlayerInventory::SLOT_LEGS => EntityStatus::BreakLegs,
+        PlayerInventory::SLOT_FEET => EntityStatus::BreakFeet,
+        PlayerInventory::SLOT_OFFHAND => EntityStatus::BreakOffhand,
+        _ if held_item.is_some_and(|held| held.slot() == slot) => EntityStatus::BreakMainhand,
+        _ => return None,
+    })
+}
+
+fn damage_items(
+    mut events: EventReader<DamageItemEvent>,
+    mut clients: Query<(
+        &mut Inventory,
+        &mut Client,
+        Option<&HeldItem>,
+        Option<&GameMode>,
+        Option<&mut EntityStatuses>,
+    )>,
+    mut break_events: EventWriter<ItemBreakEvent>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for event in events.read() {
+        let Ok((mut inventory, mut client, held_item, game_mode, statuses)) =
+            clients.get_mut(event.client)
+        else {
+            continue;
+        };
+
+        if game_mode == Some(&GameMode::Creative) || event.slot >= inventory.slot_count() {
+            continue;
+        }
+
+        let mut stack = inventory.slot(event.slot).clone();
+
+        if stack.is_empty() || stack.item.max_durability() == 0 {
+            continue;
+        }
+
+        if !damage_stack(&mut stack, event.amount, &mut rng) {
+            if stack != *inventory.slot(event.slot) {
+                inventory.set_slot(event.slot, stack);
+            }
+
+            continue;
+        }
+
+        let broken = inventory.replace_slot(event.slot, ItemStack::EMPTY);
+
+        // The clients play the break sound and show the item particles when they
+        // receive the status. The client breaking the item must receive it before
+        // the slot is cleared, so it is sent right away.
+        if let Some(status) = break_status(event.slot, held_item) {
+            client.trigger_status(status);
+
+            if let Some(mut statuses) = statuses {
+                statuses.trigger(status);
+            }
+        }
+
+        break_events.send(ItemBreakEvent {
+            client: event.client,
+            slot: event.slot,
+            stack: broken,
+        });
+    }
+}
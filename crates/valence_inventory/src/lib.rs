@@ -1,6 +1,7 @@
 #![doc = include_str!("../README.md")]
 
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::iter::FusedIterator;
 use std::num::Wrapping;
 use std::ops::Range;
@@ -10,9 +11,12 @@ use bevy_ecs::prelude::*;
 use derive_more::{Deref, DerefMut};
 use player_inventory::PlayerInventory;
 use tracing::{debug, warn};
+use transaction::{ClickContext, ItemLocation, TransactionEvents};
 use valence_server::client::{Client, FlushPacketsSet, SpawnClientsSet};
 use valence_server::event_loop::{EventLoopPreUpdate, PacketEvent};
 use valence_server::interact_block::InteractBlockEvent;
+use valence_server::interaction::InteractionEventSet;
+use valence_server::item_cooldown::HandItems;
 pub use valence_server::protocol::packets::play::click_slot_c2s::{ClickMode, SlotChange};
 use valence_server::protocol::packets::play::open_screen_s2c::WindowType;
 pub use valence_server::protocol::packets::play::player_action_c2s::PlayerAction;
@@ -25,7 +29,14 @@ use valence_server::protocol::{VarInt, WritePacket};
 use valence_server::text::IntoText;
 use valence_server::{GameMode, Hand, ItemKind, ItemStack, Text};
 
+pub mod book;
+pub mod durability;
+pub mod menu;
+pub mod merchant;
 pub mod player_inventory;
+pub mod screen;
+pub mod stacking;
+pub mod transaction;
 mod validate;
 
 pub struct InventoryPlugin;
@@ -42,11 +53,17 @@ impl Plugin for InventoryPlugin {
                 update_client_on_close_inventory.before(update_open_inventories),
                 update_player_selected_slot,
                 update_open_inventories,
+                merchant::update_trade_offers.after(update_open_inventories),
                 update_player_inventories,
                 update_cursor_item,
             )
-                .before(FlushPacketsSet),
+                .in_set(UpdateInventoriesSet),
         )
+        .add_systems(
+            PostUpdate,
+            merchant::update_merchant_results.before(UpdateInventoriesSet),
+        )
+        .configure_sets(PostUpdate, UpdateInventoriesSet.before(FlushPacketsSet))
         .add_systems(
             EventLoopPreUpdate,
             (
@@ -56,16 +73,43 @@ impl Plugin for InventoryPlugin {
                 handle_close_handled_screen,
                 handle_player_actions,
                 resync_readonly_inventory_after_block_interaction,
+                merchant::handle_select_merchant_trade,
+                merchant::handle_merchant_result_clicks,
+                merchant::return_merchant_inputs,
+                update_hand_items
+                    .after(handle_update_selected_slot)
+                    .after(handle_click_slot)
+                    .after(handle_creative_inventory_action)
+                    .before(InteractionEventSet),
             ),
         )
         .init_resource::<InventorySettings>()
+        .init_resource::<CustomClickSlots>()
         .add_event::<ClickSlotEvent>()
         .add_event::<DropItemStackEvent>()
         .add_event::<CreativeInventoryActionEvent>()
-        .add_event::<UpdateSelectedSlotEvent>();
+        .add_event::<UpdateSelectedSlotEvent>()
+        .add_event::<merchant::TradeEvent>()
+        .add_plugins((
+            screen::ScreenPlugin,
+            book::BookPlugin,
+            durability::DurabilityPlugin,
+            menu::MenuPlugin,
+            transaction::TransactionPlugin,
+        ));
+
+        // Trades are executed by the merchant systems.
+        app.world_mut()
+            .resource_mut::<CustomClickSlots>()
+            .insert((InventoryKind::Merchant, merchant::SLOT_RESULT));
     }
 }
 
+/// The [`SystemSet`] in [`PostUpdate`] where changes to inventories are sent to
+/// clients.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct UpdateInventoriesSet;
+
 #[derive(Debug, Clone, Component)]
 pub struct Inventory {
     title: Text,
@@ -833,7 +877,8 @@ fn update_client_on_close_inventory(
     }
 }
 
-// TODO: make this event user friendly.
+/// A click of a client as it was sent. See [`transaction`] for events that
+/// describe what the click changed.
 #[derive(Event, Clone, Debug)]
 pub struct ClickSlotEvent {
     pub client: Entity,
@@ -846,6 +891,14 @@ pub struct ClickSlotEvent {
     pub carried_item: ItemStack,
 }
 
+/// Items a client dropped, which should be spawned in the world.
+///
+/// Drops from clicks in inventories are sent in [`EventLoopPostUpdate`] once
+/// their [transaction](transaction::Transaction) can no longer be cancelled,
+/// and other drops in [`EventLoopPreUpdate`]. Either way they arrive before
+/// [`Update`].
+///
+/// [`EventLoopPostUpdate`]: valence_server::event_loop::EventLoopPostUpdate
 #[derive(Event, Clone, Debug)]
 pub struct DropItemStackEvent {
     pub client: Entity,
@@ -853,6 +906,7 @@ pub struct DropItemStackEvent {
     pub stack: ItemStack,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_click_slot(
     mut packets: EventReader<PacketEvent>,
     mut clients: Query<(
@@ -863,8 +917,10 @@ fn handle_click_slot(
         &mut CursorItem,
     )>,
     mut inventories: Query<&mut Inventory, Without<Client>>,
-    mut drop_item_stack_events: EventWriter<DropItemStackEvent>,
     mut click_slot_events: EventWriter<ClickSlotEvent>,
+    mut transactions: TransactionEvents,
+    custom_slots: Res<CustomClickSlots>,
+    menus: Query<(), With<menu::Menu>>,
 ) {
     for packet in packets.read() {
         let Some(pkt) = packet.decode::<ClickSlotC2s>() else {
@@ -883,6 +939,27 @@ fn handle_click_slot(
             .as_ref()
             .and_then(|open| inventories.get_mut(open.entity).ok());
 
+        let clicked_slot = match &open_inv {
+            Some(inv) if (0..inv.slot_count() as i16).contains(&pkt.slot_idx) => {
+                Some((inv.kind, pkt.slot_idx as u16))
+            }
+            None if pkt.slot_idx >= 0 => Some((InventoryKind::Player, pkt.slot_idx as u16)),
+            _ => None,
+        };
+
+        if clicked_slot.is_some_and(|slot| custom_slots.contains(&slot)) {
+            // Another plugin is responsible for this click.
+            continue;
+        }
+
+        if open_inventory
+            .as_ref()
+            .is_some_and(|open| menus.contains(open.entity))
+        {
+            // Clicks in menus are handled by the menu plugin.
+            continue;
+        }
+
         if let Err(e) = validate::validate_click_slot_packet(
             &pkt,
             &client_inv,
@@ -916,11 +993,13 @@ fn handle_click_slot(
             let stack = std::mem::take(&mut cursor_item.0);
 
             if !stack.is_empty() {
-                drop_item_stack_events.send(DropItemStackEvent {
-                    client: packet.client,
-                    from_slot: None,
+                transactions.drop(
+                    packet.client,
+                    ItemLocation::Cursor,
+                    stack.clone(),
+                    None,
                     stack,
-                });
+                );
             }
         } else if pkt.mode == ClickMode::DropKey {
             // The client is dropping an item by pressing the drop key.
@@ -975,6 +1054,8 @@ fn handle_click_slot(
                     let stack = target_inventory.slot(pkt.slot_idx as u16);
 
                     if !stack.is_empty() {
+                        let before = stack.clone();
+
                         let dropped = if entire_stack || stack.count == 1 {
                             target_inventory.replace_slot(pkt.slot_idx as u16, ItemStack::EMPTY)
                         } else {
@@ -987,11 +1068,16 @@ fn handle_click_slot(
                             old_slot
                         };
 
-                        drop_item_stack_events.send(DropItemStackEvent {
-                            client: packet.client,
-                            from_slot: Some(pkt.slot_idx as u16),
-                            stack: dropped,
-                        });
+                        transactions.drop(
+                            packet.client,
+                            ItemLocation::Slot {
+                                inventory: open_inventory.entity,
+                                slot: pkt.slot_idx as u16,
+                            },
+                            before,
+                            Some(pkt.slot_idx as u16),
+                            dropped,
+                        );
                     }
                 } else {
                     // The player is dropping an item from their inventory.
@@ -1013,6 +1099,8 @@ fn handle_click_slot(
                     let stack = client_inv.slot(slot_id);
 
                     if !stack.is_empty() {
+                        let before = stack.clone();
+
                         let dropped = if entire_stack || stack.count == 1 {
                             client_inv.replace_slot(slot_id, ItemStack::EMPTY)
                         } else {
@@ -1024,11 +1112,16 @@ fn handle_click_slot(
                             old_slot
                         };
 
-                        drop_item_stack_events.send(DropItemStackEvent {
-                            client: packet.client,
-                            from_slot: Some(slot_id),
-                            stack: dropped,
-                        });
+                        transactions.drop(
+                            packet.client,
+                            ItemLocation::Slot {
+                                inventory: packet.client,
+                                slot: slot_id,
+                            },
+                            before,
+                            Some(slot_id),
+                            dropped,
+                        );
                     }
                 }
             } else {
@@ -1052,6 +1145,8 @@ fn handle_click_slot(
                 let stack = client_inv.slot(pkt.slot_idx as u16);
 
                 if !stack.is_empty() {
+                    let before = stack.clone();
+
                     let dropped = if entire_stack || stack.count == 1 {
                         client_inv.replace_slot(pkt.slot_idx as u16, ItemStack::EMPTY)
                     } else {
@@ -1063,11 +1158,16 @@ fn handle_click_slot(
                         old_slot
                     };
 
-                    drop_item_stack_events.send(DropItemStackEvent {
-                        client: packet.client,
-                        from_slot: Some(pkt.slot_idx as u16),
-                        stack: dropped,
-                    });
+                    transactions.drop(
+                        packet.client,
+                        ItemLocation::Slot {
+                            inventory: packet.client,
+                            slot: pkt.slot_idx as u16,
+                        },
+                        before,
+                        Some(pkt.slot_idx as u16),
+                        dropped,
+                    );
                 }
             }
         } else {
@@ -1110,6 +1210,21 @@ fn handle_click_slot(
                     continue;
                 }
 
+                let window = Some((
+                    open_inventory.entity,
+                    target_inventory.kind,
+                    target_inventory.slot_count(),
+                ));
+                let locate = |idx| transaction::window_location(packet.client, window, idx);
+
+                let before = ClickContext {
+                    client: packet.client,
+                    player: &client_inv,
+                    open: Some((open_inventory.entity, &target_inventory)),
+                    cursor: &cursor_item.0,
+                }
+                .snapshot(pkt.slot_changes.iter().filter_map(|slot| locate(slot.idx)));
+
                 let mut new_cursor = pkt.carried_item.clone();
 
                 for slot in pkt.slot_changes.iter() {
@@ -1147,6 +1262,19 @@ fn handle_click_slot(
                 cursor_item.set_if_neq(CursorItem(new_cursor.clone()));
                 inv_state.client_updated_cursor_item = Some(new_cursor);
 
+                transactions.click(
+                    pkt.mode,
+                    pkt.button,
+                    locate(pkt.slot_idx),
+                    before,
+                    &ClickContext {
+                        client: packet.client,
+                        player: &client_inv,
+                        open: Some((open_inventory.entity, &target_inventory)),
+                        cursor: &cursor_item.0,
+                    },
+                );
+
                 if target_inventory.readonly || client_inv.readonly {
                     // resync the target inventory
                     client.write_packet(&InventoryS2c {
@@ -1184,6 +1312,20 @@ fn handle_click_slot(
                     continue;
                 }
 
+                let slot_count = client_inv.slot_count() as i16;
+                let locate = |idx| {
+                    transaction::window_location(packet.client, None, idx)
+                        .filter(|_| idx < slot_count)
+                };
+
+                let before = ClickContext {
+                    client: packet.client,
+                    player: &client_inv,
+                    open: None,
+                    cursor: &cursor_item.0,
+                }
+                .snapshot(pkt.slot_changes.iter().filter_map(|slot| locate(slot.idx)));
+
                 let mut new_cursor = pkt.carried_item.clone();
 
                 for slot in pkt.slot_changes.iter() {
@@ -1207,6 +1349,19 @@ fn handle_click_slot(
                 cursor_item.set_if_neq(CursorItem(new_cursor.clone()));
                 inv_state.client_updated_cursor_item = Some(new_cursor);
 
+                transactions.click(
+                    pkt.mode,
+                    pkt.button,
+                    locate(pkt.slot_idx),
+                    before,
+                    &ClickContext {
+                        client: packet.client,
+                        player: &client_inv,
+                        open: None,
+                        cursor: &cursor_item.0,
+                    },
+                );
+
                 if client_inv.readonly {
                     // resync the client inventory
                     client.write_packet(&InventoryS2c {
@@ -1364,7 +1519,11 @@ fn resync_readonly_inventory_after_block_interaction(
     }
 }
 
-// TODO: make this event user friendly.
+/// A client in creative mode set a slot of their inventory to `clicked_item`,
+/// or dropped it if `slot` is -1.
+///
+/// The same change is described by the [`transaction`] events, which can also
+/// cancel it.
 #[derive(Event, Clone, Debug)]
 pub struct CreativeInventoryActionEvent {
     pub client: Entity,
@@ -1381,7 +1540,7 @@ fn handle_creative_inventory_action(
         &GameMode,
     )>,
     mut inv_action_events: EventWriter<CreativeInventoryActionEvent>,
-    mut drop_item_stack_events: EventWriter<DropItemStackEvent>,
+    mut transactions: TransactionEvents,
 ) {
     for packet in packets.read() {
         if let Some(pkt) = packet.decode::<CreativeInventoryActionC2s>() {
@@ -1400,11 +1559,13 @@ fn handle_creative_inventory_action(
                 let stack = pkt.clicked_item.clone();
 
                 if !stack.is_empty() {
-                    drop_item_stack_events.send(DropItemStackEvent {
-                        client: packet.client,
-                        from_slot: None,
+                    transactions.drop(
+                        packet.client,
+                        ItemLocation::Creative,
+                        ItemStack::EMPTY,
+                        None,
                         stack,
-                    });
+                    );
                 }
                 continue;
             }
@@ -1415,7 +1576,12 @@ fn handle_creative_inventory_action(
             }
 
             // Set the slot without marking it as changed.
-            inventory.slots[pkt.slot as usize] = pkt.clicked_item.clone();
+            let before = std::mem::replace(
+                &mut inventory.slots[pkt.slot as usize],
+                pkt.clicked_item.clone(),
+            );
+
+            transactions.creative_set(packet.client, pkt.slot as u16, before, &pkt.clicked_item);
 
             inv_state.state_id += 1;
 
@@ -1484,6 +1650,16 @@ fn handle_update_selected_slot(
     }
 }
 
+/// Copies the kinds of the items in the client's hands to [`HandItems`].
+fn update_hand_items(mut clients: Query<(&Inventory, &HeldItem, &mut HandItems)>) {
+    for (inventory, held_item, mut hand_items) in &mut clients {
+        hand_items.set_if_neq(HandItems {
+            main_hand: inventory.slot(held_item.slot()).item,
+            off_hand: inventory.slot(PlayerInventory::SLOT_OFFHAND).item,
+        });
+    }
+}
+
 /// Convert a slot that is outside a target inventory's range to a slot that is
 /// inside the player's inventory.
 #[doc(hidden)]
@@ -1493,7 +1669,7 @@ pub fn convert_to_player_slot_id(target_kind: InventoryKind, slot_id: u16) -> u1
     slot_id - offset + 9
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum InventoryKind {
     Generic9x1,
     Generic9x2,
@@ -1634,6 +1810,21 @@ impl Default for InventorySettings {
     }
 }
 
+/// Slots of inventories that clicks are not handled for by [`InventoryPlugin`],
+/// as `(kind, slot)` pairs. Clicks on these slots are left to other plugins,
+/// which read [`ClickSlotC2s`] packets themselves and are responsible for
+/// keeping the client in sync.
+///
+/// These clicks skip the validation done for other clicks, since the slot
+/// changes and carried item in the packet are only the client's prediction of
+/// what a click the server decides does. Handlers should ignore them and
+/// resync the client from the server's state instead.
+///
+/// Slots of the player's inventory only count when no other inventory is
+/// open.
+#[derive(Resource, Clone, Default, Debug, Deref, DerefMut)]
+pub struct CustomClickSlots(pub HashSet<(InventoryKind, u16)>);
+
 #[cfg(test)]
 mod tests {
     use super::*;
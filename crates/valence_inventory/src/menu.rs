@@ -0,0 +1,344 @@
+//! Menus made of buttons, like the server selectors of minigame servers.
+//!
+//! Give an [`Inventory`] a [`Menu`] component and open it for a client with
+//! [`OpenInventory`]. The buttons of the menu are laid out over the slots of
+//! the inventory, and over several pages if they don't fit. Clients can't move
+//! any items while a menu is open. Clicking a button sends a
+//! [`MenuClickEvent`] and runs the button's callback, if it has one. Changing
+//! the `Menu` updates the inventory of every client viewing it.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_inventory::menu::*;
+//! # use valence_inventory::*;
+//! # use valence_server::{ItemKind, ItemStack};
+//! fn open_team_selector(mut commands: Commands, client: Entity) {
+//!     let menu = commands
+//!         .spawn((
+//!             Inventory::with_title(InventoryKind::Generic9x1, "Select a team"),
+//!             Menu::new(vec![
+//!                 MenuButton::new(ItemStack::new(ItemKind::RedWool, 1, None)),
+//!                 MenuButton::new(ItemStack::new(ItemKind::BlueWool, 1, None)),
+//!             ]),
+//!         ))
+//!         .id();
+//!
+//!     commands.entity(client).insert(OpenInventory::new(menu));
+//! }
+//!
+//! fn join_team(mut events: EventReader<MenuClickEvent>) {
+//!     for event in events.read() {
+//!         let team = if event.button == 0 { "red" } else { "blue" };
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemId;
+use valence_server::client::Client;
+use valence_server::event_loop::{EventLoopPreUpdate, PacketEvent};
+use valence_server::protocol::packets::play::{ClickSlotC2s, InventoryS2c};
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::{ItemKind, ItemStack};
+
+use crate::player_inventory::PlayerInventory;
+use crate::{
+    ClickMode, ClientInventoryState, CursorItem, Inventory, InventoryKind, OpenInventory,
+    UpdateInventoriesSet,
+};
+
+pub(crate) struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuClickEvent>()
+            .add_systems(EventLoopPreUpdate, handle_menu_clicks)
+            .add_systems(PostUpdate, update_menus.before(UpdateInventoriesSet));
+    }
+}
+
+/// The buttons of a menu. Must be on an entity with an [`Inventory`], which
+/// is made read-only and whose slots are replaced with the buttons.
+///
+/// All clients viewing the menu see the same page.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Menu {
+    /// The buttons, laid out over the slots row by row. Buttons with an empty
+    /// stack leave their slot empty.
+    pub buttons: Vec<MenuButton>,
+    /// The button that turns to the previous page, shown in the first slot of
+    /// the last row when the buttons don't fit on one page.
+    pub previous_page: ItemStack,
+    /// The button that turns to the next page, shown in the last slot of the
+    /// last row when the buttons don't fit on one page.
+    pub next_page: ItemStack,
+    page: usize,
+}
+
+impl Menu {
+    pub fn new(buttons: Vec<MenuButton>) -> Self {
+        Self {
+            buttons,
+            previous_page: named(ItemKind::Arrow, "Previous page"),
+            next_page: named(ItemKind::Arrow, "Next page"),
+            page: 0,
+        }
+    }
+
+    /// The page shown to clients, starting at 0.
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Turns to a page. Pages after the last page show the last page.
+    pub fn set_page(&mut self, page: usize) {
+        self.page = page;
+    }
+
+    /// The number of pages the buttons need in an inventory of the given
+    /// kind.
+    pub fn page_count(&self, kind: InventoryKind) -> usize {
+        let layout = Layout::new(kind, self.buttons.len());
+        self.buttons.len().div_ceil(layout.page_size()).max(1)
+    }
+
+    /// Returns the index of the button in a slot of the current page.
+    pub fn button_at(&self, kind: InventoryKind, slot: u16) -> Option<usize> {
+        let layout = Layout::new(kind, self.buttons.len());
+
+        if !layout.content.contains(&slot) {
+            return None;
+        }
+
+        let idx =
+            self.current_page(kind) * layout.page_size() + usize::from(slot - layout.content.start);
+
+        (idx < self.buttons.len()).then_some(idx)
+    }
+
+    fn current_page(&self, kind: InventoryKind) -> usize {
+        self.page.min(self.page_count(kind) - 1)
+    }
+
+    /// Returns the stack shown in a slot of the current page.
+    fn stack_at(&self, kind: InventoryKind, slot: u16) -> ItemStack {
+        let layout = Layout::new(kind, self.buttons.len());
+        let page = self.current_page(kind);
+
+        match layout.navigation {
+            Some((previous, _)) if slot == previous && page > 0 => self.previous_page.clone(),
+            Some((_, next)) if slot == next && page + 1 < self.page_count(kind) => {
+                self.next_page.clone()
+            }
+            _ => self
+                .button_at(kind, slot)
+                .map_or(ItemStack::EMPTY, |idx| self.buttons[idx].stack.clone()),
+        }
+    }
+}
+
+/// A button of a [`Menu`].
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct MenuButton {
+    pub stack: ItemStack,
+    /// A system run with the [`MenuClickEvent`] when the button is clicked.
+    pub on_click: Option<SystemId<MenuClickEvent>>,
+}
+
+impl MenuButton {
+    pub fn new(stack: ItemStack) -> Self {
+        Self {
+            stack,
+            on_click: None,
+        }
+    }
+
+    /// Sets the system that is run when the button is clicked. Register it
+    /// with [`World::register_system`].
+    pub fn on_click(mut self, system: SystemId<MenuClickEvent>) -> Self {
+        self.on_click = Some(system);
+        self
+    }
+}
+
+/// An event sent when a client clicks a button of a [`Menu`].
+#[derive(Event, Clone, PartialEq, Eq, Debug)]
+pub struct MenuClickEvent {
+    pub client: Entity,
+    /// The entity with the [`Menu`].
+    pub menu: Entity,
+    /// The index of the button in [`Menu::buttons`].
+    pub button: usize,
+    pub mode: ClickMode,
+    /// The mouse button or hotbar key of the click, like in
+    /// [`ClickSlotEvent`](crate::ClickSlotEvent).
+    pub mouse_button: i8,
+}
+
+/// Where the buttons and page buttons are shown in an inventory.
+struct Layout {
+    /// The slots showing buttons.
+    content: Range<u16>,
+    /// The slots of the previous and next page buttons.
+    navigation: Option<(u16, u16)>,
+}
+
+impl Layout {
+    fn new(kind: InventoryKind, buttons: usize) -> Self {
+        let slots = kind.slot_count() as u16;
+
+        if buttons <= usize::from(slots) || slots < 3 {
+            return Self {
+                content: 0..slots,
+                navigation: None,
+            };
+        }
+
+        let width = match kind {
+            InventoryKind::Generic3x3 => 3,
+            InventoryKind::Hopper => 5,
+            _ => 9,
+        }
+        .min(slots);
+
+        // The page buttons take the last row. Menus with one row keep the
+        // slots between them.
+        let content = if slots > width {
+            0..slots - width
+        } else {
+            1..slots - 1
+        };
+
+        Self {
+            content,
+            navigation: Some((slots - width, slots - 1)),
+        }
+    }
+
+    fn page_size(&self) -> usize {
+        usize::from(self.content.end - self.content.start)
+    }
+}
+
+fn named(item: ItemKind, name: &str) -> ItemStack {
+    ItemStack::new(item, 1, None).with_custom_name(name.to_owned())
+}
+
+/// Shows the buttons of menus in their inventories.
+fn update_menus(mut menus: Query<(&mut Inventory, Ref<Menu>), Without<Client>>) {
+    for (mut inventory, menu) in &mut menus {
+        if !menu.is_changed() && !inventory.is_changed() {
+            continue;
+        }
+
+        let kind = inventory.kind();
+
+        for slot in 0..inventory.slot_count() {
+            let stack = menu.stack_at(kind, slot);
+
+            if *inventory.slot(slot) != stack {
+                inventory.set_slot(slot, stack);
+            }
+        }
+
+        if !inventory.readonly {
+            inventory.readonly = true;
+        }
+    }
+}
+
+/// Handles clicks of clients viewing a menu. Clicks are not handled by
+/// [`InventoryPlugin`](crate::InventoryPlugin) for menus, so the client's
+/// view of the window is resynced after every click.
+#[allow(clippy::type_complexity)]
+fn handle_menu_clicks(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(
+        &mut Client,
+        &Inventory,
+        &ClientInventoryState,
+        &CursorItem,
+        &OpenInventory,
+    )>,
+    mut menus: Query<(&Inventory, &mut Menu), Without<Client>>,
+    mut events: EventWriter<MenuClickEvent>,
+    mut commands: Commands,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<ClickSlotC2s>() else {
+            continue;
+        };
+
+        let Ok((mut client, player_inv, inv_state, cursor_item, open_inventory)) =
+            clients.get_mut(packet.client)
+        else {
+            continue;
+        };
+
+        let Ok((menu_inv, mut menu)) = menus.get_mut(open_inventory.entity) else {
+            continue;
+        };
+
+        // Undo whatever the client predicted the click to do.
+        let slots: Vec<_> = menu_inv
+            .slot_slice()
+            .iter()
+            .chain(PlayerInventory::SLOTS_MAIN.map(|slot| player_inv.slot(slot)))
+            .cloned()
+            .collect();
+
+        client.write_packet(&InventoryS2c {
+            window_id: inv_state.window_id(),
+            state_id: VarInt(inv_state.state_id().0),
+            slots: Cow::Owned(slots),
+            carried_item: Cow::Borrowed(&cursor_item.0),
+        });
+
+        if pkt.window_id != inv_state.window_id()
+            || !(0..menu_inv.slot_count() as i16).contains(&pkt.slot_idx)
+            || matches!(pkt.mode, ClickMode::Drag | ClickMode::DoubleClick)
+        {
+            continue;
+        }
+
+        let kind = menu_inv.kind();
+        let slot = pkt.slot_idx as u16;
+        let page = menu.current_page(kind);
+
+        match Layout::new(kind, menu.buttons.len()).navigation {
+            Some((previous, _)) if slot == previous && page > 0 => {
+                menu.page = page - 1;
+            }
+            Some((_, next)) if slot == next && page + 1 < menu.page_count(kind) => {
+                menu.page = page + 1;
+            }
+            _ => {
+                let Some(button) = menu
+                    .button_at(kind, slot)
+                    .filter(|&idx| !menu.buttons[idx].stack.is_empty())
+                else {
+                    continue;
+                };
+
+                let event = MenuClickEvent {
+                    client: packet.client,
+                    menu: open_inventory.entity,
+                    button,
+                    mode: pkt.mode,
+                    mouse_button: pkt.button,
+                };
+
+                if let Some(system) = menu.buttons[button].on_click {
+                    commands.run_system_with_input(system, event.clone());
+                }
+
+                events.send(event);
+            }
+        }
+    }
+}
@@ -0,0 +1,605 @@
+//! Trading screens of villagers and other merchants.
+//!
+//! To show a trading screen, give an [`Inventory`] of kind
+//! [`InventoryKind::Merchant`] a [`Merchant`] component and open it for a
+//! client with [`OpenInventory`]. Every client should get their own merchant
+//! inventory, because the items a client puts into the trade slots belong to
+//! them.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_inventory::merchant::*;
+//! # use valence_inventory::*;
+//! # use valence_server::{ItemKind, ItemStack};
+//! fn open_shop(mut commands: Commands, client: Entity) {
+//!     let shop = commands
+//!         .spawn((
+//!             Inventory::with_title(InventoryKind::Merchant, "Shop"),
+//!             Merchant::new(vec![MerchantOffer::new(
+//!                 ItemStack::new(ItemKind::Emerald, 3, None),
+//!                 ItemStack::EMPTY,
+//!                 ItemStack::new(ItemKind::Bread, 6, None),
+//!             )]),
+//!         ))
+//!         .id();
+//!
+//!     commands.entity(client).insert(OpenInventory::new(shop));
+//! }
+//! ```
+
+use std::borrow::Cow;
+
+use bevy_ecs::prelude::*;
+use valence_server::client::Client;
+use valence_server::event_loop::PacketEvent;
+use valence_server::protocol::packets::play::set_trade_offers_s2c::TradeOffer;
+use valence_server::protocol::packets::play::{
+    ClickSlotC2s, CloseHandledScreenC2s, InventoryS2c, SelectMerchantTradeC2s, SetTradeOffersS2c,
+};
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::ItemStack;
+
+use crate::player_inventory::PlayerInventory;
+use crate::stacking::{give_to_player, merge_into, player_space_for};
+use crate::transaction::{ItemLocation, TransactionEvents};
+use crate::{ClickMode, ClientInventoryState, CursorItem, Inventory, InventoryKind, OpenInventory};
+
+/// The slots of the items the client pays with.
+pub const SLOTS_INPUT: [u16; 2] = [0, 1];
+/// The slot of the item the client gets for the selected trade.
+pub const SLOT_RESULT: u16 = 2;
+
+/// The trades offered by an inventory of kind [`InventoryKind::Merchant`].
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Merchant {
+    pub offers: Vec<MerchantOffer>,
+    /// The level shown in the title of the screen, from 1 to 5.
+    pub level: i32,
+    /// The total experience of the merchant. Trading adds the experience of
+    /// the offer.
+    pub experience: i32,
+    /// Whether the level and experience bar are shown. Wandering traders don't
+    /// have them.
+    pub is_regular_villager: bool,
+    /// Whether the merchant tells clients that sold out offers will be
+    /// restocked.
+    pub can_restock: bool,
+    /// The offer the client selected last.
+    selected: Option<usize>,
+}
+
+impl Merchant {
+    pub fn new(offers: Vec<MerchantOffer>) -> Self {
+        Self {
+            offers,
+            level: 1,
+            experience: 0,
+            is_regular_villager: true,
+            can_restock: true,
+            selected: None,
+        }
+    }
+
+    /// The index of the offer the client selected last.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Returns the index of the offer the items in the input slots pay for,
+    /// preferring the selected offer, and whether the input slots are swapped.
+    fn find_offer(&self, first: &ItemStack, second: &ItemStack) -> Option<(usize, bool)> {
+        let matches = |idx: usize| {
+            let offer = self.offers.get(idx)?;
+
+            if offer.is_disabled() {
+                None
+            } else if offer.matches(first, second) {
+                Some((idx, false))
+            } else if offer.matches(second, first) {
+                Some((idx, true))
+            } else {
+                None
+            }
+        };
+
+        self.selected
+            .and_then(matches)
+            .or_else(|| (0..self.offers.len()).find_map(matches))
+    }
+
+    fn to_packet(&self, window_id: u8) -> SetTradeOffersS2c {
+        SetTradeOffersS2c {
+            window_id: VarInt(window_id.into()),
+            trades: self.offers.iter().map(MerchantOffer::to_packet).collect(),
+            villager_level: VarInt(self.level),
+            experience: VarInt(self.experience),
+            is_regular_villager: self.is_regular_villager,
+            can_restock: self.can_restock,
+        }
+    }
+}
+
+/// One trade of a [`Merchant`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct MerchantOffer {
+    /// The first item to pay with, before the price is adjusted.
+    pub input_one: ItemStack,
+    /// The second item to pay with, or [`ItemStack::EMPTY`] if there is none.
+    pub input_two: ItemStack,
+    pub result: ItemStack,
+    /// How many times the offer has been traded. The offer is sold out when
+    /// this reaches `max_uses`.
+    pub uses: i32,
+    pub max_uses: i32,
+    /// The experience the merchant gets for the trade.
+    pub xp: i32,
+    /// Added to the count of the first input, like the discounts of players
+    /// who cured a villager.
+    pub special_price: i32,
+    /// How much the price of the first input rises with `demand`.
+    pub price_multiplier: f32,
+    pub demand: i32,
+}
+
+impl MerchantOffer {
+    pub fn new(input_one: ItemStack, input_two: ItemStack, result: ItemStack) -> Self {
+        Self {
+            input_one,
+            input_two,
+            result,
+            uses: 0,
+            max_uses: 12,
+            xp: 0,
+            special_price: 0,
+            price_multiplier: 0.0,
+            demand: 0,
+        }
+    }
+
+    /// Whether the offer is sold out.
+    pub fn is_disabled(&self) -> bool {
+        self.uses >= self.max_uses
+    }
+
+    /// Returns the first input with the special price and demand applied to
+    /// its count, like the client shows it.
+    pub fn adjusted_input_one(&self) -> ItemStack {
+        if self.input_one.is_empty() {
+            return ItemStack::EMPTY;
+        }
+
+        let count = i32::from(self.input_one.count);
+        let demand_bonus = ((count * self.demand) as f32 * self.price_multiplier)
+            .floor()
+            .max(0.0) as i32;
+        let count = (count + demand_bonus + self.special_price)
+            .clamp(1, i32::from(self.input_one.item.max_stack()));
+
+        self.input_one.clone().with_count(count as i8)
+    }
+
+    /// Returns whether the stacks are enough to pay for the offer.
+    pub fn matches(&self, first: &ItemStack, second: &ItemStack) -> bool {
+        fn accepts(given: &ItemStack, wanted: &ItemStack) -> bool {
+            if wanted.is_empty() {
+                return given.is_empty();
+            }
+
+            given.item == wanted.item
+                && given.count >= wanted.count
+                && (wanted.nbt.is_none() || given.nbt == wanted.nbt)
+        }
+
+        accepts(first, &self.adjusted_input_one()) && accepts(second, &self.input_two)
+    }
+
+    fn to_packet(&self) -> TradeOffer {
+        TradeOffer {
+            input_one: self.input_one.clone(),
+            output_item: self.result.clone(),
+            input_two: self.input_two.clone(),
+            trade_disabled: self.is_disabled(),
+            number_of_trade_uses: self.uses,
+            max_trade_uses: self.max_uses,
+            xp: self.xp,
+            special_price: self.special_price,
+            price_multiplier: self.price_multiplier,
+            demand: self.demand,
+        }
+    }
+}
+
+/// An event sent when a client takes the result of a trade. Taking out the
+/// results of several trades at once, like when shift-clicking, sends one
+/// event per trade.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct TradeEvent {
+    pub client: Entity,
+    /// The entity with the [`Merchant`].
+    pub merchant: Entity,
+    /// The index of the offer in [`Merchant::offers`].
+    pub offer: usize,
+    pub result: ItemStack,
+}
+
+/// Sends the offers of merchants to the clients viewing them.
+pub(crate) fn update_trade_offers(
+    mut clients: Query<(&mut Client, &ClientInventoryState, Ref<OpenInventory>)>,
+    merchants: Query<Ref<Merchant>>,
+) {
+    for (mut client, inv_state, open_inventory) in &mut clients {
+        let Ok(merchant) = merchants.get(open_inventory.entity) else {
+            continue;
+        };
+
+        // The offers are sent after the screen is opened.
+        if open_inventory.is_added() || merchant.is_changed() {
+            client.write_packet(&merchant.to_packet(inv_state.window_id));
+        }
+    }
+}
+
+/// Sets the result slot of merchants to the result of the offer the items in
+/// the input slots pay for.
+pub(crate) fn update_merchant_results(
+    mut merchants: Query<(&mut Inventory, Ref<Merchant>), Without<Client>>,
+) {
+    for (mut inv, merchant) in &mut merchants {
+        if !inv.is_changed() && !merchant.is_changed() {
+            continue;
+        }
+
+        let result = merchant
+            .find_offer(inv.slot(SLOTS_INPUT[0]), inv.slot(SLOTS_INPUT[1]))
+            .map_or(ItemStack::EMPTY, |(idx, _)| {
+                merchant.offers[idx].result.clone()
+            });
+
+        if *inv.slot(SLOT_RESULT) != result {
+            inv.set_slot(SLOT_RESULT, result);
+        }
+    }
+}
+
+/// Handles clients selecting an offer, which moves the items in the input
+/// slots back to the player's inventory and fills them with what the offer
+/// costs.
+pub(crate) fn handle_select_merchant_trade(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Inventory, &OpenInventory), With<Client>>,
+    mut merchants: Query<(&mut Inventory, &mut Merchant), Without<Client>>,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<SelectMerchantTradeC2s>() else {
+            continue;
+        };
+
+        let Ok((mut player_inv, open_inventory)) = clients.get_mut(packet.client) else {
+            continue;
+        };
+
+        let Ok((mut merchant_inv, mut merchant)) = merchants.get_mut(open_inventory.entity) else {
+            continue;
+        };
+
+        let Some(offer) = usize::try_from(pkt.selected_slot.0)
+            .ok()
+            .and_then(|idx| merchant.offers.get(idx))
+            .cloned()
+        else {
+            continue;
+        };
+
+        // The client knows which offer it selected.
+        merchant.bypass_change_detection().selected = Some(pkt.selected_slot.0 as usize);
+
+        if player_inv.readonly || merchant_inv.readonly {
+            continue;
+        }
+
+        let mut cleared = true;
+
+        for slot in SLOTS_INPUT {
+            let stack = merchant_inv.slot(slot).clone();
+
+            if !stack.is_empty() {
+                let rest = give_to_player(&mut player_inv, stack);
+                cleared &= rest.is_empty();
+                merchant_inv.set_slot(slot, rest);
+            }
+        }
+
+        if !cleared {
+            continue;
+        }
+
+        for (slot, wanted) in SLOTS_INPUT
+            .into_iter()
+            .zip([offer.adjusted_input_one(), offer.input_two])
+        {
+            if !wanted.is_empty() {
+                fill_input_slot(&mut player_inv, &mut merchant_inv, slot, &wanted);
+            }
+        }
+    }
+}
+
+/// Moves items like `wanted` from the player's inventory to an input slot,
+/// until the slot holds a full stack.
+fn fill_input_slot(
+    player_inv: &mut Inventory,
+    merchant_inv: &mut Inventory,
+    slot: u16,
+    wanted: &ItemStack,
+) {
+    let max_stack = wanted.item.max_stack();
+
+    for player_slot in PlayerInventory::SLOTS_MAIN {
+        let stack = player_inv.slot(player_slot);
+
+        if stack.is_empty() || stack.item != wanted.item || stack.nbt != wanted.nbt {
+            continue;
+        }
+
+        let current = merchant_inv.slot(slot);
+        let count = if current.is_empty() { 0 } else { current.count };
+        let moved = (max_stack - count).min(stack.count);
+
+        if moved <= 0 {
+            break;
+        }
+
+        let remaining = stack.count - moved;
+        let new = stack.clone().with_count(count + moved);
+
+        player_inv.set_slot(
+            player_slot,
+            if remaining > 0 {
+                new.clone().with_count(remaining)
+            } else {
+                ItemStack::EMPTY
+            },
+        );
+        merchant_inv.set_slot(slot, new);
+    }
+}
+
+/// Handles clicks on the result slot of merchants. The server executes the
+/// trade, so the slot changes in the packet are ignored and the client's view
+/// of the window is resynced afterwards. Cancelling the transaction of a
+/// dropped result gives back the inputs of its trade, but the trade still
+/// counts as used.
+#[allow(clippy::type_complexity)]
+pub(crate) fn handle_merchant_result_clicks(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(
+        &mut Client,
+        &mut Inventory,
+        &ClientInventoryState,
+        &mut CursorItem,
+        &OpenInventory,
+    )>,
+    mut merchants: Query<(&mut Inventory, &mut Merchant), Without<Client>>,
+    mut trade_events: EventWriter<TradeEvent>,
+    mut transactions: TransactionEvents,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<ClickSlotC2s>() else {
+            continue;
+        };
+
+        if pkt.slot_idx != SLOT_RESULT as i16 || pkt.window_id == 0 {
+            continue;
+        }
+
+        let Ok((mut client, mut player_inv, inv_state, mut cursor_item, open_inventory)) =
+            clients.get_mut(packet.client)
+        else {
+            continue;
+        };
+
+        if pkt.window_id != inv_state.window_id {
+            continue;
+        }
+
+        let Ok((mut merchant_inv, mut merchant)) = merchants.get_mut(open_inventory.entity) else {
+            continue;
+        };
+
+        if merchant_inv.kind() != InventoryKind::Merchant {
+            continue;
+        }
+
+        let max_trades = match pkt.mode {
+            ClickMode::Click | ClickMode::Hotbar => 1,
+            ClickMode::ShiftClick => 64,
+            ClickMode::DropKey if pkt.button == 1 => 64,
+            ClickMode::DropKey => 1,
+            _ => 0,
+        };
+
+        let result = merchant_inv.slot(SLOT_RESULT).clone();
+        let mut traded = false;
+
+        for _ in 0..max_trades {
+            if player_inv.readonly || merchant_inv.readonly || result.is_empty() {
+                break;
+            }
+
+            let Some((idx, swapped)) = merchant.find_offer(
+                merchant_inv.slot(SLOTS_INPUT[0]),
+                merchant_inv.slot(SLOTS_INPUT[1]),
+            ) else {
+                break;
+            };
+
+            // Trading continues only while the result stays the same.
+            if merchant.offers[idx].result != result {
+                break;
+            }
+
+            let delivered = match pkt.mode {
+                ClickMode::Click => merge_into(&mut cursor_item.0, &result),
+                ClickMode::ShiftClick => {
+                    player_space_for(&player_inv, &result) >= i32::from(result.count)
+                        && give_to_player(&mut player_inv, result.clone()).is_empty()
+                }
+                ClickMode::Hotbar => {
+                    let slot = match pkt.button {
+                        0..=8 => PlayerInventory::hotbar_to_slot(pkt.button as u8),
+                        40 => PlayerInventory::SLOT_OFFHAND,
+                        _ => break,
+                    };
+
+                    if player_inv.slot(slot).is_empty() {
+                        player_inv.set_slot(slot, result.clone());
+                        true
+                    } else {
+                        false
+                    }
+                }
+                ClickMode::DropKey => {
+                    let location = |slot| ItemLocation::Slot {
+                        inventory: open_inventory.entity,
+                        slot,
+                    };
+                    let before = [SLOTS_INPUT[0], SLOTS_INPUT[1], SLOT_RESULT]
+                        .into_iter()
+                        .map(|slot| (location(slot), merchant_inv.slot(slot).clone()))
+                        .collect();
+
+                    transactions.drop_with_snapshot(
+                        packet.client,
+                        location(SLOT_RESULT),
+                        before,
+                        Some(SLOT_RESULT),
+                        result.clone(),
+                    );
+                    true
+                }
+                _ => false,
+            };
+
+            if !delivered {
+                break;
+            }
+
+            // This marks the merchant changed, so the new uses and experience are
+            // sent to the client.
+            let merchant = &mut *merchant;
+            let offer = &mut merchant.offers[idx];
+            let costs = [offer.adjusted_input_one(), offer.input_two.clone()];
+            let slots = if swapped {
+                [SLOTS_INPUT[1], SLOTS_INPUT[0]]
+            } else {
+                SLOTS_INPUT
+            };
+
+            for (slot, cost) in slots.into_iter().zip(costs) {
+                let stack = merchant_inv.slot(slot);
+
+                if !cost.is_empty() {
+                    let count = stack.count - cost.count;
+                    let new = if count > 0 {
+                        stack.clone().with_count(count)
+                    } else {
+                        ItemStack::EMPTY
+                    };
+
+                    merchant_inv.set_slot(slot, new);
+                }
+            }
+
+            offer.uses += 1;
+            merchant.experience += offer.xp;
+            traded = true;
+
+            trade_events.send(TradeEvent {
+                client: packet.client,
+                merchant: open_inventory.entity,
+                offer: idx,
+                result: result.clone(),
+            });
+        }
+
+        let next_result = merchant
+            .find_offer(
+                merchant_inv.slot(SLOTS_INPUT[0]),
+                merchant_inv.slot(SLOTS_INPUT[1]),
+            )
+            .map_or(ItemStack::EMPTY, |(idx, _)| {
+                merchant.offers[idx].result.clone()
+            });
+
+        merchant_inv.set_slot(SLOT_RESULT, next_result);
+
+        if !traded && pkt.slot_changes.is_empty() && pkt.carried_item == cursor_item.0 {
+            continue;
+        }
+
+        // The client may have guessed the outcome of the click wrong.
+        client.write_packet(&InventoryS2c {
+            window_id: inv_state.window_id,
+            state_id: VarInt(inv_state.state_id.0),
+            slots: Cow::Owned(
+                merchant_inv
+                    .slots()
+                    .chain(
+                        player_inv
+                            .slots()
+                            .skip(*PlayerInventory::SLOTS_MAIN.start() as usize),
+                    )
+                    .cloned()
+                    .collect(),
+            ),
+            carried_item: Cow::Borrowed(&cursor_item.0),
+        });
+    }
+}
+
+/// Moves the items in the input slots back to the player's inventory when they
+/// close a merchant, like vanilla servers do.
+pub(crate) fn return_merchant_inputs(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Inventory, &OpenInventory), With<Client>>,
+    mut merchants: Query<&mut Inventory, (With<Merchant>, Without<Client>)>,
+    mut transactions: TransactionEvents,
+) {
+    for packet in packets.read() {
+        if packet.decode::<CloseHandledScreenC2s>().is_none() {
+            continue;
+        }
+
+        let Ok((mut player_inv, open_inventory)) = clients.get_mut(packet.client) else {
+            continue;
+        };
+
+        let Ok(mut merchant_inv) = merchants.get_mut(open_inventory.entity) else {
+            continue;
+        };
+
+        for slot in SLOTS_INPUT {
+            let stack = merchant_inv.replace_slot(slot, ItemStack::EMPTY);
+
+            if stack.is_empty() {
+                continue;
+            }
+
+            let rest = give_to_player(&mut player_inv, stack);
+
+            if !rest.is_empty() {
+                // Cancelling the drop leaves the items in the input slot.
+                transactions.drop(
+                    packet.client,
+                    ItemLocation::Slot {
+                        inventory: open_inventory.entity,
+                        slot,
+                    },
+                    rest.clone(),
+                    None,
+                    rest,
+                );
+            }
+        }
+    }
+}
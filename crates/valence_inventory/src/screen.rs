@@ -0,0 +1,123 @@
+//! Typed state of functional screens, like anvils, beacons and furnaces.
+//!
+//! Add the component of a screen to an [`Inventory`] of the matching
+//! [`InventoryKind`](crate::InventoryKind) and open it for clients with
+//! [`OpenInventory`]. Changing the component updates the screen of every
+//! client viewing the inventory, like the progress bars of furnaces. What the
+//! client does in the screen, like renaming an item in an anvil, is sent as
+//! events.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_inventory::screen::furnace::Furnace;
+//! # use valence_inventory::*;
+//! fn spawn_furnace(mut commands: Commands) {
+//!     commands.spawn((
+//!         Inventory::new(InventoryKind::Furnace),
+//!         Furnace {
+//!             fuel_left: 800,
+//!             fuel_time: 1600,
+//!             cook_progress: 100,
+//!             cook_time: 200,
+//!         },
+//!     ));
+//! }
+//! ```
+//!
+//! [`Inventory`]: crate::Inventory
+
+pub mod anvil;
+pub mod beacon;
+pub mod brewing_stand;
+pub mod enchanting_table;
+pub mod furnace;
+pub mod loom;
+pub mod stonecutter;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_server::client::Client;
+use valence_server::event_loop::{EventLoopPreUpdate, PacketEvent};
+use valence_server::protocol::packets::play::{ButtonClickC2s, ScreenHandlerPropertyUpdateS2c};
+use valence_server::protocol::WritePacket;
+
+use crate::{update_open_inventories, ClientInventoryState, OpenInventory, UpdateInventoriesSet};
+
+pub(crate) struct ScreenPlugin;
+
+impl Plugin for ScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                update_screen_properties::<anvil::Anvil>,
+                update_screen_properties::<beacon::Beacon>,
+                update_screen_properties::<brewing_stand::BrewingStand>,
+                update_screen_properties::<enchanting_table::EnchantingTable>,
+                update_screen_properties::<furnace::Furnace>,
+                update_screen_properties::<loom::Loom>,
+                update_screen_properties::<stonecutter::Stonecutter>,
+            )
+                .after(update_open_inventories)
+                .in_set(UpdateInventoriesSet),
+        )
+        .add_systems(
+            EventLoopPreUpdate,
+            (
+                anvil::handle_rename_item,
+                beacon::handle_update_beacon,
+                enchanting_table::handle_button_clicks,
+                loom::handle_button_clicks,
+                stonecutter::handle_button_clicks,
+            ),
+        )
+        .add_event::<anvil::RenameItemEvent>()
+        .add_event::<beacon::SelectBeaconEffectsEvent>()
+        .add_event::<enchanting_table::EnchantItemEvent>()
+        .add_event::<loom::SelectLoomPatternEvent>()
+        .add_event::<stonecutter::SelectStonecutterRecipeEvent>();
+    }
+}
+
+/// A component holding the properties of a screen. Property `i` has the value
+/// at index `i`.
+pub(crate) trait ScreenProperties: Component {
+    fn properties(&self) -> Vec<i16>;
+}
+
+/// Sends the properties of screens to the clients viewing them.
+fn update_screen_properties<T: ScreenProperties>(
+    mut clients: Query<(&mut Client, &ClientInventoryState, Ref<OpenInventory>)>,
+    screens: Query<Ref<T>>,
+) {
+    for (mut client, inv_state, open_inventory) in &mut clients {
+        let Ok(screen) = screens.get(open_inventory.entity) else {
+            continue;
+        };
+
+        if !open_inventory.is_added() && !screen.is_changed() {
+            continue;
+        }
+
+        for (property, value) in screen.properties().into_iter().enumerate() {
+            client.write_packet(&ScreenHandlerPropertyUpdateS2c {
+                window_id: inv_state.window_id,
+                property: property as i16,
+                value,
+            });
+        }
+    }
+}
+
+/// Returns the client that clicked a button and the inventory it was clicked
+/// in, if the click was in the open inventory.
+fn read_button_click(
+    packet: &PacketEvent,
+    clients: &Query<(&ClientInventoryState, &OpenInventory), With<Client>>,
+) -> Option<(Entity, u8)> {
+    let pkt = packet.decode::<ButtonClickC2s>()?;
+    let (inv_state, open_inventory) = clients.get(packet.client).ok()?;
+
+    (pkt.window_id as u8 == inv_state.window_id && pkt.button_id >= 0)
+        .then_some((open_inventory.entity, pkt.button_id as u8))
+}
@@ -0,0 +1,82 @@
+//! Anvils, where items are renamed and repaired.
+
+use bevy_ecs::prelude::*;
+use valence_server::client::Client;
+use valence_server::event_loop::PacketEvent;
+use valence_server::protocol::packets::play::RenameItemC2s;
+
+use super::ScreenProperties;
+use crate::OpenInventory;
+
+/// The longest name vanilla clients can give items.
+pub const MAX_NAME_LENGTH: usize = 50;
+
+/// The state of an inventory of kind [`InventoryKind::Anvil`].
+///
+/// [`InventoryKind::Anvil`]: crate::InventoryKind::Anvil
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Anvil {
+    /// The experience levels the repair costs, shown below the result. Costs
+    /// of 40 levels and more are shown as "Too Expensive!" to clients not in
+    /// creative mode.
+    pub repair_cost: i16,
+    item_name: String,
+}
+
+impl Anvil {
+    /// The name the client typed into the text field.
+    pub fn item_name(&self) -> &str {
+        &self.item_name
+    }
+}
+
+impl ScreenProperties for Anvil {
+    fn properties(&self) -> Vec<i16> {
+        vec![self.repair_cost]
+    }
+}
+
+/// An event sent when a client changes the name in the text field of an
+/// anvil.
+#[derive(Event, Clone, PartialEq, Eq, Debug)]
+pub struct RenameItemEvent {
+    pub client: Entity,
+    /// The entity with the [`Anvil`].
+    pub inventory: Entity,
+    pub name: String,
+}
+
+pub(super) fn handle_rename_item(
+    mut packets: EventReader<PacketEvent>,
+    clients: Query<&OpenInventory, With<Client>>,
+    mut anvils: Query<&mut Anvil>,
+    mut events: EventWriter<RenameItemEvent>,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<RenameItemC2s>() else {
+            continue;
+        };
+
+        if pkt.item_name.chars().count() > MAX_NAME_LENGTH {
+            continue;
+        }
+
+        let Ok(open_inventory) = clients.get(packet.client) else {
+            continue;
+        };
+
+        let Ok(mut anvil) = anvils.get_mut(open_inventory.entity) else {
+            continue;
+        };
+
+        // The client already knows the name.
+        pkt.item_name
+            .clone_into(&mut anvil.bypass_change_detection().item_name);
+
+        events.send(RenameItemEvent {
+            client: packet.client,
+            inventory: open_inventory.entity,
+            name: pkt.item_name.into(),
+        });
+    }
+}
@@ -0,0 +1,91 @@
+//! Beacons, where the effects given to nearby players are selected.
+
+use bevy_ecs::prelude::*;
+use valence_server::client::Client;
+use valence_server::event_loop::PacketEvent;
+use valence_server::protocol::packets::play::UpdateBeaconC2s;
+use valence_server::protocol::status_effects::StatusEffect;
+use valence_server::protocol::VarInt;
+
+use super::ScreenProperties;
+use crate::OpenInventory;
+
+/// The state of an inventory of kind [`InventoryKind::Beacon`].
+///
+/// [`InventoryKind::Beacon`]: crate::InventoryKind::Beacon
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Beacon {
+    /// The number of pyramid levels below the beacon, from 0 to 4. Effects are
+    /// only selectable up to this level.
+    pub power_level: i16,
+    pub primary_effect: Option<StatusEffect>,
+    pub secondary_effect: Option<StatusEffect>,
+}
+
+impl ScreenProperties for Beacon {
+    fn properties(&self) -> Vec<i16> {
+        let effect_id = |effect: Option<StatusEffect>| effect.map_or(-1, |e| e.to_raw() as i16);
+
+        vec![
+            self.power_level,
+            effect_id(self.primary_effect),
+            effect_id(self.secondary_effect),
+        ]
+    }
+}
+
+/// An event sent when a client confirms the effects selected in a beacon.
+///
+/// The beacon isn't changed. To accept the selection, set the effects of the
+/// [`Beacon`] and take the payment item out of its slot.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SelectBeaconEffectsEvent {
+    pub client: Entity,
+    /// The entity with the [`Beacon`].
+    pub inventory: Entity,
+    pub primary_effect: Option<StatusEffect>,
+    pub secondary_effect: Option<StatusEffect>,
+}
+
+pub(super) fn handle_update_beacon(
+    mut packets: EventReader<PacketEvent>,
+    clients: Query<&OpenInventory, With<Client>>,
+    beacons: Query<(), With<Beacon>>,
+    mut events: EventWriter<SelectBeaconEffectsEvent>,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<UpdateBeaconC2s>() else {
+            continue;
+        };
+
+        let Ok(open_inventory) = clients.get(packet.client) else {
+            continue;
+        };
+
+        if !beacons.contains(open_inventory.entity) {
+            continue;
+        }
+
+        // `None` if the effect ID is invalid.
+        let effect = |id: Option<VarInt>| match id {
+            Some(id) => u16::try_from(id.0)
+                .ok()
+                .and_then(StatusEffect::from_raw)
+                .map(Some),
+            None => Some(None),
+        };
+
+        let (Some(primary_effect), Some(secondary_effect)) =
+            (effect(pkt.primary_effect), effect(pkt.secondary_effect))
+        else {
+            continue;
+        };
+
+        events.send(SelectBeaconEffectsEvent {
+            client: packet.client,
+            inventory: open_inventory.entity,
+            primary_effect,
+            secondary_effect,
+        });
+    }
+}
@@ -0,0 +1,23 @@
+//! Brewing stands.
+
+use bevy_ecs::prelude::*;
+
+use super::ScreenProperties;
+
+/// The state of an inventory of kind [`InventoryKind::BrewingStand`].
+///
+/// [`InventoryKind::BrewingStand`]: crate::InventoryKind::BrewingStand
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct BrewingStand {
+    /// The ticks left until the potions are brewed. Brewing takes 400 ticks,
+    /// and the progress arrow is hidden at 0.
+    pub brew_time: i16,
+    /// The brews left before more blaze powder is needed, from 0 to 20.
+    pub fuel: i16,
+}
+
+impl ScreenProperties for BrewingStand {
+    fn properties(&self) -> Vec<i16> {
+        vec![self.brew_time, self.fuel]
+    }
+}
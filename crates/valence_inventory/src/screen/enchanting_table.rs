@@ -0,0 +1,104 @@
+//! Enchanting tables.
+
+use bevy_ecs::prelude::*;
+use valence_server::client::Client;
+use valence_server::event_loop::PacketEvent;
+
+use super::{read_button_click, ScreenProperties};
+use crate::{ClientInventoryState, OpenInventory};
+
+/// The state of an inventory of kind [`InventoryKind::Enchantment`].
+///
+/// [`InventoryKind::Enchantment`]: crate::InventoryKind::Enchantment
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct EnchantingTable {
+    /// The enchantments to choose from, from top to bottom.
+    pub options: [EnchantingOption; 3],
+    /// Decides the glyphs shown on the options.
+    pub seed: i16,
+}
+
+/// One of the enchantments to choose from in an [`EnchantingTable`].
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct EnchantingOption {
+    /// The experience level needed for the option. The option is hidden at 0.
+    /// Choosing the top option costs one level, the middle one two and the
+    /// bottom one three.
+    pub level_requirement: i16,
+    /// The enchantment shown when hovering over the option.
+    pub hint: Option<EnchantmentHint>,
+}
+
+/// The enchantment shown for an [`EnchantingOption`], like "Sharpness III
+/// . . . ?".
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct EnchantmentHint {
+    /// The raw ID of the enchantment.
+    pub enchantment: i16,
+    pub level: i16,
+}
+
+impl ScreenProperties for EnchantingTable {
+    fn properties(&self) -> Vec<i16> {
+        let options = &self.options;
+
+        options
+            .iter()
+            .map(|option| option.level_requirement)
+            .chain([self.seed])
+            .chain(
+                options
+                    .iter()
+                    .map(|option| option.hint.map_or(-1, |hint| hint.enchantment)),
+            )
+            .chain(
+                options
+                    .iter()
+                    .map(|option| option.hint.map_or(-1, |hint| hint.level)),
+            )
+            .collect()
+    }
+}
+
+/// An event sent when a client clicks one of the options of an enchanting
+/// table.
+///
+/// Nothing is enchanted. The client only sends this if it has enough levels
+/// and lapis lazuli for the option.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct EnchantItemEvent {
+    pub client: Entity,
+    /// The entity with the [`EnchantingTable`].
+    pub inventory: Entity,
+    /// The index of the option in [`EnchantingTable::options`].
+    pub option: u8,
+}
+
+pub(super) fn handle_button_clicks(
+    mut packets: EventReader<PacketEvent>,
+    clients: Query<(&ClientInventoryState, &OpenInventory), With<Client>>,
+    tables: Query<&EnchantingTable>,
+    mut events: EventWriter<EnchantItemEvent>,
+) {
+    for packet in packets.read() {
+        let Some((inventory, button)) = read_button_click(packet, &clients) else {
+            continue;
+        };
+
+        let Ok(table) = tables.get(inventory) else {
+            continue;
+        };
+
+        if table
+            .options
+            .get(button as usize)
+            .is_some_and(|option| option.level_requirement > 0)
+        {
+            events.send(EnchantItemEvent {
+                client: packet.client,
+                inventory,
+                option: button,
+            });
+        }
+    }
+}
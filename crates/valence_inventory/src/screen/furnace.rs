@@ -0,0 +1,36 @@
+//! Furnaces, blast furnaces and smokers.
+
+use bevy_ecs::prelude::*;
+
+use super::ScreenProperties;
+
+/// The progress bars of an inventory of kind [`InventoryKind::Furnace`],
+/// [`InventoryKind::BlastFurnace`] or [`InventoryKind::Smoker`].
+///
+/// [`InventoryKind::Furnace`]: crate::InventoryKind::Furnace
+/// [`InventoryKind::BlastFurnace`]: crate::InventoryKind::BlastFurnace
+/// [`InventoryKind::Smoker`]: crate::InventoryKind::Smoker
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Furnace {
+    /// The ticks left until the fuel burns out. The flame is full when this is
+    /// `fuel_time` and hidden at 0.
+    pub fuel_left: i16,
+    /// The ticks the last fuel item burns for.
+    pub fuel_time: i16,
+    /// The ticks the current item has been cooking for.
+    pub cook_progress: i16,
+    /// The ticks it takes to cook the current item. The arrow is full when
+    /// `cook_progress` reaches this.
+    pub cook_time: i16,
+}
+
+impl ScreenProperties for Furnace {
+    fn properties(&self) -> Vec<i16> {
+        vec![
+            self.fuel_left,
+            self.fuel_time,
+            self.cook_progress,
+            self.cook_time,
+        ]
+    }
+}
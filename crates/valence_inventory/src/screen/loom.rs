@@ -0,0 +1,60 @@
+//! Looms, where patterns are added to banners.
+
+use bevy_ecs::prelude::*;
+use valence_server::client::Client;
+use valence_server::event_loop::PacketEvent;
+
+use super::{read_button_click, ScreenProperties};
+use crate::{ClientInventoryState, OpenInventory};
+
+/// The state of an inventory of kind [`InventoryKind::Loom`].
+///
+/// [`InventoryKind::Loom`]: crate::InventoryKind::Loom
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Loom {
+    /// The index of the selected pattern in the list of patterns the client
+    /// shows for the banner and pattern item in the loom.
+    pub selected_pattern: Option<u8>,
+}
+
+impl ScreenProperties for Loom {
+    fn properties(&self) -> Vec<i16> {
+        vec![self.selected_pattern.map_or(-1, i16::from)]
+    }
+}
+
+/// An event sent when a client selects a pattern in a loom. The pattern is
+/// also set as the [`Loom::selected_pattern`].
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SelectLoomPatternEvent {
+    pub client: Entity,
+    /// The entity with the [`Loom`].
+    pub inventory: Entity,
+    pub pattern: u8,
+}
+
+pub(super) fn handle_button_clicks(
+    mut packets: EventReader<PacketEvent>,
+    clients: Query<(&ClientInventoryState, &OpenInventory), With<Client>>,
+    mut looms: Query<&mut Loom>,
+    mut events: EventWriter<SelectLoomPatternEvent>,
+) {
+    for packet in packets.read() {
+        let Some((inventory, button)) = read_button_click(packet, &clients) else {
+            continue;
+        };
+
+        let Ok(mut loom) = looms.get_mut(inventory) else {
+            continue;
+        };
+
+        // The client already shows the selection.
+        loom.bypass_change_detection().selected_pattern = Some(button);
+
+        events.send(SelectLoomPatternEvent {
+            client: packet.client,
+            inventory,
+            pattern: button,
+        });
+    }
+}
@@ -0,0 +1,60 @@
+//! Stonecutters.
+
+use bevy_ecs::prelude::*;
+use valence_server::client::Client;
+use valence_server::event_loop::PacketEvent;
+
+use super::{read_button_click, ScreenProperties};
+use crate::{ClientInventoryState, OpenInventory};
+
+/// The state of an inventory of kind [`InventoryKind::Stonecutter`].
+///
+/// [`InventoryKind::Stonecutter`]: crate::InventoryKind::Stonecutter
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Stonecutter {
+    /// The index of the selected recipe in the list of stonecutting recipes the
+    /// client shows for the input item.
+    pub selected_recipe: Option<u8>,
+}
+
+impl ScreenProperties for Stonecutter {
+    fn properties(&self) -> Vec<i16> {
+        vec![self.selected_recipe.map_or(-1, i16::from)]
+    }
+}
+
+/// An event sent when a client selects a recipe in a stonecutter. The recipe
+/// is also set as the [`Stonecutter::selected_recipe`].
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SelectStonecutterRecipeEvent {
+    pub client: Entity,
+    /// The entity with the [`Stonecutter`].
+    pub inventory: Entity,
+    pub recipe: u8,
+}
+
+pub(super) fn handle_button_clicks(
+    mut packets: EventReader<PacketEvent>,
+    clients: Query<(&ClientInventoryState, &OpenInventory), With<Client>>,
+    mut stonecutters: Query<&mut Stonecutter>,
+    mut events: EventWriter<SelectStonecutterRecipeEvent>,
+) {
+    for packet in packets.read() {
+        let Some((inventory, button)) = read_button_click(packet, &clients) else {
+            continue;
+        };
+
+        let Ok(mut stonecutter) = stonecutters.get_mut(inventory) else {
+            continue;
+        };
+
+        // The client already shows the selection.
+        stonecutter.bypass_change_detection().selected_recipe = Some(button);
+
+        events.send(SelectStonecutterRecipeEvent {
+            client: packet.client,
+            inventory,
+            recipe: button,
+        });
+    }
+}
@@ -0,0 +1,101 @@
+//! Helpers for combining item stacks the way clients do.
+
+use std::borrow::Cow;
+
+use valence_server::nbt::{Compound, Value};
+use valence_server::ItemStack;
+
+use crate::player_inventory::PlayerInventory;
+use crate::Inventory;
+
+/// Returns whether two stacks are of the same item and can be stacked
+/// together, ignoring their counts.
+pub fn stacks_match(a: &ItemStack, b: &ItemStack) -> bool {
+    a.item == b.item && normalized_nbt(&a.nbt) == normalized_nbt(&b.nbt)
+}
+
+/// The client adds `Damage: 0` to damageable items if it's missing, so it's
+/// ignored when comparing NBT.
+fn normalized_nbt(nbt: &Option<Compound>) -> Option<Cow<'_, Compound>> {
+    let nbt = nbt.as_ref()?;
+
+    if matches!(nbt.get("Damage"), Some(Value::Int(0))) {
+        let mut nbt = nbt.clone();
+        nbt.remove("Damage");
+        (!nbt.is_empty()).then_some(Cow::Owned(nbt))
+    } else {
+        (!nbt.is_empty()).then_some(Cow::Borrowed(nbt))
+    }
+}
+
+/// Adds all of `stack` to `dest` if it fits, returning whether it did.
+pub fn merge_into(dest: &mut ItemStack, stack: &ItemStack) -> bool {
+    if dest.is_empty() {
+        *dest = stack.clone();
+        true
+    } else if stacks_match(dest, stack) && dest.count + stack.count <= dest.item.max_stack() {
+        dest.count += stack.count;
+        true
+    } else {
+        false
+    }
+}
+
+/// Returns how many items like `stack` fit in the main slots of the player's
+/// inventory.
+pub fn player_space_for(inv: &Inventory, stack: &ItemStack) -> i32 {
+    PlayerInventory::SLOTS_MAIN
+        .map(|slot| {
+            let existing = inv.slot(slot);
+
+            if existing.is_empty() {
+                i32::from(stack.item.max_stack())
+            } else if stacks_match(existing, stack) {
+                i32::from(existing.item.max_stack() - existing.count).max(0)
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+/// Puts as much of `stack` as possible into the main slots of the player's
+/// inventory and returns the rest. Existing stacks are filled up first, and
+/// slots are tried from the end of the hotbar like vanilla does.
+pub fn give_to_player(inv: &mut Inventory, mut stack: ItemStack) -> ItemStack {
+    for slot in PlayerInventory::SLOTS_MAIN.rev() {
+        if stack.is_empty() {
+            break;
+        }
+
+        let existing = inv.slot(slot);
+
+        if !existing.is_empty() && stacks_match(existing, &stack) {
+            let moved = (existing.item.max_stack() - existing.count).clamp(0, stack.count);
+
+            if moved > 0 {
+                let new = existing.clone().with_count(existing.count + moved);
+                inv.set_slot(slot, new);
+                stack.count -= moved;
+            }
+        }
+    }
+
+    for slot in PlayerInventory::SLOTS_MAIN.rev() {
+        if stack.is_empty() {
+            break;
+        }
+
+        if inv.slot(slot).is_empty() {
+            let moved = stack.count.min(stack.item.max_stack());
+            inv.set_slot(slot, stack.clone().with_count(moved));
+            stack.count -= moved;
+        }
+    }
+
+    if stack.is_empty() {
+        ItemStack::EMPTY
+    } else {
+        stack
+    }
+}
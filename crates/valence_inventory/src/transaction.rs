@@ -0,0 +1,601 @@
+//! High-level events for the changes clients make to inventories.
+//!
+//! Every click of a client that changes an inventory is a [`Transaction`],
+//! which is described by one or more of the events in this module. Unlike
+//! [`ClickSlotEvent`](crate::ClickSlotEvent), these events are already
+//! validated and tell what happened instead of what was clicked.
+//!
+//! The events are sent in [`EventLoopPreUpdate`], and a transaction is undone
+//! by sending a [`CancelTransactionEvent`] in [`EventLoopUpdate`]. The changed
+//! slots and the cursor item are restored and sent to the client again. Items
+//! dropped by a transaction are only dropped in [`EventLoopPostUpdate`] once
+//! it's known the transaction isn't cancelled, so they're still dropped before
+//! [`Update`].
+//!
+//! [`EventLoopPreUpdate`]: valence_server::event_loop::EventLoopPreUpdate
+//! [`EventLoopUpdate`]: valence_server::event_loop::EventLoopUpdate
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_inventory::transaction::*;
+//! # use valence_server::ItemKind;
+//! fn no_moving_diamonds(
+//!     mut picked_up: EventReader<ItemPickedUpToCursorEvent>,
+//!     mut cancel: EventWriter<CancelTransactionEvent>,
+//! ) {
+//!     for event in picked_up.read() {
+//!         if event.stack.item == ItemKind::Diamond {
+//!             cancel.send(CancelTransactionEvent(event.transaction));
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use valence_server::client::Client;
+use valence_server::event_loop::EventLoopPostUpdate;
+use valence_server::ItemStack;
+
+use crate::stacking::stacks_match;
+use crate::{
+    ClickMode, ClientInventoryState, CursorItem, DropItemStackEvent, Inventory, InventoryKind,
+    OpenInventory,
+};
+
+pub(crate) struct TransactionPlugin;
+
+impl Plugin for TransactionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingTransactions>()
+            .add_event::<ItemMovedEvent>()
+            .add_event::<ItemPickedUpToCursorEvent>()
+            .add_event::<ShiftClickTransferEvent>()
+            .add_event::<DragDistributeEvent>()
+            .add_event::<HotbarSwapEvent>()
+            .add_event::<ItemDroppedFromSlotEvent>()
+            .add_event::<CancelTransactionEvent>()
+            .add_systems(EventLoopPostUpdate, revert_cancelled_transactions);
+    }
+}
+
+/// Identifies a change a client made to inventories with a single click.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Transaction {
+    client: Entity,
+    id: u64,
+}
+
+impl Transaction {
+    /// The client that made the transaction.
+    pub fn client(self) -> Entity {
+        self.client
+    }
+}
+
+/// Where an item is.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ItemLocation {
+    /// The item held by the client's cursor.
+    Cursor,
+    /// A slot of an inventory. Slots of the player's own inventory use the
+    /// client entity and the slot ids of
+    /// [`PlayerInventory`](crate::player_inventory::PlayerInventory).
+    Slot { inventory: Entity, slot: u16 },
+    /// The creative mode inventory, which items are taken from and destroyed
+    /// in by clients in creative mode.
+    Creative,
+}
+
+/// Undoes a [`Transaction`] when sent in [`EventLoopUpdate`] right after the
+/// events of the transaction. Later transactions of the same client that
+/// haven't been resolved yet are undone as well, since they depend on the
+/// cancelled one. Transactions from earlier runs of the event loop can't be
+/// undone anymore.
+///
+/// [`EventLoopUpdate`]: valence_server::event_loop::EventLoopUpdate
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CancelTransactionEvent(pub Transaction);
+
+/// Items were put into a slot from the cursor, or moved to or from the
+/// creative mode inventory.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct ItemMovedEvent {
+    pub client: Entity,
+    pub transaction: Transaction,
+    pub from: ItemLocation,
+    pub to: ItemLocation,
+    /// The moved items.
+    pub stack: ItemStack,
+}
+
+/// Items were picked up from a slot with the cursor.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct ItemPickedUpToCursorEvent {
+    pub client: Entity,
+    pub transaction: Transaction,
+    pub from: ItemLocation,
+    /// The picked up items.
+    pub stack: ItemStack,
+}
+
+/// Items were moved to the other inventory by shift clicking.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct ShiftClickTransferEvent {
+    pub client: Entity,
+    pub transaction: Transaction,
+    pub from: ItemLocation,
+    /// The slots the items were moved into.
+    pub to: Vec<ItemLocation>,
+    /// The moved items.
+    pub stack: ItemStack,
+}
+
+/// Items on the cursor were distributed over slots by dragging.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct DragDistributeEvent {
+    pub client: Entity,
+    pub transaction: Transaction,
+    /// The slots and the items that were added to them.
+    pub slots: Vec<(ItemLocation, ItemStack)>,
+}
+
+/// The contents of a slot were swapped with a hotbar or the offhand slot by
+/// pressing a number key or the swap key.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct HotbarSwapEvent {
+    pub client: Entity,
+    pub transaction: Transaction,
+    pub slot: ItemLocation,
+    /// The hotbar slot from 0 to 8, or 40 for the offhand.
+    pub hotbar_slot: u8,
+    /// The items that were moved to the hotbar slot.
+    pub to_hotbar: ItemStack,
+    /// The items that were moved from the hotbar slot.
+    pub from_hotbar: ItemStack,
+}
+
+/// Items were dropped from a slot, the cursor or the creative mode inventory.
+///
+/// The [`DropItemStackEvent`] for the drop is held back until
+/// [`EventLoopPostUpdate`] and is only sent if the transaction isn't cancelled.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct ItemDroppedFromSlotEvent {
+    pub client: Entity,
+    pub transaction: Transaction,
+    pub from: ItemLocation,
+    /// The dropped items.
+    pub stack: ItemStack,
+}
+
+/// The transactions of the current tick with the state of everything they
+/// changed from before.
+#[derive(Resource, Default)]
+struct PendingTransactions {
+    next_id: u64,
+    transactions: Vec<(Transaction, Vec<(ItemLocation, ItemStack)>)>,
+    /// Drops that are sent once it's known their transaction isn't cancelled.
+    drops: Vec<(Transaction, DropItemStackEvent)>,
+}
+
+static EMPTY: ItemStack = ItemStack::EMPTY;
+
+/// The inventories a client sees while clicking.
+pub(crate) struct ClickContext<'a> {
+    pub(crate) client: Entity,
+    pub(crate) player: &'a Inventory,
+    pub(crate) open: Option<(Entity, &'a Inventory)>,
+    pub(crate) cursor: &'a ItemStack,
+}
+
+impl ClickContext<'_> {
+    fn stack(&self, location: ItemLocation) -> &ItemStack {
+        match location {
+            ItemLocation::Cursor => self.cursor,
+            ItemLocation::Slot { inventory, slot } => match self.open {
+                Some((entity, open)) if entity == inventory => open.slot(slot),
+                _ => self.player.slot(slot),
+            },
+            ItemLocation::Creative => &EMPTY,
+        }
+    }
+
+    /// Returns the current contents of `locations` and the cursor.
+    pub(crate) fn snapshot(
+        &self,
+        locations: impl IntoIterator<Item = ItemLocation>,
+    ) -> Vec<(ItemLocation, ItemStack)> {
+        std::iter::once(ItemLocation::Cursor)
+            .chain(locations)
+            .map(|location| (location, self.stack(location).clone()))
+            .collect()
+    }
+}
+
+/// Returns the location of a slot in a window. `open` is the entity, kind and
+/// slot count of the open inventory.
+pub(crate) fn window_location(
+    client: Entity,
+    open: Option<(Entity, InventoryKind, u16)>,
+    idx: i16,
+) -> Option<ItemLocation> {
+    let idx = u16::try_from(idx).ok()?;
+
+    let (inventory, slot) = match open {
+        Some((entity, _, slot_count)) if idx < slot_count => (entity, idx),
+        Some((_, kind, _)) => (client, crate::convert_to_player_slot_id(kind, idx)),
+        None => (client, idx),
+    };
+
+    Some(ItemLocation::Slot { inventory, slot })
+}
+
+/// Sends the events of transactions. Crates that change inventories on behalf
+/// of clients use it to make their drops cancellable too.
+#[derive(SystemParam)]
+pub struct TransactionEvents<'w> {
+    pending: ResMut<'w, PendingTransactions>,
+    moved: EventWriter<'w, ItemMovedEvent>,
+    picked_up: EventWriter<'w, ItemPickedUpToCursorEvent>,
+    shift_click: EventWriter<'w, ShiftClickTransferEvent>,
+    drag: EventWriter<'w, DragDistributeEvent>,
+    hotbar_swap: EventWriter<'w, HotbarSwapEvent>,
+    dropped: EventWriter<'w, ItemDroppedFromSlotEvent>,
+}
+
+impl TransactionEvents<'_> {
+    fn begin(&mut self, client: Entity, before: Vec<(ItemLocation, ItemStack)>) -> Transaction {
+        let transaction = Transaction {
+            client,
+            id: self.pending.next_id,
+        };
+
+        self.pending.next_id += 1;
+        self.pending.transactions.push((transaction, before));
+
+        transaction
+    }
+
+    /// Sends the events for a click that has been applied. `before` is the
+    /// snapshot taken before applying it.
+    pub(crate) fn click(
+        &mut self,
+        mode: ClickMode,
+        button: i8,
+        clicked: Option<ItemLocation>,
+        before: Vec<(ItemLocation, ItemStack)>,
+        ctx: &ClickContext,
+    ) {
+        let client = ctx.client;
+
+        let changes: Vec<_> = before
+            .into_iter()
+            .filter(|(location, stack)| ctx.stack(*location) != stack)
+            .collect();
+
+        if changes.is_empty() {
+            return;
+        }
+
+        let slot_changes: Vec<_> = changes
+            .iter()
+            .filter(|(location, _)| *location != ItemLocation::Cursor)
+            .map(|(location, before)| (*location, before, ctx.stack(*location)))
+            .collect();
+
+        let clicked = clicked.and_then(|clicked| {
+            slot_changes
+                .iter()
+                .find(|(location, _, _)| *location == clicked)
+                .copied()
+        });
+
+        let transaction = self.begin(client, changes.clone());
+
+        match mode {
+            ClickMode::Click => {
+                let Some((location, before, after)) = clicked else {
+                    return;
+                };
+
+                if before.is_empty() || after.is_empty() || stacks_match(before, after) {
+                    if count(after) < count(before) {
+                        self.picked_up.send(ItemPickedUpToCursorEvent {
+                            client,
+                            transaction,
+                            from: location,
+                            stack: before.clone().with_count(count(before) - count(after)),
+                        });
+                    } else {
+                        self.moved.send(ItemMovedEvent {
+                            client,
+                            transaction,
+                            from: ItemLocation::Cursor,
+                            to: location,
+                            stack: after.clone().with_count(count(after) - count(before)),
+                        });
+                    }
+                } else {
+                    // The cursor and the slot were swapped.
+                    self.moved.send(ItemMovedEvent {
+                        client,
+                        transaction,
+                        from: ItemLocation::Cursor,
+                        to: location,
+                        stack: after.clone(),
+                    });
+                    self.picked_up.send(ItemPickedUpToCursorEvent {
+                        client,
+                        transaction,
+                        from: location,
+                        stack: before.clone(),
+                    });
+                }
+            }
+            ClickMode::ShiftClick => {
+                let Some((location, before, after)) = clicked else {
+                    return;
+                };
+
+                if count(after) < count(before) {
+                    self.shift_click.send(ShiftClickTransferEvent {
+                        client,
+                        transaction,
+                        from: location,
+                        to: slot_changes
+                            .iter()
+                            .filter(|(_, before, after)| count(after) > count(before))
+                            .map(|(location, _, _)| *location)
+                            .collect(),
+                        stack: before.clone().with_count(count(before) - count(after)),
+                    });
+                }
+            }
+            ClickMode::Hotbar => {
+                let Some((location, before, after)) = clicked else {
+                    return;
+                };
+
+                self.hotbar_swap.send(HotbarSwapEvent {
+                    client,
+                    transaction,
+                    slot: location,
+                    hotbar_slot: button as u8,
+                    to_hotbar: before.clone(),
+                    from_hotbar: after.clone(),
+                });
+            }
+            ClickMode::Drag => {
+                let slots: Vec<_> = slot_changes
+                    .iter()
+                    .filter(|(_, before, after)| count(after) > count(before))
+                    .map(|(location, before, after)| {
+                        (
+                            *location,
+                            (*after).clone().with_count(count(after) - count(before)),
+                        )
+                    })
+                    .collect();
+
+                if !slots.is_empty() {
+                    self.drag.send(DragDistributeEvent {
+                        client,
+                        transaction,
+                        slots,
+                    });
+                }
+            }
+            ClickMode::DoubleClick => {
+                for (location, before, after) in &slot_changes {
+                    if count(after) < count(before) {
+                        self.picked_up.send(ItemPickedUpToCursorEvent {
+                            client,
+                            transaction,
+                            from: *location,
+                            stack: (*before).clone().with_count(count(before) - count(after)),
+                        });
+                    }
+                }
+            }
+            ClickMode::CreativeMiddleClick | ClickMode::DropKey => {}
+        }
+    }
+
+    /// Sends the event for items that were dropped from `from`, which held
+    /// `before` until then. The [`DropItemStackEvent`] with `from_slot` is sent
+    /// later, unless the transaction is cancelled.
+    pub fn drop(
+        &mut self,
+        client: Entity,
+        from: ItemLocation,
+        before: ItemStack,
+        from_slot: Option<u16>,
+        stack: ItemStack,
+    ) {
+        self.drop_with_snapshot(client, from, vec![(from, before)], from_slot, stack);
+    }
+
+    /// Like [`drop`](Self::drop), but for drops that changed more than `from`.
+    /// `before` is the snapshot of every changed location, which is restored
+    /// if the transaction is cancelled.
+    pub fn drop_with_snapshot(
+        &mut self,
+        client: Entity,
+        from: ItemLocation,
+        before: Vec<(ItemLocation, ItemStack)>,
+        from_slot: Option<u16>,
+        stack: ItemStack,
+    ) {
+        let transaction = self.begin(client, before);
+
+        self.dropped.send(ItemDroppedFromSlotEvent {
+            client,
+            transaction,
+            from,
+            stack: stack.clone(),
+        });
+
+        self.pending.drops.push((
+            transaction,
+            DropItemStackEvent {
+                client,
+                from_slot,
+                stack,
+            },
+        ));
+    }
+
+    /// Sends the events for a slot of the player's inventory that a client in
+    /// creative mode set to `after`, which held `before` until then.
+    pub(crate) fn creative_set(
+        &mut self,
+        client: Entity,
+        slot: u16,
+        before: ItemStack,
+        after: &ItemStack,
+    ) {
+        if before == *after {
+            return;
+        }
+
+        let location = ItemLocation::Slot {
+            inventory: client,
+            slot,
+        };
+        let transaction = self.begin(client, vec![(location, before.clone())]);
+
+        let (from, to, stack) = if after.is_empty() {
+            (location, ItemLocation::Creative, before)
+        } else if before.is_empty() {
+            (ItemLocation::Creative, location, after.clone())
+        } else if stacks_match(&before, after) {
+            if count(after) > count(&before) {
+                let moved = count(after) - count(&before);
+                (
+                    ItemLocation::Creative,
+                    location,
+                    after.clone().with_count(moved),
+                )
+            } else {
+                let moved = count(&before) - count(after);
+                (location, ItemLocation::Creative, before.with_count(moved))
+            }
+        } else {
+            // The items in the slot were replaced.
+            self.moved.send(ItemMovedEvent {
+                client,
+                transaction,
+                from: location,
+                to: ItemLocation::Creative,
+                stack: before,
+            });
+
+            (ItemLocation::Creative, location, after.clone())
+        };
+
+        self.moved.send(ItemMovedEvent {
+            client,
+            transaction,
+            from,
+            to,
+            stack,
+        });
+    }
+}
+
+fn count(stack: &ItemStack) -> i8 {
+    if stack.is_empty() {
+        0
+    } else {
+        stack.count
+    }
+}
+
+fn revert_cancelled_transactions(
+    mut events: EventReader<CancelTransactionEvent>,
+    mut pending: ResMut<PendingTransactions>,
+    mut clients: Query<
+        (
+            &mut Inventory,
+            &mut ClientInventoryState,
+            &mut CursorItem,
+            Option<&mut OpenInventory>,
+        ),
+        With<Client>,
+    >,
+    mut inventories: Query<&mut Inventory, Without<Client>>,
+    mut drop_events: EventWriter<DropItemStackEvent>,
+) {
+    // The first cancelled transaction of each client.
+    let mut cancelled = HashMap::new();
+
+    for CancelTransactionEvent(transaction) in events.read() {
+        // Transactions that were already resolved can't be undone anymore.
+        if !pending.transactions.iter().any(|(t, _)| t == transaction) {
+            continue;
+        }
+
+        cancelled
+            .entry(transaction.client)
+            .and_modify(|id: &mut u64| *id = (*id).min(transaction.id))
+            .or_insert(transaction.id);
+    }
+
+    // Later transactions of the client are built on top of a cancelled one, so
+    // they are reverted too.
+    let is_reverted = |transaction: &Transaction| {
+        cancelled
+            .get(&transaction.client)
+            .is_some_and(|id| transaction.id >= *id)
+    };
+
+    for (transaction, event) in std::mem::take(&mut pending.drops) {
+        if !is_reverted(&transaction) {
+            drop_events.send(event);
+        }
+    }
+
+    let transactions = std::mem::take(&mut pending.transactions);
+
+    for (transaction, before) in transactions.into_iter().rev() {
+        if !is_reverted(&transaction) {
+            continue;
+        }
+
+        let Ok((mut player_inv, mut inv_state, mut cursor_item, mut open_inventory)) =
+            clients.get_mut(transaction.client)
+        else {
+            continue;
+        };
+
+        for (location, stack) in before {
+            match location {
+                ItemLocation::Cursor => {
+                    if cursor_item.0 != stack {
+                        cursor_item.0 = stack;
+                        inv_state.client_updated_cursor_item = None;
+                    }
+                }
+                ItemLocation::Slot { inventory, slot } if inventory == transaction.client => {
+                    player_inv.set_slot(slot, stack);
+                    inv_state.slots_changed &= !(1 << slot);
+                }
+                ItemLocation::Creative => {}
+                ItemLocation::Slot { inventory, slot } => {
+                    let Ok(mut inv) = inventories.get_mut(inventory) else {
+                        continue;
+                    };
+
+                    inv.set_slot(slot, stack);
+
+                    if let Some(open) = open_inventory.as_mut() {
+                        open.client_changed &= !(1 << slot);
+                    }
+                }
+            }
+        }
+    }
+}
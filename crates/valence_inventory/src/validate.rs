@@ -1,9 +1,12 @@
+use valence_server::item::ArmorSlot;
 use valence_server::protocol::anyhow::{self, bail, ensure};
 use valence_server::protocol::packets::play::click_slot_c2s::ClickMode;
 use valence_server::protocol::packets::play::ClickSlotC2s;
+use valence_server::{ItemKind, ItemStack};
 
-use super::{CursorItem, Inventory, InventoryWindow};
+use super::{CursorItem, Inventory, InventoryKind, InventoryWindow};
 use crate::player_inventory::PlayerInventory;
+use crate::stacking::stacks_match;
 
 /// Validates a click slot packet enforcing that all fields are valid.
 pub(super) fn validate_click_slot_packet(
@@ -27,33 +30,60 @@ pub(super) fn validate_click_slot_packet(
         player_inventory.slot_count()
     };
 
-    // check all slot ids and item counts are valid
+    // check all slot ids are valid
     ensure!(
-        packet.slot_changes.iter().all(|s| {
-            if !(0..=max_slot).contains(&(s.idx as u16)) {
-                return false;
-            }
+        packet
+            .slot_changes
+            .iter()
+            .all(|s| (0..max_slot).contains(&(s.idx as u16))),
+        "invalid slot ids"
+    );
 
-            if !s.stack.is_empty() {
-                let max_stack_size = s.stack.item.max_stack().max(s.stack.count);
-                if !(1..=max_stack_size).contains(&(s.stack.count)) {
-                    return false;
-                }
-            }
+    let window = InventoryWindow {
+        player_inventory,
+        open_inventory,
+    };
+
+    // The largest count a stack can have. Stacks larger than the maximum stack
+    // size of their item can be moved around, but not created by merging.
+    let max_count = |stack: &ItemStack| {
+        packet
+            .slot_changes
+            .iter()
+            .map(|s| window.slot(s.idx as u16))
+            .chain([&cursor_item.0])
+            .filter(|old| stacks_match(old, stack))
+            .map(|old| old.count)
+            .fold(stack.item.max_stack(), i8::max)
+    };
 
-            true
-        }),
-        "invalid slot ids or item counts"
+    // check all item counts are valid
+    ensure!(
+        packet
+            .slot_changes
+            .iter()
+            .map(|s| &s.stack)
+            .chain([&packet.carried_item])
+            .filter(|stack| !stack.is_empty())
+            .all(|stack| (1..=max_count(stack)).contains(&stack.count)),
+        "invalid item counts"
     );
 
-    // check carried item count is valid
-    if !packet.carried_item.is_empty() {
-        let carried_item = &packet.carried_item;
+    // check that items are only put into slots that accept them
+    for slot in packet.slot_changes.iter() {
+        let old_slot = window.slot(slot.idx as u16);
+        let new_slot = &slot.stack;
+
+        let adds_items = !new_slot.is_empty()
+            && (old_slot.is_empty()
+                || !stacks_match(old_slot, new_slot)
+                || new_slot.count > old_slot.count);
 
-        let max_stack_size = carried_item.item.max_stack().max(carried_item.count);
         ensure!(
-            (1..=max_stack_size).contains(&carried_item.count),
-            "invalid carried item count"
+            !adds_items || slot_accepts(&window, slot.idx as u16, new_slot.item),
+            "slot {} does not accept {:?}",
+            slot.idx,
+            new_slot.item
         );
     }
 
@@ -61,7 +91,7 @@ pub(super) fn validate_click_slot_packet(
         ClickMode::Click => {
             ensure!((0..=1).contains(&packet.button), "invalid button");
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16))
+                (0..max_slot).contains(&(packet.slot_idx as u16))
                     || packet.slot_idx == -999
                     || packet.slot_idx == -1,
                 "invalid slot index"
@@ -74,7 +104,7 @@ pub(super) fn validate_click_slot_packet(
                 "carried item must be empty for a hotbar swap"
             );
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16)),
+                (0..max_slot).contains(&(packet.slot_idx as u16)),
                 "invalid slot index"
             )
         }
@@ -88,7 +118,7 @@ pub(super) fn validate_click_slot_packet(
         ClickMode::CreativeMiddleClick => {
             ensure!(packet.button == 2, "invalid button");
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16)),
+                (0..max_slot).contains(&(packet.slot_idx as u16)),
                 "invalid slot index"
             )
         }
@@ -99,7 +129,7 @@ pub(super) fn validate_click_slot_packet(
                 "carried item must be empty for an item drop"
             );
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16)) || packet.slot_idx == -999,
+                (0..max_slot).contains(&(packet.slot_idx as u16)) || packet.slot_idx == -999,
                 "invalid slot index"
             )
         }
@@ -109,7 +139,7 @@ pub(super) fn validate_click_slot_packet(
                 "invalid button"
             );
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16)) || packet.slot_idx == -999,
+                (0..max_slot).contains(&(packet.slot_idx as u16)) || packet.slot_idx == -999,
                 "invalid slot index"
             )
         }
@@ -118,10 +148,15 @@ pub(super) fn validate_click_slot_packet(
 
     // Check that items aren't being duplicated, i.e. conservation of mass.
 
-    let window = InventoryWindow {
-        player_inventory,
-        open_inventory,
-    };
+    if packet.mode != ClickMode::CreativeMiddleClick {
+        // Together with the checks of the net item delta below, this makes sure
+        // that the count of every item stays the same, except for dropped
+        // items. Otherwise, an item could be turned into another one.
+        ensure!(
+            changed_items(packet, &window, cursor_item) <= 1,
+            "the count of more than one item changed"
+        );
+    }
 
     match packet.mode {
         ClickMode::Click => {
@@ -177,12 +212,9 @@ pub(super) fn validate_click_slot_packet(
                     );
 
                     let old_slot = window.slot(packet.slot_changes[0].idx as u16);
-                    // TODO: make sure NBT is the same.
-                    //       Sometimes, the client will add nbt data to an item if it's missing,
-                    // like       "Damage" to a sword.
                     let should_swap: bool = packet.button == 0
                         && match (!old_slot.is_empty(), !cursor_item.is_empty()) {
-                            (true, true) => old_slot.item != cursor_item.item,
+                            (true, true) => !stacks_match(old_slot, &cursor_item.0),
                             (true, false) => true,
                             (false, true) => cursor_item.count <= cursor_item.item.max_stack(),
                             (false, false) => false,
@@ -191,11 +223,8 @@ pub(super) fn validate_click_slot_packet(
                     if should_swap {
                         // assert that a swap occurs
                         ensure!(
-                            // There are some cases where the client will add NBT data that
-                            // did not previously exist.
-                            old_slot.item == packet.carried_item.item
-                                && old_slot.count == packet.carried_item.count
-                                && cursor_item.0 == packet.slot_changes[0].stack,
+                            same_stack(old_slot, &packet.carried_item)
+                                && same_stack(&cursor_item.0, &packet.slot_changes[0].stack),
                             "swapped items must match"
                         );
                     } else {
@@ -219,8 +248,8 @@ pub(super) fn validate_click_slot_packet(
                 );
             } else {
                 ensure!(
-                    (2..=3).contains(&packet.slot_changes.len()),
-                    "shift click must modify 2 or 3 slots, got {}",
+                    packet.slot_changes.len() >= 2,
+                    "shift click must modify at least 2 slots, got {}",
                     packet.slot_changes.len()
                 );
 
@@ -230,29 +259,29 @@ pub(super) fn validate_click_slot_packet(
                     "invalid item delta: expected 0, got {count_deltas}"
                 );
 
-                let Some(item_kind) = packet
+                let Some(item) = packet
                     .slot_changes
                     .iter()
                     .find(|s| !s.stack.is_empty())
-                    .map(|s| s.stack.item)
+                    .map(|s| &s.stack)
                 else {
                     bail!("shift click must move an item");
                 };
 
-                let old_slot_kind = window.slot(packet.slot_idx as u16).item;
+                let old_slot = window.slot(packet.slot_idx as u16);
                 ensure!(
-                    old_slot_kind == item_kind,
-                    "shift click must move the same item kind as modified slots"
+                    stacks_match(old_slot, item),
+                    "shift click must move the same item as modified slots"
                 );
 
-                // assert all moved items are the same kind
+                // assert all moved items are the same
                 ensure!(
                     packet
                         .slot_changes
                         .iter()
                         .filter(|s| !s.stack.is_empty())
-                        .all(|s| s.stack.item == item_kind),
-                    "shift click must move the same item kind"
+                        .all(|s| stacks_match(&s.stack, item)),
+                    "shift click must move the same item"
                 );
             }
         }
@@ -282,17 +311,13 @@ pub(super) fn validate_click_slot_packet(
                     window.slot(packet.slot_changes[0].idx as u16),
                     window.slot(packet.slot_changes[1].idx as u16),
                 ];
-                // There are some cases where the client will add NBT data that did not
-                // previously exist.
                 ensure!(
                     old_slots
                         .iter()
-                        .any(|s| s.item == packet.slot_changes[0].stack.item
-                            && s.count == packet.slot_changes[0].stack.count)
+                        .any(|s| same_stack(s, &packet.slot_changes[0].stack))
                         && old_slots
                             .iter()
-                            .any(|s| s.item == packet.slot_changes[1].stack.item
-                                && s.count == packet.slot_changes[1].stack.count),
+                            .any(|s| same_stack(s, &packet.slot_changes[1].stack)),
                     "swapped items must match"
                 );
             }
@@ -318,10 +343,7 @@ pub(super) fn validate_click_slot_packet(
                 let old_slot = window.slot(packet.slot_idx as u16);
                 let new_slot = &packet.slot_changes[0].stack;
                 let is_transmuting = match (!old_slot.is_empty(), !new_slot.is_empty()) {
-                    // TODO: make sure NBT is the same.
-                    // Sometimes, the client will add nbt data to an item if it's missing, like
-                    // "Damage" to a sword.
-                    (true, true) => old_slot.item != new_slot.item,
+                    (true, true) => !stacks_match(old_slot, new_slot),
                     (_, false) => false,
                     (false, true) => true,
                 };
@@ -403,6 +425,91 @@ fn calculate_net_item_delta(
     net_item_delta
 }
 
+/// Returns the number of different items whose total count would change if
+/// the changes in this packet were applied.
+fn changed_items(
+    packet: &ClickSlotC2s,
+    window: &InventoryWindow,
+    cursor_item: &CursorItem,
+) -> usize {
+    let removed = packet
+        .slot_changes
+        .iter()
+        .map(|slot| window.slot(slot.idx as u16))
+        .chain([&cursor_item.0])
+        .map(|stack| (stack, -i32::from(stack.count)));
+
+    let added = packet
+        .slot_changes
+        .iter()
+        .map(|slot| &slot.stack)
+        .chain([&packet.carried_item])
+        .map(|stack| (stack, i32::from(stack.count)));
+
+    let mut deltas: Vec<(&ItemStack, i32)> = vec![];
+
+    for (stack, delta) in removed.chain(added) {
+        if stack.is_empty() {
+            continue;
+        }
+
+        match deltas
+            .iter_mut()
+            .find(|(item, _)| stacks_match(item, stack))
+        {
+            Some((_, d)) => *d += delta,
+            None => deltas.push((stack, delta)),
+        }
+    }
+
+    deltas.iter().filter(|(_, delta)| *delta != 0).count()
+}
+
+/// Returns whether two stacks are the same, including their counts.
+fn same_stack(a: &ItemStack, b: &ItemStack) -> bool {
+    a.is_empty() && b.is_empty() || stacks_match(a, b) && a.count == b.count
+}
+
+/// Returns whether clients can put items of the given kind into a slot of the
+/// window.
+fn slot_accepts(window: &InventoryWindow, idx: u16, item: ItemKind) -> bool {
+    let (kind, idx) = match window.open_inventory {
+        Some(open_inv) if idx < open_inv.slot_count() => (open_inv.kind(), idx),
+        // The player's main inventory accepts everything.
+        Some(_) => return true,
+        None => (InventoryKind::Player, idx),
+    };
+
+    match (kind, idx) {
+        // Result slots
+        (InventoryKind::Player | InventoryKind::Crafting, 0)
+        | (
+            InventoryKind::Furnace
+            | InventoryKind::BlastFurnace
+            | InventoryKind::Smoker
+            | InventoryKind::Anvil
+            | InventoryKind::Grindstone
+            | InventoryKind::Cartography
+            | InventoryKind::Merchant,
+            2,
+        )
+        | (InventoryKind::Stonecutter, 1)
+        | (InventoryKind::Loom | InventoryKind::Smithing, 3) => false,
+        // Empty buckets are accepted so lava can be burned with them.
+        (InventoryKind::Furnace | InventoryKind::BlastFurnace | InventoryKind::Smoker, 1) => {
+            item.fuel_time() > 0 || item == ItemKind::Bucket
+        }
+        (InventoryKind::Player, 5) => item.armor_slot() == Some(ArmorSlot::Head),
+        (InventoryKind::Player, 6) => item.armor_slot() == Some(ArmorSlot::Chest),
+        (InventoryKind::Player, 7) => item.armor_slot() == Some(ArmorSlot::Legs),
+        (InventoryKind::Player, 8) => item.armor_slot() == Some(ArmorSlot::Feet),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod simulation;
+
 #[cfg(test)]
 mod tests {
     use valence_server::nbt::Compound;
@@ -525,9 +632,9 @@ mod tests {
         // Insert an item with no NBT data that should have NBT Data.
         inventory.set_slot(0, ItemStack::new(ItemKind::DiamondPickaxe, 1, None));
 
-        // Proper NBT Compound
+        // The client adds the missing damage.
         let mut compound = Compound::new();
-        compound.insert("Damage", Int(1));
+        compound.insert("Damage", Int(0));
 
         let packet = ClickSlotC2s {
             window_id: 1,
@@ -551,6 +658,41 @@ mod tests {
             .expect("packet should be valid");
     }
 
+    #[test]
+    fn click_filled_slot_with_different_nbt_failure() {
+        let player_inventory = Inventory::new(InventoryKind::Player);
+        let cursor_item = CursorItem(ItemStack::EMPTY);
+
+        let mut inventory = Inventory::new(InventoryKind::Generic9x1);
+        // Insert an item with no NBT data that should have NBT Data.
+        inventory.set_slot(0, ItemStack::new(ItemKind::DiamondPickaxe, 1, None));
+
+        // The client claims to pick up a damaged pickaxe.
+        let mut compound = Compound::new();
+        compound.insert("Damage", Int(1));
+
+        let packet = ClickSlotC2s {
+            window_id: 1,
+            state_id: VarInt(0),
+            slot_idx: 0,
+            button: 0,
+            mode: ClickMode::Click,
+            slot_changes: vec![SlotChange {
+                idx: 0,
+                stack: ItemStack::EMPTY,
+            }]
+            .into(),
+            carried_item: ItemStack {
+                item: ItemKind::DiamondPickaxe,
+                count: 1,
+                nbt: Some(compound),
+            },
+        };
+
+        validate_click_slot_packet(&packet, &player_inventory, Some(&inventory), &cursor_item)
+            .expect_err("packet should not be valid");
+    }
+
     #[test]
     fn click_slot_with_filled_cursor_success() {
         let player_inventory = Inventory::new(InventoryKind::Player);
@@ -711,7 +853,7 @@ mod tests {
         player_inventory.set_slot(9, ItemStack::new(ItemKind::Lead, 2, None));
         let cursor_item = CursorItem::default();
 
-        let packets = vec![
+        let packets = [
             ClickSlotC2s {
                 window_id: 0,
                 button: 0,
@@ -958,4 +1100,130 @@ mod tests {
         validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
             .expect("packet should be valid");
     }
+
+    #[test]
+    fn disallow_placing_non_armor_in_armor_slot() {
+        let player_inventory = Inventory::new(InventoryKind::Player);
+
+        let packet = |item| ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(2),
+            slot_idx: 5,
+            button: 0,
+            mode: ClickMode::Click,
+            slot_changes: vec![SlotChange {
+                idx: 5,
+                stack: ItemStack::new(item, 1, None),
+            }]
+            .into(),
+            carried_item: ItemStack::EMPTY,
+        };
+
+        validate_click_slot_packet(
+            &packet(ItemKind::DiamondHelmet),
+            &player_inventory,
+            None,
+            &CursorItem(ItemStack::new(ItemKind::DiamondHelmet, 1, None)),
+        )
+        .expect("packet should be valid");
+
+        validate_click_slot_packet(
+            &packet(ItemKind::CreeperHead),
+            &player_inventory,
+            None,
+            &CursorItem(ItemStack::new(ItemKind::CreeperHead, 1, None)),
+        )
+        .expect("heads can be worn");
+
+        validate_click_slot_packet(
+            &packet(ItemKind::Diamond),
+            &player_inventory,
+            None,
+            &CursorItem(ItemStack::new(ItemKind::Diamond, 1, None)),
+        )
+        .expect_err("diamonds can't be worn");
+    }
+
+    #[test]
+    fn disallow_placing_non_fuel_in_furnace_fuel_slot() {
+        let player_inventory = Inventory::new(InventoryKind::Player);
+        let furnace = Inventory::new(InventoryKind::Furnace);
+
+        let packet = |item| ClickSlotC2s {
+            window_id: 1,
+            state_id: VarInt(2),
+            slot_idx: 1,
+            button: 0,
+            mode: ClickMode::Click,
+            slot_changes: vec![SlotChange {
+                idx: 1,
+                stack: ItemStack::new(item, 1, None),
+            }]
+            .into(),
+            carried_item: ItemStack::EMPTY,
+        };
+
+        validate_click_slot_packet(
+            &packet(ItemKind::Coal),
+            &player_inventory,
+            Some(&furnace),
+            &CursorItem(ItemStack::new(ItemKind::Coal, 1, None)),
+        )
+        .expect("packet should be valid");
+
+        validate_click_slot_packet(
+            &packet(ItemKind::Diamond),
+            &player_inventory,
+            Some(&furnace),
+            &CursorItem(ItemStack::new(ItemKind::Diamond, 1, None)),
+        )
+        .expect_err("diamonds don't burn");
+    }
+
+    #[test]
+    fn disallow_placing_items_in_result_slot() {
+        let player_inventory = Inventory::new(InventoryKind::Player);
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 1, None));
+
+        let packet = ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(2),
+            slot_idx: 0,
+            button: 0,
+            mode: ClickMode::Click,
+            slot_changes: vec![SlotChange {
+                idx: 0,
+                stack: ItemStack::new(ItemKind::Diamond, 1, None),
+            }]
+            .into(),
+            carried_item: ItemStack::EMPTY,
+        };
+
+        validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
+            .expect_err("items can't be put into the crafting result slot");
+    }
+
+    #[test]
+    fn disallow_stacks_larger_than_max_stack_size() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::EnderPearl, 16, None));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::EnderPearl, 1, None));
+
+        let packet = ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(2),
+            slot_idx: 9,
+            button: 0,
+            mode: ClickMode::Click,
+            slot_changes: vec![SlotChange {
+                idx: 9,
+                stack: ItemStack::new(ItemKind::EnderPearl, 17, None),
+            }]
+            .into(),
+            carried_item: ItemStack::EMPTY,
+        };
+
+        validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
+            .expect_err("ender pearls stack up to 16");
+    }
 }
@@ -0,0 +1,406 @@
+//! Property-based tests that simulate vanilla clicks in the player's
+//! inventory. Clicks performed like the vanilla client must be valid, and
+//! tampered versions of them must not be.
+
+use valence_server::nbt::{compound, Compound};
+use valence_server::protocol::packets::play::click_slot_c2s::SlotChange;
+use valence_server::protocol::VarInt;
+use valence_server::rand::rngs::StdRng;
+use valence_server::rand::{Rng, SeedableRng};
+use valence_server::{ItemKind, ItemStack};
+
+use super::*;
+
+const CASES: u64 = 2000;
+
+/// The main inventory and the hotbar.
+const SLOTS: std::ops::RangeInclusive<u16> = PlayerInventory::SLOTS_MAIN;
+
+fn named(name: &str) -> Option<Compound> {
+    Some(compound! {
+        "display" => compound! {
+            "Name" => name,
+        },
+    })
+}
+
+fn random_stack(rng: &mut StdRng) -> ItemStack {
+    let (item, nbt) = match rng.gen_range(0..6) {
+        0 => (ItemKind::Diamond, None),
+        1 => (ItemKind::Diamond, named("Shiny")),
+        2 => (ItemKind::Stone, None),
+        3 => (ItemKind::EnderPearl, None),
+        4 => (ItemKind::DiamondSword, None),
+        _ => (ItemKind::DiamondSword, named("Excalibur")),
+    };
+
+    let count = rng.gen_range(1..=item.max_stack());
+
+    ItemStack::new(item, count, nbt)
+}
+
+fn random_state(rng: &mut StdRng) -> (Inventory, CursorItem) {
+    let mut inventory = Inventory::new(InventoryKind::Player);
+
+    for slot in SLOTS {
+        if rng.gen_bool(0.5) {
+            inventory.set_slot(slot, random_stack(rng));
+        }
+    }
+
+    let cursor = if rng.gen_bool(0.5) {
+        random_stack(rng)
+    } else {
+        ItemStack::EMPTY
+    };
+
+    (inventory, CursorItem(cursor))
+}
+
+fn count(stack: &ItemStack) -> i8 {
+    if stack.is_empty() {
+        0
+    } else {
+        stack.count
+    }
+}
+
+fn with_count(stack: &ItemStack, count: i8) -> ItemStack {
+    if count <= 0 {
+        ItemStack::EMPTY
+    } else {
+        stack.clone().with_count(count)
+    }
+}
+
+/// A click simulated on a copy of the inventory.
+struct Click {
+    slots: Vec<ItemStack>,
+    cursor: ItemStack,
+    slot_idx: i16,
+    button: i8,
+    mode: ClickMode,
+}
+
+impl Click {
+    fn new(inventory: &Inventory, cursor: &CursorItem, mode: ClickMode) -> Self {
+        Self {
+            slots: inventory.slot_slice().to_vec(),
+            cursor: cursor.0.clone(),
+            slot_idx: -999,
+            button: 0,
+            mode,
+        }
+    }
+
+    fn slot(&mut self, idx: u16) -> &mut ItemStack {
+        &mut self.slots[idx as usize]
+    }
+
+    /// Moves as many items from `stack` into `slot` as fit.
+    fn insert(&mut self, stack: &mut ItemStack, slot: u16) {
+        let target = self.slot(slot);
+
+        if target.is_empty() {
+            *target = std::mem::take(stack);
+        } else if stacks_match(target, stack) {
+            let moved = count(stack).min(target.item.max_stack() - target.count);
+            target.count += moved;
+            *stack = with_count(stack, count(stack) - moved);
+        }
+    }
+
+    fn to_packet(&self, inventory: &Inventory) -> ClickSlotC2s<'static> {
+        let slot_changes: Vec<_> = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|(idx, stack)| inventory.slot(*idx as u16) != *stack)
+            .map(|(idx, stack)| SlotChange {
+                idx: idx as i16,
+                stack: stack.clone(),
+            })
+            .collect();
+
+        ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(0),
+            slot_idx: self.slot_idx,
+            button: self.button,
+            mode: self.mode,
+            slot_changes: slot_changes.into(),
+            carried_item: self.cursor.clone(),
+        }
+    }
+}
+
+fn random_slot(rng: &mut StdRng) -> u16 {
+    rng.gen_range(SLOTS)
+}
+
+fn simulate_click(rng: &mut StdRng, inventory: &Inventory, cursor: &CursorItem) -> Click {
+    let mut click = Click::new(inventory, cursor, ClickMode::Click);
+    let slot = random_slot(rng);
+    let right = rng.gen_bool(0.5);
+
+    click.slot_idx = slot as i16;
+    click.button = i8::from(right);
+
+    let mut held = std::mem::take(&mut click.cursor);
+    let target = click.slot(slot).clone();
+
+    match (target.is_empty(), held.is_empty()) {
+        (true, true) => {}
+        (false, true) => {
+            // Pick up the whole stack, or the larger half.
+            let taken = if right {
+                (target.count + 1) / 2
+            } else {
+                target.count
+            };
+
+            held = with_count(&target, taken);
+            *click.slot(slot) = with_count(&target, target.count - taken);
+        }
+        (_, false) if target.is_empty() || stacks_match(&target, &held) => {
+            let mut placed = if right {
+                with_count(&held, 1)
+            } else {
+                held.clone()
+            };
+            let before = count(&placed);
+
+            click.insert(&mut placed, slot);
+
+            held = with_count(&held, count(&held) - (before - count(&placed)));
+        }
+        _ => {
+            *click.slot(slot) = std::mem::replace(&mut held, target);
+        }
+    }
+
+    click.cursor = held;
+    click
+}
+
+fn simulate_shift_click(rng: &mut StdRng, inventory: &Inventory) -> Click {
+    let mut click = Click::new(inventory, &CursorItem::default(), ClickMode::ShiftClick);
+    let slot = random_slot(rng);
+
+    click.slot_idx = slot as i16;
+
+    let targets = if PlayerInventory::SLOTS_HOTBAR.contains(&slot) {
+        9..=35
+    } else {
+        PlayerInventory::SLOTS_HOTBAR
+    };
+
+    let mut stack = std::mem::take(click.slot(slot));
+
+    // Vanilla fills matching stacks first, then empty slots.
+    for target in targets.clone() {
+        if !stack.is_empty() && !click.slot(target).is_empty() {
+            click.insert(&mut stack, target);
+        }
+    }
+
+    for target in targets {
+        if !stack.is_empty() && click.slot(target).is_empty() {
+            click.insert(&mut stack, target);
+        }
+    }
+
+    *click.slot(slot) = stack;
+    click
+}
+
+fn simulate_hotbar_swap(rng: &mut StdRng, inventory: &Inventory) -> Click {
+    let mut click = Click::new(inventory, &CursorItem::default(), ClickMode::Hotbar);
+    let slot = random_slot(rng);
+    let button = rng.gen_range(0..=8);
+
+    click.slot_idx = slot as i16;
+    click.button = button as i8;
+
+    click.slots.swap(
+        slot as usize,
+        PlayerInventory::hotbar_to_slot(button) as usize,
+    );
+
+    click
+}
+
+fn simulate_drop_key(rng: &mut StdRng, inventory: &Inventory) -> Click {
+    let mut click = Click::new(inventory, &CursorItem::default(), ClickMode::DropKey);
+    let slot = random_slot(rng);
+    let entire_stack = rng.gen_bool(0.5);
+
+    click.slot_idx = slot as i16;
+    click.button = i8::from(entire_stack);
+
+    let stack = click.slot(slot).clone();
+    let remaining = if entire_stack { 0 } else { count(&stack) - 1 };
+    *click.slot(slot) = with_count(&stack, remaining);
+
+    click
+}
+
+fn simulate_drag(rng: &mut StdRng, inventory: &Inventory, cursor: &CursorItem) -> Click {
+    let mut click = Click::new(inventory, cursor, ClickMode::Drag);
+    let right = rng.gen_bool(0.5);
+
+    click.button = if right { 6 } else { 2 };
+
+    let held = click.cursor.clone();
+
+    if held.is_empty() {
+        return click;
+    }
+
+    let mut targets: Vec<u16> = SLOTS
+        .filter(|slot| {
+            let stack = inventory.slot(*slot);
+            (stack.is_empty() || stacks_match(stack, &held)) && rng.gen_bool(0.3)
+        })
+        .collect();
+    targets.truncate(held.count as usize);
+
+    if targets.is_empty() {
+        return click;
+    }
+
+    let per_slot = if right {
+        1
+    } else {
+        held.count / targets.len() as i8
+    };
+
+    let mut remaining = held.count;
+
+    for target in targets {
+        let mut placed = with_count(&held, per_slot);
+        let before = count(&placed);
+
+        click.insert(&mut placed, target);
+
+        remaining -= before - count(&placed);
+    }
+
+    click.cursor = with_count(&held, remaining);
+    click
+}
+
+fn simulate_double_click(inventory: &Inventory, cursor: &CursorItem) -> Click {
+    let mut click = Click::new(inventory, cursor, ClickMode::DoubleClick);
+    let mut held = click.cursor.clone();
+
+    if !held.is_empty() {
+        for slot in SLOTS {
+            let stack = click.slot(slot).clone();
+
+            if !stacks_match(&stack, &held) {
+                continue;
+            }
+
+            let taken = count(&stack).min(held.item.max_stack() - held.count);
+            held.count += taken;
+            *click.slot(slot) = with_count(&stack, stack.count - taken);
+        }
+    }
+
+    click.cursor = held;
+    click
+}
+
+fn simulate(rng: &mut StdRng, inventory: &Inventory, cursor: &CursorItem) -> Click {
+    match rng.gen_range(0..6) {
+        0 => simulate_click(rng, inventory, cursor),
+        1 => simulate_shift_click(rng, inventory),
+        2 => simulate_hotbar_swap(rng, inventory),
+        3 => simulate_drop_key(rng, inventory),
+        4 => simulate_drag(rng, inventory, cursor),
+        _ => simulate_double_click(inventory, cursor),
+    }
+}
+
+/// Runs `f` with random inventories and vanilla clicks in them. Clicks that
+/// need an empty cursor are only simulated with one.
+fn for_each_click(mut f: impl FnMut(&mut StdRng, &Inventory, &CursorItem, ClickSlotC2s)) {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+
+    for _ in 0..CASES {
+        let (inventory, cursor) = random_state(&mut rng);
+        let click = simulate(&mut rng, &inventory, &cursor);
+
+        let cursor = match click.mode {
+            ClickMode::ShiftClick | ClickMode::Hotbar | ClickMode::DropKey => CursorItem::default(),
+            _ => cursor,
+        };
+
+        let packet = click.to_packet(&inventory);
+        f(&mut rng, &inventory, &cursor, packet);
+    }
+}
+
+#[test]
+fn vanilla_clicks_are_valid() {
+    for_each_click(|_, inventory, cursor, packet| {
+        if let Err(e) = validate_click_slot_packet(&packet, inventory, None, cursor) {
+            panic!("vanilla click was rejected: {e:#}\n{packet:#?}\ncursor: {cursor:?}");
+        }
+    });
+}
+
+#[test]
+fn duplicating_items_is_invalid() {
+    for_each_click(|rng, inventory, cursor, mut packet| {
+        let changes = packet.slot_changes.len();
+
+        let stack = if changes > 0 && rng.gen_bool(0.5) {
+            &mut packet.slot_changes.to_mut()[rng.gen_range(0..changes)].stack
+        } else {
+            &mut packet.carried_item
+        };
+
+        if stack.is_empty() || stack.count >= stack.item.max_stack() {
+            return;
+        }
+
+        stack.count += 1;
+
+        assert!(
+            validate_click_slot_packet(&packet, inventory, None, cursor).is_err(),
+            "duplicating click was accepted: {packet:#?}\ncursor: {cursor:?}"
+        );
+    });
+}
+
+#[test]
+fn transmuting_items_is_invalid() {
+    for_each_click(|rng, inventory, cursor, mut packet| {
+        let changes = packet.slot_changes.len();
+
+        let stack = if changes > 0 && rng.gen_bool(0.5) {
+            &mut packet.slot_changes.to_mut()[rng.gen_range(0..changes)].stack
+        } else {
+            &mut packet.carried_item
+        };
+
+        if stack.is_empty() {
+            return;
+        }
+
+        if rng.gen_bool(0.5) {
+            // Turn the items into gold, which no simulated click involves.
+            stack.item = ItemKind::GoldIngot;
+            stack.count = stack.count.min(ItemKind::GoldIngot.max_stack());
+        } else {
+            stack.nbt = named("Forged");
+        }
+
+        assert!(
+            validate_click_slot_packet(&packet, inventory, None, cursor).is_err(),
+            "transmuting click was accepted: {packet:#?}\ncursor: {cursor:?}"
+        );
+    });
+}
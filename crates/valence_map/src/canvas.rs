@@ -0,0 +1,278 @@
+use bevy_ecs::prelude::*;
+
+use crate::{font, MapColor};
+
+/// The pixels of a map.
+///
+/// Pixels are addressed by `x` from left to right and `y` from top to bottom.
+/// Drawing outside the canvas is clipped, so shapes may be partially off the
+/// map. The area changed since the last tick is tracked so that only it is
+/// sent to clients.
+#[derive(Component, Clone, Debug)]
+pub struct MapCanvas {
+    pixels: Box<[u8]>,
+    /// The smallest rectangle containing every pixel changed since the canvas
+    /// was last sent, as inclusive `[min_x, min_y, max_x, max_y]`.
+    dirty: Option<[usize; 4]>,
+}
+
+impl Default for MapCanvas {
+    fn default() -> Self {
+        Self {
+            pixels: vec![MapColor::TRANSPARENT.0; Self::SIZE * Self::SIZE].into_boxed_slice(),
+            dirty: None,
+        }
+    }
+}
+
+impl MapCanvas {
+    /// The width and height of a map in pixels.
+    pub const SIZE: usize = 128;
+
+    /// Creates a transparent canvas.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the color of the pixel at `(x, y)`, or `None` if it's outside
+    /// the canvas.
+    pub fn pixel(&self, x: i32, y: i32) -> Option<MapColor> {
+        Self::index(x, y).map(|idx| MapColor(self.pixels[idx]))
+    }
+
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: MapColor) {
+        let Some(idx) = Self::index(x, y) else {
+            return;
+        };
+
+        if self.pixels[idx] != color.0 {
+            self.pixels[idx] = color.0;
+            self.mark_dirty(x as usize, y as usize, x as usize, y as usize);
+        }
+    }
+
+    /// Sets every pixel to `color`.
+    pub fn fill(&mut self, color: MapColor) {
+        self.fill_rect([0, 0], [Self::SIZE as u32; 2], color);
+    }
+
+    /// Fills the rectangle with its top left corner at `pos`.
+    pub fn fill_rect(&mut self, pos: [i32; 2], size: [u32; 2], color: MapColor) {
+        let Some([min_x, min_y, max_x, max_y]) = Self::clip(pos, size) else {
+            return;
+        };
+
+        for y in min_y..=max_y {
+            self.pixels[y * Self::SIZE + min_x..=y * Self::SIZE + max_x].fill(color.0);
+        }
+
+        self.mark_dirty(min_x, min_y, max_x, max_y);
+    }
+
+    /// Draws the one pixel wide outline of the rectangle with its top left
+    /// corner at `pos`.
+    pub fn draw_rect(&mut self, pos: [i32; 2], size: [u32; 2], color: MapColor) {
+        if size[0] == 0 || size[1] == 0 {
+            return;
+        }
+
+        let [x, y] = pos;
+        let right = x.saturating_add_unsigned(size[0] - 1);
+        let bottom = y.saturating_add_unsigned(size[1] - 1);
+
+        self.fill_rect([x, y], [size[0], 1], color);
+        self.fill_rect([x, bottom], [size[0], 1], color);
+        self.fill_rect([x, y], [1, size[1]], color);
+        self.fill_rect([right, y], [1, size[1]], color);
+    }
+
+    /// Draws a one pixel wide line between two points, including both ends.
+    pub fn draw_line(&mut self, from: [i32; 2], to: [i32; 2], color: MapColor) {
+        let Some((from, to)) = Self::clip_line(from, to) else {
+            return;
+        };
+
+        // Bresenham's line algorithm.
+        let [mut x, mut y] = from;
+        let dx = (to[0] - x).abs();
+        let dy = -(to[1] - y).abs();
+        let step_x = if x < to[0] { 1 } else { -1 };
+        let step_y = if y < to[1] { 1 } else { -1 };
+        let mut err = dx + dy;
+
+        loop {
+            self.set_pixel(x as i32, y as i32, color);
+
+            if [x, y] == to {
+                break;
+            }
+
+            let e2 = 2 * err;
+
+            if e2 >= dy {
+                err += dy;
+                x += step_x;
+            }
+
+            if e2 <= dx {
+                err += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Draws `text` with its top left corner at `pos` using a small bitmap
+    /// font. Only printable ASCII characters are supported and `\n` starts a
+    /// new line. Returns the width and height of the drawn text, as given by
+    /// [`MapCanvas::text_size`].
+    pub fn draw_text(&mut self, pos: [i32; 2], text: &str, color: MapColor) -> [u32; 2] {
+        let [mut x, mut y] = pos;
+
+        for c in text.chars() {
+            if c == '\n' {
+                x = pos[0];
+                y += font::LINE_HEIGHT;
+                continue;
+            }
+
+            for (col, bits) in font::glyph(c).into_iter().enumerate() {
+                for row in 0..font::GLYPH_HEIGHT {
+                    if bits & (1 << row) != 0 {
+                        self.set_pixel(x + col as i32, y + row, color);
+                    }
+                }
+            }
+
+            x += font::ADVANCE;
+        }
+
+        Self::text_size(text)
+    }
+
+    /// Returns the width and height in pixels `text` takes up when drawn with
+    /// [`MapCanvas::draw_text`].
+    pub fn text_size(text: &str) -> [u32; 2] {
+        let mut width = 0;
+        let mut lines = 0;
+
+        for line in text.split('\n') {
+            let chars = line.chars().count() as i32;
+            width = width.max(chars * font::ADVANCE - 1);
+            lines += 1;
+        }
+
+        let height = lines * font::LINE_HEIGHT - (font::LINE_HEIGHT - font::GLYPH_HEIGHT);
+
+        [width.max(0) as u32, height as u32]
+    }
+
+    /// Draws an image with its top left corner at `pos`. `rgba` is the image
+    /// in rows from top to bottom, `width` pixels each. Every pixel is drawn
+    /// with the closest [`MapColor`] and pixels that are more than half
+    /// transparent are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero or the number of pixels isn't a multiple of
+    /// `width`.
+    pub fn draw_image(&mut self, pos: [i32; 2], width: u32, rgba: &[[u8; 4]]) {
+        assert!(width > 0, "image width must be positive");
+        assert_eq!(
+            rgba.len() % width as usize,
+            0,
+            "image size must be a multiple of its width"
+        );
+
+        for (i, &[r, g, b, a]) in rgba.iter().enumerate() {
+            if a < 128 {
+                continue;
+            }
+
+            let x = pos[0] + (i % width as usize) as i32;
+            let y = pos[1] + (i / width as usize) as i32;
+
+            if Self::index(x, y).is_some() {
+                self.set_pixel(x, y, MapColor::from_rgb([r, g, b]));
+            }
+        }
+    }
+
+    /// Returns the pixels in rows from top to bottom.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the area changed since the last call as `[x, y, width,
+    /// height]`.
+    pub(crate) fn take_dirty(&mut self) -> Option<[usize; 4]> {
+        let [min_x, min_y, max_x, max_y] = self.dirty.take()?;
+
+        Some([min_x, min_y, max_x - min_x + 1, max_y - min_y + 1])
+    }
+
+    /// Returns the pixels of the area `[x, y, width, height]` in rows.
+    pub(crate) fn area(&self, [x, y, width, height]: [usize; 4]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(width * height);
+
+        for row in y..y + height {
+            data.extend_from_slice(&self.pixels[row * Self::SIZE + x..][..width]);
+        }
+
+        data
+    }
+
+    fn index(x: i32, y: i32) -> Option<usize> {
+        let size = Self::SIZE as i32;
+
+        ((0..size).contains(&x) && (0..size).contains(&y)).then(|| (y * size + x) as usize)
+    }
+
+    /// Clips a rectangle to the canvas, returning its inclusive bounds.
+    fn clip(pos: [i32; 2], size: [u32; 2]) -> Option<[usize; 4]> {
+        let max = Self::SIZE as i64 - 1;
+        let min_x = i64::from(pos[0]).max(0);
+        let min_y = i64::from(pos[1]).max(0);
+        let max_x = (i64::from(pos[0]) + i64::from(size[0]) - 1).min(max);
+        let max_y = (i64::from(pos[1]) + i64::from(size[1]) - 1).min(max);
+
+        (min_x <= max_x && min_y <= max_y).then(|| [min_x, min_y, max_x, max_y].map(|n| n as usize))
+    }
+
+    /// Clips a line to the canvas with the Liang-Barsky algorithm, returning
+    /// the ends of the part on the canvas.
+    fn clip_line(from: [i32; 2], to: [i32; 2]) -> Option<([i64; 2], [i64; 2])> {
+        let max = Self::SIZE as f64 - 1.0;
+        let [x0, y0] = from.map(f64::from);
+        let [x1, y1] = to.map(f64::from);
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+
+        for (p, q) in [(-dx, x0), (dx, max - x0), (-dy, y0), (dy, max - y0)] {
+            if p == 0.0 {
+                // Parallel to this edge and outside of it.
+                if q < 0.0 {
+                    return None;
+                }
+            } else if p < 0.0 {
+                t0 = t0.max(q / p);
+            } else {
+                t1 = t1.min(q / p);
+            }
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+
+        let point = |t: f64| [(x0 + t * dx).round() as i64, (y0 + t * dy).round() as i64];
+
+        Some((point(t0), point(t1)))
+    }
+
+    fn mark_dirty(&mut self, min_x: usize, min_y: usize, max_x: usize, max_y: usize) {
+        self.dirty = Some(match self.dirty {
+            Some([x0, y0, x1, y1]) => [x0.min(min_x), y0.min(min_y), x1.max(max_x), y1.max(max_y)],
+            None => [min_x, min_y, max_x, max_y],
+        });
+    }
+}
@@ -0,0 +1,118 @@
+/// A color on a map, as an index into the map color palette.
+///
+/// Every color is one of the [base colors] at one of four
+/// [`MapBrightness`]es. The first base color is transparent.
+///
+/// [base colors]: https://minecraft.wiki/w/Map_item_format#Base_colors
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct MapColor(pub u8);
+
+/// The brightness of a [`MapColor`]. The discriminant is the index of the
+/// brightness in the palette.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum MapBrightness {
+    Low = 0,
+    Normal = 1,
+    #[default]
+    High = 2,
+    Lowest = 3,
+}
+
+impl MapBrightness {
+    const ALL: [Self; 4] = [Self::Low, Self::Normal, Self::High, Self::Lowest];
+
+    /// The factor the base color is multiplied by, out of 255.
+    const fn multiplier(self) -> u32 {
+        match self {
+            Self::Low => 180,
+            Self::Normal => 220,
+            Self::High => 255,
+            Self::Lowest => 135,
+        }
+    }
+}
+
+/// The RGB value of every base color. The first one is transparent.
+const BASE_COLORS: [u32; 62] = [
+    0x000000, 0x7fb238, 0xf7e9a3, 0xc7c7c7, 0xff0000, 0xa0a0ff, 0xa7a7a7, 0x007c00, 0xffffff,
+    0xa4a8b8, 0x976d4d, 0x707070, 0x4040ff, 0x8f7748, 0xfffcf5, 0xd87f33, 0xb24cd8, 0x6699d8,
+    0xe5e533, 0x7fcc19, 0xf27fa5, 0x4c4c4c, 0x999999, 0x4c7f99, 0x7f3fb2, 0x334cb2, 0x664c33,
+    0x667f33, 0x993333, 0x191919, 0xfaee4d, 0x5cdbd5, 0x4a80ff, 0x00d93a, 0x815631, 0x700200,
+    0xd1b1a1, 0x9f5224, 0x95576c, 0x706c8a, 0xba8524, 0x677535, 0xa04d4e, 0x392923, 0x876b62,
+    0x575c5c, 0x7a4958, 0x4c3e5c, 0x4c3223, 0x4c522a, 0x8e3c2e, 0x251610, 0xbd3031, 0x943f61,
+    0x5c191d, 0x167e86, 0x3a8e8c, 0x562c3e, 0x14b485, 0x646464, 0xd8af93, 0x7fa796,
+];
+
+impl MapColor {
+    pub const TRANSPARENT: Self = Self(0);
+    pub const WHITE: Self = Self::new(8, MapBrightness::High);
+    pub const LIGHT_GRAY: Self = Self::new(22, MapBrightness::High);
+    pub const GRAY: Self = Self::new(21, MapBrightness::High);
+    pub const BLACK: Self = Self::new(29, MapBrightness::High);
+    pub const BROWN: Self = Self::new(26, MapBrightness::High);
+    pub const RED: Self = Self::new(28, MapBrightness::High);
+    pub const ORANGE: Self = Self::new(15, MapBrightness::High);
+    pub const YELLOW: Self = Self::new(18, MapBrightness::High);
+    pub const LIME: Self = Self::new(19, MapBrightness::High);
+    pub const GREEN: Self = Self::new(27, MapBrightness::High);
+    pub const CYAN: Self = Self::new(23, MapBrightness::High);
+    pub const LIGHT_BLUE: Self = Self::new(17, MapBrightness::High);
+    pub const BLUE: Self = Self::new(25, MapBrightness::High);
+    pub const PURPLE: Self = Self::new(24, MapBrightness::High);
+    pub const MAGENTA: Self = Self::new(16, MapBrightness::High);
+    pub const PINK: Self = Self::new(20, MapBrightness::High);
+
+    /// Creates a color from the index of a base color and a brightness.
+    pub const fn new(base: u8, brightness: MapBrightness) -> Self {
+        Self(base * 4 + brightness as u8)
+    }
+
+    /// Returns the index of the base color.
+    pub const fn base(self) -> u8 {
+        self.0 / 4
+    }
+
+    pub const fn is_transparent(self) -> bool {
+        self.base() == 0 || self.base() as usize >= BASE_COLORS.len()
+    }
+
+    /// Returns the color as RGB, or `None` if it's transparent.
+    pub fn to_rgb(self) -> Option<[u8; 3]> {
+        if self.is_transparent() {
+            return None;
+        }
+
+        let base = BASE_COLORS[self.base() as usize];
+        let multiplier = MapBrightness::ALL[(self.0 % 4) as usize].multiplier();
+
+        Some([16, 8, 0].map(|shift| (((base >> shift) & 0xff) * multiplier / 255) as u8))
+    }
+
+    /// Returns the opaque color closest to the given RGB color.
+    pub fn from_rgb(rgb: [u8; 3]) -> Self {
+        let mut best = Self::TRANSPARENT;
+        let mut best_dist = u32::MAX;
+
+        for base in 1..BASE_COLORS.len() as u8 {
+            for brightness in MapBrightness::ALL {
+                let color = Self::new(base, brightness);
+                let Some(other) = color.to_rgb() else {
+                    continue;
+                };
+
+                let dist = rgb
+                    .iter()
+                    .zip(other)
+                    .map(|(&a, b)| (i32::from(a) - i32::from(b)).pow(2) as u32)
+                    .sum();
+
+                if dist < best_dist {
+                    best = color;
+                    best_dist = dist;
+                }
+            }
+        }
+
+        best
+    }
+}
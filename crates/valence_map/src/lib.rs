@@ -0,0 +1,211 @@
+#![doc = include_str!("../README.md")]
+
+mod canvas;
+mod color;
+mod font;
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+pub use canvas::MapCanvas;
+pub use color::{MapBrightness, MapColor};
+use derive_more::{Deref, DerefMut};
+use valence_inventory::player_inventory::PlayerInventory;
+use valence_inventory::{HeldItem, Inventory};
+use valence_server::client::{Client, SpawnClientsSet, UpdateClientsSet, VisibleEntityLayers};
+use valence_server::entity::{item_frame, EntityLayerId};
+use valence_server::nbt::compound;
+pub use valence_server::protocol::packets::play::map_update_s2c::IconType;
+use valence_server::protocol::packets::play::map_update_s2c::{Data, Icon};
+use valence_server::protocol::packets::play::MapUpdateS2c;
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::{Despawned, ItemKind, ItemStack, Text};
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, init_new_clients.after(SpawnClientsSet))
+            .add_systems(PostUpdate, update_maps.in_set(UpdateClientsSet));
+    }
+}
+
+/// The ID of a map. Filled map items show the map whose ID is in their `map`
+/// NBT tag.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct MapId(pub i32);
+
+impl MapId {
+    /// Returns a filled map item showing this map.
+    pub fn item_stack(self) -> ItemStack {
+        ItemStack::new(ItemKind::FilledMap, 1, Some(compound! { "map" => self.0 }))
+    }
+
+    /// Returns the ID of the map shown by the item, or `None` if it isn't a
+    /// filled map.
+    pub fn from_item_stack(stack: &ItemStack) -> Option<Self> {
+        if stack.item != ItemKind::FilledMap {
+            return None;
+        }
+
+        stack.nbt.as_ref()?.get("map")?.as_i32().map(Self)
+    }
+}
+
+/// The icons drawn on top of a map.
+#[derive(Component, Clone, PartialEq, Debug, Default, Deref, DerefMut)]
+pub struct MapIcons(pub Vec<MapIcon>);
+
+/// An icon on a map, like a player marker or a banner.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MapIcon {
+    pub kind: IconType,
+    /// The position of the icon's center. `-128` is the left or top edge of the
+    /// map and `127` the right or bottom edge, so there are two units per
+    /// pixel.
+    pub position: [i8; 2],
+    /// The rotation of the icon clockwise in steps of 22.5°, from 0 to 15.
+    pub rotation: u8,
+    /// The text shown below the icon.
+    pub name: Option<Text>,
+}
+
+impl MapIcon {
+    pub fn new(kind: IconType, position: [i8; 2]) -> Self {
+        Self {
+            kind,
+            position,
+            rotation: 0,
+            name: None,
+        }
+    }
+}
+
+/// The components of a map. The map is shown to clients holding a filled map
+/// item with the same [`MapId`], and to clients that can see an item frame
+/// holding one.
+#[derive(Bundle, Default)]
+pub struct MapBundle {
+    pub id: MapId,
+    pub canvas: MapCanvas,
+    pub icons: MapIcons,
+}
+
+/// The maps a client has been sent and is still viewing.
+#[derive(Component, Default, Debug)]
+struct ViewedMaps(HashSet<MapId>);
+
+fn init_new_clients(clients: Query<Entity, Added<Client>>, mut commands: Commands) {
+    for entity in &clients {
+        commands.entity(entity).insert(ViewedMaps::default());
+    }
+}
+
+fn update_maps(
+    mut maps: Query<(Entity, &MapId, &mut MapCanvas, Ref<MapIcons>), Without<Despawned>>,
+    frames: Query<(&item_frame::ItemStack, &EntityLayerId), Without<Despawned>>,
+    mut clients: Query<(
+        &mut Client,
+        &mut ViewedMaps,
+        &VisibleEntityLayers,
+        &Inventory,
+        &HeldItem,
+    )>,
+) {
+    // The changes to every map since last tick. Taking the changes even
+    // without viewers keeps them from piling up.
+    let mut updates = HashMap::new();
+
+    for (entity, id, mut canvas, icons) in &mut maps {
+        let canvas = canvas.bypass_change_detection();
+        let patch = canvas.take_dirty().map(|area| (area, canvas.area(area)));
+        let icons_changed = icons.is_changed() && !icons.is_added();
+
+        updates.insert(*id, (entity, patch, icons_changed));
+    }
+
+    let mut framed_maps = HashMap::<Entity, HashSet<MapId>>::new();
+
+    for (stack, layer) in &frames {
+        if let Some(id) = MapId::from_item_stack(stack) {
+            framed_maps.entry(layer.0).or_default().insert(id);
+        }
+    }
+
+    let mut viewing = HashSet::new();
+
+    for (mut client, mut viewed, visible_layers, inventory, held_item) in &mut clients {
+        viewing.clear();
+        viewing.extend(
+            [held_item.slot(), PlayerInventory::SLOT_OFFHAND]
+                .into_iter()
+                .filter_map(|slot| MapId::from_item_stack(inventory.slot(slot))),
+        );
+
+        for layer in &visible_layers.0 {
+            if let Some(ids) = framed_maps.get(layer) {
+                viewing.extend(ids);
+            }
+        }
+
+        // Maps that are no longer viewed are sent in full when they're viewed
+        // again, since the changes in between aren't sent.
+        viewed.0.retain(|id| viewing.contains(id));
+
+        for &id in &viewing {
+            let Some((entity, patch, icons_changed)) = updates.get(&id) else {
+                continue;
+            };
+
+            let Ok((_, _, canvas, icons)) = maps.get(*entity) else {
+                continue;
+            };
+
+            if viewed.0.insert(id) {
+                let area = [0, 0, MapCanvas::SIZE, MapCanvas::SIZE];
+                write_map_update(
+                    &mut *client,
+                    id,
+                    Some((area, canvas.as_bytes())),
+                    Some(&*icons),
+                );
+            } else if patch.is_some() || *icons_changed {
+                let patch = patch.as_ref().map(|(area, data)| (*area, data.as_slice()));
+                let icons = icons_changed.then_some(&*icons);
+                write_map_update(&mut *client, id, patch, icons);
+            }
+        }
+    }
+}
+
+fn write_map_update<W: WritePacket>(
+    mut writer: W,
+    id: MapId,
+    patch: Option<([usize; 4], &[u8])>,
+    icons: Option<&MapIcons>,
+) {
+    writer.write_packet(&MapUpdateS2c {
+        map_id: VarInt(id.0),
+        scale: 0,
+        locked: true,
+        icons: icons.map(|icons| {
+            icons
+                .iter()
+                .map(|icon| Icon {
+                    icon_type: icon.kind,
+                    position: icon.position,
+                    direction: (icon.rotation & 15) as i8,
+                    display_name: icon.name.as_ref().map(Cow::Borrowed),
+                })
+                .collect()
+        }),
+        data: patch.map(|([x, y, width, height], data)| Data {
+            columns: width as u8,
+            rows: height as u8,
+            position: [x as i8, y as i8],
+            data,
+        }),
+    });
+}
@@ -1,10 +1,15 @@
 use std::io::Write;
 
-pub use valence_generated::item::ItemKind;
+pub use tag::{HideFlags, SkullOwner};
+pub use valence_generated::item::{
+    ArmorSlot, FoodComponent, FoodEffect, ItemKind, ItemRarity, ToolTier,
+};
 use valence_nbt::Compound;
 
 use crate::{Decode, Encode};
 
+mod tag;
+
 /// A stack of items in an inventory.
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct ItemStack {
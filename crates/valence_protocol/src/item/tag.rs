@@ -0,0 +1,557 @@
+//! Typed access to the NBT of item stacks.
+
+use std::str::FromStr;
+
+use bitfield_struct::bitfield;
+use uuid::Uuid;
+use valence_generated::enchantment::Enchantment;
+use valence_ident::Ident;
+use valence_nbt::{compound, Compound, List, Value};
+use valence_text::{IntoText, Text};
+
+use super::ItemStack;
+
+/// The parts of an item's tooltip that are hidden from clients, stored in the
+/// `HideFlags` tag.
+#[bitfield(u8)]
+#[derive(PartialEq, Eq)]
+pub struct HideFlags {
+    pub enchantments: bool,
+    pub attribute_modifiers: bool,
+    pub unbreakable: bool,
+    pub can_destroy: bool,
+    pub can_place_on: bool,
+    /// Potion effects, book authors, firework properties, banner patterns and
+    /// other additional information.
+    pub additional: bool,
+    pub dye: bool,
+    pub armor_trim: bool,
+}
+
+/// The player whose skin is shown on a player head, stored in the
+/// `SkullOwner` tag.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct SkullOwner {
+    pub id: Option<Uuid>,
+    pub name: Option<String>,
+    /// The base64 encoded `textures` property of the player's profile. The
+    /// client shows the skin from this instead of looking it up.
+    pub textures: Option<String>,
+}
+
+impl SkullOwner {
+    /// A skull owner the client looks up by name.
+    pub fn named<N: Into<String>>(name: N) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    /// A skull owner with a fixed skin. The ID keeps heads with the same
+    /// skin stackable.
+    pub fn with_textures<T: Into<String>>(id: Uuid, textures: T) -> Self {
+        Self {
+            id: Some(id),
+            name: None,
+            textures: Some(textures.into()),
+        }
+    }
+
+    fn from_nbt(value: &Value) -> Option<Self> {
+        let owner = match value {
+            Value::String(name) => return Some(Self::named(name.clone())),
+            Value::Compound(owner) => owner,
+            _ => return None,
+        };
+
+        let id = match owner.get("Id") {
+            Some(Value::IntArray(id)) if id.len() == 4 => {
+                let [a, b, c, d] = [id[0], id[1], id[2], id[3]].map(|n| n as u32);
+                Some(Uuid::from_u64_pair(
+                    u64::from(a) << 32 | u64::from(b),
+                    u64::from(c) << 32 | u64::from(d),
+                ))
+            }
+            _ => None,
+        };
+
+        let name = match owner.get("Name") {
+            Some(Value::String(name)) => Some(name.clone()),
+            _ => None,
+        };
+
+        let textures = match owner.get("Properties") {
+            Some(Value::Compound(properties)) => match properties.get("textures") {
+                Some(Value::List(List::Compound(textures))) => {
+                    textures
+                        .first()
+                        .and_then(|texture| match texture.get("Value") {
+                            Some(Value::String(value)) => Some(value.clone()),
+                            _ => None,
+                        })
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        Some(Self { id, name, textures })
+    }
+
+    fn to_nbt(&self) -> Compound {
+        let mut owner = Compound::new();
+
+        if let Some(id) = self.id {
+            let (high, low) = id.as_u64_pair();
+            owner.insert(
+                "Id",
+                vec![
+                    (high >> 32) as i32,
+                    high as i32,
+                    (low >> 32) as i32,
+                    low as i32,
+                ],
+            );
+        }
+
+        if let Some(name) = &self.name {
+            owner.insert("Name", name.clone());
+        }
+
+        if let Some(textures) = &self.textures {
+            owner.insert(
+                "Properties",
+                compound! {
+                    "textures" => List::Compound(vec![compound! {
+                        "Value" => textures.clone(),
+                    }]),
+                },
+            );
+        }
+
+        owner
+    }
+}
+
+/// Typed accessors for the vanilla tags in [`ItemStack::nbt`]. Removing the
+/// last tag of a stack sets its NBT to `None`, so modified stacks still stack
+/// with plain ones.
+impl ItemStack {
+    /// Returns the custom name shown instead of the item's name.
+    pub fn custom_name(&self) -> Option<Text> {
+        match self.display()?.get("Name")? {
+            Value::String(name) => Text::from_str(name).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn set_custom_name<'a, T: IntoText<'a>>(&mut self, name: T) {
+        self.display_mut()
+            .insert("Name", String::from(name.into_text()));
+    }
+
+    pub fn clear_custom_name(&mut self) {
+        self.remove_display("Name");
+    }
+
+    #[must_use]
+    pub fn with_custom_name<'a, T: IntoText<'a>>(mut self, name: T) -> Self {
+        self.set_custom_name(name);
+        self
+    }
+
+    /// Returns the lines of text shown below the item's name.
+    pub fn lore(&self) -> Vec<Text> {
+        match self.display().and_then(|display| display.get("Lore")) {
+            Some(Value::List(List::String(lines))) => lines
+                .iter()
+                .map(|line| Text::from_str(line).unwrap_or_default())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Sets the lines of text shown below the item's name. Setting no lines
+    /// removes the lore.
+    pub fn set_lore<'a, I>(&mut self, lines: I)
+    where
+        I: IntoIterator,
+        I::Item: IntoText<'a>,
+    {
+        let lines: Vec<_> = lines
+            .into_iter()
+            .map(|line| String::from(line.into_text()))
+            .collect();
+
+        if lines.is_empty() {
+            self.remove_display("Lore");
+        } else {
+            self.display_mut().insert("Lore", List::String(lines));
+        }
+    }
+
+    #[must_use]
+    pub fn with_lore<'a, I>(mut self, lines: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: IntoText<'a>,
+    {
+        self.set_lore(lines);
+        self
+    }
+
+    /// Returns the level of an enchantment on this item stack, or zero if the
+    /// item stack does not have the enchantment.
+    pub fn enchantment_level(&self, enchantment: Enchantment) -> u32 {
+        self.enchantments()
+            .into_iter()
+            .find(|&(ench, _)| ench == enchantment)
+            .map_or(0, |(_, level)| level)
+    }
+
+    /// Returns the enchantments on this item stack and their levels.
+    /// Enchantments that aren't known are skipped.
+    pub fn enchantments(&self) -> Vec<(Enchantment, u32)> {
+        self.enchantment_list("Enchantments")
+    }
+
+    /// Adds an enchantment, or changes its level if the item stack already
+    /// has it. A level of zero removes the enchantment.
+    pub fn set_enchantment(&mut self, enchantment: Enchantment, level: u32) {
+        self.set_enchantment_in("Enchantments", enchantment, level);
+    }
+
+    #[must_use]
+    pub fn with_enchantment(mut self, enchantment: Enchantment, level: u32) -> Self {
+        self.set_enchantment(enchantment, level);
+        self
+    }
+
+    /// Returns the enchantments stored in an enchanted book, which are
+    /// applied to other items in an anvil.
+    pub fn stored_enchantments(&self) -> Vec<(Enchantment, u32)> {
+        self.enchantment_list("StoredEnchantments")
+    }
+
+    /// Like [`ItemStack::set_enchantment`], but for the enchantments stored
+    /// in an enchanted book.
+    pub fn set_stored_enchantment(&mut self, enchantment: Enchantment, level: u32) {
+        self.set_enchantment_in("StoredEnchantments", enchantment, level);
+    }
+
+    /// Returns how much durability this item stack has lost.
+    pub fn damage(&self) -> i32 {
+        self.tag("Damage")
+            .and_then(|damage| damage.as_i32())
+            .unwrap_or(0)
+    }
+
+    pub fn set_damage(&mut self, damage: i32) {
+        if damage == 0 {
+            self.remove_tag("Damage");
+        } else {
+            self.tag_mut().insert("Damage", damage);
+        }
+    }
+
+    #[must_use]
+    pub fn with_damage(mut self, damage: i32) -> Self {
+        self.set_damage(damage);
+        self
+    }
+
+    /// Returns the number resource packs use to pick a custom model for the
+    /// item.
+    pub fn custom_model_data(&self) -> Option<i32> {
+        self.tag("CustomModelData")?.as_i32()
+    }
+
+    pub fn set_custom_model_data(&mut self, data: Option<i32>) {
+        match data {
+            Some(data) => {
+                self.tag_mut().insert("CustomModelData", data);
+            }
+            None => self.remove_tag("CustomModelData"),
+        }
+    }
+
+    #[must_use]
+    pub fn with_custom_model_data(mut self, data: i32) -> Self {
+        self.set_custom_model_data(Some(data));
+        self
+    }
+
+    /// Returns whether the item never loses durability.
+    pub fn is_unbreakable(&self) -> bool {
+        self.tag("Unbreakable")
+            .and_then(|unbreakable| unbreakable.as_bool())
+            .unwrap_or(false)
+    }
+
+    pub fn set_unbreakable(&mut self, unbreakable: bool) {
+        if unbreakable {
+            self.tag_mut().insert("Unbreakable", true);
+        } else {
+            self.remove_tag("Unbreakable");
+        }
+    }
+
+    #[must_use]
+    pub fn with_unbreakable(mut self, unbreakable: bool) -> Self {
+        self.set_unbreakable(unbreakable);
+        self
+    }
+
+    pub fn hide_flags(&self) -> HideFlags {
+        self.tag("HideFlags")
+            .and_then(|flags| flags.as_i32())
+            .map_or(HideFlags::new(), |flags| HideFlags::from(flags as u8))
+    }
+
+    pub fn set_hide_flags(&mut self, flags: HideFlags) {
+        if flags == HideFlags::new() {
+            self.remove_tag("HideFlags");
+        } else {
+            self.tag_mut()
+                .insert("HideFlags", i32::from(u8::from(flags)));
+        }
+    }
+
+    #[must_use]
+    pub fn with_hide_flags(mut self, flags: HideFlags) -> Self {
+        self.set_hide_flags(flags);
+        self
+    }
+
+    /// Returns the player whose skin is shown on a player head.
+    pub fn skull_owner(&self) -> Option<SkullOwner> {
+        SkullOwner::from_nbt(self.tag("SkullOwner")?)
+    }
+
+    pub fn set_skull_owner(&mut self, owner: Option<SkullOwner>) {
+        match owner {
+            Some(owner) => {
+                self.tag_mut().insert("SkullOwner", owner.to_nbt());
+            }
+            None => self.remove_tag("SkullOwner"),
+        }
+    }
+
+    #[must_use]
+    pub fn with_skull_owner(mut self, owner: SkullOwner) -> Self {
+        self.set_skull_owner(Some(owner));
+        self
+    }
+
+    fn tag(&self, key: &str) -> Option<&Value> {
+        self.nbt.as_ref()?.get(key)
+    }
+
+    fn tag_mut(&mut self) -> &mut Compound {
+        self.nbt.get_or_insert_with(Compound::new)
+    }
+
+    fn remove_tag(&mut self, key: &str) {
+        if let Some(nbt) = &mut self.nbt {
+            nbt.remove(key);
+
+            if nbt.is_empty() {
+                self.nbt = None;
+            }
+        }
+    }
+
+    fn display(&self) -> Option<&Compound> {
+        match self.tag("display")? {
+            Value::Compound(display) => Some(display),
+            _ => None,
+        }
+    }
+
+    fn display_mut(&mut self) -> &mut Compound {
+        let display = self
+            .tag_mut()
+            .entry("display")
+            .or_insert_with(Compound::new);
+
+        if !matches!(display, Value::Compound(_)) {
+            *display = Compound::new().into();
+        }
+
+        let Value::Compound(display) = display else {
+            unreachable!()
+        };
+
+        display
+    }
+
+    fn remove_display(&mut self, key: &str) {
+        let Some(Value::Compound(display)) =
+            self.nbt.as_mut().and_then(|nbt| nbt.get_mut("display"))
+        else {
+            return;
+        };
+
+        display.remove(key);
+
+        if display.is_empty() {
+            self.remove_tag("display");
+        }
+    }
+
+    fn enchantment_list(&self, key: &str) -> Vec<(Enchantment, u32)> {
+        let Some(Value::List(List::Compound(enchantments))) = self.tag(key) else {
+            return vec![];
+        };
+
+        enchantments
+            .iter()
+            .filter_map(|ench| {
+                let Some(Value::String(id)) = ench.get("id") else {
+                    return None;
+                };
+
+                let id = Ident::new(id.as_str()).ok()?;
+                let enchantment = Enchantment::from_ident(id.as_str_ident())?;
+                let level = ench.get("lvl")?.as_i32()?.max(0) as u32;
+
+                Some((enchantment, level))
+            })
+            .collect()
+    }
+
+    fn set_enchantment_in(&mut self, key: &str, enchantment: Enchantment, level: u32) {
+        let matches = |ench: &Compound| match ench.get("id") {
+            Some(Value::String(id)) => Ident::new(id.as_str())
+                .is_ok_and(|id| id.as_str() == enchantment.to_ident().as_str()),
+            _ => false,
+        };
+
+        if level == 0 {
+            if let Some(Value::List(List::Compound(enchantments))) =
+                self.nbt.as_mut().and_then(|nbt| nbt.get_mut(key))
+            {
+                enchantments.retain(|ench| !matches(ench));
+
+                if enchantments.is_empty() {
+                    self.remove_tag(key);
+                }
+            }
+
+            return;
+        }
+
+        let entry = compound! {
+            "id" => enchantment.to_ident().as_str(),
+            "lvl" => level.min(i16::MAX as u32) as i16,
+        };
+
+        let list = self.tag_mut().entry(key).or_insert_with(List::new);
+
+        if !matches!(list, Value::List(List::Compound(_))) {
+            *list = List::Compound(vec![]).into();
+        }
+
+        let Value::List(List::Compound(enchantments)) = list else {
+            unreachable!()
+        };
+
+        match enchantments.iter_mut().find(|ench| matches(ench)) {
+            Some(ench) => *ench = entry,
+            None => enchantments.push(entry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_text::Color;
+
+    use super::*;
+    use crate::ItemKind;
+
+    #[test]
+    fn custom_name_and_lore_round_trip() {
+        let name = "Excalibur".into_text().color(Color::GOLD);
+
+        let mut stack = ItemStack::new(ItemKind::DiamondSword, 1, None)
+            .with_custom_name(name.clone())
+            .with_lore(["Forged in a lake", "Very sharp"]);
+
+        assert_eq!(stack.custom_name(), Some(name));
+        assert_eq!(
+            stack.lore(),
+            ["Forged in a lake".into_text(), "Very sharp".into_text()]
+        );
+
+        stack.clear_custom_name();
+        stack.set_lore(Vec::<Text>::new());
+
+        assert_eq!(stack.custom_name(), None);
+        assert_eq!(stack.nbt, None);
+    }
+
+    #[test]
+    fn enchantments_round_trip() {
+        let mut stack = ItemStack::new(ItemKind::DiamondSword, 1, None)
+            .with_enchantment(Enchantment::Sharpness, 5)
+            .with_enchantment(Enchantment::Looting, 2)
+            .with_enchantment(Enchantment::Sharpness, 3);
+
+        assert_eq!(
+            stack.enchantments(),
+            [(Enchantment::Sharpness, 3), (Enchantment::Looting, 2)]
+        );
+        assert_eq!(stack.enchantment_level(Enchantment::Looting), 2);
+        assert_eq!(stack.enchantment_level(Enchantment::Smite), 0);
+
+        stack.set_enchantment(Enchantment::Sharpness, 0);
+        stack.set_enchantment(Enchantment::Looting, 0);
+
+        assert_eq!(stack.nbt, None);
+    }
+
+    #[test]
+    fn enchantment_ids_without_namespace_are_read() {
+        let stack = ItemStack::new(
+            ItemKind::DiamondPickaxe,
+            1,
+            Some(compound! {
+                "Enchantments" => List::Compound(vec![compound! {
+                    "id" => "efficiency",
+                    "lvl" => 4_i16,
+                }]),
+            }),
+        );
+
+        assert_eq!(stack.enchantment_level(Enchantment::Efficiency), 4);
+    }
+
+    #[test]
+    fn skull_owner_round_trip() {
+        let owner =
+            SkullOwner::with_textures(Uuid::from_u128(0x1234_5678_9abc), "e3RleHR1cmVzfQ==");
+        let stack = ItemStack::new(ItemKind::PlayerHead, 1, None).with_skull_owner(owner.clone());
+
+        assert_eq!(stack.skull_owner(), Some(owner));
+    }
+
+    #[test]
+    fn other_tags_round_trip() {
+        let flags = HideFlags::new()
+            .with_enchantments(true)
+            .with_unbreakable(true);
+
+        let stack = ItemStack::new(ItemKind::IronPickaxe, 1, None)
+            .with_damage(42)
+            .with_custom_model_data(7)
+            .with_unbreakable(true)
+            .with_hide_flags(flags);
+
+        assert_eq!(stack.damage(), 42);
+        assert_eq!(stack.custom_model_data(), Some(7));
+        assert!(stack.is_unbreakable());
+        assert_eq!(stack.hide_flags(), flags);
+    }
+}
@@ -65,7 +65,7 @@ pub use raw::RawBytes;
 use serde::{Deserialize, Serialize};
 pub use sound::Sound;
 pub use text::Text;
-pub use valence_generated::{block, packet_id, status_effects};
+pub use valence_generated::{block, enchantment, packet_id, status_effects};
 pub use valence_ident::Ident;
 pub use valence_protocol_macros::{Decode, Encode, Packet};
 pub use var_int::VarInt;
@@ -1,59 +1,117 @@
 use std::borrow::Cow;
 use std::io::Write;
 
-use anyhow::ensure;
+use anyhow::{bail, ensure};
 use valence_ident::Ident;
 
-use crate::{Decode, Encode, ItemStack, Packet, RawBytes};
+use crate::{Decode, Encode, ItemStack, Packet, VarInt};
 
 #[derive(Clone, Debug, Encode, Decode, Packet)]
 pub struct SynchronizeRecipesS2c<'a> {
-    // TODO: this should be a Vec<Recipe<'a>>
-    pub recipes: RawBytes<'a>,
+    pub recipes: Vec<Recipe<'a>>,
 }
 
-#[derive(Clone, Debug, Encode)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Recipe<'a> {
-    pub kind: Ident<Cow<'a, str>>,
     pub recipe_id: Ident<Cow<'a, str>>,
     pub data: RecipeData<'a>,
 }
 
-#[derive(Clone, Debug, Encode)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum RecipeData<'a> {
-    CraftingShapeless(CraftingShapedData<'a>),
-    // TODO: fill in the rest.
-    CraftingShaped,
-    CraftingSpecialArmordye,
-    CraftingSpecialBookcloning,
-    CraftingSpecialMapcloning,
-    CraftingSpecialMapextending,
-    CraftingSpecialFireworkRocket,
-    CraftingSpecialFireworkStar,
-    CraftingSpecialFireworkStarFade,
-    CraftingSpecialRepairitem,
-    CraftingSpecialTippedarrow,
-    CraftingSpecialBannerduplicate,
-    CraftingSpecialShielddecoration,
-    CraftingSpecialShulkerboxcoloring,
-    CraftingSpecialSuspiciousStew,
-    CraftingDecoratedPot,
-    Smelting,
-    Blasting,
-    Smoking,
-    CampfireCooking,
-    Stonecutting,
-    SmithingTransform,
-    SmithingTrim,
-}
-
-#[derive(Clone, Debug)]
+    CraftingShaped(CraftingShapedData<'a>),
+    CraftingShapeless(CraftingShapelessData<'a>),
+    /// A recipe implemented by the client, like
+    /// `minecraft:crafting_special_armordye` or
+    /// `minecraft:crafting_decorated_pot`.
+    CraftingSpecial {
+        kind: Ident<Cow<'a, str>>,
+        category: CraftingCategory,
+    },
+    Smelting(CookingData<'a>),
+    Blasting(CookingData<'a>),
+    Smoking(CookingData<'a>),
+    CampfireCooking(CookingData<'a>),
+    Stonecutting(StonecuttingData<'a>),
+    SmithingTransform(SmithingTransformData<'a>),
+    SmithingTrim(SmithingTrimData<'a>),
+}
+
+impl RecipeData<'_> {
+    /// Returns the ID of the recipe's type.
+    pub fn kind(&self) -> &str {
+        match self {
+            RecipeData::CraftingShaped(_) => "minecraft:crafting_shaped",
+            RecipeData::CraftingShapeless(_) => "minecraft:crafting_shapeless",
+            RecipeData::CraftingSpecial { kind, .. } => kind.as_str(),
+            RecipeData::Smelting(_) => "minecraft:smelting",
+            RecipeData::Blasting(_) => "minecraft:blasting",
+            RecipeData::Smoking(_) => "minecraft:smoking",
+            RecipeData::CampfireCooking(_) => "minecraft:campfire_cooking",
+            RecipeData::Stonecutting(_) => "minecraft:stonecutting",
+            RecipeData::SmithingTransform(_) => "minecraft:smithing_transform",
+            RecipeData::SmithingTrim(_) => "minecraft:smithing_trim",
+        }
+    }
+}
+
+impl Encode for Recipe<'_> {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        self.data.kind().encode(&mut w)?;
+        self.recipe_id.encode(&mut w)?;
+
+        match &self.data {
+            RecipeData::CraftingShaped(data) => data.encode(w),
+            RecipeData::CraftingShapeless(data) => data.encode(w),
+            RecipeData::CraftingSpecial { category, .. } => category.encode(w),
+            RecipeData::Smelting(data)
+            | RecipeData::Blasting(data)
+            | RecipeData::Smoking(data)
+            | RecipeData::CampfireCooking(data) => data.encode(w),
+            RecipeData::Stonecutting(data) => data.encode(w),
+            RecipeData::SmithingTransform(data) => data.encode(w),
+            RecipeData::SmithingTrim(data) => data.encode(w),
+        }
+    }
+}
+
+impl<'a> Decode<'a> for Recipe<'a> {
+    fn decode(r: &mut &'a [u8]) -> anyhow::Result<Self> {
+        let kind = Ident::<Cow<str>>::decode(r)?;
+        let recipe_id = Ident::decode(r)?;
+
+        let data = match kind.as_str() {
+            "minecraft:crafting_shaped" => RecipeData::CraftingShaped(Decode::decode(r)?),
+            "minecraft:crafting_shapeless" => RecipeData::CraftingShapeless(Decode::decode(r)?),
+            "minecraft:smelting" => RecipeData::Smelting(Decode::decode(r)?),
+            "minecraft:blasting" => RecipeData::Blasting(Decode::decode(r)?),
+            "minecraft:smoking" => RecipeData::Smoking(Decode::decode(r)?),
+            "minecraft:campfire_cooking" => RecipeData::CampfireCooking(Decode::decode(r)?),
+            "minecraft:stonecutting" => RecipeData::Stonecutting(Decode::decode(r)?),
+            "minecraft:smithing_transform" => RecipeData::SmithingTransform(Decode::decode(r)?),
+            "minecraft:smithing_trim" => RecipeData::SmithingTrim(Decode::decode(r)?),
+            s if s.starts_with("minecraft:crafting_special_")
+                || s == "minecraft:crafting_decorated_pot" =>
+            {
+                RecipeData::CraftingSpecial {
+                    category: CraftingCategory::decode(r)?,
+                    kind,
+                }
+            }
+            other => bail!("unknown recipe type \"{other}\""),
+        };
+
+        Ok(Self { recipe_id, data })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub struct CraftingShapedData<'a> {
     pub width: u32,
     pub height: u32,
     pub group: &'a str,
-    pub category: CraftingShapedCategory,
-    /// Length must be width * height.
+    pub category: CraftingCategory,
+    /// The ingredients in rows. Length must be width * height.
     pub ingredients: Cow<'a, [Ingredient<'a>]>,
     pub result: ItemStack,
     pub show_notification: bool,
@@ -71,8 +129,8 @@ impl Encode for CraftingShapedData<'_> {
             show_notification,
         } = self;
 
-        width.encode(&mut w)?;
-        height.encode(&mut w)?;
+        VarInt(*width as i32).encode(&mut w)?;
+        VarInt(*height as i32).encode(&mut w)?;
         group.encode(&mut w)?;
         category.encode(&mut w)?;
 
@@ -95,12 +153,88 @@ impl Encode for CraftingShapedData<'_> {
     }
 }
 
+impl<'a> Decode<'a> for CraftingShapedData<'a> {
+    fn decode(r: &mut &'a [u8]) -> anyhow::Result<Self> {
+        let width = VarInt::decode(r)?.0;
+        let height = VarInt::decode(r)?.0;
+
+        ensure!(
+            (0..=3).contains(&width) && (0..=3).contains(&height),
+            "bad shaped recipe dimensions of {width}x{height}"
+        );
+
+        let group = <&str>::decode(r)?;
+        let category = CraftingCategory::decode(r)?;
+        let ingredients = (0..width * height)
+            .map(|_| Ingredient::decode(r))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            width: width as u32,
+            height: height as u32,
+            group,
+            category,
+            ingredients: ingredients.into(),
+            result: ItemStack::decode(r)?,
+            show_notification: bool::decode(r)?,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct CraftingShapelessData<'a> {
+    pub group: &'a str,
+    pub category: CraftingCategory,
+    pub ingredients: Cow<'a, [Ingredient<'a>]>,
+    pub result: ItemStack,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
-pub enum CraftingShapedCategory {
+pub enum CraftingCategory {
     Building,
     Redstone,
     Equipment,
     Misc,
 }
 
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct CookingData<'a> {
+    pub group: &'a str,
+    pub category: CookingCategory,
+    pub ingredient: Ingredient<'a>,
+    pub result: ItemStack,
+    pub experience: f32,
+    pub cooking_time: VarInt,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum CookingCategory {
+    Food,
+    Blocks,
+    Misc,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct StonecuttingData<'a> {
+    pub group: &'a str,
+    pub ingredient: Ingredient<'a>,
+    pub result: ItemStack,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct SmithingTransformData<'a> {
+    pub template: Ingredient<'a>,
+    pub base: Ingredient<'a>,
+    pub addition: Ingredient<'a>,
+    pub result: ItemStack,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct SmithingTrimData<'a> {
+    pub template: Ingredient<'a>,
+    pub base: Ingredient<'a>,
+    pub addition: Ingredient<'a>,
+}
+
+/// The item stacks that can be used for an ingredient of a recipe.
 pub type Ingredient<'a> = Cow<'a, [ItemStack]>;
@@ -0,0 +1,235 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use bevy_ecs::prelude::*;
+use valence_server::client::Client;
+use valence_server::event_loop::PacketEvent;
+use valence_server::protocol::packets::play::recipe_category_options_c2s::RecipeBookId;
+use valence_server::protocol::packets::play::synchronize_recipes_s2c::Recipe as PacketRecipe;
+use valence_server::protocol::packets::play::unlock_recipes_s2c::UpdateRecipeBookAction;
+use valence_server::protocol::packets::play::{
+    RecipeBookDataC2s, RecipeCategoryOptionsC2s, SynchronizeRecipesS2c, UnlockRecipesS2c,
+};
+use valence_server::protocol::WritePacket;
+use valence_server::Ident;
+
+use crate::RecipeRegistry;
+
+/// The recipes a client has unlocked in their recipe book, and the state of
+/// the book's tabs.
+///
+/// Unlocked recipes are shown in the recipe book, where the player can click
+/// them to fill the crafting grid. Newly unlocked recipes are highlighted
+/// until the player has looked at them. Clients can craft any recipe in the
+/// [`RecipeRegistry`], unlocked or not.
+#[derive(Component, Clone, Default, Debug)]
+pub struct RecipeBook {
+    pub crafting: RecipeBookState,
+    pub furnace: RecipeBookState,
+    pub blast_furnace: RecipeBookState,
+    pub smoker: RecipeBookState,
+    unlocked: BTreeSet<Ident<String>>,
+    highlighted: BTreeSet<Ident<String>>,
+    /// What the client was last sent, or `None` if nothing was sent yet.
+    sent: Option<SentRecipeBook>,
+}
+
+/// Whether one of the tabs of the recipe book is open and only shows the
+/// recipes that can be crafted.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct RecipeBookState {
+    pub open: bool,
+    pub filter_active: bool,
+}
+
+#[derive(Clone, Debug)]
+struct SentRecipeBook {
+    unlocked: BTreeSet<Ident<String>>,
+    states: [RecipeBookState; 4],
+}
+
+impl RecipeBook {
+    /// Unlocks a recipe. Returns `true` if the recipe wasn't unlocked before.
+    pub fn unlock<I: Into<Ident<String>>>(&mut self, id: I) -> bool {
+        let id = id.into();
+
+        if self.unlocked.insert(id.clone()) {
+            self.highlighted.insert(id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes a recipe from the book. Returns `true` if the recipe was
+    /// unlocked.
+    pub fn lock(&mut self, id: &str) -> bool {
+        self.highlighted.remove(id);
+        self.unlocked.remove(id)
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains(id)
+    }
+
+    /// Returns the IDs of the unlocked recipes.
+    pub fn iter(&self) -> impl Iterator<Item = &Ident<String>> + '_ {
+        self.unlocked.iter()
+    }
+
+    pub fn state(&self, book: RecipeBookId) -> RecipeBookState {
+        self.states()[book as usize]
+    }
+
+    pub fn state_mut(&mut self, book: RecipeBookId) -> &mut RecipeBookState {
+        match book {
+            RecipeBookId::Crafting => &mut self.crafting,
+            RecipeBookId::Furnace => &mut self.furnace,
+            RecipeBookId::BlastFurnace => &mut self.blast_furnace,
+            RecipeBookId::Smoker => &mut self.smoker,
+        }
+    }
+
+    fn states(&self) -> [RecipeBookState; 4] {
+        [self.crafting, self.furnace, self.blast_furnace, self.smoker]
+    }
+
+    fn write_packet<'a, W: WritePacket>(
+        &self,
+        mut writer: W,
+        action: UpdateRecipeBookAction<'a>,
+        recipe_ids: Vec<Ident<Cow<'a, str>>>,
+    ) {
+        writer.write_packet(&UnlockRecipesS2c {
+            action,
+            crafting_recipe_book_open: self.crafting.open,
+            crafting_recipe_book_filter_active: self.crafting.filter_active,
+            smelting_recipe_book_open: self.furnace.open,
+            smelting_recipe_book_filter_active: self.furnace.filter_active,
+            blast_furnace_recipe_book_open: self.blast_furnace.open,
+            blast_furnace_recipe_book_filter_active: self.blast_furnace.filter_active,
+            smoker_recipe_book_open: self.smoker.open,
+            smoker_recipe_book_filter_active: self.smoker.filter_active,
+            recipe_ids,
+        });
+    }
+}
+
+pub(crate) fn init_new_clients(clients: Query<Entity, Added<Client>>, mut commands: Commands) {
+    for entity in &clients {
+        commands.entity(entity).insert(RecipeBook::default());
+    }
+}
+
+/// Sends the recipes in the registry to new clients, and to every client when
+/// the registry changes.
+pub(crate) fn sync_recipes(
+    registry: Res<RecipeRegistry>,
+    mut clients: Query<(&mut Client, Ref<RecipeBook>)>,
+) {
+    let mut packet = None;
+
+    for (mut client, book) in &mut clients {
+        if !registry.is_changed() && !book.is_added() {
+            continue;
+        }
+
+        let packet = packet.get_or_insert_with(|| SynchronizeRecipesS2c {
+            recipes: registry
+                .iter()
+                .map(|(id, recipe)| PacketRecipe {
+                    recipe_id: id.as_str_ident().into(),
+                    data: recipe.to_packet(),
+                })
+                .collect(),
+        });
+
+        client.write_packet(packet);
+    }
+}
+
+pub(crate) fn update_recipe_books(
+    mut clients: Query<(&mut Client, &mut RecipeBook), Changed<RecipeBook>>,
+) {
+    for (mut client, mut book) in &mut clients {
+        let book = book.bypass_change_detection();
+        let states = book.states();
+
+        match &book.sent {
+            None => book.write_packet(
+                &mut *client,
+                UpdateRecipeBookAction::Init {
+                    recipe_ids: book
+                        .highlighted
+                        .iter()
+                        .map(|id| id.as_str_ident().into())
+                        .collect(),
+                },
+                book.unlocked
+                    .iter()
+                    .map(|id| id.as_str_ident().into())
+                    .collect(),
+            ),
+            Some(sent) => {
+                let removed: Vec<_> = sent
+                    .unlocked
+                    .difference(&book.unlocked)
+                    .map(|id| id.as_str_ident().into())
+                    .collect();
+
+                let added: Vec<_> = book
+                    .unlocked
+                    .difference(&sent.unlocked)
+                    .map(|id| id.as_str_ident().into())
+                    .collect();
+
+                let removing = !removed.is_empty();
+
+                if removing {
+                    book.write_packet(&mut *client, UpdateRecipeBookAction::Remove, removed);
+                }
+
+                // The states of the tabs are sent with every packet.
+                if !added.is_empty() || (sent.states != states && !removing) {
+                    book.write_packet(&mut *client, UpdateRecipeBookAction::Add, added);
+                }
+            }
+        }
+
+        book.sent = Some(SentRecipeBook {
+            unlocked: book.unlocked.clone(),
+            states,
+        });
+    }
+}
+
+pub(crate) fn handle_recipe_book_packets(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<&mut RecipeBook>,
+) {
+    for packet in packets.read() {
+        let Ok(mut book) = clients.get_mut(packet.client) else {
+            continue;
+        };
+
+        // The client already knows about these changes, so they aren't sent
+        // back.
+        let book = book.bypass_change_detection();
+
+        if let Some(pkt) = packet.decode::<RecipeCategoryOptionsC2s>() {
+            *book.state_mut(pkt.book_id) = RecipeBookState {
+                open: pkt.book_open,
+                filter_active: pkt.filter_active,
+            };
+
+            let states = book.states();
+
+            if let Some(sent) = &mut book.sent {
+                sent.states = states;
+            }
+        } else if let Some(pkt) = packet.decode::<RecipeBookDataC2s>() {
+            // The player has seen the recipe.
+            book.highlighted.remove(pkt.recipe_id.as_str());
+        }
+    }
+}
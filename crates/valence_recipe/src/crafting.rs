@@ -0,0 +1,707 @@
+use std::borrow::Cow;
+
+use bevy_ecs::prelude::*;
+use valence_inventory::player_inventory::PlayerInventory;
+use valence_inventory::stacking::{give_to_player, merge_into, player_space_for, stacks_match};
+use valence_inventory::transaction::{ItemLocation, TransactionEvents};
+use valence_inventory::{
+    ClientInventoryState, CursorItem, Inventory, InventoryKind, OpenInventory,
+};
+use valence_server::client::Client;
+use valence_server::event_loop::PacketEvent;
+use valence_server::protocol::packets::play::click_slot_c2s::ClickMode;
+use valence_server::protocol::packets::play::{
+    ClickSlotC2s, CloseHandledScreenC2s, CraftFailedResponseS2c, CraftRequestC2s, InventoryS2c,
+};
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::{Ident, ItemKind, ItemStack};
+
+use crate::{Ingredient, Recipe, RecipeBook, RecipeRegistry};
+
+/// The slot of the crafting result in crafting inventories. The crafting grid
+/// starts at the next slot.
+pub(crate) const RESULT_SLOT: u16 = 0;
+
+/// An event sent when a client takes the result of a recipe out of a crafting
+/// grid. Taking out the results of several crafts at once, like when
+/// shift-clicking, sends one event per craft.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct CraftEvent {
+    pub client: Entity,
+    pub recipe: Ident<String>,
+    /// The items made by one craft.
+    pub result: ItemStack,
+}
+
+/// Returns the width of the square crafting grid in inventories of this kind.
+fn grid_width(kind: InventoryKind) -> Option<usize> {
+    match kind {
+        InventoryKind::Player => Some(2),
+        InventoryKind::Crafting => Some(3),
+        _ => None,
+    }
+}
+
+fn grid_slots(width: usize) -> std::ops::RangeInclusive<u16> {
+    RESULT_SLOT + 1..=RESULT_SLOT + (width * width) as u16
+}
+
+fn find_recipe<'a>(
+    registry: &'a RecipeRegistry,
+    inv: &Inventory,
+    width: usize,
+) -> Option<(&'a Ident<String>, &'a Recipe)> {
+    let grid: Vec<_> = grid_slots(width)
+        .map(|slot| inv.slot(slot).clone())
+        .collect();
+
+    registry.find_crafting_recipe(&grid, width)
+}
+
+/// Sets the result slot of every crafting grid that changed.
+pub(crate) fn update_crafting_results(
+    registry: Res<RecipeRegistry>,
+    mut inventories: Query<&mut Inventory>,
+) {
+    for mut inv in &mut inventories {
+        if !registry.is_changed() && !inv.is_changed() {
+            continue;
+        }
+
+        let Some(width) = grid_width(inv.kind()) else {
+            continue;
+        };
+
+        let result = find_recipe(&registry, &inv, width)
+            .map_or(ItemStack::EMPTY, |(_, recipe)| recipe.result().clone());
+
+        if *inv.slot(RESULT_SLOT) != result {
+            inv.set_slot(RESULT_SLOT, result);
+        }
+    }
+}
+
+/// The inventories involved in crafting. The crafting grid is in the player's
+/// inventory unless a crafting table is open.
+struct CraftingWindow<'a> {
+    player: &'a mut Inventory,
+    table: Option<&'a mut Inventory>,
+    width: usize,
+}
+
+impl CraftingWindow<'_> {
+    fn grid(&self) -> &Inventory {
+        self.table.as_deref().unwrap_or(self.player)
+    }
+
+    fn grid_mut(&mut self) -> &mut Inventory {
+        match &mut self.table {
+            Some(table) => table,
+            None => self.player,
+        }
+    }
+
+    fn is_readonly(&self) -> bool {
+        self.player.readonly || self.table.as_ref().is_some_and(|table| table.readonly)
+    }
+
+    fn find_recipe<'a>(
+        &self,
+        registry: &'a RecipeRegistry,
+    ) -> Option<(&'a Ident<String>, &'a Recipe)> {
+        find_recipe(registry, self.grid(), self.width)
+    }
+
+    /// Takes one item out of every slot of the grid and updates the result
+    /// slot. Returns the remainders of ingredients that didn't fit in the
+    /// grid, like buckets of stacked milk buckets, with their slots.
+    fn consume_ingredients(&mut self, registry: &RecipeRegistry) -> Vec<(u16, ItemStack)> {
+        let width = self.width;
+        let grid = self.grid_mut();
+        let mut leftovers = vec![];
+
+        for slot in grid_slots(width) {
+            let stack = grid.slot(slot).clone();
+
+            if stack.is_empty() {
+                continue;
+            }
+
+            let remainder =
+                crafting_remainder(stack.item).map(|item| ItemStack::new(item, 1, None));
+
+            if stack.count > 1 {
+                let count = stack.count - 1;
+                grid.set_slot(slot, stack.with_count(count));
+                leftovers.extend(remainder.map(|remainder| (slot, remainder)));
+            } else {
+                grid.set_slot(slot, remainder.unwrap_or(ItemStack::EMPTY));
+            }
+        }
+
+        self.update_result(registry);
+
+        leftovers
+    }
+
+    fn update_result(&mut self, registry: &RecipeRegistry) {
+        let result = self
+            .find_recipe(registry)
+            .map_or(ItemStack::EMPTY, |(_, recipe)| recipe.result().clone());
+
+        self.grid_mut().set_slot(RESULT_SLOT, result);
+    }
+
+    /// Moves the items in the grid to the player's inventory. Returns `false`
+    /// if they don't all fit.
+    fn clear_grid(&mut self) -> bool {
+        for slot in grid_slots(self.width) {
+            let stack = self.grid_mut().replace_slot(slot, ItemStack::EMPTY);
+
+            if !give_to_player(self.player, stack).is_empty() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Moves one item for every ingredient from the player's inventory to the
+    /// grid. Nothing is moved if there aren't enough items, or if a slot of
+    /// the grid is full.
+    fn place_ingredients(&mut self, ingredients: &[(u16, &Ingredient)]) -> bool {
+        let mut available: Vec<_> = PlayerInventory::SLOTS_MAIN
+            .map(|slot| self.player.slot(slot).count)
+            .collect();
+        let mut sources = Vec::with_capacity(ingredients.len());
+
+        for &(grid_slot, ingredient) in ingredients {
+            let current = self.grid().slot(grid_slot);
+
+            if !current.is_empty() && current.count >= current.item.max_stack() {
+                return false;
+            }
+
+            let Some(source) = PlayerInventory::SLOTS_MAIN.into_iter().find(|&slot| {
+                let stack = self.player.slot(slot);
+                let idx = (slot - PlayerInventory::SLOTS_MAIN.start()) as usize;
+
+                available[idx] > 0
+                    && ingredient.test(stack)
+                    && (current.is_empty() || stacks_match(current, stack))
+            }) else {
+                return false;
+            };
+
+            available[(source - PlayerInventory::SLOTS_MAIN.start()) as usize] -= 1;
+            sources.push((grid_slot, source));
+        }
+
+        for (grid_slot, source) in sources {
+            let stack = self.player.slot(source).clone();
+            let count = stack.count;
+
+            self.player.set_slot(
+                source,
+                if count > 1 {
+                    stack.clone().with_count(count - 1)
+                } else {
+                    ItemStack::EMPTY
+                },
+            );
+
+            let grid = self.grid_mut();
+            let current = grid.slot(grid_slot);
+            let new = if current.is_empty() {
+                stack.with_count(1)
+            } else {
+                current.clone().with_count(current.count + 1)
+            };
+
+            grid.set_slot(grid_slot, new);
+        }
+
+        true
+    }
+
+    /// Returns the current contents of everything a craft can change: the
+    /// grid and result slot in `grid`, the player's inventory and `cursor`.
+    fn snapshot(
+        &self,
+        grid: Entity,
+        client: Entity,
+        cursor: &ItemStack,
+    ) -> Vec<(ItemLocation, ItemStack)> {
+        let grid_stacks = (RESULT_SLOT..=*grid_slots(self.width).end()).map(|slot| {
+            (
+                ItemLocation::Slot {
+                    inventory: grid,
+                    slot,
+                },
+                self.grid().slot(slot).clone(),
+            )
+        });
+        let player_stacks = PlayerInventory::SLOTS_MAIN
+            .chain([PlayerInventory::SLOT_OFFHAND])
+            .map(|slot| {
+                (
+                    ItemLocation::Slot {
+                        inventory: client,
+                        slot,
+                    },
+                    self.player.slot(slot).clone(),
+                )
+            });
+
+        std::iter::once((ItemLocation::Cursor, cursor.clone()))
+            .chain(grid_stacks)
+            .chain(player_stacks)
+            .collect()
+    }
+
+    /// Returns every slot of the window as the client sees it.
+    fn slots(&self) -> Vec<ItemStack> {
+        match &self.table {
+            Some(table) => table
+                .slots()
+                .chain(
+                    self.player
+                        .slots()
+                        .skip(*PlayerInventory::SLOTS_MAIN.start() as usize),
+                )
+                .cloned()
+                .collect(),
+            None => self.player.slots().cloned().collect(),
+        }
+    }
+}
+
+/// Handles clicks on the result slot of crafting grids. The server decides
+/// what the click does, so the client's view of the window is resynced
+/// afterwards. Cancelling the transaction of a drop undoes the craft it came
+/// from, although its [`CraftEvent`] has been sent already.
+#[allow(clippy::type_complexity)]
+pub(crate) fn handle_result_clicks(
+    mut packets: EventReader<PacketEvent>,
+    registry: Res<RecipeRegistry>,
+    mut clients: Query<(
+        &mut Client,
+        &mut Inventory,
+        &ClientInventoryState,
+        &mut CursorItem,
+        Option<&OpenInventory>,
+    )>,
+    mut inventories: Query<&mut Inventory, Without<Client>>,
+    mut craft_events: EventWriter<CraftEvent>,
+    mut transactions: TransactionEvents,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<ClickSlotC2s>() else {
+            continue;
+        };
+
+        if pkt.slot_idx != RESULT_SLOT as i16 {
+            continue;
+        }
+
+        let Ok((mut client, mut player_inv, inv_state, mut cursor_item, open_inventory)) =
+            clients.get_mut(packet.client)
+        else {
+            continue;
+        };
+
+        let mut grid_entity = packet.client;
+        let mut table = match open_inventory {
+            Some(open) => match inventories.get_mut(open.entity) {
+                Ok(inv) if inv.kind() == InventoryKind::Crafting => {
+                    grid_entity = open.entity;
+                    Some(inv)
+                }
+                // The click is on another kind of inventory.
+                _ => continue,
+            },
+            None => None,
+        };
+
+        if (pkt.window_id == 0) != table.is_none() {
+            continue;
+        }
+
+        let mut window = CraftingWindow {
+            width: if table.is_some() { 3 } else { 2 },
+            player: &mut player_inv,
+            table: table.as_deref_mut(),
+        };
+
+        let max_crafts = match pkt.mode {
+            ClickMode::Click | ClickMode::Hotbar => 1,
+            ClickMode::ShiftClick => 64,
+            ClickMode::DropKey if pkt.button == 1 => 64,
+            ClickMode::DropKey => 1,
+            _ => 0,
+        };
+
+        let result = window.grid().slot(RESULT_SLOT).clone();
+        let mut crafted = false;
+
+        for _ in 0..max_crafts {
+            if window.is_readonly() {
+                break;
+            }
+
+            let Some((id, recipe)) = window.find_recipe(&registry) else {
+                break;
+            };
+
+            // Crafting continues only while the result stays the same.
+            if result.is_empty() || *recipe.result() != result {
+                break;
+            }
+
+            let before = window.snapshot(grid_entity, packet.client, &cursor_item.0);
+            let result_location = ItemLocation::Slot {
+                inventory: grid_entity,
+                slot: RESULT_SLOT,
+            };
+
+            let delivered = match pkt.mode {
+                ClickMode::Click => merge_into(&mut cursor_item.0, &result),
+                ClickMode::ShiftClick => {
+                    player_space_for(window.player, &result) >= i32::from(result.count)
+                        && give_to_player(window.player, result.clone()).is_empty()
+                }
+                ClickMode::Hotbar => {
+                    let slot = match pkt.button {
+                        0..=8 => PlayerInventory::hotbar_to_slot(pkt.button as u8),
+                        40 => PlayerInventory::SLOT_OFFHAND,
+                        _ => break,
+                    };
+
+                    let mut stack = window.player.slot(slot).clone();
+                    let merged = merge_into(&mut stack, &result);
+                    window.player.set_slot(slot, stack);
+                    merged
+                }
+                ClickMode::DropKey => {
+                    transactions.drop_with_snapshot(
+                        packet.client,
+                        result_location,
+                        before.clone(),
+                        Some(RESULT_SLOT),
+                        result.clone(),
+                    );
+                    true
+                }
+                _ => false,
+            };
+
+            if !delivered {
+                break;
+            }
+
+            crafted = true;
+            craft_events.send(CraftEvent {
+                client: packet.client,
+                recipe: id.clone(),
+                result: result.clone(),
+            });
+
+            for (slot, leftover) in window.consume_ingredients(&registry) {
+                let rest = give_to_player(window.player, leftover);
+
+                if !rest.is_empty() {
+                    transactions.drop_with_snapshot(
+                        packet.client,
+                        ItemLocation::Slot {
+                            inventory: grid_entity,
+                            slot,
+                        },
+                        before.clone(),
+                        None,
+                        rest,
+                    );
+                }
+            }
+        }
+
+        // The client may have guessed the outcome of the click wrong.
+        if !crafted && pkt.slot_changes.is_empty() && pkt.carried_item == cursor_item.0 {
+            continue;
+        }
+
+        client.write_packet(&InventoryS2c {
+            window_id: if window.table.is_some() {
+                inv_state.window_id()
+            } else {
+                0
+            },
+            state_id: VarInt(inv_state.state_id().0),
+            slots: Cow::Owned(window.slots()),
+            carried_item: Cow::Borrowed(&cursor_item.0),
+        });
+    }
+}
+
+/// Handles clicks on recipes in the recipe book by filling the crafting grid
+/// with the recipe's ingredients from the player's inventory.
+#[allow(clippy::type_complexity)]
+pub(crate) fn handle_craft_requests(
+    mut packets: EventReader<PacketEvent>,
+    registry: Res<RecipeRegistry>,
+    mut clients: Query<(
+        &mut Client,
+        &mut Inventory,
+        &ClientInventoryState,
+        Option<&OpenInventory>,
+        Option<&RecipeBook>,
+    )>,
+    mut inventories: Query<&mut Inventory, Without<Client>>,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<CraftRequestC2s>() else {
+            continue;
+        };
+
+        let Ok((mut client, mut player_inv, inv_state, open_inventory, book)) =
+            clients.get_mut(packet.client)
+        else {
+            continue;
+        };
+
+        let mut table_inv = match open_inventory {
+            Some(open) if pkt.window_id as u8 == inv_state.window_id() => {
+                match inventories.get_mut(open.entity) {
+                    Ok(inv) if inv.kind() == InventoryKind::Crafting => Some(inv),
+                    _ => continue,
+                }
+            }
+            None if pkt.window_id == 0 => None,
+            _ => continue,
+        };
+
+        let id = pkt.recipe.as_str();
+
+        if book.is_some_and(|book| !book.is_unlocked(id)) {
+            continue;
+        }
+
+        let width = if table_inv.is_some() { 3 } else { 2 };
+
+        let Some(ingredients) = registry
+            .get(id)
+            .and_then(|recipe| grid_ingredients(recipe, width))
+        else {
+            continue;
+        };
+
+        // Work on copies so that nothing changes when the grid can't be
+        // cleared.
+        let mut player = player_inv.clone();
+        let mut table = table_inv.as_deref().cloned();
+
+        let mut window = CraftingWindow {
+            player: &mut player,
+            table: table.as_mut(),
+            width,
+        };
+
+        if window.is_readonly() || !window.clear_grid() {
+            continue;
+        }
+
+        let max_sets = if pkt.make_all { 64 } else { 1 };
+        let mut sets = 0;
+
+        while sets < max_sets && window.place_ingredients(&ingredients) {
+            sets += 1;
+        }
+
+        window.update_result(&registry);
+
+        if sets == 0 {
+            // Shows the recipe in the grid without items.
+            client.write_packet(&CraftFailedResponseS2c {
+                window_id: pkt.window_id as u8,
+                recipe: pkt.recipe.clone(),
+            });
+        }
+
+        copy_slots(&player, &mut player_inv);
+
+        if let (Some(table), Some(table_inv)) = (&table, &mut table_inv) {
+            copy_slots(table, table_inv);
+        }
+    }
+}
+
+/// Moves the items in the player's crafting grid back to their inventory when
+/// they close it, like the client does.
+pub(crate) fn return_player_grid_items(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<&mut Inventory, With<Client>>,
+    mut transactions: TransactionEvents,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<CloseHandledScreenC2s>() else {
+            continue;
+        };
+
+        if pkt.window_id != 0 {
+            continue;
+        }
+
+        let Ok(mut inv) = clients.get_mut(packet.client) else {
+            continue;
+        };
+
+        for slot in PlayerInventory::SLOTS_CRAFT_INPUT {
+            if inv.slot(slot).is_empty() {
+                continue;
+            }
+
+            let stack = inv.replace_slot(slot, ItemStack::EMPTY);
+            let rest = give_to_player(&mut inv, stack);
+
+            if !rest.is_empty() {
+                // Cancelling the drop leaves the items in the grid.
+                transactions.drop(
+                    packet.client,
+                    ItemLocation::Slot {
+                        inventory: packet.client,
+                        slot,
+                    },
+                    rest.clone(),
+                    Some(slot),
+                    rest,
+                );
+            }
+        }
+    }
+}
+
+/// The crafting table inventory a client is viewing.
+#[derive(Component, Copy, Clone, Debug)]
+pub(crate) struct ViewedCraftingTable(Entity);
+
+/// Moves the items in a crafting table's grid back to the client's inventory
+/// when they stop viewing it, whether they closed it or the server did. Items
+/// that don't fit are dropped. The grid is left alone while other clients are
+/// still viewing the same table.
+///
+/// This runs after the event loop, so the drops can only be cancelled and are
+/// only sent in the event loop of the next tick.
+pub(crate) fn return_table_grid_items(
+    clients: Query<
+        (Entity, Option<&OpenInventory>, Option<&ViewedCraftingTable>),
+        (
+            With<Client>,
+            Or<(Changed<OpenInventory>, With<ViewedCraftingTable>)>,
+        ),
+    >,
+    viewers: Query<&OpenInventory>,
+    mut inventories: Query<&mut Inventory>,
+    mut transactions: TransactionEvents,
+    mut commands: Commands,
+) {
+    for (client, open, viewed) in &clients {
+        let open_table = open.map(|open| open.entity).filter(|&entity| {
+            inventories
+                .get(entity)
+                .is_ok_and(|inv| inv.kind() == InventoryKind::Crafting)
+        });
+        let viewed_table = viewed.map(|viewed| viewed.0);
+
+        if open_table == viewed_table {
+            continue;
+        }
+
+        match open_table {
+            Some(table) => commands.entity(client).insert(ViewedCraftingTable(table)),
+            None => commands.entity(client).remove::<ViewedCraftingTable>(),
+        };
+
+        let Some(table) = viewed_table else {
+            continue;
+        };
+
+        if viewers.iter().any(|open| open.entity == table) {
+            continue;
+        }
+
+        let Ok([mut table_inv, mut player_inv]) = inventories.get_many_mut([table, client]) else {
+            continue;
+        };
+
+        for slot in grid_slots(3) {
+            if table_inv.slot(slot).is_empty() {
+                continue;
+            }
+
+            let stack = table_inv.replace_slot(slot, ItemStack::EMPTY);
+            let rest = give_to_player(&mut player_inv, stack);
+
+            if !rest.is_empty() {
+                // Cancelling the drop leaves the items in the table.
+                transactions.drop(
+                    client,
+                    ItemLocation::Slot {
+                        inventory: table,
+                        slot,
+                    },
+                    rest.clone(),
+                    None,
+                    rest,
+                );
+            }
+        }
+    }
+}
+
+/// Returns the grid slots and ingredients to place for a crafting recipe, or
+/// `None` if the recipe doesn't fit in the grid.
+fn grid_ingredients(recipe: &Recipe, width: usize) -> Option<Vec<(u16, &Ingredient)>> {
+    let first = *grid_slots(width).start();
+
+    match recipe {
+        Recipe::Shaped(recipe) if recipe.width <= width && recipe.height <= width => Some(
+            recipe
+                .ingredients
+                .iter()
+                .enumerate()
+                .filter(|(_, ingredient)| !ingredient.is_empty())
+                .map(|(i, ingredient)| {
+                    let (x, y) = (i % recipe.width, i / recipe.width);
+                    (first + (y * width + x) as u16, ingredient)
+                })
+                .collect(),
+        ),
+        Recipe::Shapeless(recipe) if recipe.ingredients.len() <= width * width => Some(
+            recipe
+                .ingredients
+                .iter()
+                .enumerate()
+                .map(|(i, ingredient)| (first + i as u16, ingredient))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// The item left in the grid after an ingredient is used.
+fn crafting_remainder(item: ItemKind) -> Option<ItemKind> {
+    match item {
+        ItemKind::MilkBucket | ItemKind::WaterBucket | ItemKind::LavaBucket => {
+            Some(ItemKind::Bucket)
+        }
+        ItemKind::HoneyBottle | ItemKind::DragonBreath => Some(ItemKind::GlassBottle),
+        _ => None,
+    }
+}
+
+fn copy_slots(src: &Inventory, dest: &mut Inventory) {
+    for (slot, stack) in src.slots().enumerate() {
+        if dest.slot(slot as u16) != stack {
+            dest.set_slot(slot as u16, stack.clone());
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Reading recipes in the JSON format of vanilla datapacks.
+
+use anyhow::{bail, Context};
+use serde_json::{Map, Value};
+use valence_server::registry::tags::TagsRegistry;
+use valence_server::{ItemKind, ItemStack};
+
+use crate::{
+    CookingCategory, CookingKind, CookingRecipe, CraftingCategory, Ingredient, Recipe,
+    ShapedRecipe, ShapelessRecipe, StonecuttingRecipe,
+};
+
+/// Parses a recipe. Returns `None` if the recipe type isn't supported.
+pub(crate) fn parse_recipe(json: &Value, tags: &TagsRegistry) -> anyhow::Result<Option<Recipe>> {
+    let obj = json.as_object().context("recipe must be an object")?;
+    let kind = str_field(obj, "type")?;
+    let group = obj
+        .get("group")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+
+    let cooking_kind = match kind.strip_prefix("minecraft:").unwrap_or(kind) {
+        "crafting_shaped" => {
+            let pattern: Vec<&str> = obj
+                .get("pattern")
+                .and_then(Value::as_array)
+                .context("missing pattern")?
+                .iter()
+                .map(|row| row.as_str().context("pattern rows must be strings"))
+                .collect::<anyhow::Result<_>>()?;
+
+            let key = obj
+                .get("key")
+                .and_then(Value::as_object)
+                .context("missing key")?
+                .iter()
+                .map(|(k, v)| {
+                    let mut chars = k.chars();
+
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) if c != ' ' => Ok((c, parse_ingredient(v, tags)?)),
+                        _ => bail!("invalid key \"{k}\""),
+                    }
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let mut recipe = ShapedRecipe::from_pattern(&pattern, &key, parse_result(obj)?)?;
+            recipe.group = group;
+            recipe.category = parse_crafting_category(obj)?;
+            recipe.show_notification = obj
+                .get("show_notification")
+                .and_then(Value::as_bool)
+                .unwrap_or(true);
+
+            return Ok(Some(Recipe::Shaped(recipe)));
+        }
+        "crafting_shapeless" => {
+            let ingredients = obj
+                .get("ingredients")
+                .and_then(Value::as_array)
+                .context("missing ingredients")?
+                .iter()
+                .map(|v| parse_ingredient(v, tags))
+                .collect::<anyhow::Result<_>>()?;
+
+            let mut recipe = ShapelessRecipe::new(ingredients, parse_result(obj)?);
+            recipe.group = group;
+            recipe.category = parse_crafting_category(obj)?;
+
+            return Ok(Some(Recipe::Shapeless(recipe)));
+        }
+        "stonecutting" => {
+            let count = obj.get("count").and_then(Value::as_i64).unwrap_or(1);
+
+            let mut recipe = StonecuttingRecipe::new(
+                parse_ingredient(obj.get("ingredient").context("missing ingredient")?, tags)?,
+                ItemStack::new(parse_item(str_field(obj, "result")?)?, count as i8, None),
+            );
+            recipe.group = group;
+
+            return Ok(Some(Recipe::Stonecutting(recipe)));
+        }
+        "smelting" => CookingKind::Smelting,
+        "blasting" => CookingKind::Blasting,
+        "smoking" => CookingKind::Smoking,
+        "campfire_cooking" => CookingKind::CampfireCooking,
+        _ => return Ok(None),
+    };
+
+    let result = match obj.get("result") {
+        Some(Value::String(item)) => ItemStack::new(parse_item(item)?, 1, None),
+        _ => parse_result(obj)?,
+    };
+
+    let mut recipe = CookingRecipe::new(
+        cooking_kind,
+        parse_ingredient(obj.get("ingredient").context("missing ingredient")?, tags)?,
+        result,
+    );
+    recipe.group = group;
+    recipe.experience = obj.get("experience").and_then(Value::as_f64).unwrap_or(0.0) as f32;
+
+    if let Some(time) = obj.get("cookingtime").and_then(Value::as_i64) {
+        recipe.cooking_time = time as i32;
+    }
+
+    recipe.category = match obj.get("category").and_then(Value::as_str) {
+        Some("food") => CookingCategory::Food,
+        Some("blocks") => CookingCategory::Blocks,
+        Some("misc") | None => CookingCategory::Misc,
+        Some(other) => bail!("unknown cooking category \"{other}\""),
+    };
+
+    Ok(Some(Recipe::Cooking(recipe)))
+}
+
+/// Parses an ingredient, which is an item, an item tag or an array of those.
+fn parse_ingredient(json: &Value, tags: &TagsRegistry) -> anyhow::Result<Ingredient> {
+    let mut items = vec![];
+
+    match json {
+        Value::Array(values) => {
+            for value in values {
+                items.extend(parse_ingredient(value, tags)?.0);
+            }
+        }
+        Value::Object(obj) => {
+            if let Some(item) = obj.get("item").and_then(Value::as_str) {
+                items.push(parse_item(item)?);
+            } else if let Some(tag) = obj.get("tag").and_then(Value::as_str) {
+                let tag = tag.strip_prefix('#').unwrap_or(tag);
+                let tag = if tag.contains(':') {
+                    tag.to_owned()
+                } else {
+                    format!("minecraft:{tag}")
+                };
+
+                let ids = tags
+                    .registries
+                    .get("minecraft:item")
+                    .and_then(|item_tags| item_tags.get(tag.as_str()))
+                    .with_context(|| format!("unknown item tag \"{tag}\""))?;
+
+                for id in ids {
+                    items.push(
+                        ItemKind::from_raw(id.0 as u16)
+                            .with_context(|| format!("invalid item ID {} in tag", id.0))?,
+                    );
+                }
+            } else {
+                bail!("ingredient must have an item or a tag");
+            }
+        }
+        _ => bail!("ingredient must be an object or an array"),
+    }
+
+    anyhow::ensure!(!items.is_empty(), "ingredient must not be empty");
+
+    Ok(Ingredient(items))
+}
+
+/// Parses a `result` object with an item and an optional count.
+fn parse_result(obj: &Map<String, Value>) -> anyhow::Result<ItemStack> {
+    let result = obj
+        .get("result")
+        .and_then(Value::as_object)
+        .context("missing result")?;
+
+    let count = result.get("count").and_then(Value::as_i64).unwrap_or(1);
+
+    Ok(ItemStack::new(
+        parse_item(str_field(result, "item")?)?,
+        count as i8,
+        None,
+    ))
+}
+
+fn parse_item(name: &str) -> anyhow::Result<ItemKind> {
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+
+    ItemKind::from_str(name).with_context(|| format!("unknown item \"{name}\""))
+}
+
+fn parse_crafting_category(obj: &Map<String, Value>) -> anyhow::Result<CraftingCategory> {
+    Ok(match obj.get("category").and_then(Value::as_str) {
+        Some("building") => CraftingCategory::Building,
+        Some("redstone") => CraftingCategory::Redstone,
+        Some("equipment") => CraftingCategory::Equipment,
+        Some("misc") | None => CraftingCategory::Misc,
+        Some(other) => bail!("unknown crafting category \"{other}\""),
+    })
+}
+
+fn str_field<'a>(obj: &'a Map<String, Value>, name: &str) -> anyhow::Result<&'a str> {
+    obj.get(name)
+        .and_then(Value::as_str)
+        .with_context(|| format!("missing {name}"))
+}
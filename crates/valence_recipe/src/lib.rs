@@ -0,0 +1,186 @@
+#![doc = include_str!("../README.md")]
+
+mod book;
+mod crafting;
+mod json;
+mod recipe;
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+pub use book::{RecipeBook, RecipeBookState};
+pub use crafting::CraftEvent;
+use indexmap::IndexMap;
+pub use recipe::*;
+use valence_inventory::{CustomClickSlots, InventoryKind, UpdateInventoriesSet};
+use valence_server::client::{FlushPacketsSet, SpawnClientsSet};
+pub use valence_server::protocol::packets::play::recipe_category_options_c2s::RecipeBookId;
+use valence_server::registry::tags::TagsRegistry;
+use valence_server::{EventLoopPreUpdate, Ident, ItemStack};
+
+pub struct RecipePlugin;
+
+impl Plugin for RecipePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecipeRegistry>()
+            .add_event::<CraftEvent>()
+            .add_systems(PreUpdate, book::init_new_clients.after(SpawnClientsSet))
+            .add_systems(
+                EventLoopPreUpdate,
+                (
+                    crafting::handle_result_clicks,
+                    crafting::handle_craft_requests,
+                    crafting::return_player_grid_items,
+                    book::handle_recipe_book_packets,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    (
+                        crafting::return_table_grid_items,
+                        crafting::update_crafting_results,
+                    )
+                        .chain()
+                        .before(UpdateInventoriesSet),
+                    (book::sync_recipes, book::update_recipe_books)
+                        .chain()
+                        .before(FlushPacketsSet),
+                ),
+            );
+
+        // Clicks on crafting results are handled by this plugin.
+        app.world_mut()
+            .get_resource_or_insert_with(CustomClickSlots::default)
+            .extend([
+                (InventoryKind::Player, crafting::RESULT_SLOT),
+                (InventoryKind::Crafting, crafting::RESULT_SLOT),
+            ]);
+    }
+}
+
+/// The recipes known to the server, in the order they were inserted. Clients
+/// are sent every recipe when they join and again when the registry changes.
+#[derive(Resource, Default, Debug)]
+pub struct RecipeRegistry {
+    recipes: IndexMap<Ident<String>, Recipe>,
+}
+
+impl RecipeRegistry {
+    /// Inserts a recipe, returning the recipe that had the same ID.
+    pub fn insert<I: Into<Ident<String>>>(&mut self, id: I, recipe: Recipe) -> Option<Recipe> {
+        self.recipes.insert(id.into(), recipe)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Recipe> {
+        self.recipes.get(id)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Recipe> {
+        self.recipes.shift_remove(id)
+    }
+
+    pub fn clear(&mut self) {
+        self.recipes.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&Ident<String>, &Recipe)> + '_ {
+        self.recipes.iter()
+    }
+
+    /// Returns the first crafting recipe matching the items in a square
+    /// crafting grid. `grid` is the grid in rows from top to bottom, `width`
+    /// slots each.
+    pub fn find_crafting_recipe(
+        &self,
+        grid: &[ItemStack],
+        width: usize,
+    ) -> Option<(&Ident<String>, &Recipe)> {
+        self.recipes
+            .iter()
+            .find(|(_, recipe)| recipe.matches_grid(grid, width))
+    }
+
+    /// Inserts a recipe in the JSON format of vanilla datapacks. Item tags in
+    /// ingredients are looked up in `tags`.
+    ///
+    /// Shaped, shapeless, cooking and stonecutting recipes are supported.
+    /// Other recipe types return an error.
+    pub fn insert_json<I: Into<Ident<String>>>(
+        &mut self,
+        id: I,
+        json: &str,
+        tags: &TagsRegistry,
+    ) -> anyhow::Result<()> {
+        let json = serde_json::from_str(json)?;
+        let recipe = json::parse_recipe(&json, tags)?.context("unsupported recipe type")?;
+
+        self.insert(id, recipe);
+
+        Ok(())
+    }
+
+    /// Inserts every recipe in a directory of recipes in the JSON format of
+    /// vanilla datapacks, like `data/minecraft/recipes`. The ID of a recipe
+    /// is `namespace` followed by its path in the directory without the
+    /// extension. Recipes of unsupported types are skipped.
+    ///
+    /// Returns the number of recipes inserted.
+    pub fn load_dir<P: AsRef<Path>>(
+        &mut self,
+        namespace: &str,
+        dir: P,
+        tags: &TagsRegistry,
+    ) -> anyhow::Result<usize> {
+        let mut count = 0;
+        let mut dirs = vec![dir.as_ref().to_path_buf()];
+
+        while let Some(current) = dirs.pop() {
+            for entry in fs::read_dir(&current)? {
+                let path = entry?.path();
+
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+
+                if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                    continue;
+                }
+
+                let name = path
+                    .strip_prefix(dir.as_ref())?
+                    .with_extension("")
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+
+                let id = Ident::<String>::try_from(format!("{namespace}:{name}"))?;
+
+                let recipe = fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| json::parse_recipe(&serde_json::from_str(&json)?, tags))
+                    .with_context(|| format!("failed to load recipe {}", path.display()))?;
+
+                if let Some(recipe) = recipe {
+                    self.insert(id, recipe);
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+}
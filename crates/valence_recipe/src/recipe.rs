@@ -0,0 +1,415 @@
+use std::borrow::Cow;
+
+pub use valence_server::protocol::packets::play::synchronize_recipes_s2c::{
+    CookingCategory, CraftingCategory,
+};
+use valence_server::protocol::packets::play::synchronize_recipes_s2c::{
+    CookingData, CraftingShapedData, CraftingShapelessData, RecipeData, StonecuttingData,
+};
+use valence_server::{ItemKind, ItemStack};
+
+/// The items accepted in one place of a recipe. An empty ingredient only
+/// matches an empty slot.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Ingredient(pub Vec<ItemKind>);
+
+impl Ingredient {
+    pub const EMPTY: Self = Self(vec![]);
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns whether `stack` can be used for this ingredient.
+    pub fn test(&self, stack: &ItemStack) -> bool {
+        if self.is_empty() {
+            stack.is_empty()
+        } else {
+            !stack.is_empty() && self.0.contains(&stack.item)
+        }
+    }
+
+    fn to_packet<'a>(&self) -> Cow<'a, [ItemStack]> {
+        self.0
+            .iter()
+            .map(|&item| ItemStack::new(item, 1, None))
+            .collect()
+    }
+}
+
+impl From<ItemKind> for Ingredient {
+    fn from(item: ItemKind) -> Self {
+        Self(vec![item])
+    }
+}
+
+impl<const N: usize> From<[ItemKind; N]> for Ingredient {
+    fn from(items: [ItemKind; N]) -> Self {
+        Self(items.into())
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Recipe {
+    Shaped(ShapedRecipe),
+    Shapeless(ShapelessRecipe),
+    Cooking(CookingRecipe),
+    Stonecutting(StonecuttingRecipe),
+}
+
+impl Recipe {
+    /// The item stack made by the recipe.
+    pub fn result(&self) -> &ItemStack {
+        match self {
+            Recipe::Shaped(recipe) => &recipe.result,
+            Recipe::Shapeless(recipe) => &recipe.result,
+            Recipe::Cooking(recipe) => &recipe.result,
+            Recipe::Stonecutting(recipe) => &recipe.result,
+        }
+    }
+
+    /// Returns whether this is a crafting recipe matching the items in a
+    /// square crafting grid. `grid` is the grid in rows from top to bottom,
+    /// `width` slots each.
+    pub fn matches_grid(&self, grid: &[ItemStack], width: usize) -> bool {
+        match self {
+            Recipe::Shaped(recipe) => recipe.matches_grid(grid, width),
+            Recipe::Shapeless(recipe) => recipe.matches_grid(grid),
+            Recipe::Cooking(_) | Recipe::Stonecutting(_) => false,
+        }
+    }
+
+    pub(crate) fn to_packet(&self) -> RecipeData<'_> {
+        match self {
+            Recipe::Shaped(recipe) => RecipeData::CraftingShaped(CraftingShapedData {
+                width: recipe.width as u32,
+                height: recipe.height as u32,
+                group: &recipe.group,
+                category: recipe.category,
+                ingredients: recipe
+                    .ingredients
+                    .iter()
+                    .map(Ingredient::to_packet)
+                    .collect(),
+                result: recipe.result.clone(),
+                show_notification: recipe.show_notification,
+            }),
+            Recipe::Shapeless(recipe) => RecipeData::CraftingShapeless(CraftingShapelessData {
+                group: &recipe.group,
+                category: recipe.category,
+                ingredients: recipe
+                    .ingredients
+                    .iter()
+                    .map(Ingredient::to_packet)
+                    .collect(),
+                result: recipe.result.clone(),
+            }),
+            Recipe::Cooking(recipe) => {
+                let data = CookingData {
+                    group: &recipe.group,
+                    category: recipe.category,
+                    ingredient: recipe.ingredient.to_packet(),
+                    result: recipe.result.clone(),
+                    experience: recipe.experience,
+                    cooking_time: recipe.cooking_time.into(),
+                };
+
+                match recipe.kind {
+                    CookingKind::Smelting => RecipeData::Smelting(data),
+                    CookingKind::Blasting => RecipeData::Blasting(data),
+                    CookingKind::Smoking => RecipeData::Smoking(data),
+                    CookingKind::CampfireCooking => RecipeData::CampfireCooking(data),
+                }
+            }
+            Recipe::Stonecutting(recipe) => RecipeData::Stonecutting(StonecuttingData {
+                group: &recipe.group,
+                ingredient: recipe.ingredient.to_packet(),
+                result: recipe.result.clone(),
+            }),
+        }
+    }
+}
+
+/// A crafting recipe with its ingredients in a fixed pattern. The pattern can
+/// be placed anywhere in the crafting grid and may be mirrored horizontally.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShapedRecipe {
+    pub width: usize,
+    pub height: usize,
+    /// The ingredients in rows from top to bottom. There are `width * height`
+    /// of them.
+    pub ingredients: Vec<Ingredient>,
+    pub result: ItemStack,
+    /// Recipes in the same group are shown together in the recipe book.
+    pub group: String,
+    pub category: CraftingCategory,
+    /// Whether a toast is shown when the recipe is unlocked.
+    pub show_notification: bool,
+}
+
+impl ShapedRecipe {
+    /// Creates a recipe from rows of characters and the ingredient each
+    /// character stands for, like the recipes of vanilla datapacks. Spaces
+    /// are empty slots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is empty, larger than 3×3, has rows of different
+    /// lengths or uses a character missing from `key`. Empty rows and columns
+    /// at the edges of the pattern are removed.
+    ///
+    /// ```
+    /// # use valence_recipe::*;
+    /// # use valence_server::{ItemKind, ItemStack};
+    /// let recipe = ShapedRecipe::new(
+    ///     &["#", "#"],
+    ///     &[('#', ItemKind::OakPlanks.into())],
+    ///     ItemStack::new(ItemKind::Stick, 4, None),
+    /// );
+    ///
+    /// assert_eq!([recipe.width, recipe.height], [1, 2]);
+    /// ```
+    #[track_caller]
+    pub fn new<S: AsRef<str>>(
+        pattern: &[S],
+        key: &[(char, Ingredient)],
+        result: ItemStack,
+    ) -> Self {
+        match Self::from_pattern(pattern, key, result) {
+            Ok(recipe) => recipe,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    pub(crate) fn from_pattern<S: AsRef<str>>(
+        pattern: &[S],
+        key: &[(char, Ingredient)],
+        result: ItemStack,
+    ) -> anyhow::Result<Self> {
+        let height = pattern.len();
+        let width = pattern
+            .first()
+            .map_or(0, |row| row.as_ref().chars().count());
+
+        anyhow::ensure!(
+            (1..=3).contains(&width) && (1..=3).contains(&height),
+            "shaped recipe pattern must be between 1×1 and 3×3"
+        );
+
+        let mut ingredients = Vec::with_capacity(width * height);
+
+        for row in pattern {
+            let row = row.as_ref();
+
+            anyhow::ensure!(
+                row.chars().count() == width,
+                "rows of shaped recipe pattern must have the same length"
+            );
+
+            for c in row.chars() {
+                if c == ' ' {
+                    ingredients.push(Ingredient::EMPTY);
+                } else {
+                    let Some((_, ingredient)) = key.iter().find(|(k, _)| *k == c) else {
+                        anyhow::bail!("missing key for '{c}' in shaped recipe pattern");
+                    };
+
+                    ingredients.push(ingredient.clone());
+                }
+            }
+        }
+
+        // Empty rows and columns around the pattern don't matter when it's
+        // placed in the grid.
+        let Some([min_x, min_y, max_x, max_y]) =
+            bounds(ingredients.iter().map(|ingr| !ingr.is_empty()), width)
+        else {
+            anyhow::bail!("shaped recipe pattern must not be empty");
+        };
+
+        let ingredients = (min_y..=max_y)
+            .flat_map(|y| {
+                ingredients[y * width + min_x..=y * width + max_x]
+                    .iter()
+                    .cloned()
+            })
+            .collect();
+
+        Ok(Self {
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1,
+            ingredients,
+            result,
+            group: String::new(),
+            category: CraftingCategory::Misc,
+            show_notification: true,
+        })
+    }
+
+    /// Returns whether the recipe matches the items in a square crafting grid
+    /// `width` slots wide.
+    pub fn matches_grid(&self, grid: &[ItemStack], width: usize) -> bool {
+        let Some([min_x, min_y, max_x, max_y]) =
+            bounds(grid.iter().map(|stack| !stack.is_empty()), width)
+        else {
+            return false;
+        };
+
+        if max_x - min_x + 1 != self.width || max_y - min_y + 1 != self.height {
+            return false;
+        }
+
+        let matches = |mirrored: bool| {
+            (0..self.height).all(|y| {
+                (0..self.width).all(|x| {
+                    let recipe_x = if mirrored { self.width - 1 - x } else { x };
+                    let ingredient = &self.ingredients[y * self.width + recipe_x];
+
+                    ingredient.test(&grid[(min_y + y) * width + min_x + x])
+                })
+            })
+        };
+
+        matches(false) || matches(true)
+    }
+}
+
+/// A crafting recipe with ingredients that can be placed anywhere in the
+/// crafting grid.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShapelessRecipe {
+    pub ingredients: Vec<Ingredient>,
+    pub result: ItemStack,
+    pub group: String,
+    pub category: CraftingCategory,
+}
+
+impl ShapelessRecipe {
+    pub fn new(ingredients: Vec<Ingredient>, result: ItemStack) -> Self {
+        Self {
+            ingredients,
+            result,
+            group: String::new(),
+            category: CraftingCategory::Misc,
+        }
+    }
+
+    /// Returns whether the recipe matches the items in a crafting grid.
+    pub fn matches_grid(&self, grid: &[ItemStack]) -> bool {
+        let stacks: Vec<_> = grid.iter().filter(|stack| !stack.is_empty()).collect();
+
+        if stacks.len() != self.ingredients.len() {
+            return false;
+        }
+
+        // Ingredients may accept more than one item, so find a stack for every
+        // ingredient by backtracking.
+        fn assign(ingredients: &[Ingredient], stacks: &[&ItemStack], used: &mut [bool]) -> bool {
+            let Some((ingredient, rest)) = ingredients.split_first() else {
+                return true;
+            };
+
+            for (i, stack) in stacks.iter().enumerate() {
+                if !used[i] && ingredient.test(stack) {
+                    used[i] = true;
+
+                    if assign(rest, stacks, used) {
+                        return true;
+                    }
+
+                    used[i] = false;
+                }
+            }
+
+            false
+        }
+
+        assign(&self.ingredients, &stacks, &mut vec![false; stacks.len()])
+    }
+}
+
+/// The block a [`CookingRecipe`] is made in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CookingKind {
+    Smelting,
+    Blasting,
+    Smoking,
+    CampfireCooking,
+}
+
+impl CookingKind {
+    /// The default cooking time of recipes of this kind, in ticks.
+    pub const fn default_cooking_time(self) -> i32 {
+        match self {
+            CookingKind::Smelting => 200,
+            CookingKind::Blasting | CookingKind::Smoking => 100,
+            CookingKind::CampfireCooking => 600,
+        }
+    }
+}
+
+/// A recipe for a furnace, blast furnace, smoker or campfire.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CookingRecipe {
+    pub kind: CookingKind,
+    pub ingredient: Ingredient,
+    pub result: ItemStack,
+    pub experience: f32,
+    /// The time it takes to cook the ingredient, in ticks.
+    pub cooking_time: i32,
+    pub group: String,
+    pub category: CookingCategory,
+}
+
+impl CookingRecipe {
+    pub fn new(kind: CookingKind, ingredient: Ingredient, result: ItemStack) -> Self {
+        Self {
+            kind,
+            ingredient,
+            result,
+            experience: 0.0,
+            cooking_time: kind.default_cooking_time(),
+            group: String::new(),
+            category: CookingCategory::Misc,
+        }
+    }
+}
+
+/// A recipe for the stonecutter.
+#[derive(Clone, PartialEq, Debug)]
+pub struct StonecuttingRecipe {
+    pub ingredient: Ingredient,
+    pub result: ItemStack,
+    pub group: String,
+}
+
+impl StonecuttingRecipe {
+    pub fn new(ingredient: Ingredient, result: ItemStack) -> Self {
+        Self {
+            ingredient,
+            result,
+            group: String::new(),
+        }
+    }
+}
+
+/// Returns the inclusive bounds of the filled cells of a grid as `[min_x,
+/// min_y, max_x, max_y]`, or `None` if every cell is empty.
+fn bounds<I: IntoIterator<Item = bool>>(filled: I, width: usize) -> Option<[usize; 4]> {
+    let mut bounds: Option<[usize; 4]> = None;
+
+    for (i, filled) in filled.into_iter().enumerate() {
+        if !filled {
+            continue;
+        }
+
+        let (x, y) = (i % width, i / width);
+
+        bounds = Some(match bounds {
+            Some([x0, y0, x1, y1]) => [x0.min(x), y0.min(y), x1.max(x), y1.max(y)],
+            None => [x, y, x, y],
+        });
+    }
+
+    bounds
+}
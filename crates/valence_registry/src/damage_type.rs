@@ -0,0 +1,178 @@
+//! Contains damage types and the damage type registry. Minecraft's default
+//! damage types are added to the registry by default.
+//!
+//! ### **NOTE:**
+//! - Modifying the damage type registry after the server has started can break
+//!   invariants within clients! Make sure there are no clients spawned before
+//!   mutating.
+//! - The `minecraft:damage_type` tags sent to clients refer to damage types by
+//!   their index in this registry. Reordering or removing the default damage
+//!   types will also require updating the [`TagsRegistry`].
+//!
+//! [`TagsRegistry`]: crate::TagsRegistry
+
+use std::ops::{Deref, DerefMut};
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use valence_ident::{ident, Ident};
+use valence_nbt::serde::CompoundSerializer;
+
+use crate::codec::{RegistryCodec, RegistryValue};
+use crate::{Registry, RegistryIdx, RegistrySet};
+
+pub struct DamageTypePlugin;
+
+impl Plugin for DamageTypePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageTypeRegistry>()
+            .add_systems(PreStartup, load_default_damage_types)
+            .add_systems(PostUpdate, update_damage_type_registry.before(RegistrySet));
+    }
+}
+
+/// Loads the default damage types from the registry codec.
+fn load_default_damage_types(mut reg: ResMut<DamageTypeRegistry>, codec: Res<RegistryCodec>) {
+    let mut helper = move || -> anyhow::Result<()> {
+        for value in codec.registry(DamageTypeRegistry::KEY) {
+            let damage_type = DamageType::deserialize(value.element.clone())?;
+
+            reg.insert(value.name.clone(), damage_type);
+        }
+
+        Ok(())
+    };
+
+    if let Err(e) = helper() {
+        error!("failed to load default damage types from registry codec: {e:#}");
+    }
+}
+
+/// Updates the registry codec as the damage type registry is modified by
+/// users.
+fn update_damage_type_registry(reg: Res<DamageTypeRegistry>, mut codec: ResMut<RegistryCodec>) {
+    if reg.is_changed() {
+        let damage_types = codec.registry_mut(DamageTypeRegistry::KEY);
+
+        damage_types.clear();
+
+        damage_types.extend(reg.iter().map(|(_, name, damage_type)| {
+            RegistryValue {
+                name: name.into(),
+                element: damage_type
+                    .serialize(CompoundSerializer)
+                    .expect("failed to serialize damage type"),
+            }
+        }));
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct DamageTypeRegistry {
+    reg: Registry<DamageTypeId, DamageType>,
+}
+
+impl DamageTypeRegistry {
+    pub const KEY: Ident<&'static str> = ident!("damage_type");
+}
+
+impl Deref for DamageTypeRegistry {
+    type Target = Registry<DamageTypeId, DamageType>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reg
+    }
+}
+
+impl DerefMut for DamageTypeRegistry {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.reg
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct DamageTypeId(u16);
+
+impl DamageTypeId {
+    /// Returns the raw protocol ID of this damage type.
+    pub fn to_raw(self) -> i32 {
+        i32::from(self.0)
+    }
+}
+
+impl RegistryIdx for DamageTypeId {
+    const MAX: usize = u16::MAX as usize;
+
+    #[inline]
+    fn to_index(self) -> usize {
+        self.0 as usize
+    }
+
+    #[inline]
+    fn from_index(idx: usize) -> Self {
+        Self(idx as u16)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DamageType {
+    /// Used to build the translation key of death messages, e.g.
+    /// `death.attack.<message_id>`.
+    pub message_id: String,
+    pub scaling: DamageScaling,
+    /// The amount of exhaustion added to players that take this damage.
+    pub exhaustion: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects: Option<DamageEffects>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub death_message_type: Option<DeathMessageType>,
+}
+
+impl Default for DamageType {
+    fn default() -> Self {
+        Self {
+            message_id: "generic".into(),
+            scaling: DamageScaling::WhenCausedByLivingNonPlayer,
+            exhaustion: 0.0,
+            effects: None,
+            death_message_type: None,
+        }
+    }
+}
+
+/// Whether damage of a type is scaled by the difficulty when the target is a
+/// player.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageScaling {
+    Never,
+    #[default]
+    WhenCausedByLivingNonPlayer,
+    Always,
+}
+
+/// Controls the sound played by the client when damage of a type is taken.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageEffects {
+    #[default]
+    Hurt,
+    Thorns,
+    Drowning,
+    Burning,
+    Poking,
+    Freezing,
+}
+
+/// Controls how death messages are built for a damage type.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathMessageType {
+    #[default]
+    Default,
+    FallVariants,
+    IntentionalGameDesign,
+}
@@ -1,11 +1,15 @@
 #![doc = include_str!("../README.md")]
 
 mod components;
+mod sidebar;
+mod team;
 use std::collections::BTreeSet;
 
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 pub use components::*;
+pub use sidebar::*;
+pub use team::*;
 use tracing::{debug, warn};
 use valence_server::client::{Client, OldVisibleEntityLayers, VisibleEntityLayers};
 use valence_server::entity::EntityLayerId;
@@ -22,7 +26,7 @@ use valence_server::protocol::{VarInt, WritePacket};
 use valence_server::text::IntoText;
 use valence_server::{Despawned, EntityLayer};
 
-/// Provides all necessary systems to manage scoreboards.
+/// Provides all necessary systems to manage scoreboards, teams and sidebars.
 pub struct ScoreboardPlugin;
 
 impl Plugin for ScoreboardPlugin {
@@ -48,6 +52,27 @@ impl Plugin for ScoreboardPlugin {
                 .after(create_or_update_objectives)
                 .after(handle_new_clients)
                 .in_set(ScoreboardSet),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                create_or_update_teams,
+                update_team_members.after(create_or_update_teams),
+                remove_despawned_teams,
+                handle_new_team_clients,
+            )
+                .in_set(ScoreboardSet),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                update_client_sidebars,
+                remove_client_sidebars,
+                update_layer_sidebars,
+                remove_despawned_sidebars,
+                handle_new_sidebar_clients.after(update_layer_sidebars),
+            )
+                .in_set(ScoreboardSet),
         );
     }
 }
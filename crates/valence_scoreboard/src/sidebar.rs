@@ -0,0 +1,395 @@
+use bevy_ecs::prelude::*;
+use tracing::warn;
+use valence_server::client::{Client, OldVisibleEntityLayers, VisibleEntityLayers};
+use valence_server::entity::EntityLayerId;
+use valence_server::protocol::packets::play::scoreboard_display_s2c::ScoreboardPosition;
+use valence_server::protocol::packets::play::scoreboard_objective_update_s2c::{
+    ObjectiveMode, ObjectiveRenderType,
+};
+use valence_server::protocol::packets::play::scoreboard_player_update_s2c::ScoreboardPlayerUpdateAction;
+use valence_server::protocol::packets::play::team_s2c::{
+    CollisionRule, Mode, NameTagVisibility, TeamColor, TeamFlags,
+};
+use valence_server::protocol::packets::play::{
+    ScoreboardDisplayS2c, ScoreboardObjectiveUpdateS2c, ScoreboardPlayerUpdateS2c, TeamS2c,
+};
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::text::IntoText;
+use valence_server::{Despawned, EntityLayer, Text};
+
+/// A sidebar with a title and up to [`Sidebar::MAX_LINES`] lines of text.
+///
+/// Insert it on a client to show it to that client only, or spawn it with an
+/// [`EntityLayerId`] to show it to every client that can see the layer. Like
+/// other scoreboard entities, a layer sidebar is removed by inserting
+/// [`Despawned`].
+///
+/// Every line is the prefix of its own team with a fixed score, so changing a
+/// line only updates that team and the lines never reorder or flicker. The
+/// sidebar takes up the [`ScoreboardPosition::Sidebar`] display slot, so it
+/// replaces any objective displayed there.
+#[derive(Component, Clone, Debug)]
+pub struct Sidebar {
+    title: Text,
+    lines: Vec<Text>,
+    /// The title as last sent to clients, or `None` if the sidebar hasn't been
+    /// sent yet.
+    sent_title: Option<Text>,
+    /// The lines as last sent to clients.
+    sent_lines: Vec<Text>,
+}
+
+impl Sidebar {
+    /// The maximum number of lines the client displays.
+    pub const MAX_LINES: usize = 15;
+
+    pub fn new<T: IntoText<'static>>(title: T) -> Self {
+        Self {
+            title: title.into_text(),
+            lines: vec![],
+            sent_title: None,
+            sent_lines: vec![],
+        }
+    }
+
+    /// Sets the lines of a new sidebar.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than [`Sidebar::MAX_LINES`] lines.
+    pub fn with_lines<I, T>(mut self, lines: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoText<'static>,
+    {
+        self.set_lines(lines);
+        self
+    }
+
+    pub fn title(&self) -> &Text {
+        &self.title
+    }
+
+    pub fn set_title<T: IntoText<'static>>(&mut self, title: T) {
+        self.title = title.into_text();
+    }
+
+    /// Returns the lines from top to bottom.
+    pub fn lines(&self) -> &[Text] {
+        &self.lines
+    }
+
+    pub fn line(&self, idx: usize) -> Option<&Text> {
+        self.lines.get(idx)
+    }
+
+    /// Sets the line at `idx`, counting from the top. Any missing lines above
+    /// it are filled with empty lines.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is not less than [`Sidebar::MAX_LINES`].
+    pub fn set_line<T: IntoText<'static>>(&mut self, idx: usize, text: T) {
+        assert!(
+            idx < Self::MAX_LINES,
+            "sidebar line index {idx} is out of bounds"
+        );
+
+        if idx >= self.lines.len() {
+            self.lines.resize_with(idx + 1, Text::default);
+        }
+
+        self.lines[idx] = text.into_text();
+    }
+
+    /// Adds a line below the current lines.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sidebar already has [`Sidebar::MAX_LINES`] lines.
+    pub fn push_line<T: IntoText<'static>>(&mut self, text: T) {
+        assert!(self.lines.len() < Self::MAX_LINES, "sidebar is full");
+
+        self.lines.push(text.into_text());
+    }
+
+    /// Replaces all lines.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than [`Sidebar::MAX_LINES`] lines.
+    pub fn set_lines<I, T>(&mut self, lines: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoText<'static>,
+    {
+        self.lines.clear();
+
+        for line in lines {
+            self.push_line(line);
+        }
+    }
+
+    /// Removes the lines from `len` onwards.
+    pub fn truncate(&mut self, len: usize) {
+        self.lines.truncate(len);
+    }
+
+    /// Removes all lines, keeping the title.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Writes the packets that create the sidebar on `entity` as last sent.
+    fn write_create<W: WritePacket>(&self, entity: Entity, mut writer: W) {
+        let Some(title) = &self.sent_title else {
+            return;
+        };
+
+        let name = &objective_name(entity);
+
+        writer.write_packet(&ScoreboardObjectiveUpdateS2c {
+            objective_name: name,
+            mode: ObjectiveMode::Create {
+                objective_display_name: title.into_cow_text(),
+                render_type: ObjectiveRenderType::Integer,
+            },
+        });
+
+        // Every team is created up front so lines can come and go without
+        // creating and removing teams.
+        let empty = Text::default();
+
+        for idx in 0..Self::MAX_LINES {
+            let prefix = self.sent_lines.get(idx).unwrap_or(&empty);
+
+            writer.write_packet(&TeamS2c {
+                team_name: &line_team_name(name, idx),
+                mode: Mode::CreateTeam {
+                    team_display_name: "".into_cow_text(),
+                    friendly_flags: TeamFlags::new(),
+                    name_tag_visibility: NameTagVisibility::Always,
+                    collision_rule: CollisionRule::Always,
+                    team_color: TeamColor::Reset,
+                    team_prefix: prefix.into_cow_text(),
+                    team_suffix: "".into_cow_text(),
+                    entities: vec![&line_entry(entity, idx)],
+                },
+            });
+        }
+
+        for idx in 0..self.sent_lines.len() {
+            write_line_score(entity, idx, &mut writer);
+        }
+
+        writer.write_packet(&ScoreboardDisplayS2c {
+            position: ScoreboardPosition::Sidebar,
+            score_name: name,
+        });
+    }
+
+    /// Writes the packets that bring clients up to date with the current title
+    /// and lines of the sidebar on `entity`.
+    fn write_changes<W: WritePacket>(&mut self, entity: Entity, mut writer: W) {
+        let Some(sent_title) = &self.sent_title else {
+            self.sent_title = Some(self.title.clone());
+            self.sent_lines.clone_from(&self.lines);
+            self.write_create(entity, writer);
+            return;
+        };
+
+        let name = &objective_name(entity);
+
+        if *sent_title != self.title {
+            writer.write_packet(&ScoreboardObjectiveUpdateS2c {
+                objective_name: name,
+                mode: ObjectiveMode::Update {
+                    objective_display_name: (&self.title).into_cow_text(),
+                    render_type: ObjectiveRenderType::Integer,
+                },
+            });
+        }
+
+        for idx in 0..Self::MAX_LINES {
+            let old = self.sent_lines.get(idx);
+            let new = self.lines.get(idx);
+
+            match (old, new) {
+                (old, Some(new)) if old != Some(new) => {
+                    // The team of a removed line keeps its old prefix, so it's
+                    // always updated when the line comes back.
+                    writer.write_packet(&TeamS2c {
+                        team_name: &line_team_name(name, idx),
+                        mode: Mode::UpdateTeamInfo {
+                            team_display_name: "".into_cow_text(),
+                            friendly_flags: TeamFlags::new(),
+                            name_tag_visibility: NameTagVisibility::Always,
+                            collision_rule: CollisionRule::Always,
+                            team_color: TeamColor::Reset,
+                            team_prefix: new.into_cow_text(),
+                            team_suffix: "".into_cow_text(),
+                        },
+                    });
+
+                    if old.is_none() {
+                        write_line_score(entity, idx, &mut writer);
+                    }
+                }
+                (Some(_), None) => {
+                    writer.write_packet(&ScoreboardPlayerUpdateS2c {
+                        entity_name: &line_entry(entity, idx),
+                        action: ScoreboardPlayerUpdateAction::Remove {
+                            objective_name: name,
+                        },
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        self.sent_title = Some(self.title.clone());
+        self.sent_lines.clone_from(&self.lines);
+    }
+}
+
+/// Writes the packets that remove the sidebar on `entity`.
+fn write_remove<W: WritePacket>(entity: Entity, mut writer: W) {
+    let name = &objective_name(entity);
+
+    writer.write_packet(&ScoreboardObjectiveUpdateS2c {
+        objective_name: name,
+        mode: ObjectiveMode::Remove,
+    });
+
+    for idx in 0..Sidebar::MAX_LINES {
+        writer.write_packet(&TeamS2c {
+            team_name: &line_team_name(name, idx),
+            mode: Mode::RemoveTeam,
+        });
+    }
+}
+
+/// Lines are ordered by descending score, so the top line gets the highest.
+fn write_line_score<W: WritePacket>(entity: Entity, idx: usize, mut writer: W) {
+    writer.write_packet(&ScoreboardPlayerUpdateS2c {
+        entity_name: &line_entry(entity, idx),
+        action: ScoreboardPlayerUpdateAction::Update {
+            objective_name: &objective_name(entity),
+            objective_score: VarInt((Sidebar::MAX_LINES - idx) as i32),
+        },
+    });
+}
+
+/// The name of the objective backing the sidebar on `entity`.
+fn objective_name(entity: Entity) -> String {
+    format!("sb.{}", entity.index())
+}
+
+/// The invisible score holder of a line of the sidebar on `entity`. It's made
+/// of formatting codes, so it renders as nothing: the line as a hex digit, the
+/// hex digits of the entity's index and a reset. Sidebars shown to a client at
+/// the same time don't share holders, so they can't move each other's lines
+/// into their teams.
+fn line_entry(entity: Entity, idx: usize) -> String {
+    let mut entry = String::new();
+
+    for digit in format!("{idx:x}{:x}", entity.index()).chars() {
+        entry.push('§');
+        entry.push(digit);
+    }
+
+    entry.push_str("§r");
+    entry
+}
+
+fn line_team_name(objective_name: &str, idx: usize) -> String {
+    format!("{objective_name}.{idx}")
+}
+
+pub(crate) fn update_client_sidebars(
+    mut clients: Query<(Entity, &mut Sidebar, &mut Client), Changed<Sidebar>>,
+) {
+    for (entity, mut sidebar, mut client) in &mut clients {
+        sidebar
+            .bypass_change_detection()
+            .write_changes(entity, &mut *client);
+    }
+}
+
+pub(crate) fn remove_client_sidebars(
+    mut removed: RemovedComponents<Sidebar>,
+    mut clients: Query<&mut Client, Without<Sidebar>>,
+) {
+    for entity in removed.read() {
+        if let Ok(mut client) = clients.get_mut(entity) {
+            write_remove(entity, &mut *client);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_layer_sidebars(
+    mut sidebars: Query<
+        (Entity, &mut Sidebar, &EntityLayerId),
+        (Changed<Sidebar>, Without<Client>, Without<Despawned>),
+    >,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for (entity, mut sidebar, entity_layer) in &mut sidebars {
+        let Ok(mut layer) = layers.get_mut(entity_layer.0) else {
+            warn!(
+                "No layer found for entity layer ID {:?}, can't update sidebar",
+                entity_layer
+            );
+            continue;
+        };
+
+        sidebar
+            .bypass_change_detection()
+            .write_changes(entity, &mut *layer);
+    }
+}
+
+pub(crate) fn remove_despawned_sidebars(
+    mut commands: Commands,
+    sidebars: Query<(Entity, &EntityLayerId), (With<Sidebar>, With<Despawned>, Without<Client>)>,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for (entity, entity_layer) in &sidebars {
+        commands.entity(entity).despawn();
+
+        let Ok(mut layer) = layers.get_mut(entity_layer.0) else {
+            warn!(
+                "No layer found for entity layer ID {:?}, can't remove sidebar",
+                entity_layer
+            );
+            continue;
+        };
+
+        write_remove(entity, &mut *layer);
+    }
+}
+
+/// Must occur after `update_layer_sidebars`.
+pub(crate) fn handle_new_sidebar_clients(
+    mut clients: Query<
+        (&mut Client, &VisibleEntityLayers, &OldVisibleEntityLayers),
+        Or<(Added<Client>, Changed<VisibleEntityLayers>)>,
+    >,
+    sidebars: Query<(Entity, &Sidebar, &EntityLayerId), (Without<Client>, Without<Despawned>)>,
+) {
+    for (mut client, visible_layers, old_visible_layers) in &mut clients {
+        let is_new = client.is_added();
+
+        for (entity, sidebar, layer) in &sidebars {
+            let was_visible = !is_new && old_visible_layers.get().contains(&layer.0);
+            let is_visible = visible_layers.0.contains(&layer.0);
+
+            match (was_visible, is_visible) {
+                (false, true) => sidebar.write_create(entity, &mut *client),
+                (true, false) => write_remove(entity, &mut *client),
+                _ => {}
+            }
+        }
+    }
+}
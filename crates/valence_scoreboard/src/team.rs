@@ -0,0 +1,322 @@
+use std::collections::BTreeSet;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use derive_more::{Deref, DerefMut};
+use tracing::warn;
+use valence_server::client::{Client, OldVisibleEntityLayers, VisibleEntityLayers};
+use valence_server::entity::EntityLayerId;
+use valence_server::protocol::packets::play::team_s2c::Mode;
+pub use valence_server::protocol::packets::play::team_s2c::{
+    CollisionRule, NameTagVisibility, TeamColor, TeamFlags,
+};
+use valence_server::protocol::packets::play::TeamS2c;
+use valence_server::protocol::WritePacket;
+use valence_server::text::IntoText;
+use valence_server::uuid::Uuid;
+use valence_server::{Despawned, EntityLayer, Text};
+
+/// A string that identifies a team. It's generally not safe to modify this
+/// after it's been created.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Component, Deref)]
+pub struct Team(pub(crate) String);
+
+impl Team {
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Self(name.into())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The name of a team shown in chat and commands.
+#[derive(Debug, Clone, PartialEq, Component, Deref, DerefMut)]
+pub struct TeamDisplay(pub Text);
+
+/// Text shown before the names of the team's members.
+#[derive(Debug, Clone, PartialEq, Component, Deref, DerefMut)]
+pub struct TeamPrefix(pub Text);
+
+/// Text shown after the names of the team's members.
+#[derive(Debug, Clone, PartialEq, Component, Deref, DerefMut)]
+pub struct TeamSuffix(pub Text);
+
+/// The members of a team. Players are identified by their username and other
+/// entities by their UUID.
+#[derive(Debug, Clone, PartialEq, Eq, Component, Default)]
+pub struct TeamMembers(pub(crate) BTreeSet<String>);
+
+impl TeamMembers {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a player by username, or any other entity by the string form of
+    /// its UUID. Returns whether the member was added.
+    pub fn insert<M: Into<String>>(&mut self, member: M) -> bool {
+        self.0.insert(member.into())
+    }
+
+    /// Adds an entity that is not a player. Returns whether the entity was
+    /// added.
+    pub fn insert_entity(&mut self, uuid: Uuid) -> bool {
+        self.insert(uuid.to_string())
+    }
+
+    /// Removes a member. Returns whether the member was present.
+    pub fn remove(&mut self, member: &str) -> bool {
+        self.0.remove(member)
+    }
+
+    /// Removes an entity that is not a player. Returns whether the entity was
+    /// present.
+    pub fn remove_entity(&mut self, uuid: Uuid) -> bool {
+        self.remove(&uuid.to_string())
+    }
+
+    pub fn contains(&self, member: &str) -> bool {
+        self.0.contains(member)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+impl<M: Into<String>> FromIterator<M> for TeamMembers {
+    fn from_iter<I: IntoIterator<Item = M>>(iter: I) -> Self {
+        Self(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// The members of a team as last sent to clients.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Component)]
+pub struct OldTeamMembers(pub(crate) BTreeSet<String>);
+
+impl OldTeamMembers {
+    /// Returns the members that were added and removed.
+    pub fn diff<'a>(&'a self, members: &'a TeamMembers) -> (Vec<&'a str>, Vec<&'a str>) {
+        let added = members.0.difference(&self.0).map(String::as_str).collect();
+        let removed = self.0.difference(&members.0).map(String::as_str).collect();
+
+        (added, removed)
+    }
+}
+
+#[derive(Bundle)]
+pub struct TeamBundle {
+    pub name: Team,
+    pub display: TeamDisplay,
+    pub prefix: TeamPrefix,
+    pub suffix: TeamSuffix,
+    pub color: TeamColor,
+    pub flags: TeamFlags,
+    pub name_tag_visibility: NameTagVisibility,
+    pub collision_rule: CollisionRule,
+    pub members: TeamMembers,
+    pub old_members: OldTeamMembers,
+    pub layer: EntityLayerId,
+}
+
+impl Default for TeamBundle {
+    fn default() -> Self {
+        Self {
+            name: Team::new(""),
+            display: TeamDisplay("".into_text()),
+            prefix: TeamPrefix("".into_text()),
+            suffix: TeamSuffix("".into_text()),
+            color: Default::default(),
+            // Same as vanilla.
+            flags: TeamFlags::new()
+                .with_friendly_fire(true)
+                .with_see_invisible_teammates(true),
+            name_tag_visibility: Default::default(),
+            collision_rule: Default::default(),
+            members: Default::default(),
+            old_members: Default::default(),
+            layer: Default::default(),
+        }
+    }
+}
+
+#[derive(QueryData)]
+pub(crate) struct TeamInfoQuery {
+    display: &'static TeamDisplay,
+    prefix: &'static TeamPrefix,
+    suffix: &'static TeamSuffix,
+    color: &'static TeamColor,
+    flags: &'static TeamFlags,
+    name_tag_visibility: &'static NameTagVisibility,
+    collision_rule: &'static CollisionRule,
+}
+
+impl TeamInfoQueryItem<'_> {
+    fn create_mode<'a>(&'a self, members: &'a TeamMembers) -> Mode<'a> {
+        Mode::CreateTeam {
+            team_display_name: (&self.display.0).into_cow_text(),
+            friendly_flags: *self.flags,
+            name_tag_visibility: *self.name_tag_visibility,
+            collision_rule: *self.collision_rule,
+            team_color: *self.color,
+            team_prefix: (&self.prefix.0).into_cow_text(),
+            team_suffix: (&self.suffix.0).into_cow_text(),
+            entities: members.iter().collect(),
+        }
+    }
+
+    fn update_mode(&self) -> Mode<'_> {
+        Mode::UpdateTeamInfo {
+            team_display_name: (&self.display.0).into_cow_text(),
+            friendly_flags: *self.flags,
+            name_tag_visibility: *self.name_tag_visibility,
+            collision_rule: *self.collision_rule,
+            team_color: *self.color,
+            team_prefix: (&self.prefix.0).into_cow_text(),
+            team_suffix: (&self.suffix.0).into_cow_text(),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn create_or_update_teams(
+    mut teams: Query<
+        (
+            Ref<Team>,
+            TeamInfoQuery,
+            &TeamMembers,
+            &mut OldTeamMembers,
+            &EntityLayerId,
+        ),
+        (
+            Or<(
+                Added<Team>,
+                Changed<TeamDisplay>,
+                Changed<TeamPrefix>,
+                Changed<TeamSuffix>,
+                Changed<TeamColor>,
+                Changed<TeamFlags>,
+                Changed<NameTagVisibility>,
+                Changed<CollisionRule>,
+            )>,
+            Without<Despawned>,
+        ),
+    >,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for (team, info, members, mut old_members, entity_layer) in &mut teams {
+        if team.name().is_empty() {
+            warn!("Team name is empty");
+        }
+
+        let Ok(mut layer) = layers.get_mut(entity_layer.0) else {
+            warn!(
+                "No layer found for entity layer ID {:?}, can't update team",
+                entity_layer
+            );
+            continue;
+        };
+
+        let mode = if team.is_added() {
+            // The members are sent along with the team.
+            old_members.0.clone_from(&members.0);
+            info.create_mode(members)
+        } else {
+            info.update_mode()
+        };
+
+        layer.write_packet(&TeamS2c {
+            team_name: &team.0,
+            mode,
+        });
+    }
+}
+
+/// Must occur after `create_or_update_teams`.
+pub(crate) fn update_team_members(
+    mut teams: Query<
+        (&Team, &TeamMembers, &mut OldTeamMembers, &EntityLayerId),
+        (Changed<TeamMembers>, Without<Despawned>),
+    >,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for (team, members, mut old_members, entity_layer) in &mut teams {
+        let Ok(mut layer) = layers.get_mut(entity_layer.0) else {
+            warn!(
+                "No layer found for entity layer ID {:?}, can't update team members",
+                entity_layer
+            );
+            continue;
+        };
+
+        let (added, removed) = old_members.diff(members);
+
+        if !added.is_empty() {
+            layer.write_packet(&TeamS2c {
+                team_name: &team.0,
+                mode: Mode::AddEntities { entities: added },
+            });
+        }
+
+        if !removed.is_empty() {
+            layer.write_packet(&TeamS2c {
+                team_name: &team.0,
+                mode: Mode::RemoveEntities { entities: removed },
+            });
+        }
+
+        old_members.0.clone_from(&members.0);
+    }
+}
+
+pub(crate) fn remove_despawned_teams(
+    mut commands: Commands,
+    teams: Query<(Entity, &Team, &EntityLayerId), With<Despawned>>,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for (entity, team, entity_layer) in &teams {
+        commands.entity(entity).despawn();
+
+        let Ok(mut layer) = layers.get_mut(entity_layer.0) else {
+            warn!(
+                "No layer found for entity layer ID {:?}, can't remove team",
+                entity_layer
+            );
+            continue;
+        };
+
+        layer.write_packet(&TeamS2c {
+            team_name: &team.0,
+            mode: Mode::RemoveTeam,
+        });
+    }
+}
+
+pub(crate) fn handle_new_team_clients(
+    mut clients: Query<
+        (&mut Client, &VisibleEntityLayers, &OldVisibleEntityLayers),
+        Or<(Added<Client>, Changed<VisibleEntityLayers>)>,
+    >,
+    teams: Query<(&Team, TeamInfoQuery, &TeamMembers, &EntityLayerId), Without<Despawned>>,
+) {
+    for (mut client, visible_layers, old_visible_layers) in &mut clients {
+        let is_new = client.is_added();
+
+        for (team, info, members, layer) in &teams {
+            let was_visible = !is_new && old_visible_layers.get().contains(&layer.0);
+            let is_visible = visible_layers.0.contains(&layer.0);
+
+            let mode = match (was_visible, is_visible) {
+                (false, true) => info.create_mode(members),
+                (true, false) => Mode::RemoveTeam,
+                _ => continue,
+            };
+
+            client.write_packet(&TeamS2c {
+                team_name: &team.0,
+                mode,
+            });
+        }
+    }
+}
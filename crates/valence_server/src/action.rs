@@ -21,6 +21,10 @@ impl Plugin for ActionPlugin {
     }
 }
 
+/// Sent for every digging action a client reports. These are not validated,
+/// so the client may claim to break any block at any speed. Only the
+/// `BlockMinedEvent` of `valence_digging` is validated, so break blocks in
+/// response to that instead.
 #[derive(Event, Copy, Clone, Debug)]
 pub struct DiggingEvent {
     pub client: Entity,
@@ -60,9 +64,6 @@ fn handle_player_action(
                 seq.update(pkt.sequence.0);
             }
 
-            // TODO: check that digging is happening within configurable distance to client.
-            // TODO: check that blocks are being broken at the appropriate speeds.
-
             match pkt.action {
                 PlayerAction::StartDestroyBlock => {
                     digging_events.send(DiggingEvent {
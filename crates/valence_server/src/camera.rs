@@ -0,0 +1,279 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_entity::{EntityId, EntityLayerId, InitEntitiesSet, Look, Position};
+use valence_protocol::packets::play::{SetCameraEntityS2c, SpectatorTeleportC2s};
+use valence_protocol::{GameMode, VarInt, WritePacket};
+use valence_server_common::{Despawned, UniqueId};
+
+use crate::client::{
+    Client, FlushPacketsSet, LoadEntityForClientEvent, UpdateClientsSet, VisibleChunkLayer,
+    VisibleEntityLayers,
+};
+use crate::client_command::{SneakEvent, SneakState};
+use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::interact_entity::{EntityInteraction, InteractEntityEvent};
+use crate::layer::UpdateLayersPreClientSet;
+use crate::teleport::TeleportState;
+use crate::EventLoopUpdate;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpectatorTeleportEvent>()
+            .add_systems(EventLoopPreUpdate, handle_spectator_teleport)
+            .add_systems(
+                EventLoopUpdate,
+                (
+                    spectate_on_attack,
+                    release_camera_on_sneak,
+                    teleport_spectators,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                follow_camera_targets
+                    .after(InitEntitiesSet)
+                    .before(UpdateLayersPreClientSet),
+            )
+            .add_systems(
+                PostUpdate,
+                send_camera.after(UpdateClientsSet).before(FlushPacketsSet),
+            );
+    }
+}
+
+/// Makes a client view the world through the eyes of another entity. Remove
+/// the component to give the client its own view back.
+///
+/// The camera is set again whenever the target is loaded for the client, so
+/// it survives the target leaving and reentering the client's view or
+/// visible entity layers, as well as respawns. The component is removed when
+/// the target is despawned.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CameraTarget {
+    /// The entity the client is viewing through.
+    pub entity: Entity,
+    /// Whether the client's [`Position`] is moved along with the target. This
+    /// keeps the chunks and entities around the target loaded when it moves
+    /// away from the client.
+    pub follow: bool,
+    /// Whether the client gets its own view back by sneaking.
+    pub release_on_sneak: bool,
+}
+
+impl CameraTarget {
+    /// Forces the client to view through `entity` until the component is
+    /// removed. Useful for cutscenes and killcams.
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            follow: false,
+            release_on_sneak: false,
+        }
+    }
+
+    /// Spectates `entity` like spectators do in vanilla. The client follows
+    /// the entity and can stop spectating by sneaking.
+    pub fn spectate(entity: Entity) -> Self {
+        Self {
+            entity,
+            follow: true,
+            release_on_sneak: true,
+        }
+    }
+}
+
+/// Sent when a client in spectator mode asks to be teleported to an entity.
+/// The client is teleported to the target if it is in one of the client's
+/// [`VisibleEntityLayers`].
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SpectatorTeleportEvent {
+    pub client: Entity,
+    pub target: Entity,
+}
+
+fn handle_spectator_teleport(
+    mut packets: EventReader<PacketEvent>,
+    clients: Query<&GameMode>,
+    entities: Query<(Entity, &UniqueId), Without<Despawned>>,
+    mut events: EventWriter<SpectatorTeleportEvent>,
+) {
+    for packet in packets.read() {
+        if let Some(pkt) = packet.decode::<SpectatorTeleportC2s>() {
+            if clients.get(packet.client) != Ok(&GameMode::Spectator) {
+                continue;
+            }
+
+            if let Some((target, _)) = entities.iter().find(|(_, id)| id.0 == pkt.target) {
+                events.send(SpectatorTeleportEvent {
+                    client: packet.client,
+                    target,
+                });
+            }
+        }
+    }
+}
+
+fn spectate_on_attack(
+    mut events: EventReader<InteractEntityEvent>,
+    clients: Query<&GameMode>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        if event.interact == EntityInteraction::Attack
+            && clients.get(event.client) == Ok(&GameMode::Spectator)
+        {
+            commands
+                .entity(event.client)
+                .insert(CameraTarget::spectate(event.entity));
+        }
+    }
+}
+
+fn release_camera_on_sneak(
+    mut events: EventReader<SneakEvent>,
+    clients: Query<&CameraTarget>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        if event.state == SneakState::Start
+            && clients
+                .get(event.client)
+                .is_ok_and(|target| target.release_on_sneak)
+        {
+            commands.entity(event.client).remove::<CameraTarget>();
+        }
+    }
+}
+
+fn teleport_spectators(
+    mut events: EventReader<SpectatorTeleportEvent>,
+    mut entities: Query<(&mut Position, &mut Look)>,
+    layers: Query<&EntityLayerId>,
+    visible_layers: Query<&VisibleEntityLayers>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Ok(visible_layers) = visible_layers.get(event.client) else {
+            continue;
+        };
+
+        if !layers
+            .get(event.target)
+            .is_ok_and(|layer| visible_layers.0.contains(&layer.0))
+        {
+            continue;
+        }
+
+        let Ok((&target_pos, &target_look)) = entities.get(event.target) else {
+            continue;
+        };
+
+        if let Ok((mut pos, mut look)) = entities.get_mut(event.client) {
+            *pos = target_pos;
+            *look = target_look;
+        }
+
+        commands.entity(event.client).remove::<CameraTarget>();
+    }
+}
+
+fn follow_camera_targets(
+    mut clients: Query<(Entity, &CameraTarget, Option<&mut TeleportState>)>,
+    mut positions: Query<&mut Position>,
+    despawned: Query<(), With<Despawned>>,
+    mut commands: Commands,
+) {
+    for (client, target, teleport_state) in &mut clients {
+        if target.entity == client {
+            continue;
+        }
+
+        let Ok(&Position(target_pos)) = positions.get(target.entity) else {
+            commands.entity(client).remove::<CameraTarget>();
+            continue;
+        };
+
+        if despawned.contains(target.entity) {
+            commands.entity(client).remove::<CameraTarget>();
+            continue;
+        }
+
+        if !target.follow {
+            continue;
+        }
+
+        if let Ok(mut pos) = positions.get_mut(client) {
+            if pos.0 != target_pos {
+                pos.0 = target_pos;
+
+                // The client isn't controlling its own position while
+                // spectating, so don't teleport it.
+                if let Some(mut state) = teleport_state {
+                    state.synced_pos = target_pos;
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn send_camera(
+    mut clients: Query<(
+        Entity,
+        &mut Client,
+        Option<Ref<CameraTarget>>,
+        Ref<VisibleChunkLayer>,
+    )>,
+    entity_ids: Query<&EntityId>,
+    mut load_events: EventReader<LoadEntityForClientEvent>,
+    mut removed: RemovedComponents<CameraTarget>,
+) {
+    for client in removed.read() {
+        if let Ok((_, mut client, None, _)) = clients.get_mut(client) {
+            // The client always knows itself as entity 0.
+            client.write_packet(&SetCameraEntityS2c {
+                entity_id: VarInt(0),
+            });
+        }
+    }
+
+    let camera_id = |client: Entity, target: Entity| {
+        if client == target {
+            Some(VarInt(0))
+        } else {
+            entity_ids.get(target).ok().map(|id| VarInt(id.get()))
+        }
+    };
+
+    // The camera is reset by the respawn packet sent when the chunk layer is
+    // changed.
+    for (entity, mut client, target, chunk_layer) in &mut clients {
+        let Some(target) = target else {
+            continue;
+        };
+
+        if target.is_changed() || chunk_layer.is_changed() {
+            if let Some(entity_id) = camera_id(entity, target.entity) {
+                client.write_packet(&SetCameraEntityS2c { entity_id });
+            }
+        }
+    }
+
+    // The client ignores the camera packet if it doesn't know about the target,
+    // so it's sent again once the target is loaded.
+    for event in load_events.read() {
+        let Ok((entity, mut client, Some(target), chunk_layer)) = clients.get_mut(event.client)
+        else {
+            continue;
+        };
+
+        if target.entity == event.entity_loaded && !target.is_changed() && !chunk_layer.is_changed()
+        {
+            if let Some(entity_id) = camera_id(entity, target.entity) {
+                client.write_packet(&SetCameraEntityS2c { entity_id });
+            }
+        }
+    }
+}
@@ -129,6 +129,10 @@ pub struct ClientBundle {
     pub keepalive_state: crate::keepalive::KeepaliveState,
     pub ping: crate::keepalive::Ping,
     pub teleport_state: crate::teleport::TeleportState,
+    pub movement_state: crate::movement::MovementState,
+    pub interaction_state: crate::interaction::InteractionState,
+    pub item_cooldowns: crate::item_cooldown::ItemCooldowns,
+    pub hand_items: crate::item_cooldown::HandItems,
     pub game_mode: GameMode,
     pub prev_game_mode: crate::spawn::PrevGameMode,
     pub death_location: crate::spawn::DeathLocation,
@@ -170,6 +174,10 @@ impl ClientBundle {
             keepalive_state: crate::keepalive::KeepaliveState::new(),
             ping: Default::default(),
             teleport_state: crate::teleport::TeleportState::new(),
+            movement_state: Default::default(),
+            interaction_state: Default::default(),
+            item_cooldowns: Default::default(),
+            hand_items: Default::default(),
             game_mode: GameMode::default(),
             prev_game_mode: Default::default(),
             death_location: Default::default(),
@@ -0,0 +1,191 @@
+//! Enchantments and their effects on gameplay.
+//!
+//! The functions in this module compute the vanilla effects of the
+//! enchantments on item stacks. They are used by the combat, health and
+//! digging plugins, and can be used to implement custom mechanics that
+//! behave like vanilla.
+
+use rand::Rng;
+pub use valence_protocol::enchantment::*;
+use valence_protocol::ItemStack;
+
+use crate::entity::EntityKind;
+
+/// Returns the extra damage the Sharpness, Smite and Bane of Arthropods
+/// enchantments on a weapon deal to an entity, before it is scaled by the
+/// attack cooldown.
+pub fn attack_damage_bonus(weapon: &ItemStack, target: EntityKind) -> f32 {
+    let mut bonus = 0.0;
+
+    let sharpness = weapon.enchantment_level(Enchantment::Sharpness);
+    if sharpness > 0 {
+        bonus += 0.5 * sharpness as f32 + 0.5;
+    }
+
+    if is_undead(target) {
+        bonus += 2.5 * weapon.enchantment_level(Enchantment::Smite) as f32;
+    }
+
+    if is_arthropod(target) {
+        bonus += 2.5 * weapon.enchantment_level(Enchantment::BaneOfArthropods) as f32;
+    }
+
+    bonus
+}
+
+/// Returns the extra knockback strength of an attack with a weapon, in
+/// levels of the Knockback enchantment. Sprinting adds another level.
+pub fn knockback_bonus(weapon: &ItemStack) -> u32 {
+    weapon.enchantment_level(Enchantment::Knockback)
+}
+
+/// Returns the number of ticks an entity hit with a weapon is set on fire for
+/// by the Fire Aspect enchantment.
+pub fn fire_aspect_ticks(weapon: &ItemStack) -> u32 {
+    weapon.enchantment_level(Enchantment::FireAspect) * 80
+}
+
+/// Returns the speed the Efficiency enchantment adds to a tool when mining a
+/// block the tool is effective on.
+pub fn dig_speed_bonus(tool: &ItemStack) -> f32 {
+    let level = tool.enchantment_level(Enchantment::Efficiency);
+
+    if level > 0 {
+        (level * level + 1) as f32
+    } else {
+        0.0
+    }
+}
+
+/// The kinds of damage some protection enchantments reduce. These correspond
+/// to the `minecraft:is_*` damage type tags.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct ProtectedDamage {
+    pub fire: bool,
+    pub fall: bool,
+    pub explosion: bool,
+    pub projectile: bool,
+}
+
+/// Returns the total protection the protection enchantments on some armor
+/// give against a kind of damage. Use [`damage_after_protection`] to apply
+/// it.
+pub fn protection<'a, I>(armor: I, damage: ProtectedDamage) -> u32
+where
+    I: IntoIterator<Item = &'a ItemStack>,
+{
+    armor
+        .into_iter()
+        .flat_map(|stack| stack.enchantments())
+        .map(|(enchantment, level)| match enchantment {
+            Enchantment::Protection => level,
+            Enchantment::FireProtection if damage.fire => level * 2,
+            Enchantment::FeatherFalling if damage.fall => level * 3,
+            Enchantment::BlastProtection if damage.explosion => level * 2,
+            Enchantment::ProjectileProtection if damage.projectile => level * 2,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Computes the damage left after the protection from [`protection`] has
+/// absorbed its share, using the vanilla formula. Protection above 20 has no
+/// effect.
+pub fn damage_after_protection(amount: f32, protection: u32) -> f32 {
+    amount * (1.0 - protection.min(20) as f32 / 25.0)
+}
+
+/// Returns whether the Unbreaking enchantment on an item stack prevents it
+/// from losing durability this time it is used.
+pub fn prevents_durability_loss<R: Rng + ?Sized>(stack: &ItemStack, rng: &mut R) -> bool {
+    let level = stack.enchantment_level(Enchantment::Unbreaking);
+
+    if level == 0 || (EnchantmentTarget::Armor.includes(stack.item) && rng.gen::<f32>() < 0.6) {
+        return false;
+    }
+
+    rng.gen_range(0..=level) > 0
+}
+
+/// Returns `true` for entities affected by the Smite enchantment.
+pub fn is_undead(kind: EntityKind) -> bool {
+    matches!(
+        kind,
+        EntityKind::ZOMBIE
+            | EntityKind::HUSK
+            | EntityKind::DROWNED
+            | EntityKind::ZOMBIE_VILLAGER
+            | EntityKind::ZOMBIFIED_PIGLIN
+            | EntityKind::ZOGLIN
+            | EntityKind::SKELETON
+            | EntityKind::STRAY
+            | EntityKind::WITHER_SKELETON
+            | EntityKind::SKELETON_HORSE
+            | EntityKind::ZOMBIE_HORSE
+            | EntityKind::PHANTOM
+            | EntityKind::WITHER
+    )
+}
+
+/// Returns `true` for entities affected by the Bane of Arthropods enchantment.
+pub fn is_arthropod(kind: EntityKind) -> bool {
+    matches!(
+        kind,
+        EntityKind::SPIDER
+            | EntityKind::CAVE_SPIDER
+            | EntityKind::BEE
+            | EntityKind::SILVERFISH
+            | EntityKind::ENDERMITE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use valence_protocol::ItemKind;
+
+    use super::*;
+
+    #[test]
+    fn protection_is_capped() {
+        let boots = ItemStack::new(ItemKind::DiamondBoots, 1, None)
+            .with_enchantment(Enchantment::Protection, 4)
+            .with_enchantment(Enchantment::FeatherFalling, 4);
+        let helmet = ItemStack::new(ItemKind::DiamondHelmet, 1, None)
+            .with_enchantment(Enchantment::FireProtection, 4);
+
+        let fall = ProtectedDamage {
+            fall: true,
+            ..Default::default()
+        };
+
+        assert_eq!(protection([&boots, &helmet], ProtectedDamage::default()), 4);
+        assert_eq!(protection([&boots, &helmet], fall), 16);
+        assert_eq!(
+            damage_after_protection(10.0, 16),
+            10.0 * (1.0 - 16.0 / 25.0)
+        );
+        assert_eq!(
+            damage_after_protection(10.0, 40),
+            10.0 * (1.0 - 20.0 / 25.0)
+        );
+    }
+
+    #[test]
+    fn unbreaking_prevents_some_durability_loss() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let plain = ItemStack::new(ItemKind::DiamondPickaxe, 1, None);
+        let unbreaking = plain.clone().with_enchantment(Enchantment::Unbreaking, 3);
+
+        assert!(!(0..100).any(|_| prevents_durability_loss(&plain, &mut rng)));
+
+        // With Unbreaking III, tools lose durability a quarter of the time.
+        let lost = (0..4000)
+            .filter(|_| !prevents_durability_loss(&unbreaking, &mut rng))
+            .count();
+
+        assert!((800..1200).contains(&lost), "{lost}");
+    }
+}
@@ -6,16 +6,30 @@ use valence_protocol::{BlockPos, Direction, Hand};
 
 use crate::action::ActionSequence;
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::interaction::{
+    InteractionEventSet, InteractionSettings, InteractionTarget, InteractionViolationEvent,
+    InteractorQuery,
+};
+use crate::item_cooldown::{HandItems, ItemCooldowns};
+use crate::{ChunkLayer, Server};
 
 pub struct InteractBlockPlugin;
 
 impl Plugin for InteractBlockPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<InteractBlockEvent>()
-            .add_systems(EventLoopPreUpdate, handle_interact_block);
+        app.init_resource::<InteractionSettings>()
+            .add_event::<InteractBlockEvent>()
+            .add_event::<InteractionViolationEvent>()
+            .add_systems(
+                EventLoopPreUpdate,
+                handle_interact_block.in_set(InteractionEventSet),
+            );
     }
 }
 
+/// Sent when a client interacts with a block. Interactions failing the checks
+/// enabled in [`InteractionSettings`] or using an item on cooldown in
+/// [`ItemCooldowns`] are not sent.
 #[derive(Event, Copy, Clone, Debug)]
 pub struct InteractBlockEvent {
     pub client: Entity,
@@ -35,16 +49,50 @@ pub struct InteractBlockEvent {
 
 fn handle_interact_block(
     mut packets: EventReader<PacketEvent>,
-    mut clients: Query<&mut ActionSequence>,
+    mut clients: Query<(
+        &mut ActionSequence,
+        Option<(&ItemCooldowns, &HandItems)>,
+        InteractorQuery,
+    )>,
+    layers: Query<&ChunkLayer>,
     mut events: EventWriter<InteractBlockEvent>,
+    mut violation_events: EventWriter<InteractionViolationEvent>,
+    settings: Res<InteractionSettings>,
+    server: Res<Server>,
 ) {
     for packet in packets.read() {
         if let Some(pkt) = packet.decode::<PlayerInteractBlockC2s>() {
-            if let Ok(mut action_seq) = clients.get_mut(packet.client) {
+            if let Ok((mut action_seq, cooldowns, mut interactor)) = clients.get_mut(packet.client)
+            {
                 action_seq.update(pkt.sequence.0);
-            }
 
-            // TODO: check that the block interaction is valid.
+                if cooldowns.is_some_and(|(cooldowns, hand_items)| {
+                    cooldowns.is_hand_cooling_down(*hand_items, pkt.hand)
+                }) {
+                    continue;
+                }
+
+                if let Some(violation) = interactor.check_block(
+                    pkt.position,
+                    pkt.face,
+                    pkt.cursor_pos,
+                    pkt.head_inside_block,
+                    &layers,
+                    &settings,
+                    server.current_tick(),
+                ) {
+                    // Undo whatever the client predicted.
+                    interactor.resync_blocks(pkt.position, pkt.face, &layers);
+
+                    violation_events.send(InteractionViolationEvent {
+                        client: packet.client,
+                        target: InteractionTarget::Block(pkt.position),
+                        violation,
+                    });
+
+                    continue;
+                }
+            }
 
             events.send(InteractBlockEvent {
                 client: packet.client,
@@ -1,20 +1,33 @@
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
-use valence_entity::EntityManager;
+use valence_entity::hitbox::Hitbox;
+use valence_entity::{EntityLayerId, EntityManager, Position};
 pub use valence_protocol::packets::play::player_interact_entity_c2s::EntityInteraction;
 use valence_protocol::packets::play::PlayerInteractEntityC2s;
 
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::interaction::{
+    InteractionEventSet, InteractionSettings, InteractionTarget, InteractionViolationEvent,
+    InteractorQuery,
+};
+use crate::{ChunkLayer, Server};
 
 pub struct InteractEntityPlugin;
 
 impl Plugin for InteractEntityPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<InteractEntityEvent>()
-            .add_systems(EventLoopPreUpdate, handle_interact_entity);
+        app.init_resource::<InteractionSettings>()
+            .add_event::<InteractEntityEvent>()
+            .add_event::<InteractionViolationEvent>()
+            .add_systems(
+                EventLoopPreUpdate,
+                handle_interact_entity.in_set(InteractionEventSet),
+            );
     }
 }
 
+/// Sent when a client interacts with an entity. Interactions failing the
+/// checks enabled in [`InteractionSettings`] are not sent.
 #[derive(Event, Copy, Clone, Debug)]
 pub struct InteractEntityEvent {
     pub client: Entity,
@@ -26,18 +39,42 @@ pub struct InteractEntityEvent {
     pub interact: EntityInteraction,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_interact_entity(
     mut packets: EventReader<PacketEvent>,
     entities: Res<EntityManager>,
+    mut clients: Query<InteractorQuery>,
+    targets: Query<(&Position, Option<&Hitbox>, &EntityLayerId)>,
+    layers: Query<&ChunkLayer>,
     mut events: EventWriter<InteractEntityEvent>,
+    mut violation_events: EventWriter<InteractionViolationEvent>,
+    settings: Res<InteractionSettings>,
+    server: Res<Server>,
 ) {
     for packet in packets.read() {
         if let Some(pkt) = packet.decode::<PlayerInteractEntityC2s>() {
-            // TODO: check that the entity is in the same instance as the player.
-            // TODO: check that the distance between the player and the interacted entity is
-            // within some configurable tolerance level.
-
             if let Some(entity) = entities.get_by_id(pkt.entity_id.0) {
+                if let (Ok(mut interactor), Ok((position, hitbox, layer_id))) =
+                    (clients.get_mut(packet.client), targets.get(entity))
+                {
+                    if let Some(violation) = interactor.check_entity(
+                        position.0,
+                        hitbox,
+                        layer_id.0,
+                        &layers,
+                        &settings,
+                        server.current_tick(),
+                    ) {
+                        violation_events.send(InteractionViolationEvent {
+                            client: packet.client,
+                            target: InteractionTarget::Entity(entity),
+                            violation,
+                        });
+
+                        continue;
+                    }
+                }
+
                 events.send(InteractEntityEvent {
                     client: packet.client,
                     entity,
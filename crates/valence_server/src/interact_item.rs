@@ -5,16 +5,22 @@ use valence_protocol::Hand;
 
 use crate::action::ActionSequence;
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::interaction::InteractionEventSet;
+use crate::item_cooldown::{HandItems, ItemCooldowns};
 
 pub struct InteractItemPlugin;
 
 impl Plugin for InteractItemPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<InteractItemEvent>()
-            .add_systems(EventLoopPreUpdate, handle_player_interact_item);
+        app.add_event::<InteractItemEvent>().add_systems(
+            EventLoopPreUpdate,
+            handle_player_interact_item.in_set(InteractionEventSet),
+        );
     }
 }
 
+/// Sent when a client uses the item in one of its hands. Not sent if the item
+/// is on cooldown in [`ItemCooldowns`].
 #[derive(Event, Copy, Clone, Debug)]
 pub struct InteractItemEvent {
     pub client: Entity,
@@ -24,13 +30,19 @@ pub struct InteractItemEvent {
 
 fn handle_player_interact_item(
     mut packets: EventReader<PacketEvent>,
-    mut clients: Query<&mut ActionSequence>,
+    mut clients: Query<(&mut ActionSequence, Option<(&ItemCooldowns, &HandItems)>)>,
     mut events: EventWriter<InteractItemEvent>,
 ) {
     for packet in packets.read() {
         if let Some(pkt) = packet.decode::<PlayerInteractItemC2s>() {
-            if let Ok(mut action_seq) = clients.get_mut(packet.client) {
+            if let Ok((mut action_seq, cooldowns)) = clients.get_mut(packet.client) {
                 action_seq.update(pkt.sequence.0);
+
+                if cooldowns.is_some_and(|(cooldowns, hand_items)| {
+                    cooldowns.is_hand_cooling_down(*hand_items, pkt.hand)
+                }) {
+                    continue;
+                }
             }
 
             events.send(InteractItemEvent {
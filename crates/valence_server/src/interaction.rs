@@ -0,0 +1,324 @@
+//! Validation of block and entity interactions.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use valence_entity::hitbox::Hitbox;
+use valence_entity::{entity, Pose, Position};
+use valence_math::{Aabb, DVec3, Vec3};
+use valence_protocol::packets::play::BlockUpdateS2c;
+use valence_protocol::{BlockPos, Direction, WritePacket};
+
+use crate::client::{Client, VisibleChunkLayer, VisibleEntityLayers};
+use crate::ChunkLayer;
+
+/// The [`SystemSet`] in [`EventLoopPreUpdate`] where block, entity and item
+/// interaction packets are turned into events.
+///
+/// [`EventLoopPreUpdate`]: crate::EventLoopPreUpdate
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct InteractionEventSet;
+
+/// Configuration resource for checking block and entity interactions.
+///
+/// All checks are disabled by default, except that clients can't interact
+/// with entities in layers they don't see. Interactions that fail a check are
+/// dropped and an [`InteractionViolationEvent`] is sent instead of
+/// the usual [`InteractBlockEvent`] or [`InteractEntityEvent`]. The blocks
+/// around a dropped block interaction are sent to the client again to undo
+/// anything it predicted.
+///
+/// [`InteractBlockEvent`]: crate::interact_block::InteractBlockEvent
+/// [`InteractEntityEvent`]: crate::interact_entity::InteractEntityEvent
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct InteractionSettings {
+    /// The maximum distance from the client's eyes to the point on the block
+    /// it clicked. Vanilla clients reach 4.5 blocks in survival and 5 in
+    /// creative. `None` disables the check.
+    pub max_block_reach: Option<f64>,
+    /// The maximum distance from the client's eyes to the hitbox of the
+    /// entity it interacted with. Vanilla servers allow 6 blocks. `None`
+    /// disables the check.
+    pub max_entity_reach: Option<f64>,
+    /// Flag interactions through the collision shapes of other blocks.
+    pub check_line_of_sight: bool,
+    /// Flag block interactions where the clicked point is outside the block
+    /// or the clicked face is facing away from the client.
+    pub check_face: bool,
+    /// The maximum number of block and entity interactions a client can make
+    /// within [`rate_limit_window`](Self::rate_limit_window) ticks. `None`
+    /// disables the limit.
+    pub max_interactions: Option<u32>,
+    /// The length of the rate limit window in ticks.
+    pub rate_limit_window: i64,
+}
+
+impl Default for InteractionSettings {
+    fn default() -> Self {
+        Self {
+            max_block_reach: None,
+            max_entity_reach: None,
+            check_line_of_sight: false,
+            check_face: false,
+            max_interactions: None,
+            rate_limit_window: 20,
+        }
+    }
+}
+
+/// Per-client state needed for the checks in [`InteractionSettings`].
+#[derive(Component, Default, Debug)]
+pub struct InteractionState {
+    /// The tick the current rate limit window started on.
+    window_start: i64,
+    /// The number of interactions made in the current window.
+    count: u32,
+}
+
+/// Event sent when a client's interaction fails one of the checks enabled in
+/// [`InteractionSettings`].
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct InteractionViolationEvent {
+    pub client: Entity,
+    pub target: InteractionTarget,
+    pub violation: InteractionViolation,
+}
+
+/// What a client tried to interact with.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InteractionTarget {
+    Block(BlockPos),
+    Entity(Entity),
+}
+
+/// The check an interaction failed.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InteractionViolation {
+    /// The target was out of reach.
+    Reach,
+    /// There were blocks between the client and the target.
+    LineOfSight,
+    /// The clicked point or face of the block was impossible.
+    Face,
+    /// The client interacted too often.
+    RateLimit,
+    /// The entity is not in any of the client's [`VisibleEntityLayers`].
+    NotVisible,
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct InteractorQuery {
+    pub(crate) client: &'static mut Client,
+    position: &'static Position,
+    pose: Option<&'static entity::Pose>,
+    chunk_layer: &'static VisibleChunkLayer,
+    entity_layers: &'static VisibleEntityLayers,
+    state: &'static mut InteractionState,
+}
+
+impl InteractorQueryItem<'_> {
+    fn eye_pos(&self) -> DVec3 {
+        let eye_height = match self.pose.map(|pose| pose.0) {
+            Some(Pose::Sneaking) => 1.27,
+            Some(Pose::FallFlying | Pose::Swimming | Pose::SpinAttack) => 0.4,
+            Some(Pose::Sleeping) => 0.2,
+            _ => 1.62,
+        };
+
+        self.position.0 + DVec3::new(0.0, eye_height, 0.0)
+    }
+
+    /// Counts an interaction towards the rate limit, returning whether the
+    /// limit is exceeded.
+    fn rate_limited(&mut self, settings: &InteractionSettings, tick: i64) -> bool {
+        let Some(max) = settings.max_interactions else {
+            return false;
+        };
+
+        if tick - self.state.window_start >= settings.rate_limit_window {
+            self.state.window_start = tick;
+            self.state.count = 0;
+        }
+
+        self.state.count += 1;
+        self.state.count > max
+    }
+
+    /// Checks an interaction with the block at `position`, clicked at
+    /// `cursor` on `face`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn check_block(
+        &mut self,
+        position: BlockPos,
+        face: Direction,
+        cursor: Vec3,
+        head_inside_block: bool,
+        layers: &Query<&ChunkLayer>,
+        settings: &InteractionSettings,
+        tick: i64,
+    ) -> Option<InteractionViolation> {
+        if self.rate_limited(settings, tick) {
+            return Some(InteractionViolation::RateLimit);
+        }
+
+        let eye = self.eye_pos();
+        let cursor = cursor.as_dvec3();
+        let hit = DVec3::new(
+            f64::from(position.x),
+            f64::from(position.y),
+            f64::from(position.z),
+        ) + cursor;
+
+        if settings
+            .max_block_reach
+            .is_some_and(|reach| eye.distance(hit) > reach)
+        {
+            return Some(InteractionViolation::Reach);
+        }
+
+        if settings.check_face && !head_inside_block {
+            // Same tolerance as vanilla, since some outline shapes stick out
+            // of the block.
+            let outside =
+                cursor.cmplt(DVec3::splat(-0.5)).any() || cursor.cmpgt(DVec3::splat(1.5)).any();
+
+            // The client must be on the outer side of the clicked face.
+            const EPS: f64 = 1e-4;
+            let facing_away = match face {
+                Direction::Down => eye.y > hit.y + EPS,
+                Direction::Up => eye.y < hit.y - EPS,
+                Direction::North => eye.z > hit.z + EPS,
+                Direction::South => eye.z < hit.z - EPS,
+                Direction::West => eye.x > hit.x + EPS,
+                Direction::East => eye.x < hit.x - EPS,
+            };
+
+            if outside || facing_away {
+                return Some(InteractionViolation::Face);
+            }
+        }
+
+        if settings.check_line_of_sight && !head_inside_block {
+            if let Ok(layer) = layers.get(self.chunk_layer.0) {
+                if !line_of_sight(layer, eye, hit, Some(position)) {
+                    return Some(InteractionViolation::LineOfSight);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Checks an interaction with an entity at `position` in the entity
+    /// layer `layer`.
+    pub(crate) fn check_entity(
+        &mut self,
+        position: DVec3,
+        hitbox: Option<&Hitbox>,
+        layer: Entity,
+        layers: &Query<&ChunkLayer>,
+        settings: &InteractionSettings,
+        tick: i64,
+    ) -> Option<InteractionViolation> {
+        if !self.entity_layers.0.contains(&layer) {
+            return Some(InteractionViolation::NotVisible);
+        }
+
+        if self.rate_limited(settings, tick) {
+            return Some(InteractionViolation::RateLimit);
+        }
+
+        let eye = self.eye_pos();
+        let hitbox = hitbox.map_or_else(|| Aabb::new_point(position), |hitbox| hitbox.get());
+
+        if settings
+            .max_entity_reach
+            .is_some_and(|reach| hitbox.distance_to_point(eye) > reach)
+        {
+            return Some(InteractionViolation::Reach);
+        }
+
+        if settings.check_line_of_sight {
+            if let Ok(layer) = layers.get(self.chunk_layer.0) {
+                let center = (hitbox.min() + hitbox.max()) / 2.0;
+
+                // Lenient for entities partially behind blocks.
+                if !line_of_sight(layer, eye, center, None)
+                    && !line_of_sight(layer, eye, hitbox.projected_point(eye), None)
+                {
+                    return Some(InteractionViolation::LineOfSight);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Sends the blocks a client may have changed with an interaction at
+    /// `position` back to it.
+    pub(crate) fn resync_blocks(
+        &mut self,
+        position: BlockPos,
+        face: Direction,
+        layers: &Query<&ChunkLayer>,
+    ) {
+        let Ok(layer) = layers.get(self.chunk_layer.0) else {
+            return;
+        };
+
+        for pos in [position, position.get_in_direction(face)] {
+            if let Some(block) = layer.block(pos) {
+                self.client.write_packet(&BlockUpdateS2c {
+                    position: pos,
+                    block_id: block.state,
+                });
+            }
+        }
+    }
+}
+
+/// Returns whether the segment from `from` to `to` doesn't pass through the
+/// collision shapes of any block other than `ignore`. Shapes containing
+/// `from` are ignored.
+fn line_of_sight(layer: &ChunkLayer, from: DVec3, to: DVec3, ignore: Option<BlockPos>) -> bool {
+    const EPS: f64 = 1e-6;
+
+    let dir = to - from;
+    let min = from.min(to).floor();
+    let max = from.max(to).floor();
+
+    for y in min.y as i32..=max.y as i32 {
+        for z in min.z as i32..=max.z as i32 {
+            for x in min.x as i32..=max.x as i32 {
+                let pos = BlockPos::new(x, y, z);
+
+                if Some(pos) == ignore {
+                    continue;
+                }
+
+                let Some(block) = layer.block(pos) else {
+                    continue;
+                };
+
+                let offset = DVec3::new(f64::from(x), f64::from(y), f64::from(z));
+
+                for shape in block.state.collision_shapes() {
+                    let shape = shape + offset;
+
+                    if shape.contains_point(from) {
+                        continue;
+                    }
+
+                    if let Some([near, far]) = shape.ray_intersection(from, dir) {
+                        // Ignore rays grazing the shape.
+                        if near < 1.0 - EPS && far - near > EPS {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    true
+}
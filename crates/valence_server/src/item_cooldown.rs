@@ -0,0 +1,117 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_protocol::packets::play::CooldownUpdateS2c;
+use valence_protocol::{Hand, ItemKind, VarInt, WritePacket};
+
+use crate::client::{Client, UpdateClientsSet};
+
+pub struct ItemCooldownPlugin;
+
+impl Plugin for ItemCooldownPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, update_item_cooldowns.in_set(UpdateClientsSet));
+    }
+}
+
+/// The items a client can't use for a while, like ender pearls after
+/// throwing one.
+///
+/// Items on cooldown are shown with a grey overlay on the client and count
+/// down every tick. While the item in a hand is on cooldown, the client's
+/// [`InteractItemEvent`]s and [`InteractBlockEvent`]s using that hand are not
+/// sent. The items in the client's hands are taken from [`HandItems`].
+///
+/// [`InteractItemEvent`]: crate::interact_item::InteractItemEvent
+/// [`InteractBlockEvent`]: crate::interact_block::InteractBlockEvent
+#[derive(Component, Default, Debug)]
+pub struct ItemCooldowns {
+    /// The remaining ticks of each item on cooldown.
+    cooldowns: HashMap<ItemKind, u32>,
+    /// The items whose cooldown needs to be sent to the client.
+    changed: HashSet<ItemKind>,
+}
+
+/// The kinds of the items in a client's hands. `valence_inventory` keeps this
+/// up to date with the client's inventory. Without it, nothing is known to be
+/// held unless this is set manually.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct HandItems {
+    pub main_hand: ItemKind,
+    pub off_hand: ItemKind,
+}
+
+impl HandItems {
+    /// Returns the kind of the item in the hand.
+    pub fn get(&self, hand: Hand) -> ItemKind {
+        match hand {
+            Hand::Main => self.main_hand,
+            Hand::Off => self.off_hand,
+        }
+    }
+}
+
+impl ItemCooldowns {
+    /// Returns the remaining ticks of the item's cooldown, or 0 if it is not
+    /// on cooldown.
+    pub fn get(&self, item: ItemKind) -> u32 {
+        self.cooldowns.get(&item).copied().unwrap_or(0)
+    }
+
+    pub fn is_cooling_down(&self, item: ItemKind) -> bool {
+        self.cooldowns.contains_key(&item)
+    }
+
+    /// Puts the item on cooldown for the given number of ticks, replacing any
+    /// current cooldown. A cooldown of 0 ticks ends the current cooldown.
+    pub fn set(&mut self, item: ItemKind, ticks: u32) {
+        if ticks == 0 {
+            if self.cooldowns.remove(&item).is_none() {
+                return;
+            }
+        } else {
+            self.cooldowns.insert(item, ticks);
+        }
+
+        self.changed.insert(item);
+    }
+
+    /// Ends the item's cooldown.
+    pub fn clear(&mut self, item: ItemKind) {
+        self.set(item, 0);
+    }
+
+    /// Returns the items on cooldown and their remaining ticks.
+    pub fn iter(&self) -> impl Iterator<Item = (ItemKind, u32)> + '_ {
+        self.cooldowns.iter().map(|(&item, &ticks)| (item, ticks))
+    }
+
+    /// Returns whether the item in the hand is on cooldown.
+    pub(crate) fn is_hand_cooling_down(&self, hand_items: HandItems, hand: Hand) -> bool {
+        self.is_cooling_down(hand_items.get(hand))
+    }
+}
+
+fn update_item_cooldowns(mut clients: Query<(&mut Client, &mut ItemCooldowns)>) {
+    for (mut client, mut cooldowns) in &mut clients {
+        if cooldowns.cooldowns.is_empty() && cooldowns.changed.is_empty() {
+            continue;
+        }
+
+        let cooldowns = cooldowns.as_mut();
+
+        for item in cooldowns.changed.drain() {
+            client.write_packet(&CooldownUpdateS2c {
+                item_id: item,
+                cooldown_ticks: VarInt(cooldowns.cooldowns.get(&item).map_or(0, |&t| t as i32)),
+            });
+        }
+
+        // The client counts down on its own, so cooldowns ending aren't sent.
+        cooldowns.cooldowns.retain(|_, ticks| {
+            *ticks -= 1;
+            *ticks > 0
+        });
+    }
+}
@@ -3,27 +3,33 @@
 pub mod abilities;
 pub mod action;
 pub mod brand;
+pub mod camera;
 mod chunk_view;
 pub mod client;
 pub mod client_command;
 pub mod client_settings;
 pub mod custom_payload;
+pub mod enchantment;
 pub mod event_loop;
 pub mod hand_swing;
 pub mod interact_block;
 pub mod interact_entity;
 pub mod interact_item;
+pub mod interaction;
+pub mod item_cooldown;
 pub mod keepalive;
 pub mod layer;
 pub mod message;
 pub mod movement;
 pub mod op_level;
 pub mod resource_pack;
+pub mod sign;
 pub mod spawn;
 pub mod status;
 pub mod status_effect;
 pub mod teleport;
 pub mod title;
+pub mod vehicle;
 
 pub use chunk_view::ChunkView;
 pub use event_loop::{EventLoopPostUpdate, EventLoopPreUpdate, EventLoopUpdate};
@@ -1,13 +1,25 @@
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
-use valence_entity::{HeadYaw, Look, OnGround, Position};
-use valence_math::DVec3;
+use bevy_ecs::query::QueryData;
+use valence_entity::active_status_effects::ActiveStatusEffects;
+use valence_entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_entity::entity::{self, Flags};
+use valence_entity::hitbox::HitboxShape;
+use valence_entity::{HeadYaw, Look, OnGround, Pose, Position};
+use valence_math::{Aabb, DVec3};
 use valence_protocol::packets::play::{
-    FullC2s, LookAndOnGroundC2s, OnGroundOnlyC2s, PositionAndOnGroundC2s, VehicleMoveC2s,
+    FullC2s, LookAndOnGroundC2s, OnGroundOnlyC2s, PositionAndOnGroundC2s,
 };
+use valence_protocol::status_effects::StatusEffect;
+use valence_protocol::{BlockPos, GameMode};
 
+use crate::abilities::{FlyingSpeed, PlayerAbilitiesFlags};
+use crate::block::{BlockKind, BlockState, PropName, PropValue};
+use crate::client::VisibleChunkLayer;
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
 use crate::teleport::TeleportState;
+use crate::vehicle::Vehicle;
+use crate::ChunkLayer;
 
 pub struct MovementPlugin;
 
@@ -15,13 +27,65 @@ impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<MovementSettings>()
             .add_event::<MovementEvent>()
+            .add_event::<MovementViolationEvent>()
             .add_systems(EventLoopPreUpdate, handle_client_movement);
     }
 }
 
 /// Configuration resource for client movement checks.
-#[derive(Resource, Default)]
-pub struct MovementSettings; // TODO
+///
+/// All checks are disabled by default. Moves that fail an enabled check send a
+/// [`MovementViolationEvent`]. The checks are deliberately lenient, but things
+/// the server doesn't know about such as knockback from plugins or bouncing on
+/// slime blocks can still cause false positives.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct MovementSettings {
+    /// Flag clients moving horizontally faster than their movement speed
+    /// attribute, status effects and flying speed allow.
+    pub check_speed: bool,
+    /// Multiplier applied to the maximum speed to account for lag and
+    /// momentum.
+    pub speed_leniency: f64,
+    /// Flag clients that aren't allowed to fly but don't fall while in the
+    /// air.
+    pub check_flight: bool,
+    /// The number of consecutive moves a client can make in the air without
+    /// falling before it is flagged. A normal jump rises for 6 moves.
+    pub max_air_ticks: u32,
+    /// Flag clients moving through the collision shapes of blocks. Spectators
+    /// are never flagged.
+    pub check_collision: bool,
+    /// Flag clients claiming to be on the ground without standing on a block.
+    pub check_on_ground: bool,
+    /// The maximum distance a client can move in a single move. Moves farther
+    /// than this are flagged as teleports. `None` disables the check.
+    pub max_move_distance: Option<f64>,
+    /// Reject moves which fail a check and teleport the client back to its
+    /// last valid position. If this is `false`, moves are only reported.
+    pub rubber_band: bool,
+}
+
+impl Default for MovementSettings {
+    fn default() -> Self {
+        Self {
+            check_speed: false,
+            speed_leniency: 1.2,
+            check_flight: false,
+            max_air_ticks: 10,
+            check_collision: false,
+            check_on_ground: false,
+            max_move_distance: None,
+            rubber_band: true,
+        }
+    }
+}
+
+/// Per-client state needed for the checks in [`MovementSettings`].
+#[derive(Component, Default, Debug)]
+pub struct MovementState {
+    /// The number of consecutive moves made in the air without falling.
+    air_ticks: u32,
+}
 
 /// Event sent when a client successfully moves.
 #[derive(Event, Clone, Debug)]
@@ -35,145 +99,154 @@ pub struct MovementEvent {
     pub old_on_ground: bool,
 }
 
+/// Event sent when a client's move fails one of the checks enabled in
+/// [`MovementSettings`].
+#[derive(Event, Clone, Debug)]
+pub struct MovementViolationEvent {
+    pub client: Entity,
+    pub violation: MovementViolation,
+    /// The position the client tried to move to.
+    pub position: DVec3,
+    /// The position of the client before the move.
+    pub old_position: DVec3,
+    /// Whether the move was rejected and the client teleported back.
+    pub rubber_banded: bool,
+}
+
+/// The check a move failed.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MovementViolation {
+    /// The client moved faster than it can.
+    Speed,
+    /// The client stayed in the air without being allowed to fly.
+    Flight,
+    /// The client moved through blocks.
+    Collision,
+    /// The client claimed to be on the ground while in the air.
+    OnGround,
+    /// The client moved farther than [`MovementSettings::max_move_distance`]
+    /// in one move.
+    Teleport,
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct MovementQuery {
+    pos: &'static mut Position,
+    look: &'static mut Look,
+    head_yaw: &'static mut HeadYaw,
+    on_ground: &'static mut OnGround,
+    teleport_state: &'static mut TeleportState,
+    state: &'static mut MovementState,
+    game_mode: &'static GameMode,
+    abilities: &'static PlayerAbilitiesFlags,
+    flying_speed: &'static FlyingSpeed,
+    chunk_layer: &'static VisibleChunkLayer,
+    flags: Option<&'static Flags>,
+    pose: Option<&'static entity::Pose>,
+    hitbox: Option<&'static HitboxShape>,
+    attributes: Option<&'static EntityAttributes>,
+    effects: Option<&'static ActiveStatusEffects>,
+    vehicle: Has<Vehicle>,
+}
+
 fn handle_client_movement(
     mut packets: EventReader<PacketEvent>,
-    mut clients: Query<(
-        &mut Position,
-        &mut Look,
-        &mut HeadYaw,
-        &mut OnGround,
-        &mut TeleportState,
-    )>,
+    mut clients: Query<MovementQuery>,
+    chunk_layers: Query<&ChunkLayer>,
+    settings: Res<MovementSettings>,
     mut movement_events: EventWriter<MovementEvent>,
+    mut violation_events: EventWriter<MovementViolationEvent>,
 ) {
     for packet in packets.read() {
         if let Some(pkt) = packet.decode::<PositionAndOnGroundC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
+            if let Ok(client) = clients.get_mut(packet.client) {
                 let mov = MovementEvent {
                     client: packet.client,
                     position: pkt.position,
-                    old_position: pos.0,
-                    look: *look,
-                    old_look: *look,
+                    old_position: client.pos.0,
+                    look: *client.look,
+                    old_look: *client.look,
                     on_ground: pkt.on_ground,
-                    old_on_ground: on_ground.0,
+                    old_on_ground: client.on_ground.0,
                 };
 
                 handle(
                     mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
+                    client,
+                    &settings,
+                    &chunk_layers,
                     &mut movement_events,
+                    &mut violation_events,
                 );
             }
         } else if let Some(pkt) = packet.decode::<FullC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
+            if let Ok(client) = clients.get_mut(packet.client) {
                 let mov = MovementEvent {
                     client: packet.client,
                     position: pkt.position,
-                    old_position: pos.0,
+                    old_position: client.pos.0,
                     look: Look {
                         yaw: pkt.yaw,
                         pitch: pkt.pitch,
                     },
-                    old_look: *look,
+                    old_look: *client.look,
                     on_ground: pkt.on_ground,
-                    old_on_ground: on_ground.0,
+                    old_on_ground: client.on_ground.0,
                 };
 
                 handle(
                     mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
+                    client,
+                    &settings,
+                    &chunk_layers,
                     &mut movement_events,
+                    &mut violation_events,
                 );
             }
         } else if let Some(pkt) = packet.decode::<LookAndOnGroundC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
+            if let Ok(client) = clients.get_mut(packet.client) {
                 let mov = MovementEvent {
                     client: packet.client,
-                    position: pos.0,
-                    old_position: pos.0,
+                    position: client.pos.0,
+                    old_position: client.pos.0,
                     look: Look {
                         yaw: pkt.yaw,
                         pitch: pkt.pitch,
                     },
-                    old_look: *look,
+                    old_look: *client.look,
                     on_ground: pkt.on_ground,
-                    old_on_ground: on_ground.0,
+                    old_on_ground: client.on_ground.0,
                 };
 
                 handle(
                     mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
+                    client,
+                    &settings,
+                    &chunk_layers,
                     &mut movement_events,
+                    &mut violation_events,
                 );
             }
         } else if let Some(pkt) = packet.decode::<OnGroundOnlyC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
+            if let Ok(client) = clients.get_mut(packet.client) {
                 let mov = MovementEvent {
                     client: packet.client,
-                    position: pos.0,
-                    old_position: pos.0,
-                    look: *look,
-                    old_look: *look,
+                    position: client.pos.0,
+                    old_position: client.pos.0,
+                    look: *client.look,
+                    old_look: *client.look,
                     on_ground: pkt.on_ground,
-                    old_on_ground: on_ground.0,
-                };
-
-                handle(
-                    mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
-                    &mut movement_events,
-                );
-            }
-        } else if let Some(pkt) = packet.decode::<VehicleMoveC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
-                let mov = MovementEvent {
-                    client: packet.client,
-                    position: pkt.position,
-                    old_position: pos.0,
-                    look: Look {
-                        yaw: pkt.yaw,
-                        pitch: pkt.pitch,
-                    },
-                    old_look: *look,
-                    on_ground: on_ground.0,
-                    old_on_ground: on_ground.0,
+                    old_on_ground: client.on_ground.0,
                 };
 
                 handle(
                     mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
+                    client,
+                    &settings,
+                    &chunk_layers,
                     &mut movement_events,
+                    &mut violation_events,
                 );
             }
         }
@@ -182,26 +255,229 @@ fn handle_client_movement(
 
 fn handle(
     mov: MovementEvent,
-    mut pos: Mut<Position>,
-    mut look: Mut<Look>,
-    mut head_yaw: Mut<HeadYaw>,
-    mut on_ground: Mut<OnGround>,
-    mut teleport_state: Mut<TeleportState>,
+    mut client: MovementQueryItem,
+    settings: &MovementSettings,
+    chunk_layers: &Query<&ChunkLayer>,
     movement_events: &mut EventWriter<MovementEvent>,
+    violation_events: &mut EventWriter<MovementViolationEvent>,
 ) {
-    if teleport_state.pending_teleports() != 0 {
+    if client.teleport_state.pending_teleports() != 0 {
         return;
     }
 
-    // TODO: check that the client isn't moving too fast / flying.
-    // TODO: check that the client isn't clipping through blocks.
+    let layer = chunk_layers.get(client.chunk_layer.0).ok();
 
-    pos.set_if_neq(Position(mov.position));
-    teleport_state.synced_pos = mov.position;
-    look.set_if_neq(mov.look);
-    teleport_state.synced_look = mov.look;
-    head_yaw.set_if_neq(HeadYaw(mov.look.yaw));
-    on_ground.set_if_neq(OnGround(mov.on_ground));
+    if let Some(violation) = check_move(&mov, &mut client, settings, layer) {
+        violation_events.send(MovementViolationEvent {
+            client: mov.client,
+            violation,
+            position: mov.position,
+            old_position: mov.old_position,
+            rubber_banded: settings.rubber_band,
+        });
+
+        if settings.rubber_band {
+            // The client thinks it's at the new position, so the teleport system sends it
+            // back to where it was.
+            client.teleport_state.synced_pos = mov.position;
+            client.pos.set_changed();
+            return;
+        }
+    }
+
+    client.pos.set_if_neq(Position(mov.position));
+    client.teleport_state.synced_pos = mov.position;
+    client.look.set_if_neq(mov.look);
+    client.teleport_state.synced_look = mov.look;
+    client.head_yaw.set_if_neq(HeadYaw(mov.look.yaw));
+    client.on_ground.set_if_neq(OnGround(mov.on_ground));
 
     movement_events.send(mov);
 }
+
+/// Ground friction of a normal block multiplied by air drag.
+const GROUND_DRAG: f64 = 0.546;
+/// Air drag while flying.
+const FLYING_DRAG: f64 = 0.91;
+/// Multiplier on the movement speed while sprinting.
+const SPRINT_MULTIPLIER: f64 = 1.3;
+/// Horizontal speed gained from sprint jumping.
+const JUMP_BOOST: f64 = 0.3;
+/// Distance to look below the client for blocks it could be standing on.
+const GROUND_DISTANCE: f64 = 0.05;
+/// Shrinks hitboxes so that touching a block doesn't count as colliding with
+/// it.
+const COLLISION_EPSILON: f64 = 1e-4;
+
+fn check_move(
+    mov: &MovementEvent,
+    client: &mut MovementQueryItem,
+    settings: &MovementSettings,
+    layer: Option<&ChunkLayer>,
+) -> Option<MovementViolation> {
+    if client.vehicle {
+        client.state.air_ticks = 0;
+        return None;
+    }
+
+    let delta = mov.position - mov.old_position;
+
+    if settings
+        .max_move_distance
+        .is_some_and(|max| delta.length() > max)
+    {
+        return Some(MovementViolation::Teleport);
+    }
+
+    let gliding = client.pose.is_some_and(|pose| pose.0 == Pose::FallFlying)
+        || client.flags.is_some_and(|flags| flags.fall_flying());
+
+    if settings.check_speed && !gliding {
+        let max_speed = if client.abilities.flying() {
+            f64::from(client.flying_speed.0) * 2.0 / (1.0 - FLYING_DRAG)
+        } else {
+            let movement_speed = client
+                .attributes
+                .and_then(|attrs| attrs.get_compute_value(EntityAttribute::GenericMovementSpeed))
+                .unwrap_or(0.1);
+
+            movement_speed * SPRINT_MULTIPLIER / (1.0 - GROUND_DRAG) + JUMP_BOOST
+        };
+
+        if delta.x.hypot(delta.z) > max_speed * settings.speed_leniency {
+            return Some(MovementViolation::Speed);
+        }
+    }
+
+    // The remaining checks need to know about the blocks around the client.
+    let layer = layer?;
+
+    let shape = client
+        .hitbox
+        .map_or_else(|| player_hitbox(client.pose), HitboxShape::get);
+
+    if settings.check_collision
+        && *client.game_mode != GameMode::Spectator
+        && !collides(layer, shape + mov.old_position)
+    {
+        // Check points along the way so clients can't skip through thin walls.
+        let steps = (delta.length() / 0.25).ceil().clamp(1.0, 64.0) as u32;
+
+        for i in 1..=steps {
+            let pos = mov.old_position + delta * (f64::from(i) / f64::from(steps));
+
+            if collides(layer, shape + pos) {
+                return Some(MovementViolation::Collision);
+            }
+        }
+    }
+
+    let bottom = shape + mov.position;
+    let feet = Aabb::new(
+        bottom.min() - DVec3::new(0.0, GROUND_DISTANCE, 0.0),
+        DVec3::new(bottom.max().x, bottom.min().y, bottom.max().z),
+    );
+
+    let supported = collides(layer, feet);
+
+    if settings.check_on_ground && mov.on_ground && !supported {
+        return Some(MovementViolation::OnGround);
+    }
+
+    if settings.check_flight {
+        let levitating = client
+            .effects
+            .is_some_and(|effects| effects.has_effect(StatusEffect::Levitation));
+
+        if supported
+            || delta.y < 0.0
+            || gliding
+            || levitating
+            || client.abilities.allow_flying()
+            || in_climbable_or_fluid(layer, bottom)
+        {
+            client.state.air_ticks = 0;
+        } else {
+            client.state.air_ticks += 1;
+
+            if client.state.air_ticks > settings.max_air_ticks {
+                return Some(MovementViolation::Flight);
+            }
+        }
+    }
+
+    None
+}
+
+fn player_hitbox(pose: Option<&entity::Pose>) -> Aabb {
+    let height = match pose.map(|pose| pose.0) {
+        Some(Pose::Sneaking) => 1.5,
+        Some(Pose::FallFlying | Pose::Swimming | Pose::SpinAttack) => 0.6,
+        _ => 1.8,
+    };
+
+    Aabb::from_bottom_size(DVec3::ZERO, DVec3::new(0.6, height, 0.6))
+}
+
+/// Calls `f` with every loaded block intersecting `aabb`, stopping when it
+/// returns `true`.
+fn any_block(
+    layer: &ChunkLayer,
+    aabb: Aabb,
+    mut f: impl FnMut(BlockPos, BlockState) -> bool,
+) -> bool {
+    let min = aabb.min().floor();
+    let max = aabb.max().floor();
+
+    for y in min.y as i32..=max.y as i32 {
+        for z in min.z as i32..=max.z as i32 {
+            for x in min.x as i32..=max.x as i32 {
+                let pos = BlockPos::new(x, y, z);
+
+                if let Some(block) = layer.block(pos) {
+                    if f(pos, block.state) {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// Returns whether `aabb` intersects the collision shape of a block.
+fn collides(layer: &ChunkLayer, aabb: Aabb) -> bool {
+    let min = aabb.min() + COLLISION_EPSILON;
+    let aabb = Aabb::new(min, (aabb.max() - COLLISION_EPSILON).max(min));
+
+    any_block(layer, aabb, |pos, state| {
+        let offset = DVec3::new(f64::from(pos.x), f64::from(pos.y), f64::from(pos.z));
+
+        state
+            .collision_shapes()
+            .any(|shape| (shape + offset).intersects(aabb))
+    })
+}
+
+/// Returns whether `aabb` is in a block that lets entities move up or hover.
+fn in_climbable_or_fluid(layer: &ChunkLayer, aabb: Aabb) -> bool {
+    any_block(layer, aabb, |_, state| {
+        state.is_liquid()
+            || state.get(PropName::Waterlogged) == Some(PropValue::True)
+            || matches!(
+                state.to_kind(),
+                BlockKind::Ladder
+                    | BlockKind::Vine
+                    | BlockKind::Scaffolding
+                    | BlockKind::Cobweb
+                    | BlockKind::BubbleColumn
+                    | BlockKind::TwistingVines
+                    | BlockKind::TwistingVinesPlant
+                    | BlockKind::WeepingVines
+                    | BlockKind::WeepingVinesPlant
+                    | BlockKind::CaveVines
+                    | BlockKind::CaveVinesPlant
+            )
+    })
+}
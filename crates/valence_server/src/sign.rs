@@ -0,0 +1,439 @@
+//! Sign text and the sign editor.
+//!
+//! The text of signs is stored in their block entity. [`ChunkLayer::sign`]
+//! and [`ChunkLayer::set_sign`] read and write it as [`SignData`].
+//!
+//! Send an [`OpenSignEditorEvent`] to open the sign editor for a client. The
+//! editor can also be opened on a fake sign that only exists for that client,
+//! which makes it usable as a text input prompt. When the client is done
+//! editing, a [`SignEditEvent`] is sent.
+
+use std::borrow::Cow;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_nbt::{Compound, List, Value};
+use valence_protocol::block::BlockEntityKind;
+use valence_protocol::packets::play::{
+    BlockEntityUpdateS2c, BlockUpdateS2c, SignEditorOpenS2c, UpdateSignC2s,
+};
+use valence_protocol::text::IntoText;
+use valence_protocol::{BlockPos, BlockState, Text, WritePacket};
+
+use crate::client::{Client, FlushPacketsSet, VisibleChunkLayer};
+use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::layer::chunk::Block;
+use crate::ChunkLayer;
+
+pub struct SignPlugin;
+
+impl Plugin for SignPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OpenSignEditorEvent>()
+            .add_event::<SignEditEvent>()
+            .add_systems(EventLoopPreUpdate, handle_update_sign)
+            .add_systems(PostUpdate, open_sign_editors.before(FlushPacketsSet));
+    }
+}
+
+/// The text on both sides of a sign.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct SignData {
+    pub front: SignText,
+    pub back: SignText,
+    /// Waxed signs can't be edited by clients.
+    pub waxed: bool,
+}
+
+/// The text on one side of a sign.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct SignText {
+    pub lines: [Text; 4],
+    pub color: DyeColor,
+    pub glowing: bool,
+}
+
+/// The colors of dyes, used for the text color of signs.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub enum DyeColor {
+    White,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    #[default]
+    Black,
+}
+
+impl DyeColor {
+    pub const ALL: [Self; 16] = [
+        Self::White,
+        Self::Orange,
+        Self::Magenta,
+        Self::LightBlue,
+        Self::Yellow,
+        Self::Lime,
+        Self::Pink,
+        Self::Gray,
+        Self::LightGray,
+        Self::Cyan,
+        Self::Purple,
+        Self::Blue,
+        Self::Brown,
+        Self::Green,
+        Self::Red,
+        Self::Black,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::White => "white",
+            Self::Orange => "orange",
+            Self::Magenta => "magenta",
+            Self::LightBlue => "light_blue",
+            Self::Yellow => "yellow",
+            Self::Lime => "lime",
+            Self::Pink => "pink",
+            Self::Gray => "gray",
+            Self::LightGray => "light_gray",
+            Self::Cyan => "cyan",
+            Self::Purple => "purple",
+            Self::Blue => "blue",
+            Self::Brown => "brown",
+            Self::Green => "green",
+            Self::Red => "red",
+            Self::Black => "black",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|color| color.name() == name)
+    }
+}
+
+impl SignData {
+    /// Reads the sign data from the NBT of a sign block entity. Missing
+    /// fields use their default values.
+    pub fn from_nbt(nbt: &Compound) -> Self {
+        Self {
+            front: nbt
+                .get("front_text")
+                .and_then(as_compound)
+                .map(SignText::from_nbt)
+                .unwrap_or_default(),
+            back: nbt
+                .get("back_text")
+                .and_then(as_compound)
+                .map(SignText::from_nbt)
+                .unwrap_or_default(),
+            waxed: matches!(nbt.get("is_waxed"), Some(Value::Byte(b)) if *b != 0),
+        }
+    }
+
+    /// Writes the sign data into the NBT of a sign block entity. Other fields
+    /// are kept.
+    pub fn write_nbt(&self, nbt: &mut Compound) {
+        nbt.insert("front_text", self.front.to_nbt());
+        nbt.insert("back_text", self.back.to_nbt());
+        nbt.insert("is_waxed", self.waxed);
+    }
+
+    pub fn to_nbt(&self) -> Compound {
+        let mut nbt = Compound::new();
+        self.write_nbt(&mut nbt);
+        nbt
+    }
+
+    pub fn side(&self, is_front_text: bool) -> &SignText {
+        if is_front_text {
+            &self.front
+        } else {
+            &self.back
+        }
+    }
+
+    pub fn side_mut(&mut self, is_front_text: bool) -> &mut SignText {
+        if is_front_text {
+            &mut self.front
+        } else {
+            &mut self.back
+        }
+    }
+}
+
+impl SignText {
+    pub fn new(lines: [Text; 4]) -> Self {
+        Self {
+            lines,
+            ..Default::default()
+        }
+    }
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        let mut lines: [Text; 4] = Default::default();
+
+        if let Some(Value::List(List::String(messages))) = nbt.get("messages") {
+            for (line, message) in lines.iter_mut().zip(messages) {
+                *line = message
+                    .parse()
+                    .unwrap_or_else(|_| message.clone().into_text());
+            }
+        }
+
+        Self {
+            lines,
+            color: match nbt.get("color") {
+                Some(Value::String(name)) => DyeColor::from_name(name).unwrap_or_default(),
+                _ => DyeColor::default(),
+            },
+            glowing: matches!(nbt.get("has_glowing_text"), Some(Value::Byte(b)) if *b != 0),
+        }
+    }
+
+    fn to_nbt(&self) -> Compound {
+        let messages = self.lines.iter().cloned().map(String::from).collect();
+
+        let mut nbt = Compound::new();
+        nbt.insert("messages", List::String(messages));
+        nbt.insert("color", self.color.name());
+        nbt.insert("has_glowing_text", self.glowing);
+        nbt
+    }
+}
+
+fn as_compound(value: &Value) -> Option<&Compound> {
+    match value {
+        Value::Compound(nbt) => Some(nbt),
+        _ => None,
+    }
+}
+
+fn is_sign(state: BlockState) -> bool {
+    matches!(
+        state.block_entity_kind(),
+        Some(BlockEntityKind::Sign | BlockEntityKind::HangingSign)
+    )
+}
+
+impl ChunkLayer {
+    /// Returns the data of the sign at `pos`, or `None` if there is no sign.
+    pub fn sign<P: Into<BlockPos>>(&self, pos: P) -> Option<SignData> {
+        let block = self.block(pos)?;
+
+        if !is_sign(block.state) {
+            return None;
+        }
+
+        Some(block.nbt.map(SignData::from_nbt).unwrap_or_default())
+    }
+
+    /// Sets the data of the sign at `pos`. Returns whether there is a sign at
+    /// `pos`.
+    pub fn set_sign<P: Into<BlockPos>>(&mut self, pos: P, data: &SignData) -> bool {
+        let pos = pos.into();
+
+        let Some(state) = self.block(pos).map(|block| block.state) else {
+            return false;
+        };
+
+        if !is_sign(state) {
+            return false;
+        }
+
+        if let Some(nbt) = self.block_entity_mut(pos) {
+            data.write_nbt(nbt);
+        } else {
+            self.set_block(pos, Block::new(state, Some(data.to_nbt())));
+        }
+
+        true
+    }
+}
+
+/// Opens the sign editor for a client.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct OpenSignEditorEvent {
+    pub client: Entity,
+    pub position: BlockPos,
+    /// Whether the front or the back of the sign is edited.
+    pub is_front_text: bool,
+    /// If set, a fake sign with this data is shown at `position` to the client
+    /// instead of the block in the world, which is restored after the editor
+    /// is closed. The client must have the chunk at `position` loaded.
+    pub fake_sign: Option<SignData>,
+}
+
+/// An event sent when a client finishes editing a sign. Edits of real signs
+/// have already been written to the sign. Formatting codes and control
+/// characters are removed from the lines.
+///
+/// The event is only sent if the editor was opened with an
+/// [`OpenSignEditorEvent`] for the same position and side, and a real sign
+/// still exists there and isn't waxed.
+#[derive(Event, Clone, PartialEq, Eq, Debug)]
+pub struct SignEditEvent {
+    pub client: Entity,
+    pub position: BlockPos,
+    pub is_front_text: bool,
+    pub lines: [String; 4],
+    /// Whether the sign was a fake sign.
+    pub fake: bool,
+}
+
+/// The sign editor a client has open.
+#[derive(Component, Copy, Clone, Debug)]
+struct OpenSignEditor {
+    position: BlockPos,
+    is_front_text: bool,
+    fake: bool,
+}
+
+fn open_sign_editors(
+    mut events: EventReader<OpenSignEditorEvent>,
+    mut clients: Query<(&mut Client, &VisibleChunkLayer, Option<&OpenSignEditor>)>,
+    layers: Query<&ChunkLayer>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Ok((mut client, visible_layer, open_editor)) = clients.get_mut(event.client) else {
+            continue;
+        };
+
+        if let Some(editor) = open_editor.filter(|editor| editor.fake) {
+            if let Ok(layer) = layers.get(visible_layer.0) {
+                resync_sign(&mut client, editor.position, layer);
+            }
+        }
+
+        if let Some(data) = &event.fake_sign {
+            client.write_packet(&BlockUpdateS2c {
+                position: event.position,
+                block_id: BlockState::OAK_SIGN,
+            });
+            client.write_packet(&BlockEntityUpdateS2c {
+                position: event.position,
+                kind: BlockEntityKind::Sign,
+                data: Cow::Owned(data.to_nbt()),
+            });
+        }
+
+        client.write_packet(&SignEditorOpenS2c {
+            location: event.position,
+            is_front_text: event.is_front_text,
+        });
+
+        commands.entity(event.client).insert(OpenSignEditor {
+            position: event.position,
+            is_front_text: event.is_front_text,
+            fake: event.fake_sign.is_some(),
+        });
+    }
+}
+
+fn handle_update_sign(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Client, &VisibleChunkLayer, Option<&OpenSignEditor>)>,
+    mut layers: Query<&mut ChunkLayer>,
+    mut events: EventWriter<SignEditEvent>,
+    mut commands: Commands,
+) {
+    for packet in packets.read() {
+        let Some(pkt) = packet.decode::<UpdateSignC2s>() else {
+            continue;
+        };
+
+        let Ok((mut client, visible_layer, open_editor)) = clients.get_mut(packet.client) else {
+            continue;
+        };
+
+        let Ok(mut layer) = layers.get_mut(visible_layer.0) else {
+            continue;
+        };
+
+        let Some(editor) = open_editor
+            .copied()
+            .filter(|editor| editor.position == pkt.position)
+        else {
+            // The client may have changed its copy of the sign.
+            resync_sign(&mut client, pkt.position, &layer);
+            continue;
+        };
+
+        commands.entity(packet.client).remove::<OpenSignEditor>();
+
+        if editor.fake {
+            resync_sign(&mut client, editor.position, &layer);
+        }
+
+        if editor.is_front_text != pkt.is_front_text {
+            continue;
+        }
+
+        let lines = pkt.lines.map(|line| strip_formatting(line.0));
+
+        if !editor.fake {
+            let Some(mut data) = layer.sign(pkt.position).filter(|data| !data.waxed) else {
+                resync_sign(&mut client, pkt.position, &layer);
+                continue;
+            };
+
+            data.side_mut(pkt.is_front_text).lines = lines.clone().map(Text::text);
+
+            layer.set_sign(pkt.position, &data);
+        }
+
+        events.send(SignEditEvent {
+            client: packet.client,
+            position: pkt.position,
+            is_front_text: pkt.is_front_text,
+            lines,
+            fake: editor.fake,
+        });
+    }
+}
+
+/// Sends the block at `pos` in `layer` to the client.
+fn resync_sign(client: &mut Client, pos: BlockPos, layer: &ChunkLayer) {
+    let Some(block) = layer.block(pos) else {
+        return;
+    };
+
+    client.write_packet(&BlockUpdateS2c {
+        position: pos,
+        block_id: block.state,
+    });
+
+    if let (Some(kind), Some(nbt)) = (block.state.block_entity_kind(), block.nbt) {
+        client.write_packet(&BlockEntityUpdateS2c {
+            position: pos,
+            kind,
+            data: Cow::Borrowed(nbt),
+        });
+    }
+}
+
+/// Removes `§` formatting codes and control characters.
+fn strip_formatting(line: &str) -> String {
+    let mut res = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else if !c.is_control() {
+            res.push(c);
+        }
+    }
+
+    res
+}
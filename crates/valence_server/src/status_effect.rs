@@ -37,11 +37,8 @@ impl Plugin for StatusEffectPlugin {
             .add_event::<StatusEffectRemoved>()
             .add_systems(
                 EventLoopPostUpdate,
-                (
-                    add_status_effects,
-                    update_active_status_effects,
-                    add_status_effects,
-                ),
+                // Effects that run out are removed in the same run they expire in.
+                (update_active_status_effects, add_status_effects).chain(),
             );
     }
 }
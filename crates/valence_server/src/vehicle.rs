@@ -0,0 +1,626 @@
+use std::collections::HashMap;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_entity::hitbox::Hitbox;
+use valence_entity::{
+    EntityId, EntityKind, EntityLayerId, InitEntitiesSet, Look, PassengerIds, Position,
+};
+use valence_math::DVec3;
+use valence_protocol::packets::play::{
+    BoatPaddleStateC2s, EntityPassengersSetS2c, PlayerInputC2s, VehicleMoveC2s, VehicleMoveS2c,
+};
+use valence_protocol::{VarInt, WritePacket};
+use valence_server_common::Despawned;
+
+use crate::client::{Client, FlushPacketsSet, LoadEntityForClientEvent, UpdateClientsSet};
+use crate::client_command::{SneakEvent, SneakState};
+use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::interact_entity::{EntityInteraction, InteractEntityEvent};
+use crate::layer::UpdateLayersPreClientSet;
+use crate::teleport::TeleportState;
+use crate::{EntityLayer, EventLoopUpdate, Layer};
+
+pub struct VehiclePlugin;
+
+impl Plugin for VehiclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MountEvent>()
+            .add_event::<DismountEvent>()
+            .add_event::<VehicleInputEvent>()
+            .add_event::<BoatPaddleEvent>()
+            .add_event::<VehicleMoveEvent>()
+            .add_systems(EventLoopPreUpdate, handle_vehicle_packets)
+            .add_systems(EventLoopUpdate, (mount_on_interact, dismount_on_sneak))
+            .add_systems(
+                PostUpdate,
+                (
+                    update_passengers,
+                    update_passenger_ids,
+                    place_dismounted_entities,
+                    move_passengers,
+                    correct_vehicle_movement,
+                )
+                    .chain()
+                    .after(InitEntitiesSet)
+                    .before(UpdateLayersPreClientSet),
+            )
+            .add_systems(
+                PostUpdate,
+                send_own_passengers
+                    .after(UpdateClientsSet)
+                    .before(FlushPacketsSet),
+            );
+    }
+}
+
+/// The entity this entity is riding. Insert this component to mount an entity
+/// on another and remove it to dismount.
+///
+/// The [`Passengers`] of the vehicle are updated to match at the end of the
+/// tick. Mounts that would make an entity ride itself are undone.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Vehicle(pub Entity);
+
+/// The entities riding this entity in the order they mounted it. The first
+/// passenger is the one controlling the vehicle.
+///
+/// This is kept in sync with the [`Vehicle`] components of the passengers and
+/// can't be modified directly.
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub struct Passengers(Vec<Entity>);
+
+impl Passengers {
+    /// Returns all passengers in the order they mounted.
+    pub fn get(&self) -> &[Entity] {
+        &self.0
+    }
+
+    /// Returns the passenger controlling the vehicle, if any.
+    pub fn controlling(&self) -> Option<Entity> {
+        self.0.first().copied()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.0.contains(&entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Lets clients mount an entity by interacting with it. Mounted clients
+/// dismount by sneaking.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Rideable {
+    /// The number of passengers that can ride the entity at once.
+    pub max_passengers: usize,
+}
+
+impl Default for Rideable {
+    fn default() -> Self {
+        Self { max_passengers: 1 }
+    }
+}
+
+/// Decides who moves a vehicle controlled by a client. Vehicles without this
+/// component are controlled by the client.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum VehicleControl {
+    /// The controlling client moves the vehicle, like boats and horses in
+    /// vanilla. A [`VehicleMoveEvent`] is sent for every move. The server can
+    /// still move the vehicle by changing its [`Position`].
+    #[default]
+    Client,
+    /// The server moves the vehicle, usually based on [`VehicleInputEvent`]s.
+    /// Moves made by the client are rejected.
+    Server,
+}
+
+/// Sent when an entity starts riding a vehicle.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct MountEvent {
+    pub passenger: Entity,
+    pub vehicle: Entity,
+}
+
+/// Sent when an entity stops riding a vehicle, including when either of them
+/// is despawned.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DismountEvent {
+    pub passenger: Entity,
+    pub vehicle: Entity,
+}
+
+/// The steering input of a client riding a vehicle.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct VehicleInputEvent {
+    pub client: Entity,
+    /// Positive to the left, negative to the right.
+    pub sideways: f32,
+    /// Positive forward, negative backward.
+    pub forward: f32,
+    pub jump: bool,
+    /// Whether the client is holding the sneak key, which dismounts it.
+    pub sneak: bool,
+}
+
+/// Sent when a client in a boat changes which paddles are turning.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BoatPaddleEvent {
+    pub client: Entity,
+    pub left_paddle_turning: bool,
+    pub right_paddle_turning: bool,
+}
+
+/// Sent when a client moves the vehicle it controls.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct VehicleMoveEvent {
+    pub client: Entity,
+    pub vehicle: Entity,
+    pub position: DVec3,
+    pub old_position: DVec3,
+    pub look: Look,
+    pub old_look: Look,
+}
+
+/// The position of a vehicle as last seen by its controlling client.
+#[derive(Component, Copy, Clone, Debug)]
+struct VehicleSync {
+    position: DVec3,
+    look: Look,
+}
+
+impl Default for VehicleSync {
+    fn default() -> Self {
+        Self {
+            position: DVec3::NAN,
+            look: Look::new(f32::NAN, f32::NAN),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_vehicle_packets(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Client, Option<&Vehicle>)>,
+    mut vehicles: Query<(
+        &mut Position,
+        &mut Look,
+        &Passengers,
+        Option<&VehicleControl>,
+        Option<&mut VehicleSync>,
+    )>,
+    mut input_events: EventWriter<VehicleInputEvent>,
+    mut paddle_events: EventWriter<BoatPaddleEvent>,
+    mut move_events: EventWriter<VehicleMoveEvent>,
+) {
+    for packet in packets.read() {
+        if let Some(pkt) = packet.decode::<PlayerInputC2s>() {
+            input_events.send(VehicleInputEvent {
+                client: packet.client,
+                sideways: pkt.sideways,
+                forward: pkt.forward,
+                jump: pkt.flags.jump(),
+                sneak: pkt.flags.unmount(),
+            });
+        } else if let Some(pkt) = packet.decode::<BoatPaddleStateC2s>() {
+            paddle_events.send(BoatPaddleEvent {
+                client: packet.client,
+                left_paddle_turning: pkt.left_paddle_turning,
+                right_paddle_turning: pkt.right_paddle_turning,
+            });
+        } else if let Some(pkt) = packet.decode::<VehicleMoveC2s>() {
+            let Ok((mut client, Some(&Vehicle(vehicle)))) = clients.get_mut(packet.client) else {
+                continue;
+            };
+
+            let Ok((mut pos, mut look, passengers, control, sync)) = vehicles.get_mut(vehicle)
+            else {
+                continue;
+            };
+
+            if passengers.controlling() != Some(packet.client) {
+                continue;
+            }
+
+            if control == Some(&VehicleControl::Server) {
+                if pkt.position != pos.0 {
+                    client.write_packet(&VehicleMoveS2c {
+                        position: pos.0,
+                        yaw: look.yaw,
+                        pitch: look.pitch,
+                    });
+                }
+
+                continue;
+            }
+
+            let event = VehicleMoveEvent {
+                client: packet.client,
+                vehicle,
+                position: pkt.position,
+                old_position: pos.0,
+                look: Look::new(pkt.yaw, pkt.pitch),
+                old_look: *look,
+            };
+
+            pos.set_if_neq(Position(event.position));
+            look.set_if_neq(event.look);
+
+            if let Some(mut sync) = sync {
+                sync.position = event.position;
+                sync.look = event.look;
+            }
+
+            move_events.send(event);
+        }
+    }
+}
+
+fn mount_on_interact(
+    mut events: EventReader<InteractEntityEvent>,
+    clients: Query<(), (With<Client>, Without<Vehicle>)>,
+    vehicles: Query<(&Rideable, Option<&Passengers>)>,
+    mut commands: Commands,
+) {
+    // The number of clients mounting each vehicle this tick.
+    let mut mounting = HashMap::<Entity, usize>::new();
+
+    for event in events.read() {
+        if event.sneaking
+            || event.client == event.entity
+            || !matches!(event.interact, EntityInteraction::Interact(_))
+            || !clients.contains(event.client)
+        {
+            continue;
+        }
+
+        let Ok((rideable, passengers)) = vehicles.get(event.entity) else {
+            continue;
+        };
+
+        let count = mounting.entry(event.entity).or_default();
+
+        if passengers.map_or(0, |p| p.len()) + *count >= rideable.max_passengers {
+            continue;
+        }
+
+        *count += 1;
+        commands.entity(event.client).insert(Vehicle(event.entity));
+    }
+}
+
+fn dismount_on_sneak(
+    mut inputs: EventReader<VehicleInputEvent>,
+    mut sneaks: EventReader<SneakEvent>,
+    riders: Query<(), With<Vehicle>>,
+    mut commands: Commands,
+) {
+    let dismounting = inputs
+        .read()
+        .filter(|input| input.sneak)
+        .map(|input| input.client)
+        .chain(
+            sneaks
+                .read()
+                .filter(|sneak| sneak.state == SneakState::Start)
+                .map(|sneak| sneak.client),
+        );
+
+    for client in dismounting {
+        if riders.contains(client) {
+            commands.entity(client).remove::<Vehicle>();
+        }
+    }
+}
+
+/// Returns `true` if `entity` is riding `vehicle`, directly or indirectly.
+fn is_riding(riders: &Query<&Vehicle>, mut entity: Entity, vehicle: Entity) -> bool {
+    // Limit the depth in case the mounts already contain a cycle.
+    for _ in 0..64 {
+        match riders.get(entity) {
+            Ok(&Vehicle(next)) if next == vehicle => return true,
+            Ok(&Vehicle(next)) => entity = next,
+            Err(_) => return false,
+        }
+    }
+
+    false
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_passengers(
+    changed_riders: Query<(Entity, &Vehicle), Changed<Vehicle>>,
+    riders: Query<&Vehicle>,
+    mut vehicles: Query<(Entity, &mut Passengers)>,
+    entities: Query<Has<Despawned>, With<EntityKind>>,
+    with_ids: Query<(), With<PassengerIds>>,
+    mut mount_events: EventWriter<MountEvent>,
+    mut dismount_events: EventWriter<DismountEvent>,
+    mut commands: Commands,
+) {
+    let is_alive = |entity: Entity| entities.get(entity) == Ok(false);
+
+    // Remove passengers which stopped riding their vehicle.
+    for (vehicle, mut passengers) in &mut vehicles {
+        let vehicle_alive = is_alive(vehicle);
+
+        let still_riding = |passenger: &Entity| {
+            vehicle_alive
+                && is_alive(*passenger)
+                && riders.get(*passenger).is_ok_and(|v| v.0 == vehicle)
+        };
+
+        if passengers.0.iter().all(still_riding) {
+            continue;
+        }
+
+        let (kept, removed): (Vec<_>, Vec<_>) = passengers.0.iter().partition(|p| still_riding(p));
+
+        passengers.0 = kept;
+
+        for passenger in removed {
+            // The vehicle was despawned out from under the passenger.
+            if is_alive(passenger) && riders.get(passenger).is_ok_and(|v| v.0 == vehicle) {
+                commands.entity(passenger).remove::<Vehicle>();
+            }
+
+            dismount_events.send(DismountEvent { passenger, vehicle });
+        }
+    }
+
+    // Add passengers which started riding a vehicle.
+    let mut new_vehicles = HashMap::<Entity, Vec<Entity>>::new();
+
+    for (passenger, &Vehicle(vehicle)) in &changed_riders {
+        if !is_alive(passenger) {
+            continue;
+        }
+
+        if vehicle == passenger || !is_alive(vehicle) || is_riding(&riders, vehicle, passenger) {
+            commands.entity(passenger).remove::<Vehicle>();
+            continue;
+        }
+
+        if let Ok((_, mut passengers)) = vehicles.get_mut(vehicle) {
+            if passengers.contains(passenger) {
+                continue;
+            }
+
+            passengers.0.push(passenger);
+        } else {
+            new_vehicles.entry(vehicle).or_default().push(passenger);
+        }
+
+        if !with_ids.contains(passenger) {
+            commands.entity(passenger).insert(PassengerIds::default());
+        }
+
+        mount_events.send(MountEvent { passenger, vehicle });
+    }
+
+    for (vehicle, passengers) in new_vehicles {
+        let mut vehicle = commands.entity(vehicle);
+
+        vehicle.insert((Passengers(passengers), VehicleSync::default()));
+
+        if !with_ids.contains(vehicle.id()) {
+            vehicle.insert(PassengerIds::default());
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_passenger_ids(
+    mut removed_vehicles: RemovedComponents<Vehicle>,
+    riders: Query<(), With<Vehicle>>,
+    vehicles: Query<
+        (
+            Entity,
+            &Passengers,
+            &EntityId,
+            &Position,
+            &EntityLayerId,
+            Has<Despawned>,
+        ),
+        Changed<Passengers>,
+    >,
+    entity_ids: Query<&EntityId>,
+    mut passenger_ids: Query<&mut PassengerIds>,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for entity in removed_vehicles.read() {
+        if !riders.contains(entity) {
+            if let Ok(mut ids) = passenger_ids.get_mut(entity) {
+                ids.vehicle = None;
+            }
+        }
+    }
+
+    for (vehicle, passengers, vehicle_id, pos, layer_id, despawned) in &vehicles {
+        let ids: Vec<_> = passengers
+            .get()
+            .iter()
+            .filter_map(|&p| entity_ids.get(p).ok())
+            .map(|id| VarInt(id.get()))
+            .collect();
+
+        let vehicle_id = VarInt(vehicle_id.get());
+
+        if let Ok(mut vehicle_ids) = passenger_ids.get_mut(vehicle) {
+            vehicle_ids.passengers.clone_from(&ids);
+        }
+
+        for &passenger in passengers.get() {
+            if let Ok(mut passenger_ids) = passenger_ids.get_mut(passenger) {
+                passenger_ids.vehicle = Some((vehicle_id, ids.clone()));
+            }
+        }
+
+        if despawned {
+            continue;
+        }
+
+        if let Ok(mut layer) = layers.get_mut(layer_id.0) {
+            layer
+                .view_writer(pos.0)
+                .write_packet(&EntityPassengersSetS2c {
+                    entity_id: vehicle_id,
+                    passengers: ids.into(),
+                });
+        }
+    }
+}
+
+/// Moves dismounted entities on top of their vehicle.
+fn place_dismounted_entities(
+    mut events: EventReader<DismountEvent>,
+    mut entities: Query<(&mut Position, Option<&Hitbox>, Has<Vehicle>)>,
+) {
+    for event in events.read() {
+        let Ok((pos, hitbox, _)) = entities.get(event.vehicle) else {
+            continue;
+        };
+
+        let top = DVec3::new(
+            pos.0.x,
+            hitbox.map_or(pos.0.y, |hitbox| hitbox.get().max().y),
+            pos.0.z,
+        );
+
+        if let Ok((mut pos, _, false)) = entities.get_mut(event.passenger) {
+            pos.set_if_neq(Position(top));
+        }
+    }
+}
+
+/// Moves passengers along with their vehicle.
+fn move_passengers(
+    vehicles: Query<(Entity, &Passengers)>,
+    mut entities: Query<(&mut Position, Option<&mut TeleportState>)>,
+) {
+    for (vehicle, passengers) in &vehicles {
+        let Ok((pos, _)) = entities.get(vehicle) else {
+            continue;
+        };
+
+        let pos = pos.0;
+
+        for &passenger in passengers.get() {
+            if let Ok((mut passenger_pos, teleport_state)) = entities.get_mut(passenger) {
+                if passenger_pos.0 != pos {
+                    passenger_pos.0 = pos;
+
+                    // Riding clients are moved by the client itself, so don't teleport them.
+                    if let Some(mut state) = teleport_state {
+                        state.synced_pos = pos;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Sends the position of vehicles moved by the server to their controlling
+/// client.
+#[allow(clippy::type_complexity)]
+fn correct_vehicle_movement(
+    mut vehicles: Query<
+        (&Position, &Look, &Passengers, &mut VehicleSync),
+        Or<(Changed<Position>, Changed<Look>)>,
+    >,
+    mut clients: Query<&mut Client>,
+) {
+    for (pos, look, passengers, mut sync) in &mut vehicles {
+        if sync.position == pos.0 && sync.look == *look {
+            continue;
+        }
+
+        let Some(mut client) = passengers
+            .controlling()
+            .and_then(|c| clients.get_mut(c).ok())
+        else {
+            continue;
+        };
+
+        sync.position = pos.0;
+        sync.look = *look;
+
+        client.write_packet(&VehicleMoveS2c {
+            position: pos.0,
+            yaw: look.yaw,
+            pitch: look.pitch,
+        });
+    }
+}
+
+/// Clients know themselves by an entity ID of zero, so the passenger packets
+/// they are part of have to be rewritten. This happens after the packets for
+/// all viewers are sent so that the rewritten packets take precedence.
+fn send_own_passengers(
+    mut clients: Query<(&mut Client, &EntityId, Ref<PassengerIds>)>,
+    entities: Query<(&EntityId, &PassengerIds)>,
+    mut load_events: EventReader<LoadEntityForClientEvent>,
+) {
+    for (mut client, &self_id, ids) in &mut clients {
+        if ids.is_changed() {
+            write_own_passenger_packets(&mut client, self_id, self_id, &ids);
+        }
+    }
+
+    for event in load_events.read() {
+        let Ok((mut client, &self_id, _)) = clients.get_mut(event.client) else {
+            continue;
+        };
+
+        let Ok((&entity_id, ids)) = entities.get(event.entity_loaded) else {
+            continue;
+        };
+
+        let self_var_int = VarInt(self_id.get());
+
+        let involves_client = ids.passengers.contains(&self_var_int)
+            || ids.vehicle.as_ref().is_some_and(|(id, passengers)| {
+                *id == self_var_int || passengers.contains(&self_var_int)
+            });
+
+        if involves_client {
+            write_own_passenger_packets(&mut client, self_id, entity_id, ids);
+        }
+    }
+}
+
+fn write_own_passenger_packets(
+    client: &mut Client,
+    self_id: EntityId,
+    entity_id: EntityId,
+    ids: &PassengerIds,
+) {
+    let map = |id: VarInt| {
+        if id.0 == self_id.get() {
+            VarInt(0)
+        } else {
+            id
+        }
+    };
+
+    let map_all = |ids: &[VarInt]| ids.iter().copied().map(map).collect::<Vec<_>>();
+
+    client.write_packet(&EntityPassengersSetS2c {
+        entity_id: map(VarInt(entity_id.get())),
+        passengers: map_all(&ids.passengers).into(),
+    });
+
+    if let Some((vehicle_id, passengers)) = &ids.vehicle {
+        client.write_packet(&EntityPassengersSetS2c {
+            entity_id: map(*vehicle_id),
+            passengers: map_all(passengers).into(),
+        });
+    }
+}
@@ -0,0 +1,148 @@
+#![doc = include_str!("../README.md")]
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_server::client::{Client, FlushPacketsSet, UpdateClientsSet, VisibleChunkLayer};
+use valence_server::protocol::packets::play::WorldTimeUpdateS2c;
+use valence_server::protocol::WritePacket;
+use valence_server::{ChunkLayer, Server};
+
+/// The number of ticks in a Minecraft day.
+pub const DAY_LENGTH: i64 = 24000;
+
+/// How often the time is sent to clients, in ticks. The client keeps the time
+/// advancing by itself in between.
+const UPDATE_INTERVAL: i64 = 20;
+
+pub struct WorldTimePlugin;
+
+impl Plugin for WorldTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                advance_world_time.before(UpdateClientsSet),
+                update_client_time
+                    .after(UpdateClientsSet)
+                    .before(FlushPacketsSet),
+            ),
+        );
+    }
+}
+
+/// The time of a chunk layer. Inserting this on a client overrides the time of
+/// the client's [`VisibleChunkLayer`] for that client.
+///
+/// Both values advance by one every tick. Changing the component sends the new
+/// time to clients immediately.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct WorldTime {
+    /// The number of ticks the world has existed for.
+    pub world_age: i64,
+    /// The time of day in ticks. 0 is sunrise, 6000 is noon, 12000 is sunset
+    /// and 18000 is midnight. The value is not wrapped around at the end of a
+    /// day, see [`WorldTime::day`].
+    pub time_of_day: i64,
+    /// Whether the time of day advances. The world age always advances.
+    pub daylight_cycle: bool,
+}
+
+impl WorldTime {
+    /// Returns a world time starting at the given time of day.
+    pub fn new(time_of_day: i64) -> Self {
+        Self {
+            time_of_day,
+            ..Default::default()
+        }
+    }
+
+    /// The number of days that have passed.
+    pub fn day(&self) -> i64 {
+        self.time_of_day.div_euclid(DAY_LENGTH)
+    }
+
+    /// The time of day within the current day, in \[0, 24000).
+    pub fn time_in_day(&self) -> i64 {
+        self.time_of_day.rem_euclid(DAY_LENGTH)
+    }
+
+    fn to_packet(self) -> WorldTimeUpdateS2c {
+        // A negative time of day stops the client from advancing it.
+        let time_of_day = if self.daylight_cycle {
+            self.time_of_day
+        } else if self.time_of_day == 0 {
+            -1
+        } else {
+            -self.time_of_day.abs()
+        };
+
+        WorldTimeUpdateS2c {
+            world_age: self.world_age,
+            time_of_day,
+        }
+    }
+}
+
+impl Default for WorldTime {
+    fn default() -> Self {
+        Self {
+            world_age: 0,
+            time_of_day: 0,
+            daylight_cycle: true,
+        }
+    }
+}
+
+fn advance_world_time(mut times: Query<&mut WorldTime>) {
+    for mut time in &mut times {
+        // Advancing the time is not a change that needs to be sent right away.
+        let time = time.bypass_change_detection();
+
+        time.world_age += 1;
+
+        if time.daylight_cycle {
+            time.time_of_day += 1;
+        }
+    }
+}
+
+fn update_client_time(
+    mut clients: Query<(&mut Client, Ref<VisibleChunkLayer>, Option<Ref<WorldTime>>)>,
+    layers: Query<Ref<WorldTime>, (With<ChunkLayer>, Without<Client>)>,
+    mut removed: RemovedComponents<WorldTime>,
+    server: Res<Server>,
+) {
+    let periodic = server.current_tick() % UPDATE_INTERVAL == 0;
+
+    for (mut client, chunk_layer, override_time) in &mut clients {
+        let (time, changed) = match override_time {
+            Some(time) => (*time, time.is_changed()),
+            None => match layers.get(chunk_layer.0) {
+                Ok(time) => (*time, time.is_changed()),
+                Err(_) => continue,
+            },
+        };
+
+        if periodic || changed || chunk_layer.is_changed() {
+            client.write_packet(&time.to_packet());
+        }
+    }
+
+    // Clients whose override was removed go back to the time of their layer.
+    for entity in removed.read() {
+        let Ok((mut client, chunk_layer, None)) = clients.get_mut(entity) else {
+            continue;
+        };
+
+        if periodic || chunk_layer.is_changed() {
+            // Already sent above.
+            continue;
+        }
+
+        if let Ok(time) = layers.get(chunk_layer.0) {
+            if !time.is_changed() {
+                client.write_packet(&time.to_packet());
+            }
+        }
+    }
+}
@@ -101,23 +101,11 @@ fn toggle_gamemode_on_sneak(
     }
 }
 
-fn digging(
-    clients: Query<&GameMode>,
-    mut layers: Query<&mut ChunkLayer>,
-    mut events: EventReader<DiggingEvent>,
-) {
+fn digging(mut layers: Query<&mut ChunkLayer>, mut events: EventReader<BlockMinedEvent>) {
     let mut layer = layers.single_mut();
 
     for event in events.read() {
-        let Ok(game_mode) = clients.get(event.client) else {
-            continue;
-        };
-
-        if (*game_mode == GameMode::Creative && event.state == DiggingState::Start)
-            || (*game_mode == GameMode::Survival && event.state == DiggingState::Stop)
-        {
-            layer.set_block(event.position, BlockState::AIR);
-        }
+        layer.set_block(event.position, BlockState::AIR);
     }
 }
 
@@ -1,27 +1,15 @@
 #![allow(clippy::type_complexity)]
 
-use bevy_ecs::query::QueryData;
 use rand::Rng;
-use valence::entity::EntityStatuses;
-use valence::math::Vec3Swizzles;
 use valence::prelude::*;
 
 const SPAWN_Y: i32 = 64;
 const ARENA_RADIUS: i32 = 32;
 
-/// Attached to every client.
-#[derive(Component, Default)]
-struct CombatState {
-    /// The tick the client was last attacked.
-    last_attacked_tick: i64,
-    has_bonus_knockback: bool,
-}
-
 pub fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_systems(Startup, setup)
-        .add_systems(EventLoopUpdate, handle_combat_events)
         .add_systems(
             Update,
             (
@@ -76,24 +64,23 @@ fn setup(
 fn init_clients(
     mut clients: Query<
         (
-            Entity,
             &mut EntityLayerId,
             &mut VisibleChunkLayer,
             &mut VisibleEntityLayers,
             &mut Position,
+            &mut RespawnPosition,
             &mut GameMode,
         ),
         Added<Client>,
     >,
     layers: Query<Entity, (With<ChunkLayer>, With<EntityLayer>)>,
-    mut commands: Commands,
 ) {
     for (
-        entity,
         mut layer_id,
         mut visible_chunk_layer,
         mut visible_entity_layers,
         mut pos,
+        mut respawn_pos,
         mut game_mode,
     ) in &mut clients
     {
@@ -103,77 +90,8 @@ fn init_clients(
         visible_chunk_layer.0 = layer;
         visible_entity_layers.0.insert(layer);
         pos.set([0.0, f64::from(SPAWN_Y) + 1.0, 0.0]);
-        *game_mode = GameMode::Creative;
-
-        commands.entity(entity).insert(CombatState::default());
-    }
-}
-
-#[derive(QueryData)]
-#[query_data(mutable)]
-struct CombatQuery {
-    client: &'static mut Client,
-    pos: &'static Position,
-    state: &'static mut CombatState,
-    statuses: &'static mut EntityStatuses,
-}
-
-fn handle_combat_events(
-    server: Res<Server>,
-    mut clients: Query<CombatQuery>,
-    mut sprinting: EventReader<SprintEvent>,
-    mut interact_entity: EventReader<InteractEntityEvent>,
-) {
-    for &SprintEvent { client, state } in sprinting.read() {
-        if let Ok(mut client) = clients.get_mut(client) {
-            client.state.has_bonus_knockback = state == SprintState::Start;
-        }
-    }
-
-    for &InteractEntityEvent {
-        client: attacker_client,
-        entity: victim_client,
-        ..
-    } in interact_entity.read()
-    {
-        let Ok([mut attacker, mut victim]) = clients.get_many_mut([attacker_client, victim_client])
-        else {
-            // Victim or attacker does not exist, or the attacker is attacking itself.
-            continue;
-        };
-
-        if server.current_tick() - victim.state.last_attacked_tick < 10 {
-            // Victim is still on attack cooldown.
-            continue;
-        }
-
-        victim.state.last_attacked_tick = server.current_tick();
-
-        let victim_pos = victim.pos.0.xz();
-        let attacker_pos = attacker.pos.0.xz();
-
-        let dir = (victim_pos - attacker_pos).normalize().as_vec2();
-
-        let knockback_xz = if attacker.state.has_bonus_knockback {
-            18.0
-        } else {
-            8.0
-        };
-        let knockback_y = if attacker.state.has_bonus_knockback {
-            8.432
-        } else {
-            6.432
-        };
-
-        victim
-            .client
-            .set_velocity([dir.x * knockback_xz, knockback_y, dir.y * knockback_xz]);
-
-        attacker.state.has_bonus_knockback = false;
-
-        victim.client.trigger_status(EntityStatus::PlayAttackSound);
-
-        victim.statuses.trigger(EntityStatus::PlayAttackSound);
+        respawn_pos.pos = BlockPos::new(0, SPAWN_Y + 1, 0);
+        *game_mode = GameMode::Survival;
     }
 }
 
@@ -3,20 +3,18 @@
 use std::collections::HashMap;
 
 use bevy_ecs::query::QueryData;
+use valence::combat::{AttackEvent, CancelAttackEvent, CombatProxy};
 use valence::entity::cow::CowEntityBundle;
 use valence::entity::entity::Flags;
-use valence::entity::living::Health;
 use valence::entity::pig::PigEntityBundle;
 use valence::entity::player::PlayerEntityBundle;
 use valence::entity::{EntityAnimations, EntityStatuses, OnGround, Velocity};
 use valence::interact_block::InteractBlockEvent;
 use valence::inventory::HeldItem;
 use valence::log::debug;
-use valence::math::Vec3Swizzles;
 use valence::nbt::{compound, List};
 use valence::prelude::*;
 use valence::scoreboard::*;
-use valence::status::RequestRespawnEvent;
 
 const ARENA_Y: i32 = 64;
 const ARENA_MID_WIDTH: i32 = 2;
@@ -28,7 +26,6 @@ const SPAWN_POS: [f64; 3] = [
 ];
 const SPAWN_BOX_WIDTH: i32 = 5;
 const SPAWN_BOX_HEIGHT: i32 = 4;
-const PLAYER_MAX_HEALTH: f32 = 20.0;
 
 pub fn main() {
     App::new()
@@ -38,7 +35,6 @@ pub fn main() {
         })
         .add_plugins(DefaultPlugins)
         .add_systems(Startup, setup)
-        .add_systems(EventLoopUpdate, handle_combat_events)
         .add_systems(
             Update,
             (
@@ -52,7 +48,7 @@ pub fn main() {
                 // visualize_triggers,
                 update_clones,
                 teleport_oob_clients,
-                necromancy,
+                prevent_friendly_fire,
                 update_scoreboard,
             ),
         )
@@ -369,7 +365,6 @@ fn init_clients(
             &mut VisibleEntityLayers,
             &mut Position,
             &mut GameMode,
-            &mut Health,
         ),
         Added<Client>,
     >,
@@ -383,7 +378,6 @@ fn init_clients(
         mut visible_entity_layers,
         mut pos,
         mut game_mode,
-        mut health,
     ) in &mut clients
     {
         let layer = main_layers.single();
@@ -394,7 +388,6 @@ fn init_clients(
         visible_entity_layers.0.insert(globals.scoreboard_layer);
         pos.set(SPAWN_POS);
         *game_mode = GameMode::Adventure;
-        health.0 = PLAYER_MAX_HEALTH;
 
         client.send_chat_message(
             "Welcome to Valence! Select a team by jumping in the team's portal.".italic(),
@@ -434,9 +427,9 @@ impl Team {
 }
 
 fn digging(
-    mut clients: Query<(&GameMode, &Team, Entity, &mut Client, &mut Inventory)>,
+    mut clients: Query<(&Team, Entity, &mut Client, &mut Inventory)>,
     mut layers: Query<&mut ChunkLayer>,
-    mut events: EventReader<DiggingEvent>,
+    mut events: EventReader<BlockMinedEvent>,
     mut commands: Commands,
     globals: Res<CtfGlobals>,
     mut flag_manager: ResMut<FlagManager>,
@@ -444,59 +437,48 @@ fn digging(
     let mut layer = layers.single_mut();
 
     for event in events.read() {
-        let Ok((game_mode, team, ent, mut client, mut inv)) = clients.get_mut(event.client) else {
+        let Ok((team, ent, mut client, mut inv)) = clients.get_mut(event.client) else {
             continue;
         };
 
-        if (*game_mode == GameMode::Creative && event.state == DiggingState::Start)
-            || (*game_mode == GameMode::Survival && event.state == DiggingState::Stop)
-        {
-            let Some(block) = layer.block(event.position) else {
-                continue;
-            };
-            let is_flag = event.position == globals.red_flag || event.position == globals.blue_flag;
-
-            match (team, block.state) {
-                (Team::Blue, BlockState::RED_WOOL) => {
-                    if event.position == globals.red_flag {
-                        commands.entity(event.client).insert(HasFlag(Team::Red));
-                        client.send_chat_message("You have the flag!".italic());
-                        flag_manager.red = Some(ent);
-                        return;
-                    }
-                }
-                (Team::Red, BlockState::BLUE_WOOL) => {
-                    if event.position == globals.blue_flag {
-                        commands.entity(event.client).insert(HasFlag(Team::Blue));
-                        client.send_chat_message("You have the flag!".italic());
-                        flag_manager.blue = Some(ent);
-                        return;
-                    }
-                }
-                _ => {}
+        let Some(block) = layer.block(event.position) else {
+            continue;
+        };
+        let is_flag = event.position == globals.red_flag || event.position == globals.blue_flag;
+
+        match (team, block.state) {
+            (Team::Blue, BlockState::RED_WOOL) if event.position == globals.red_flag => {
+                commands.entity(event.client).insert(HasFlag(Team::Red));
+                client.send_chat_message("You have the flag!".italic());
+                flag_manager.red = Some(ent);
+                return;
             }
-
-            if event.position.y <= ARENA_Y
-                || block.state.to_kind() == BlockKind::OakFence
-                || is_flag
-            {
-                continue;
+            (Team::Red, BlockState::BLUE_WOOL) if event.position == globals.blue_flag => {
+                commands.entity(event.client).insert(HasFlag(Team::Blue));
+                client.send_chat_message("You have the flag!".italic());
+                flag_manager.blue = Some(ent);
+                return;
             }
+            _ => {}
+        }
 
-            let prev = layer.set_block(event.position, BlockState::AIR);
+        if event.position.y <= ARENA_Y || block.state.to_kind() == BlockKind::OakFence || is_flag {
+            continue;
+        }
 
-            if let Some(prev) = prev {
-                let kind: ItemKind = prev.state.to_kind().to_item_kind();
-                if let Some(slot) = inv.first_slot_with_item_in(kind, 64, 9..45) {
-                    let count = inv.slot(slot).count;
-                    inv.set_slot_amount(slot, count + 1);
+        let prev = layer.set_block(event.position, BlockState::AIR);
+
+        if let Some(prev) = prev {
+            let kind: ItemKind = prev.state.to_kind().to_item_kind();
+            if let Some(slot) = inv.first_slot_with_item_in(kind, 64, 9..45) {
+                let count = inv.slot(slot).count;
+                inv.set_slot_amount(slot, count + 1);
+            } else {
+                let stack = ItemStack::new(kind, 1, None);
+                if let Some(empty_slot) = inv.first_empty_slot_in(9..45) {
+                    inv.set_slot(empty_slot, stack);
                 } else {
-                    let stack = ItemStack::new(kind, 1, None);
-                    if let Some(empty_slot) = inv.first_empty_slot_in(9..45) {
-                        inv.set_slot(empty_slot, stack);
-                    } else {
-                        debug!("No empty slot to give item to player: {:?}", kind);
-                    }
+                    debug!("No empty slot to give item to player: {:?}", kind);
                 }
             }
         }
@@ -560,6 +542,7 @@ fn do_team_selector_portals(
             &mut GameMode,
             &mut Client,
             &mut VisibleEntityLayers,
+            &mut RespawnPosition,
             &UniqueId,
         ),
         Without<Team>,
@@ -578,6 +561,7 @@ fn do_team_selector_portals(
             mut game_mode,
             mut client,
             mut ent_layers,
+            mut respawn_pos,
             unique_id,
         ) = player;
         if pos.0.y < f64::from(SPAWN_BOX[1]) - 5.0 {
@@ -608,10 +592,7 @@ fn do_team_selector_portals(
                     None,
                 ),
             );
-            let combat_state = CombatState::default();
-            commands
-                .entity(player)
-                .insert((team, inventory, combat_state));
+            commands.entity(player).insert((team, inventory));
             pos.0 = team.spawn_pos();
             let yaw = match team {
                 Team::Red => -90.0,
@@ -619,6 +600,8 @@ fn do_team_selector_portals(
             };
             look.yaw = yaw;
             look.pitch = 0.0;
+            respawn_pos.pos = team.spawn_pos().into();
+            respawn_pos.yaw = yaw;
             head_yaw.0 = yaw;
             let chat_text: Text = "You are on team ".into_text() + team.team_text() + "!";
             client.send_chat_message(chat_text);
@@ -646,7 +629,7 @@ fn do_team_selector_portals(
                 position: *pos,
                 ..Default::default()
             });
-            player_glowing.insert(ClonedEntity(player));
+            player_glowing.insert((ClonedEntity(player), CombatProxy(player)));
 
             let enemy_layer = ctf_layers.enemy_layers[&team];
             let mut player_enemy = commands.spawn(PlayerEntityBundle {
@@ -655,7 +638,7 @@ fn do_team_selector_portals(
                 position: *pos,
                 ..Default::default()
             });
-            player_enemy.insert(ClonedEntity(player));
+            player_enemy.insert((ClonedEntity(player), CombatProxy(player)));
         }
     }
 }
@@ -904,106 +887,21 @@ fn update_clones(
     }
 }
 
-/// Attached to every client.
-#[derive(Component, Default)]
-struct CombatState {
-    /// The tick the client was last attacked.
-    last_attacked_tick: i64,
-    has_bonus_knockback: bool,
-}
-
-#[derive(QueryData)]
-#[query_data(mutable)]
-struct CombatQuery {
-    client: &'static mut Client,
-    pos: &'static Position,
-    state: &'static mut CombatState,
-    statuses: &'static mut EntityStatuses,
-    health: &'static mut Health,
-    inventory: &'static Inventory,
-    held_item: &'static HeldItem,
-    team: &'static Team,
-}
-
-fn handle_combat_events(
-    server: Res<Server>,
-    mut clients: Query<CombatQuery>,
-    mut sprinting: EventReader<SprintEvent>,
-    mut interact_entity: EventReader<InteractEntityEvent>,
-    clones: Query<&ClonedEntity>,
+/// Cancels attacks between players of the same team.
+fn prevent_friendly_fire(
+    mut attacks: EventReader<AttackEvent>,
+    mut cancel: EventWriter<CancelAttackEvent>,
+    teams: Query<&Team>,
 ) {
-    for &SprintEvent { client, state } in sprinting.read() {
-        if let Ok(mut client) = clients.get_mut(client) {
-            client.state.has_bonus_knockback = state == SprintState::Start;
-        }
-    }
-
-    for &InteractEntityEvent {
-        client: attacker_client,
-        entity: victim_client,
-        ..
-    } in interact_entity.read()
-    {
-        let true_victim_ent = clones
-            .get(victim_client)
-            .map(|cloned| cloned.0)
-            .unwrap_or(victim_client);
-        let Ok([mut attacker, mut victim]) =
-            clients.get_many_mut([attacker_client, true_victim_ent])
-        else {
-            debug!("Failed to get clients for combat event");
-            // Victim or attacker does not exist, or the attacker is attacking itself.
-            continue;
-        };
-
-        if attacker.team == victim.team {
-            // Attacker and victim are on the same team.
-            continue;
-        }
-
-        if server.current_tick() - victim.state.last_attacked_tick < 10 {
-            // Victim is still on attack cooldown.
-            continue;
+    for attack in attacks.read() {
+        if let Ok([attacker_team, target_team]) = teams.get_many([attack.attacker, attack.target]) {
+            if attacker_team == target_team {
+                cancel.send(CancelAttackEvent {
+                    attacker: attack.attacker,
+                    target: attack.target,
+                });
+            }
         }
-
-        victim.state.last_attacked_tick = server.current_tick();
-
-        let victim_pos = victim.pos.0.xz();
-        let attacker_pos = attacker.pos.0.xz();
-
-        let dir = (victim_pos - attacker_pos).normalize().as_vec2();
-
-        let knockback_xz = if attacker.state.has_bonus_knockback {
-            18.0
-        } else {
-            8.0
-        };
-        let knockback_y = if attacker.state.has_bonus_knockback {
-            8.432
-        } else {
-            6.432
-        };
-
-        victim
-            .client
-            .set_velocity([dir.x * knockback_xz, knockback_y, dir.y * knockback_xz]);
-
-        attacker.state.has_bonus_knockback = false;
-
-        victim.client.trigger_status(EntityStatus::PlayAttackSound);
-        victim.statuses.trigger(EntityStatus::PlayAttackSound);
-
-        let stack = attacker.inventory.slot(attacker.held_item.slot());
-
-        let damage = match stack.item {
-            ItemKind::WoodenSword => 4.0,
-            ItemKind::StoneSword => 5.0,
-            ItemKind::IronSword => 6.0,
-            ItemKind::DiamondSword => 7.0,
-            _ => 1.0,
-        };
-
-        victim.health.0 -= damage;
     }
 }
 
@@ -1015,32 +913,6 @@ fn teleport_oob_clients(mut clients: Query<(&mut Position, &Team), With<Client>>
     }
 }
 
-/// Handles respawning dead players.
-fn necromancy(
-    mut clients: Query<(
-        &mut VisibleChunkLayer,
-        &mut RespawnPosition,
-        &Team,
-        &mut Health,
-    )>,
-    mut events: EventReader<RequestRespawnEvent>,
-    layers: Query<Entity, (With<ChunkLayer>, With<EntityLayer>)>,
-) {
-    for event in events.read() {
-        if let Ok((mut visible_chunk_layer, mut respawn_pos, team, mut health)) =
-            clients.get_mut(event.client)
-        {
-            respawn_pos.pos = team.spawn_pos().into();
-            health.0 = PLAYER_MAX_HEALTH;
-
-            let main_layer = layers.single();
-
-            // this gets the client to get rid of the respawn screen
-            visible_chunk_layer.0 = main_layer;
-        }
-    }
-}
-
 fn update_scoreboard(
     mut objectives: Query<&mut ObjectiveScores, With<Objective>>,
     score: Res<Score>,
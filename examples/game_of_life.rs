@@ -172,14 +172,12 @@ impl LifeBoard {
     }
 }
 
-fn toggle_cell_on_dig(mut events: EventReader<DiggingEvent>, mut board: ResMut<LifeBoard>) {
+fn toggle_cell_on_dig(mut events: EventReader<BlockMinedEvent>, mut board: ResMut<LifeBoard>) {
     for event in events.read() {
-        if event.state == DiggingState::Start {
-            let (x, z) = (event.position.x, event.position.z);
+        let (x, z) = (event.position.x, event.position.z);
 
-            let live = board.get(x, z);
-            board.set(x, z, !live);
-        }
+        let live = board.get(x, z);
+        board.set(x, z, !live);
     }
 }
 
@@ -1,13 +1,13 @@
-//! This example shows how to use a read-only [`OpenInventory`] as a menu,
-//! in which the player is able to select items by clicking on them.
-//! This is commonly used on minigame servers (e.g for team selection).
+//! This example shows how to use a [`Menu`] in which the player is able to
+//! select items by clicking on them. This is commonly used on minigame servers
+//! (e.g for team selection).
 
 #![allow(clippy::type_complexity)]
 
 const SPAWN_Y: i32 = 64;
 
-use item_menu::{ItemMenu, ItemMenuPlugin, MenuItemSelectEvent};
 use valence::interact_item::InteractItemEvent;
+use valence::inventory::menu::{Menu, MenuButton, MenuClickEvent};
 use valence::prelude::*;
 use valence::protocol::sound::SoundCategory;
 use valence::protocol::Sound;
@@ -16,7 +16,6 @@ use valence_inventory::HeldItem;
 pub fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugins(ItemMenuPlugin)
         .add_systems(Startup, setup)
         .add_systems(
             Update,
@@ -53,8 +52,28 @@ fn setup(
     }
 
     commands.spawn(layer);
+
+    let menu = commands
+        .spawn((
+            Inventory::with_title(InventoryKind::Generic3x3, "Select a team"),
+            Menu::new(vec![
+                MenuButton::default(),
+                MenuButton::default(),
+                MenuButton::default(),
+                MenuButton::new(ItemStack::new(ItemKind::RedWool, 1, None)),
+                MenuButton::default(),
+                MenuButton::new(ItemStack::new(ItemKind::GreenWool, 1, None)),
+            ]),
+        ))
+        .id();
+
+    commands.insert_resource(TeamMenu(menu));
 }
 
+/// The menu opened with the compass. It is shared by all clients.
+#[derive(Resource)]
+struct TeamMenu(Entity);
+
 fn init_clients(
     mut clients: Query<
         (
@@ -93,39 +112,33 @@ fn init_clients(
 
 fn on_item_interact(
     mut commands: Commands,
-    clients: Query<(Entity, &HeldItem, &Inventory)>,
+    clients: Query<(&HeldItem, &Inventory)>,
+    team_menu: Res<TeamMenu>,
     mut events: EventReader<InteractItemEvent>,
 ) {
     for event in events.read() {
-        let Ok((player_ent, held_item, inventory)) = clients.get(event.client) else {
+        let Ok((held_item, inventory)) = clients.get(event.client) else {
             continue;
         };
         if *inventory.slot(held_item.slot()) == ItemStack::new(ItemKind::Compass, 1, None) {
-            open_menu(&mut commands, player_ent);
+            commands
+                .entity(event.client)
+                .insert(OpenInventory::new(team_menu.0));
         }
     }
 }
 
-fn open_menu(commands: &mut Commands, player: Entity) {
-    let mut menu_inv = Inventory::new(InventoryKind::Generic3x3);
-
-    menu_inv.set_slot(3, ItemStack::new(ItemKind::RedWool, 1, None));
-    menu_inv.set_slot(5, ItemStack::new(ItemKind::GreenWool, 1, None));
-
-    let menu = ItemMenu::new(menu_inv);
-    commands.entity(player).insert(menu);
-}
-
 fn on_make_selection(
+    mut commands: Commands,
     mut clients: Query<(&mut Client, &Position)>,
-    mut events: EventReader<MenuItemSelectEvent>,
+    mut events: EventReader<MenuClickEvent>,
 ) {
     for event in events.read() {
         let Ok((mut client, pos)) = clients.get_mut(event.client) else {
             continue;
         };
 
-        let selected_color = match event.idx {
+        let selected_color = match event.button {
             3 => "§cRED",
             5 => "§aGREEN",
             _ => continue,
@@ -139,90 +152,7 @@ fn on_make_selection(
             1.0,
         );
         client.send_chat_message(format!("you clicked: {selected_color}"));
-    }
-}
-
-mod item_menu {
-    use valence::prelude::*;
-    use valence_inventory::ClickSlotEvent;
-
-    pub(crate) struct ItemMenuPlugin;
 
-    impl Plugin for ItemMenuPlugin {
-        fn build(&self, app: &mut App) {
-            app.add_systems(Update, (open_menu, select_menu_item))
-                .add_event::<MenuItemSelectEvent>()
-                .observe(close_menu);
-        }
-    }
-
-    /// This event is fired when the player interacts with an item in the menu.
-    #[derive(Debug, Clone, PartialEq, Eq, Event)]
-    pub(crate) struct MenuItemSelectEvent {
-        /// Player entity
-        pub client: Entity,
-        /// Index of the item in the menu
-        pub idx: u16,
-    }
-
-    /// The [`ItemMenu`] is a component, so it will open up once you attach it
-    /// to a player and it will close once you remove it from the player (or
-    /// in this implementation also if the player closes it).
-    #[derive(Debug, Clone, Component)]
-    pub(crate) struct ItemMenu {
-        /// Item menu
-        pub menu: Inventory,
-    }
-
-    impl ItemMenu {
-        pub(crate) fn new(mut menu: Inventory) -> Self {
-            menu.readonly = true;
-            Self { menu }
-        }
-    }
-
-    fn open_menu(
-        mut commands: Commands,
-        mut clients: Query<(Entity, &mut ItemMenu), Added<ItemMenu>>,
-    ) {
-        for (player, item_menu) in &mut clients {
-            let inventory = commands.spawn(item_menu.menu.clone()).id();
-
-            commands
-                .entity(player)
-                .insert(OpenInventory::new(inventory));
-        }
-    }
-
-    fn close_menu(
-        _trigger: Trigger<OnRemove, OpenInventory>,
-        mut commands: Commands,
-        clients: Query<Entity, With<ItemMenu>>,
-    ) {
-        for player in clients.iter() {
-            commands.entity(player).remove::<ItemMenu>();
-        }
-    }
-
-    fn select_menu_item(
-        mut clients: Query<(Entity, &ItemMenu)>,
-        mut events: EventReader<ClickSlotEvent>,
-        mut event_writer: EventWriter<MenuItemSelectEvent>,
-    ) {
-        for event in events.read() {
-            let selected_slot = event.slot_id;
-            let Ok((player, item_menu)) = clients.get_mut(event.client) else {
-                continue;
-            };
-            // check that the selected item is not in the player's own inventory
-            if selected_slot as u16 >= item_menu.menu.slot_count() {
-                continue;
-            }
-
-            event_writer.send(MenuItemSelectEvent {
-                client: player,
-                idx: selected_slot as u16,
-            });
-        }
+        commands.entity(event.client).remove::<OpenInventory>();
     }
 }
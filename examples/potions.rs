@@ -5,44 +5,16 @@ use valence::entity::active_status_effects::{ActiveStatusEffect, ActiveStatusEff
 use valence::log::LogPlugin;
 use valence::network::ConnectionMode;
 use valence::prelude::*;
-use valence::status_effects::{AttributeModifier, StatusEffect};
-use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
-use valence_server::entity::entity::Flags;
-use valence_server::entity::living::{Absorption, Health};
-use valence_server::status_effect::{StatusEffectAdded, StatusEffectRemoved};
+use valence::status_effects::StatusEffect;
 
 const SPAWN_Y: i32 = 64;
 
 // Notes: Some potion effects are implemented by the client (i.e. we don't need
-// to send any more packets than just telling the client about them) and some
-// are implemented by the server. The ones implemented by the client are:
-// - Jump Boost
-// - Night Vision
-// - Nausea
-// - Blindness
-// - Darkness
-// - Slow Falling
-// - Levitation
-// Perhaps also (haven't tested):
-// - Dolphin's Grace
-// - Conduit Power
-//
-// There are also a few different potion effects that are implemented by the
-// server. Some can be implemented right now, for example:
-// - Speed
-// - Instant Health
-// - Regeneration
-// - Absorption
-// - Glowing
-// - etc. (i.e. the ones with AttributeModifiers, direct health changes or other
-//   trivial effects)
-//
-// Some can't be implemented right now because they require features that aren't
-// implemented yet or must be implemented yourself, for example:
-// - Water Breathing (requires the ability to breathe underwater)
-// - Fire Resistance (requires the ability to not take damage from fire)
-// - Hunger (requires the ability to get hungry)
-// - Bad Omen (requires the ability to get a raid)
+// to send any more packets than just telling the client about them), like Jump
+// Boost, Night Vision or Levitation. The effects with server-side logic, like
+// Instant Health, Regeneration, Absorption or the ones with attribute
+// modifiers, are handled by `EffectBehaviorPlugin`, which is part of the
+// default plugins.
 fn main() {
     App::new()
         .insert_resource(NetworkSettings {
@@ -51,22 +23,8 @@ fn main() {
         })
         .add_plugins(DefaultPlugins.build().disable::<LogPlugin>())
         .add_systems(Startup, setup)
-        .add_systems(
-            EventLoopUpdate,
-            (
-                add_potion_effect,
-                handle_status_effect_added,
-                handle_status_effect_removed,
-            ),
-        )
-        .add_systems(
-            Update,
-            (
-                init_clients,
-                despawn_disconnected_clients,
-                handle_status_effect_update,
-            ),
-        )
+        .add_systems(EventLoopUpdate, add_potion_effect)
+        .add_systems(Update, (init_clients, despawn_disconnected_clients))
         .run();
 }
 
@@ -129,7 +87,6 @@ fn init_clients(
 
         client.send_chat_message("Welcome to the potions example.".bold());
         client.send_chat_message("Sneak to apply a random potion effect.".into_text());
-        client.send_chat_message("Note: Some potion effects are not implemented yet.".into_text());
     }
 }
 
@@ -150,185 +107,3 @@ pub fn add_potion_effect(
         }
     }
 }
-
-fn adjust_modifier_amount(amplifier: u8, amount: f64) -> f64 {
-    amount * f64::from(amplifier + 1)
-}
-
-fn apply_potion_attribute(
-    attributes: &mut Mut<EntityAttributes>,
-    health: &mut Option<Mut<Health>>,
-    amplifier: u8,
-    attr: AttributeModifier,
-) {
-    attributes.remove_modifier(attr.attribute, attr.uuid);
-
-    let amount = adjust_modifier_amount(amplifier, attr.value);
-
-    attributes.set_modifier(attr.attribute, attr.uuid, amount, attr.operation);
-
-    // not quite how vanilla does it, but it's close enough
-    if attr.attribute == EntityAttribute::GenericMaxHealth {
-        if let Some(ref mut health) = health {
-            health.0 = health.0.min(
-                attributes
-                    .get_compute_value(EntityAttribute::GenericMaxHealth)
-                    .unwrap_or(0.0) as f32,
-            );
-        }
-    }
-}
-
-fn remove_potion_attribute(
-    attributes: &mut Mut<EntityAttributes>,
-    health: &mut Option<Mut<Health>>,
-    attr: AttributeModifier,
-) {
-    attributes.remove_modifier(attr.attribute, attr.uuid);
-
-    if attr.attribute == EntityAttribute::GenericMaxHealth {
-        if let Some(ref mut health) = health {
-            health.0 = health.0.min(
-                attributes
-                    .get_compute_value(EntityAttribute::GenericMaxHealth)
-                    .unwrap_or(0.0) as f32,
-            );
-        }
-    }
-}
-
-#[allow(clippy::type_complexity)]
-pub fn handle_status_effect_added(
-    mut clients: Query<(
-        &ActiveStatusEffects,
-        &mut EntityAttributes,
-        Option<&mut Health>,
-        Option<&mut Absorption>,
-        &mut Flags,
-    )>,
-    mut events: EventReader<StatusEffectAdded>,
-) {
-    for event in events.read() {
-        if let Ok((status, mut attributes, mut health, absorption, mut flags)) =
-            clients.get_mut(event.entity)
-        {
-            let effect = status.get_current_effect(event.status_effect).unwrap();
-
-            match event.status_effect {
-                StatusEffect::Absorption => {
-                    // not quite how vanilla does it. if you want to do it the vanilla way, you'll
-                    // need to keep track of the previous absorption value and subtract that from
-                    // the new value (because you can take damage while having absorption)
-                    if let Some(mut absorption) = absorption {
-                        absorption.0 += f32::from(effect.amplifier() + 1) * 4.0;
-                    }
-                }
-                StatusEffect::InstantHealth => {
-                    if let Some(mut health) = health {
-                        health.0 += (4 << effect.amplifier().min(31)) as f32;
-                    }
-                }
-                StatusEffect::InstantDamage => {
-                    if let Some(mut health) = health {
-                        health.0 -= (6 << effect.amplifier().min(31)) as f32;
-                    }
-                }
-                StatusEffect::Glowing => {
-                    flags.set_glowing(true);
-                }
-                StatusEffect::Invisibility => {
-                    flags.set_invisible(true);
-                }
-                status => {
-                    for attr in status.attribute_modifiers() {
-                        apply_potion_attribute(
-                            &mut attributes,
-                            &mut health,
-                            effect.amplifier(),
-                            attr,
-                        );
-                    }
-                }
-            }
-        }
-    }
-}
-
-pub fn handle_status_effect_removed(
-    mut clients: Query<(
-        &mut EntityAttributes,
-        Option<&mut Health>,
-        Option<&mut Absorption>,
-        &mut Flags,
-    )>,
-    mut events: EventReader<StatusEffectRemoved>,
-) {
-    for event in events.read() {
-        if let Ok((mut attributes, mut health, absorption, mut flags)) =
-            clients.get_mut(event.entity)
-        {
-            let effect = &event.status_effect;
-            match effect.status_effect() {
-                StatusEffect::Absorption => {
-                    if let Some(mut absorption) = absorption {
-                        absorption.0 -= f32::from(effect.amplifier() + 1) * 4.0;
-                    }
-                }
-                StatusEffect::Glowing => {
-                    flags.set_glowing(false);
-                }
-                StatusEffect::Invisibility => {
-                    flags.set_invisible(false);
-                }
-                status => {
-                    for attr in status.attribute_modifiers() {
-                        remove_potion_attribute(&mut attributes, &mut health, attr);
-                    }
-                }
-            }
-        }
-    }
-}
-
-pub fn handle_status_effect_update(
-    mut clients: Query<(&ActiveStatusEffects, &EntityAttributes, Option<&mut Health>)>,
-) {
-    for (status, attributes, mut health) in &mut clients.iter_mut() {
-        for effect in status.get_current_effects() {
-            match effect.status_effect() {
-                StatusEffect::Regeneration => {
-                    let i = 50 >> u32::from(effect.amplifier().min(31));
-
-                    if i == 0 || effect.active_ticks() % i == 0 {
-                        if let Some(ref mut health) = health {
-                            health.0 = (health.0 + 1.0).min(
-                                attributes
-                                    .get_compute_value(EntityAttribute::GenericMaxHealth)
-                                    .unwrap_or(0.0) as f32,
-                            );
-                        }
-                    }
-                }
-                StatusEffect::Poison => {
-                    let i = 25 >> u32::from(effect.amplifier().min(31));
-
-                    if i == 0 || effect.active_ticks() % i == 0 {
-                        if let Some(ref mut health) = health {
-                            health.0 = (health.0 - 1.0).max(1.0);
-                        }
-                    }
-                }
-                StatusEffect::Wither => {
-                    let i = 40 >> u32::from(effect.amplifier().min(31));
-
-                    if i == 0 || effect.active_ticks() % i == 0 {
-                        if let Some(ref mut health) = health {
-                            health.0 = (health.0 - 1.0).max(0.0);
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
-}
@@ -3,13 +3,12 @@ package rs.valence.extractor.extractors;
 import com.google.gson.JsonArray;
 import com.google.gson.JsonElement;
 import com.google.gson.JsonObject;
-import com.mojang.serialization.JsonOps;
 import net.minecraft.enchantment.Enchantment;
 import net.minecraft.registry.DynamicRegistryManager;
-import net.minecraft.registry.Registries;
 import net.minecraft.registry.RegistryKeys;
-import net.minecraft.registry.RegistryOps;
+import net.minecraft.registry.tag.EnchantmentTags;
 import net.minecraft.server.MinecraftServer;
+import net.minecraft.text.TranslatableTextContent;
 import rs.valence.extractor.Main;
 
 public class Enchants implements Main.Extractor {
@@ -26,13 +25,86 @@ public class Enchants implements Main.Extractor {
 
     @Override
     public JsonElement extract() {
-        var enchantsJson = new JsonObject();
+        var enchantsJson = new JsonArray();
+        var targetsJson = new JsonObject();
+        var registry = registryManager.get(RegistryKeys.ENCHANTMENT);
 
-        for (var enchant : registryManager.get(RegistryKeys.ENCHANTMENT).streamEntries().toList()) {
-            enchantsJson.add(enchant.getKey().orElseThrow().getValue().toString(), Enchantment.CODEC.encodeStart(RegistryOps.of(JsonOps.INSTANCE, registryManager), enchant.value()).getOrThrow());
+        for (var enchant : registry) {
+            var entry = registry.getEntry(enchant);
+            var target = target(enchant);
+            var enchantJson = new JsonObject();
 
+            enchantJson.addProperty("id", registry.getRawId(enchant));
+            enchantJson.addProperty("name", registry.getId(enchant).getPath());
+
+            if (enchant.description().getContent() instanceof TranslatableTextContent translatable) {
+                enchantJson.addProperty("translation_key", translatable.getKey());
+            }
+
+            enchantJson.addProperty("max_level", enchant.getMaxLevel());
+            enchantJson.addProperty("rarity", rarity(enchant));
+            enchantJson.addProperty("target", target);
+            enchantJson.addProperty("treasure", entry.isIn(EnchantmentTags.TREASURE));
+            enchantJson.addProperty("curse", entry.isIn(EnchantmentTags.CURSE));
+
+            var incompatibleJson = new JsonArray();
+
+            for (var other : enchant.exclusiveSet()) {
+                if (other.value() != enchant) {
+                    incompatibleJson.add(other.getKey().orElseThrow().getValue().getPath());
+                }
+            }
+
+            enchantJson.add("incompatible", incompatibleJson);
+
+            enchantsJson.add(enchantJson);
+
+            // The items of a target are the items the first enchantment with it accepts.
+            if (!targetsJson.has(target)) {
+                var itemsJson = new JsonArray();
+
+                for (var item : enchant.getApplicableItems()) {
+                    itemsJson.add(item.getKey().orElseThrow().getValue().getPath());
+                }
+
+                targetsJson.add(target, itemsJson);
+            }
         }
 
-        return enchantsJson;
+        var json = new JsonObject();
+        json.add("enchantments", enchantsJson);
+        json.add("targets", targetsJson);
+
+        return json;
+    }
+
+    private static String rarity(Enchantment enchant) {
+        return switch (enchant.getWeight()) {
+            case 10 -> "Common";
+            case 5 -> "Uncommon";
+            case 2 -> "Rare";
+            default -> "VeryRare";
+        };
+    }
+
+    private static String target(Enchantment enchant) {
+        var tag = enchant.getApplicableItems().getTagKey().orElseThrow().id().getPath();
+
+        return switch (tag) {
+            case "enchantable/armor" -> "Armor";
+            case "enchantable/foot_armor" -> "ArmorFeet";
+            case "enchantable/leg_armor" -> "ArmorLegs";
+            case "enchantable/chest_armor" -> "ArmorChest";
+            case "enchantable/head_armor" -> "ArmorHead";
+            case "enchantable/mining", "enchantable/mining_loot" -> "Digger";
+            case "enchantable/fishing" -> "FishingRod";
+            case "enchantable/trident" -> "Trident";
+            case "enchantable/durability" -> "Breakable";
+            case "enchantable/bow" -> "Bow";
+            case "enchantable/equippable" -> "Wearable";
+            case "enchantable/crossbow" -> "Crossbow";
+            case "enchantable/vanishing" -> "Vanishable";
+            default -> "Weapon";
+        };
     }
 }
@@ -4,17 +4,30 @@ import com.google.gson.JsonArray;
 import com.google.gson.JsonElement;
 import com.google.gson.JsonObject;
 import com.mojang.serialization.JsonOps;
+import java.util.Locale;
+import net.minecraft.block.entity.AbstractFurnaceBlockEntity;
 import net.minecraft.component.ComponentMap;
 import net.minecraft.component.DataComponentTypes;
 import net.minecraft.component.type.FoodComponent;
 import net.minecraft.enchantment.Enchantment;
+import net.minecraft.entity.EquipmentSlot;
+import net.minecraft.item.AxeItem;
+import net.minecraft.item.Equipment;
+import net.minecraft.item.HoeItem;
 import net.minecraft.item.Item;
 import net.minecraft.item.ItemStack;
+import net.minecraft.item.MiningToolItem;
+import net.minecraft.item.PickaxeItem;
+import net.minecraft.item.ShovelItem;
+import net.minecraft.item.ToolItem;
+import net.minecraft.item.ToolMaterials;
 import net.minecraft.registry.DynamicRegistryManager;
 import net.minecraft.registry.Registries;
 import net.minecraft.registry.RegistryKeys;
 import net.minecraft.registry.RegistryOps;
+import net.minecraft.registry.tag.ItemTags;
 import net.minecraft.server.MinecraftServer;
+import net.minecraft.util.Rarity;
 import rs.valence.extractor.Main;
 
 public class Items implements Main.Extractor {
@@ -32,6 +45,7 @@ public class Items implements Main.Extractor {
     @Override
     public JsonElement extract() throws Exception {
         var itemsJson = new JsonArray();
+        var fuelTimes = AbstractFurnaceBlockEntity.createFuelTimeMap();
 
         for (var item : registryManager.get(RegistryKeys.ITEM).streamEntries().toList()) {
             var itemJson = new JsonObject();
@@ -44,6 +58,51 @@ public class Items implements Main.Extractor {
             itemJson.addProperty("max_durability", realItem.getDefaultStack().getMaxDamage());
             itemJson.addProperty("enchantability", realItem.getEnchantability());
             itemJson.addProperty("fireproof", realItem.getComponents().contains(DataComponentTypes.FIRE_RESISTANT));
+            itemJson.addProperty("rarity", realItem.getComponents().getOrDefault(DataComponentTypes.RARITY, Rarity.COMMON).asString());
+
+            var equipment = Equipment.fromStack(realItem.getDefaultStack());
+            if (equipment != null && equipment.getSlotType().getType() == EquipmentSlot.Type.HUMANOID_ARMOR) {
+                itemJson.addProperty("armor_slot", equipment.getSlotType().getName());
+            }
+
+            if (realItem instanceof ToolItem toolItem && toolItem.getMaterial() instanceof ToolMaterials material) {
+                itemJson.addProperty("tool_tier", material.name().toLowerCase(Locale.ROOT));
+
+                if (realItem instanceof MiningToolItem) {
+                    itemJson.addProperty("mining_speed", material.getMiningSpeedMultiplier());
+                    itemJson.addProperty("mineable", mineableTag(realItem));
+                }
+            }
+
+            var fuelTime = fuelTimes.get(realItem);
+            if (fuelTime != null) {
+                itemJson.addProperty("fuel_time", fuelTime);
+            }
+
+            var food = realItem.getComponents().get(DataComponentTypes.FOOD);
+            if (food != null) {
+                var foodJson = new JsonObject();
+                foodJson.addProperty("hunger", food.nutrition());
+                // Stored as the saturation modifier like in older versions.
+                foodJson.addProperty("saturation", food.nutrition() == 0 ? 0 : food.saturation() / (food.nutrition() * 2.0f));
+                foodJson.addProperty("always_edible", food.canAlwaysEat());
+                foodJson.addProperty("meat", item.isIn(ItemTags.MEAT));
+                foodJson.addProperty("snack", food.eatSeconds() < 1.6f);
+
+                var effectsJson = new JsonArray();
+                for (var entry : food.effects()) {
+                    var effect = entry.effect();
+                    var effectJson = new JsonObject();
+                    effectJson.addProperty("id", effect.getEffectType().getKey().orElseThrow().getValue().getPath());
+                    effectJson.addProperty("duration", effect.getDuration());
+                    effectJson.addProperty("amplifier", effect.getAmplifier());
+                    effectJson.addProperty("chance", entry.probability());
+                    effectsJson.add(effectJson);
+                }
+                foodJson.add("effects", effectsJson);
+
+                itemJson.add("food", foodJson);
+            }
 
             itemJson.add("components", ComponentMap.CODEC.encodeStart(RegistryOps.of(JsonOps.INSTANCE, registryManager), realItem.getComponents()).getOrThrow());
 
@@ -51,4 +110,18 @@ public class Items implements Main.Extractor {
         }
         return itemsJson;
     }
+
+    private static String mineableTag(Item item) {
+        if (item instanceof PickaxeItem) {
+            return "minecraft:mineable/pickaxe";
+        } else if (item instanceof AxeItem) {
+            return "minecraft:mineable/axe";
+        } else if (item instanceof ShovelItem) {
+            return "minecraft:mineable/shovel";
+        } else if (item instanceof HoeItem) {
+            return "minecraft:mineable/hoe";
+        }
+
+        throw new IllegalArgumentException("unknown mining tool " + item);
+    }
 }
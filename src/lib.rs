@@ -36,6 +36,7 @@ mod tests;
 #[cfg(feature = "log")]
 pub use bevy_log as log;
 use registry::biome::BiomePlugin;
+use registry::damage_type::DamageTypePlugin;
 use registry::dimension_type::DimensionTypePlugin;
 #[cfg(feature = "advancement")]
 pub use valence_advancement as advancement;
@@ -43,24 +44,35 @@ pub use valence_advancement as advancement;
 pub use valence_anvil as anvil;
 #[cfg(feature = "boss_bar")]
 pub use valence_boss_bar as boss_bar;
+#[cfg(feature = "combat")]
+pub use valence_combat as combat;
 #[cfg(feature = "command")]
 pub use valence_command as command;
 #[cfg(feature = "command")]
 pub use valence_command_macros as command_macros;
+#[cfg(feature = "digging")]
+pub use valence_digging as digging;
 #[cfg(feature = "equipment")]
 pub use valence_equipment as equipment;
+#[cfg(feature = "health")]
+pub use valence_health as health;
 #[cfg(feature = "inventory")]
 pub use valence_inventory as inventory;
 pub use valence_lang as lang;
+#[cfg(feature = "map")]
+pub use valence_map as map;
 #[cfg(feature = "network")]
 pub use valence_network as network;
 #[cfg(feature = "player_list")]
 pub use valence_player_list as player_list;
+#[cfg(feature = "recipe")]
+pub use valence_recipe as recipe;
 use valence_registry::RegistryPlugin;
 #[cfg(feature = "scoreboard")]
 pub use valence_scoreboard as scoreboard;
 use valence_server::abilities::AbilitiesPlugin;
 use valence_server::action::ActionPlugin;
+use valence_server::camera::CameraPlugin;
 use valence_server::client::ClientPlugin;
 use valence_server::client_command::ClientCommandPlugin;
 use valence_server::client_settings::ClientSettingsPlugin;
@@ -72,6 +84,7 @@ use valence_server::hand_swing::HandSwingPlugin;
 use valence_server::interact_block::InteractBlockPlugin;
 use valence_server::interact_entity::InteractEntityPlugin;
 use valence_server::interact_item::InteractItemPlugin;
+use valence_server::item_cooldown::ItemCooldownPlugin;
 use valence_server::keepalive::KeepalivePlugin;
 use valence_server::layer::LayerPlugin;
 use valence_server::message::MessagePlugin;
@@ -79,14 +92,18 @@ use valence_server::movement::MovementPlugin;
 use valence_server::op_level::OpLevelPlugin;
 pub use valence_server::protocol::status_effects;
 use valence_server::resource_pack::ResourcePackPlugin;
+use valence_server::sign::SignPlugin;
 use valence_server::status::StatusPlugin;
 use valence_server::status_effect::StatusEffectPlugin;
 use valence_server::teleport::TeleportPlugin;
+use valence_server::vehicle::VehiclePlugin;
 pub use valence_server::*;
 #[cfg(feature = "weather")]
 pub use valence_weather as weather;
 #[cfg(feature = "world_border")]
 pub use valence_world_border as world_border;
+#[cfg(feature = "world_time")]
+pub use valence_world_time as world_time;
 
 /// Contains the most frequently used items in Valence projects.
 ///
@@ -110,12 +127,20 @@ pub mod prelude {
         event::AdvancementTabChangeEvent, Advancement, AdvancementBundle, AdvancementClientUpdate,
         AdvancementCriteria, AdvancementDisplay, AdvancementFrameType, AdvancementRequirements,
     };
+    #[cfg(feature = "combat")]
+    pub use valence_combat::{AttackEvent, CombatSettings};
+    #[cfg(feature = "digging")]
+    pub use valence_digging::{BlockMinedEvent, DiggingSettings};
     #[cfg(feature = "equipment")]
     pub use valence_equipment::Equipment;
+    #[cfg(feature = "health")]
+    pub use valence_health::{DamageEvent, DamageSource, DeathEvent, HealthSettings};
     #[cfg(feature = "inventory")]
     pub use valence_inventory::{
         CursorItem, Inventory, InventoryKind, InventoryWindow, InventoryWindowMut, OpenInventory,
     };
+    #[cfg(feature = "map")]
+    pub use valence_map::{MapBundle, MapCanvas, MapColor, MapId};
     #[cfg(feature = "network")]
     pub use valence_network::{
         ConnectionMode, ErasedNetworkCallbacks, NetworkCallbacks, NetworkSettings, NewClientInfo,
@@ -123,7 +148,10 @@ pub mod prelude {
     };
     #[cfg(feature = "player_list")]
     pub use valence_player_list::{PlayerList, PlayerListEntry};
+    #[cfg(feature = "recipe")]
+    pub use valence_recipe::{RecipeBook, RecipeRegistry};
     pub use valence_registry::biome::{Biome, BiomeId, BiomeRegistry};
+    pub use valence_registry::damage_type::{DamageType, DamageTypeRegistry};
     pub use valence_registry::dimension_type::{DimensionType, DimensionTypeRegistry};
     pub use valence_server::action::{DiggingEvent, DiggingState};
     pub use valence_server::block::{BlockKind, BlockState, PropName, PropValue};
@@ -160,6 +188,8 @@ pub mod prelude {
         ident, BlockPos, ChunkPos, ChunkView, Despawned, Direction, GameMode, Hand, ItemKind,
         ItemStack, Server, UniqueId,
     };
+    #[cfg(feature = "world_time")]
+    pub use valence_world_time::WorldTime;
 
     pub use super::DefaultPlugins;
 }
@@ -170,6 +200,14 @@ pub mod prelude {
 /// [`DefaultPlugins`] obeys Cargo feature flags. Users may exert control over
 /// this plugin group by disabling `default-features` in their `Cargo.toml` and
 /// enabling only those features that they wish to use.
+///
+/// With the default `health` and `combat` features, damage, death, respawning,
+/// status effects and melee combat are handled by the server. Applications
+/// with their own implementation should disable those plugins, e.g. with
+/// `DefaultPlugins.build().disable::<CombatPlugin>()`, instead of changing
+/// [`Health`] next to them.
+///
+/// [`Health`]: valence_server::entity::living::Health
 pub struct DefaultPlugins;
 
 impl PluginGroup for DefaultPlugins {
@@ -180,6 +218,7 @@ impl PluginGroup for DefaultPlugins {
             .add(RegistryPlugin)
             .add(BiomePlugin)
             .add(DimensionTypePlugin)
+            .add(DamageTypePlugin)
             .add(EntityPlugin)
             .add(HitboxPlugin)
             .add(LayerPlugin)
@@ -197,10 +236,14 @@ impl PluginGroup for DefaultPlugins {
             .add(HandSwingPlugin)
             .add(InteractBlockPlugin)
             .add(InteractItemPlugin)
+            .add(ItemCooldownPlugin)
             .add(OpLevelPlugin)
             .add(ResourcePackPlugin)
+            .add(SignPlugin)
             .add(StatusPlugin)
             .add(StatusEffectPlugin)
+            .add(VehiclePlugin)
+            .add(CameraPlugin)
             .add(AbilitiesPlugin);
 
         #[cfg(feature = "log")]
@@ -248,6 +291,38 @@ impl PluginGroup for DefaultPlugins {
             group = group.add(valence_world_border::WorldBorderPlugin)
         }
 
+        #[cfg(feature = "world_time")]
+        {
+            group = group.add(valence_world_time::WorldTimePlugin)
+        }
+
+        #[cfg(feature = "health")]
+        {
+            group = group
+                .add(valence_health::HealthPlugin)
+                .add(valence_health::EffectBehaviorPlugin)
+        }
+
+        #[cfg(feature = "combat")]
+        {
+            group = group.add(valence_combat::CombatPlugin)
+        }
+
+        #[cfg(feature = "digging")]
+        {
+            group = group.add(valence_digging::DiggingPlugin)
+        }
+
+        #[cfg(feature = "map")]
+        {
+            group = group.add(valence_map::MapPlugin)
+        }
+
+        #[cfg(feature = "recipe")]
+        {
+            group = group.add(valence_recipe::RecipePlugin)
+        }
+
         #[cfg(feature = "boss_bar")]
         {
             group = group.add(valence_boss_bar::BossBarPlugin)
@@ -11,7 +11,13 @@ use valence_ident::ident;
 use valence_network::NetworkPlugin;
 use valence_registry::{BiomeRegistry, DimensionTypeRegistry};
 use valence_server::client::{ClientBundle, ClientBundleArgs, ClientConnection, ReceivedPacket};
+use valence_server::entity::living::Health;
+use valence_server::entity::pig::PigEntityBundle;
+use valence_server::entity::{EntityLayerId, Position};
+use valence_server::interaction::InteractionViolationEvent;
 use valence_server::keepalive::KeepaliveSettings;
+use valence_server::math::DVec3;
+use valence_server::movement::MovementViolationEvent;
 use valence_server::protocol::decode::PacketFrame;
 use valence_server::protocol::packets::play::{PlayerPositionLookS2c, TeleportConfirmC2s};
 use valence_server::protocol::{Decode, Encode, Packet, PacketDecoder, PacketEncoder, VarInt};
@@ -72,12 +78,109 @@ impl ScenarioSingleClient {
     }
 }
 
+impl ScenarioSingleClient {
+    /// Like [`new`](Self::new), but also runs the first update, clears the
+    /// packets received so far and gives the client 20 health.
+    pub fn with_full_health() -> Self {
+        let mut scenario = Self::new();
+
+        scenario.app.update();
+        scenario.helper.clear_received();
+
+        scenario
+            .app
+            .world_mut()
+            .get_mut::<Health>(scenario.client)
+            .unwrap()
+            .0 = 20.0;
+
+        scenario
+    }
+
+    /// Returns the events of type `E` that haven't been dropped yet.
+    pub fn read_events<E: Event + Clone>(&self) -> Vec<E> {
+        let events = self.app.world().resource::<Events<E>>();
+        events.get_reader().read(events).cloned().collect()
+    }
+
+    /// Returns the violations reported by events of type `E` during the last
+    /// update.
+    pub fn violations<E: ViolationEvent>(&self) -> Vec<E::Violation> {
+        self.app
+            .world()
+            .resource::<Events<E>>()
+            .iter_current_update_events()
+            .map(ViolationEvent::violation)
+            .collect()
+    }
+
+    /// Spawns a pig with the components in `extra` in the scenario's layer.
+    /// The pig is sent to the client before this returns, and the packets
+    /// received so far are cleared.
+    pub fn spawn_pig<P: Into<DVec3>, B: Bundle>(&mut self, pos: P, extra: B) -> Entity {
+        let pig = self
+            .app
+            .world_mut()
+            .spawn((
+                PigEntityBundle {
+                    layer: EntityLayerId(self.layer),
+                    position: Position(pos.into()),
+                    ..Default::default()
+                },
+                extra,
+            ))
+            .id();
+
+        self.app.update();
+        self.helper.clear_received();
+
+        pig
+    }
+}
+
+/// Returns the health of a living entity.
+pub fn health(app: &App, entity: Entity) -> f32 {
+    app.world().get::<Health>(entity).unwrap().0
+}
+
 impl Default for ScenarioSingleClient {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Events sent when a client breaks one of the rules checked by the server.
+pub trait ViolationEvent: Event {
+    type Violation;
+
+    fn violation(&self) -> Self::Violation;
+}
+
+impl ViolationEvent for MovementViolationEvent {
+    type Violation = valence_server::movement::MovementViolation;
+
+    fn violation(&self) -> Self::Violation {
+        self.violation
+    }
+}
+
+impl ViolationEvent for InteractionViolationEvent {
+    type Violation = valence_server::interaction::InteractionViolation;
+
+    fn violation(&self) -> Self::Violation {
+        self.violation
+    }
+}
+
+#[cfg(feature = "digging")]
+impl ViolationEvent for valence_digging::DiggingViolationEvent {
+    type Violation = valence_digging::DiggingViolation;
+
+    fn violation(&self) -> Self::Violation {
+        self.violation
+    }
+}
+
 /// Creates a mock client bundle that can be used for unit testing.
 ///
 /// Returns the client, and a helper to inject packets as if the client sent
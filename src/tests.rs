@@ -1,12 +1,30 @@
+mod book;
 mod boss_bar;
+mod camera;
 mod client;
+mod combat;
+mod digging;
+mod effects;
 mod equipment;
 mod example;
+mod health;
 mod hunger;
+mod interaction;
 mod inventory;
+mod item_cooldown;
 mod layer;
+mod map;
+mod menu;
+mod merchant;
+mod movement;
 mod player_list;
 mod potions;
+mod recipe;
 mod scoreboard;
+mod screen;
+mod sign;
+mod transaction;
+mod vehicle;
 mod weather;
 mod world_border;
+mod world_time;
@@ -0,0 +1,145 @@
+use crate::inventory::book::{BookEditEvent, OpenBookEvent, WrittenBook};
+use crate::inventory::Inventory;
+use crate::protocol::packets::play::{
+    BookUpdateC2s, OpenWrittenBookS2c, ScreenHandlerSlotUpdateS2c,
+};
+use crate::protocol::{Packet, VarInt};
+use crate::testing::ScenarioSingleClient;
+use crate::{ItemKind, ItemStack};
+
+fn give_book_and_quill(scenario: &mut ScenarioSingleClient) {
+    scenario.app.update();
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::WritableBook, 1, None));
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+}
+
+#[test]
+fn open_book_restores_held_item() {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario.app.world_mut().send_event(OpenBookEvent {
+        client: scenario.client,
+        book: WrittenBook::new(vec!["Hello".into()]),
+    });
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<OpenWrittenBookS2c>(1);
+    frames.assert_count::<ScreenHandlerSlotUpdateS2c>(2);
+    // The book is only in the client's hand while it is opened.
+    let ids: Vec<_> = frames.0.iter().map(|frame| frame.id).collect();
+    assert_eq!(
+        ids,
+        [
+            ScreenHandlerSlotUpdateS2c::ID,
+            OpenWrittenBookS2c::ID,
+            ScreenHandlerSlotUpdateS2c::ID
+        ]
+    );
+
+    let book = frames.first::<ScreenHandlerSlotUpdateS2c>();
+    assert_eq!(book.slot_idx, 36);
+    assert_eq!(book.slot_data.item, ItemKind::WrittenBook);
+
+    // The client's inventory is unchanged.
+    let inventory = scenario
+        .app
+        .world()
+        .get::<Inventory>(scenario.client)
+        .unwrap();
+    assert!(inventory.slot(36).is_empty());
+}
+
+#[test]
+fn editing_book_updates_pages() {
+    let mut scenario = ScenarioSingleClient::new();
+    give_book_and_quill(&mut scenario);
+
+    scenario.helper.send(&BookUpdateC2s {
+        slot: VarInt(0),
+        entries: vec!["first".into(), "second".into()].into(),
+        title: None,
+    });
+    scenario.app.update();
+
+    let events = scenario.read_events::<BookEditEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].slot, 36);
+    assert_eq!(events[0].pages, ["first", "second"]);
+
+    let inventory = scenario
+        .app
+        .world()
+        .get::<Inventory>(scenario.client)
+        .unwrap();
+    let book = inventory.slot(36);
+    assert_eq!(book.item, ItemKind::WritableBook);
+    assert!(book.nbt.as_ref().unwrap().contains_key("pages"));
+
+    // The client already has the edited book.
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<ScreenHandlerSlotUpdateS2c>(0);
+}
+
+#[test]
+fn signing_book_makes_written_book() {
+    let mut scenario = ScenarioSingleClient::new();
+    give_book_and_quill(&mut scenario);
+
+    scenario.helper.send(&BookUpdateC2s {
+        slot: VarInt(0),
+        entries: vec!["The end".into()].into(),
+        title: Some("Story".into()),
+    });
+    scenario.app.update();
+
+    let events = scenario.read_events::<BookEditEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].title.as_deref(), Some("Story"));
+
+    let inventory = scenario
+        .app
+        .world()
+        .get::<Inventory>(scenario.client)
+        .unwrap();
+    let book = inventory.slot(36);
+    assert_eq!(book.item, ItemKind::WrittenBook);
+    assert!(book.nbt.as_ref().unwrap().contains_key("author"));
+}
+
+#[test]
+fn invalid_edit_is_rejected() {
+    let mut scenario = ScenarioSingleClient::new();
+    give_book_and_quill(&mut scenario);
+
+    // Too long for the default settings.
+    let page = "a".repeat(2000);
+
+    scenario.helper.send(&BookUpdateC2s {
+        slot: VarInt(0),
+        entries: vec![page.as_str().into()].into(),
+        title: None,
+    });
+    scenario.app.update();
+
+    assert!(scenario.read_events::<BookEditEvent>().is_empty());
+
+    // The client's copy of the book is reverted.
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<ScreenHandlerSlotUpdateS2c>(1);
+}
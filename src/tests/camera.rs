@@ -0,0 +1,237 @@
+use bevy_ecs::prelude::*;
+use valence_server::camera::CameraTarget;
+use valence_server::client::{VisibleChunkLayer, VisibleEntityLayers};
+use valence_server::entity::{EntityId, Position};
+use valence_server::interact_entity::EntityInteraction;
+use valence_server::math::DVec3;
+use valence_server::protocol::packets::play::client_command_c2s::ClientCommand;
+use valence_server::protocol::packets::play::{
+    ClientCommandC2s, PlayerInteractEntityC2s, PlayerPositionLookS2c, SetCameraEntityS2c,
+    SpectatorTeleportC2s,
+};
+use valence_server::protocol::VarInt;
+use valence_server::{Despawned, GameMode, UniqueId};
+
+use crate::testing::ScenarioSingleClient;
+
+fn camera_id(scenario: &mut ScenarioSingleClient) -> Option<i32> {
+    scenario
+        .helper
+        .collect_received()
+        .0
+        .iter()
+        .rev()
+        .find_map(|frame| frame.decode::<SetCameraEntityS2c>().ok())
+        .map(|pkt| pkt.entity_id.0)
+}
+
+#[test]
+fn camera_target_sets_camera() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([1.0, 0.0, 0.0], ());
+    let pig_id = scenario.app.world().get::<EntityId>(pig).unwrap().get();
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(CameraTarget::new(pig));
+    scenario.app.update();
+
+    assert_eq!(camera_id(&mut scenario), Some(pig_id));
+
+    // Nothing is sent while the target doesn't change.
+    scenario.app.update();
+
+    assert_eq!(camera_id(&mut scenario), None);
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .remove::<CameraTarget>();
+    scenario.app.update();
+
+    // The client gets its own view back.
+    assert_eq!(camera_id(&mut scenario), Some(0));
+}
+
+#[test]
+fn camera_is_restored_when_target_is_reloaded() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([1.0, 0.0, 0.0], ());
+    let pig_id = scenario.app.world().get::<EntityId>(pig).unwrap().get();
+    let layer = scenario.layer;
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(CameraTarget::new(pig));
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<VisibleEntityLayers>(scenario.client)
+        .unwrap()
+        .0
+        .clear();
+    scenario.app.update();
+
+    assert_eq!(camera_id(&mut scenario), None);
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<VisibleEntityLayers>(scenario.client)
+        .unwrap()
+        .0
+        .insert(layer);
+    scenario.app.update();
+
+    assert_eq!(camera_id(&mut scenario), Some(pig_id));
+}
+
+#[test]
+fn camera_is_restored_on_respawn() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([1.0, 0.0, 0.0], ());
+    let pig_id = scenario.app.world().get::<EntityId>(pig).unwrap().get();
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(CameraTarget::new(pig));
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<VisibleChunkLayer>(scenario.client)
+        .unwrap()
+        .set_changed();
+    scenario.app.update();
+
+    assert_eq!(camera_id(&mut scenario), Some(pig_id));
+}
+
+#[test]
+fn despawning_target_releases_camera() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([1.0, 0.0, 0.0], ());
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(CameraTarget::new(pig));
+    scenario.app.update();
+
+    scenario.app.world_mut().entity_mut(pig).insert(Despawned);
+    scenario.app.update();
+
+    assert!(scenario
+        .app
+        .world()
+        .get::<CameraTarget>(scenario.client)
+        .is_none());
+    assert_eq!(camera_id(&mut scenario), Some(0));
+}
+
+#[test]
+fn spectator_follows_target_and_releases_on_sneak() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([10.0, 0.0, 0.0], ());
+    let pig_id = scenario.app.world().get::<EntityId>(pig).unwrap().get();
+
+    *scenario
+        .app
+        .world_mut()
+        .get_mut::<GameMode>(scenario.client)
+        .unwrap() = GameMode::Spectator;
+    scenario.app.update();
+
+    // Attacking an entity as a spectator spectates it.
+    scenario.helper.send(&PlayerInteractEntityC2s {
+        entity_id: VarInt(pig_id),
+        interact: EntityInteraction::Attack,
+        sneaking: false,
+    });
+    scenario.app.update();
+
+    assert_eq!(
+        scenario.app.world().get::<CameraTarget>(scenario.client),
+        Some(&CameraTarget::spectate(pig))
+    );
+
+    scenario.app.update();
+
+    // The client is moved along without being teleported.
+    assert_eq!(
+        scenario
+            .app
+            .world()
+            .get::<Position>(scenario.client)
+            .unwrap()
+            .0,
+        DVec3::new(10.0, 0.0, 0.0)
+    );
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(0);
+
+    scenario.helper.send(&ClientCommandC2s {
+        entity_id: VarInt(0),
+        action: ClientCommand::StartSneaking,
+        jump_boost: VarInt(0),
+    });
+    scenario.app.update();
+
+    assert!(scenario
+        .app
+        .world()
+        .get::<CameraTarget>(scenario.client)
+        .is_none());
+    assert_eq!(camera_id(&mut scenario), Some(0));
+}
+
+#[test]
+fn spectator_teleport() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([5.0, 2.0, 5.0], ());
+    let uuid = scenario.app.world().get::<UniqueId>(pig).unwrap().0;
+
+    let teleport = |scenario: &mut ScenarioSingleClient| {
+        scenario.helper.send(&SpectatorTeleportC2s { target: uuid });
+        scenario.app.update();
+
+        scenario
+            .app
+            .world()
+            .get::<Position>(scenario.client)
+            .unwrap()
+            .0
+    };
+
+    // Only spectators can teleport.
+    assert_ne!(teleport(&mut scenario), DVec3::new(5.0, 2.0, 5.0));
+
+    *scenario
+        .app
+        .world_mut()
+        .get_mut::<GameMode>(scenario.client)
+        .unwrap() = GameMode::Spectator;
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    assert_eq!(teleport(&mut scenario), DVec3::new(5.0, 2.0, 5.0));
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(1);
+}
@@ -0,0 +1,231 @@
+use bevy_ecs::prelude::*;
+use valence_inventory::Inventory;
+use valence_server::enchantment::Enchantment;
+use valence_server::entity::living::Health;
+use valence_server::entity::{EntityId, Velocity};
+use valence_server::interact_entity::EntityInteraction;
+use valence_server::protocol::packets::play::PlayerInteractEntityC2s;
+use valence_server::protocol::VarInt;
+use valence_server::{ItemKind, ItemStack};
+
+use crate::combat::{AttackEvent, CancelAttackEvent, CombatProxy, CombatSettings};
+use crate::health::FireTicks;
+use crate::testing::{health, MockClientHelper, ScenarioSingleClient};
+
+/// Spawns a pig next to the client with plenty of health and waits for the
+/// client's attack cooldown to recharge.
+fn spawn_target(scenario: &mut ScenarioSingleClient, pos: [f64; 3]) -> Entity {
+    let pig = scenario.spawn_pig(pos, ());
+    scenario.app.world_mut().get_mut::<Health>(pig).unwrap().0 = 100.0;
+
+    for _ in 0..20 {
+        scenario.app.update();
+    }
+
+    scenario.helper.clear_received();
+
+    pig
+}
+
+fn attack(app: &mut bevy_app::App, helper: &mut MockClientHelper, target: Entity) {
+    let entity_id = app.world().get::<EntityId>(target).unwrap().get();
+
+    helper.send(&PlayerInteractEntityC2s {
+        entity_id: VarInt(entity_id),
+        interact: EntityInteraction::Attack,
+        sneaking: false,
+    });
+
+    app.update();
+}
+
+#[test]
+fn attack_damages_and_knocks_back_target() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = spawn_target(&mut scenario, [1.0, 0.0, 0.0]);
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = scenario;
+
+    attack(&mut app, &mut helper, pig);
+
+    assert_eq!(health(&app, pig), 99.0);
+
+    let velocity = app.world().get::<Velocity>(pig).unwrap().0;
+    assert!(velocity.x > 0.0, "pig was not knocked away: {velocity}");
+}
+
+#[test]
+fn attack_out_of_reach_is_ignored() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = spawn_target(&mut scenario, [10.0, 0.0, 0.0]);
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = scenario;
+
+    attack(&mut app, &mut helper, pig);
+
+    assert_eq!(health(&app, pig), 100.0);
+}
+
+#[test]
+fn cancelled_attack_deals_no_damage() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = spawn_target(&mut scenario, [1.0, 0.0, 0.0]);
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = scenario;
+
+    app.add_systems(
+        bevy_app::Update,
+        |mut attacks: EventReader<AttackEvent>, mut cancel: EventWriter<CancelAttackEvent>| {
+            for attack in attacks.read() {
+                cancel.send(CancelAttackEvent {
+                    attacker: attack.attacker,
+                    target: attack.target,
+                });
+            }
+        },
+    );
+
+    attack(&mut app, &mut helper, pig);
+
+    assert_eq!(health(&app, pig), 100.0);
+    assert_eq!(app.world().get::<Velocity>(pig).unwrap().0.x, 0.0);
+}
+
+#[test]
+fn attacking_proxy_damages_its_entity() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = spawn_target(&mut scenario, [50.0, 0.0, 0.0]);
+    let proxy = spawn_target(&mut scenario, [1.0, 0.0, 0.0]);
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(proxy)
+        .insert(CombatProxy(pig));
+
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = scenario;
+
+    attack(&mut app, &mut helper, proxy);
+
+    assert_eq!(health(&app, pig), 99.0);
+    assert_eq!(health(&app, proxy), 100.0);
+}
+
+fn hit_twice_with_sword(settings: CombatSettings) -> f32 {
+    let mut scenario = ScenarioSingleClient::new();
+    scenario.app.insert_resource(settings);
+
+    let pig = spawn_target(&mut scenario, [1.0, 0.0, 0.0]);
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::DiamondSword, 1, None));
+
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = scenario;
+
+    attack(&mut app, &mut helper, pig);
+    assert_eq!(health(&app, pig), 93.0);
+
+    // Wait for the invulnerability ticks to run out.
+    for _ in 0..10 {
+        app.update();
+    }
+
+    attack(&mut app, &mut helper, pig);
+
+    93.0 - health(&app, pig)
+}
+
+#[test]
+fn attack_cooldown_weakens_attacks() {
+    let damage = hit_twice_with_sword(CombatSettings::default());
+
+    // A diamond sword takes 12.5 ticks to recharge.
+    assert!(damage > 1.4 && damage < 7.0, "damage was {damage}");
+}
+
+#[test]
+fn legacy_combat_has_no_cooldown() {
+    let damage = hit_twice_with_sword(CombatSettings::legacy());
+
+    assert_eq!(damage, 7.0);
+}
+
+#[test]
+fn fire_aspect_sets_target_on_fire() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = spawn_target(&mut scenario, [1.0, 0.0, 0.0]);
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(pig)
+        .insert(FireTicks(0));
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .set_slot(
+            36,
+            ItemStack::new(ItemKind::IronSword, 1, None)
+                .with_enchantment(Enchantment::FireAspect, 2),
+        );
+
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = scenario;
+
+    attack(&mut app, &mut helper, pig);
+
+    let fire = app.world().get::<FireTicks>(pig).unwrap();
+    assert!(fire.0 > 150 && fire.0 <= 160, "fire ticks were {}", fire.0);
+}
+
+#[test]
+fn hits_wear_down_weapon() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = spawn_target(&mut scenario, [1.0, 0.0, 0.0]);
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::IronPickaxe, 1, None));
+
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        client,
+        ..
+    } = scenario;
+
+    attack(&mut app, &mut helper, pig);
+
+    let pickaxe = app.world().get::<Inventory>(client).unwrap().slot(36);
+    assert_eq!(pickaxe.damage(), 2);
+}
@@ -0,0 +1,504 @@
+use bevy_ecs::prelude::*;
+use valence_digging::{
+    BlockHardness, BlockMinedEvent, Digging, DiggingSettings, DiggingViolation,
+    DiggingViolationEvent, ServerMining,
+};
+use valence_inventory::durability::ItemBreakEvent;
+use valence_inventory::Inventory;
+use valence_server::block::BlockKind;
+use valence_server::entity::active_status_effects::{ActiveStatusEffect, ActiveStatusEffects};
+use valence_server::entity::{OnGround, Position};
+use valence_server::protocol::packets::play::player_action_c2s::PlayerAction;
+use valence_server::protocol::packets::play::{
+    BlockBreakingProgressS2c, BlockUpdateS2c, EntityStatusEffectS2c, EntityStatusS2c,
+    PlayerActionC2s, RemoveEntityStatusEffectS2c,
+};
+use valence_server::protocol::status_effects::StatusEffect;
+use valence_server::protocol::VarInt;
+use valence_server::{BlockPos, BlockState, ChunkLayer, Direction, GameMode, ItemKind, ItemStack};
+
+use crate::layer::chunk::UnloadedChunk;
+use crate::testing::{create_mock_client, MockClientHelper, ScenarioSingleClient};
+
+const POS: BlockPos = BlockPos::new(1, 63, 1);
+
+/// Places `block` in front of a client standing on the ground.
+fn prepare(block: BlockState) -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    let mut layer = scenario
+        .app
+        .world_mut()
+        .get_mut::<ChunkLayer>(scenario.layer)
+        .unwrap();
+
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+    layer.set_block(POS, block);
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Position>(scenario.client)
+        .unwrap()
+        .set([0.5, 64.0, 0.5]);
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<OnGround>(scenario.client)
+        .unwrap()
+        .0 = true;
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn dig(scenario: &mut ScenarioSingleClient, action: PlayerAction) {
+    scenario.helper.send(&PlayerActionC2s {
+        action,
+        position: POS,
+        direction: Direction::Up,
+        sequence: VarInt(0),
+    });
+    scenario.app.update();
+}
+
+fn mined(scenario: &ScenarioSingleClient) -> Vec<BlockState> {
+    scenario
+        .app
+        .world()
+        .resource::<Events<BlockMinedEvent>>()
+        .iter_current_update_events()
+        .map(|event| event.block)
+        .collect()
+}
+
+#[test]
+fn instant_break_is_mined_on_start() {
+    let mut scenario = prepare(BlockState::TORCH);
+
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+
+    assert_eq!(mined(&scenario), [BlockState::TORCH]);
+    assert!(scenario
+        .app
+        .world()
+        .get::<Digging>(scenario.client)
+        .is_none());
+}
+
+#[test]
+fn creative_breaks_instantly() {
+    let mut scenario = prepare(BlockState::STONE);
+
+    *scenario
+        .app
+        .world_mut()
+        .get_mut::<GameMode>(scenario.client)
+        .unwrap() = GameMode::Creative;
+
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+
+    assert_eq!(mined(&scenario), [BlockState::STONE]);
+}
+
+#[test]
+fn breaking_too_fast_is_rejected() {
+    let mut scenario = prepare(BlockState::DIRT);
+
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+
+    assert!(mined(&scenario).is_empty());
+    assert!(scenario
+        .app
+        .world()
+        .get::<Digging>(scenario.client)
+        .is_some());
+
+    dig(&mut scenario, PlayerAction::StopDestroyBlock);
+
+    assert!(mined(&scenario).is_empty());
+    assert_eq!(
+        scenario.violations::<DiggingViolationEvent>(),
+        [DiggingViolation::TooFast]
+    );
+
+    // The block is restored for the client.
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<BlockUpdateS2c>(1);
+    assert_eq!(frames.first::<BlockUpdateS2c>().block_id, BlockState::DIRT);
+}
+
+#[test]
+fn breaking_at_normal_speed_is_mined() {
+    let mut scenario = prepare(BlockState::DIRT);
+
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+
+    // Dirt takes 15 ticks to break by hand. Latency is accounted for.
+    for _ in 0..10 {
+        scenario.app.update();
+    }
+
+    dig(&mut scenario, PlayerAction::StopDestroyBlock);
+
+    assert_eq!(mined(&scenario), [BlockState::DIRT]);
+    assert!(scenario.violations::<DiggingViolationEvent>().is_empty());
+}
+
+#[test]
+fn mining_fatigue_slows_breaking() {
+    let mut scenario = prepare(BlockState::DIRT);
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<ActiveStatusEffects>(scenario.client)
+        .unwrap()
+        .apply(ActiveStatusEffect::from_effect(StatusEffect::MiningFatigue).with_infinite());
+    scenario.app.update();
+
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+
+    for _ in 0..10 {
+        scenario.app.update();
+    }
+
+    dig(&mut scenario, PlayerAction::StopDestroyBlock);
+
+    assert_eq!(
+        scenario.violations::<DiggingViolationEvent>(),
+        [DiggingViolation::TooFast]
+    );
+}
+
+#[test]
+fn stopping_without_starting_is_rejected() {
+    let mut scenario = prepare(BlockState::DIRT);
+
+    dig(&mut scenario, PlayerAction::StopDestroyBlock);
+
+    assert_eq!(
+        scenario.violations::<DiggingViolationEvent>(),
+        [DiggingViolation::NotStarted]
+    );
+
+    scenario.app.insert_resource(DiggingSettings {
+        check_break_speed: false,
+        ..Default::default()
+    });
+
+    dig(&mut scenario, PlayerAction::StopDestroyBlock);
+
+    assert_eq!(mined(&scenario), [BlockState::DIRT]);
+}
+
+#[test]
+fn digging_out_of_reach_is_rejected() {
+    let mut scenario = prepare(BlockState::TORCH);
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Position>(scenario.client)
+        .unwrap()
+        .set([10.5, 64.0, 0.5]);
+
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+
+    assert!(mined(&scenario).is_empty());
+    assert_eq!(
+        scenario.violations::<DiggingViolationEvent>(),
+        [DiggingViolation::TooFar]
+    );
+
+    let frames = scenario.helper.collect_received();
+    assert_eq!(frames.first::<BlockUpdateS2c>().block_id, BlockState::TORCH);
+}
+
+#[test]
+fn progress_is_shown_to_other_players() {
+    let mut scenario = prepare(BlockState::DIRT);
+
+    let (mut bundle, mut other) = create_mock_client("other");
+    bundle.player.layer.0 = scenario.layer;
+    bundle.visible_chunk_layer.0 = scenario.layer;
+    bundle.visible_entity_layers.0.insert(scenario.layer);
+    scenario.app.world_mut().spawn(bundle);
+    scenario.app.update();
+    other.clear_received();
+
+    let stages = |other: &mut MockClientHelper| -> Vec<u8> {
+        other
+            .collect_received()
+            .0
+            .iter()
+            .filter_map(|frame| frame.decode::<BlockBreakingProgressS2c>().ok())
+            .map(|pkt| pkt.destroy_stage)
+            .collect()
+    };
+
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+
+    assert_eq!(stages(&mut other), [0]);
+
+    for _ in 0..4 {
+        scenario.app.update();
+    }
+
+    // A third of the way through.
+    assert_eq!(stages(&mut other), [1, 2, 3]);
+
+    dig(&mut scenario, PlayerAction::AbortDestroyBlock);
+
+    // Any stage outside of 0..=9 removes the animation.
+    assert_eq!(stages(&mut other), [10]);
+
+    // The digging client doesn't get its own progress.
+    assert!(stages(&mut scenario.helper).is_empty());
+}
+
+fn prepare_server_mining(block: BlockState, hardness: f32) -> ScenarioSingleClient {
+    let mut scenario = prepare(block);
+
+    scenario
+        .app
+        .world_mut()
+        .resource_mut::<BlockHardness>()
+        .set(block.to_kind(), hardness);
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(ServerMining);
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+/// Runs updates until the block is mined and returns how many it took.
+fn ticks_until_mined(scenario: &mut ScenarioSingleClient, max: usize) -> Option<usize> {
+    (1..=max).find(|_| {
+        scenario.app.update();
+        !mined(scenario).is_empty()
+    })
+}
+
+#[test]
+fn server_mining_hides_client_progress() {
+    let mut scenario = prepare(BlockState::DIRT);
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(ServerMining);
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<EntityStatusEffectS2c>(1);
+    assert_eq!(frames.first::<EntityStatusEffectS2c>().amplifier, u8::MAX);
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .remove::<ServerMining>();
+    scenario.app.update();
+
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<RemoveEntityStatusEffectS2c>(1);
+}
+
+#[test]
+fn server_mining_uses_custom_hardness() {
+    // Twice as hard as normal dirt.
+    let mut scenario = prepare_server_mining(BlockState::DIRT, 1.0);
+
+    scenario.helper.send(&PlayerActionC2s {
+        action: PlayerAction::StartDestroyBlock,
+        position: POS,
+        direction: Direction::Up,
+        sequence: VarInt(0),
+    });
+
+    let ticks = ticks_until_mined(&mut scenario, 40).unwrap();
+    assert!((30..=31).contains(&ticks), "mined after {ticks} ticks");
+
+    // The client is shown the progress the server makes.
+    let stages: Vec<_> = scenario
+        .helper
+        .collect_received()
+        .0
+        .iter()
+        .filter_map(|frame| frame.decode::<BlockBreakingProgressS2c>().ok())
+        .map(|pkt| pkt.destroy_stage)
+        .collect();
+
+    assert_eq!(stages, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+}
+
+#[test]
+fn server_mining_ignores_client_finishing() {
+    let mut scenario = prepare_server_mining(BlockState::DIRT, 1.0);
+
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+    dig(&mut scenario, PlayerAction::StopDestroyBlock);
+
+    assert!(mined(&scenario).is_empty());
+    assert!(scenario.violations::<DiggingViolationEvent>().is_empty());
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<BlockUpdateS2c>(1);
+
+    dig(&mut scenario, PlayerAction::AbortDestroyBlock);
+
+    assert!(scenario
+        .app
+        .world()
+        .get::<Digging>(scenario.client)
+        .is_none());
+}
+
+#[test]
+fn server_mining_instant_break_block() {
+    let mut scenario = prepare_server_mining(BlockState::TORCH, 0.5);
+
+    // The client breaks the torch instantly, so it's sent back.
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+
+    assert!(mined(&scenario).is_empty());
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<BlockUpdateS2c>(1);
+
+    // The client keeps breaking the torch while holding the button.
+    let mut ticks = 1;
+
+    while mined(&scenario).is_empty() {
+        assert!(ticks < 20, "torch was not mined");
+
+        if ticks % 5 == 0 {
+            dig(&mut scenario, PlayerAction::StartDestroyBlock);
+        } else {
+            scenario.app.update();
+        }
+
+        ticks += 1;
+    }
+
+    // Letting go of the button stops digging after a while.
+    scenario
+        .app
+        .world_mut()
+        .resource_mut::<BlockHardness>()
+        .set(BlockKind::Torch, 5.0);
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<ChunkLayer>(scenario.layer)
+        .unwrap()
+        .set_block(POS, BlockState::TORCH);
+
+    dig(&mut scenario, PlayerAction::StartDestroyBlock);
+
+    for _ in 0..30 {
+        scenario.app.update();
+    }
+
+    assert!(scenario
+        .app
+        .world()
+        .get::<Digging>(scenario.client)
+        .is_none());
+}
+
+#[test]
+fn mining_wears_down_and_breaks_tool() {
+    let mut scenario = prepare(BlockState::STONE);
+
+    scenario
+        .app
+        .world_mut()
+        .resource_mut::<DiggingSettings>()
+        .check_break_speed = false;
+
+    let max_durability = i32::from(ItemKind::WoodenPickaxe.max_durability());
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .set_slot(
+            36,
+            ItemStack::new(ItemKind::WoodenPickaxe, 1, None).with_damage(max_durability - 2),
+        );
+
+    let held = |scenario: &ScenarioSingleClient| {
+        scenario
+            .app
+            .world()
+            .get::<Inventory>(scenario.client)
+            .unwrap()
+            .slot(36)
+            .clone()
+    };
+
+    dig(&mut scenario, PlayerAction::StopDestroyBlock);
+
+    assert_eq!(held(&scenario).damage(), max_durability - 1);
+
+    scenario.helper.clear_received();
+    dig(&mut scenario, PlayerAction::StopDestroyBlock);
+
+    assert!(held(&scenario).is_empty());
+
+    let frames = scenario.helper.collect_received();
+    let status = frames.first::<EntityStatusS2c>();
+    assert_eq!(status.entity_status, 47);
+
+    let breaks = scenario.app.world().resource::<Events<ItemBreakEvent>>();
+    assert_eq!(breaks.iter_current_update_events().count(), 1);
+}
+
+#[test]
+fn hardness_overrides_only_wear_tools_with_server_mining() {
+    let mut scenario = prepare(BlockState::STONE);
+
+    scenario
+        .app
+        .world_mut()
+        .resource_mut::<DiggingSettings>()
+        .check_break_speed = false;
+
+    // Blocks with no hardness don't wear tools down.
+    scenario
+        .app
+        .world_mut()
+        .resource_mut::<BlockHardness>()
+        .set(BlockKind::Stone, 0.0);
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::WoodenPickaxe, 1, None));
+
+    dig(&mut scenario, PlayerAction::StopDestroyBlock);
+
+    let inventory = scenario
+        .app
+        .world()
+        .get::<Inventory>(scenario.client)
+        .unwrap();
+    assert_eq!(inventory.slot(36).damage(), 1);
+}
@@ -0,0 +1,218 @@
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+use valence_server::entity::active_status_effects::{ActiveStatusEffect, ActiveStatusEffects};
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::Flags;
+use valence_server::entity::living::{Absorption, Health};
+use valence_server::protocol::status_effects::StatusEffect;
+
+use crate::health::effect::{StatusEffectBehavior, StatusEffectBehaviors};
+use crate::health::{DamageEvent, DamageSource, HealthSettings};
+use crate::ident;
+use crate::testing::{health, ScenarioSingleClient};
+
+fn apply(app: &mut App, entity: Entity, effect: ActiveStatusEffect) {
+    app.world_mut()
+        .get_mut::<ActiveStatusEffects>(entity)
+        .unwrap()
+        .apply(effect);
+}
+
+#[test]
+fn speed_modifies_movement_speed() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    let speed = |app: &App| {
+        app.world()
+            .get::<EntityAttributes>(client)
+            .unwrap()
+            .get_compute_value(EntityAttribute::GenericMovementSpeed)
+            .unwrap()
+    };
+
+    let base = speed(&app);
+
+    apply(
+        &mut app,
+        client,
+        ActiveStatusEffect::from_effect(StatusEffect::Speed)
+            .with_amplifier(1)
+            .with_duration(10),
+    );
+    app.update();
+
+    // Speed II grants 40% movement speed.
+    assert!((speed(&app) - base * 1.4).abs() < 1e-6);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert_eq!(speed(&app), base);
+}
+
+#[test]
+fn poison_damages_but_does_not_kill() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    app.world_mut()
+        .resource_mut::<HealthSettings>()
+        .natural_regeneration = false;
+    app.world_mut().get_mut::<Health>(client).unwrap().0 = 3.0;
+
+    apply(
+        &mut app,
+        client,
+        ActiveStatusEffect::from_effect(StatusEffect::Poison).with_duration(200),
+    );
+
+    for _ in 0..200 {
+        app.update();
+    }
+
+    assert_eq!(health(&app, client), 1.0);
+}
+
+#[test]
+fn instant_health_heals() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    app.world_mut().get_mut::<Health>(client).unwrap().0 = 10.0;
+
+    apply(
+        &mut app,
+        client,
+        ActiveStatusEffect::from_effect(StatusEffect::InstantHealth),
+    );
+    app.update();
+
+    assert_eq!(health(&app, client), 14.0);
+
+    // The effect is removed again without healing any further.
+    app.update();
+
+    assert_eq!(health(&app, client), 14.0);
+    assert!(!app
+        .world()
+        .get::<ActiveStatusEffects>(client)
+        .unwrap()
+        .has_effect(StatusEffect::InstantHealth));
+}
+
+#[test]
+fn absorption_soaks_up_damage() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    apply(
+        &mut app,
+        client,
+        ActiveStatusEffect::from_effect(StatusEffect::Absorption).with_duration(100),
+    );
+    app.update();
+
+    assert_eq!(app.world().get::<Absorption>(client).unwrap().0, 4.0);
+
+    app.world_mut().send_event(DamageEvent {
+        target: client,
+        amount: 6.0,
+        source: DamageSource::new(ident!("generic")),
+    });
+    app.update();
+
+    assert_eq!(app.world().get::<Absorption>(client).unwrap().0, 0.0);
+    assert_eq!(health(&app, client), 18.0);
+}
+
+#[test]
+fn invisibility_sets_entity_flag() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    apply(
+        &mut app,
+        client,
+        ActiveStatusEffect::from_effect(StatusEffect::Invisibility).with_duration(5),
+    );
+    app.update();
+
+    assert!(app.world().get::<Flags>(client).unwrap().invisible());
+
+    for _ in 0..5 {
+        app.update();
+    }
+
+    assert!(!app.world().get::<Flags>(client).unwrap().invisible());
+}
+
+#[test]
+fn invisibility_keeps_flag_set_by_others() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    app.world_mut()
+        .get_mut::<Flags>(client)
+        .unwrap()
+        .set_invisible(true);
+
+    apply(
+        &mut app,
+        client,
+        ActiveStatusEffect::from_effect(StatusEffect::Invisibility).with_duration(5),
+    );
+
+    for _ in 0..6 {
+        app.update();
+    }
+
+    assert!(app.world().get::<Flags>(client).unwrap().invisible());
+}
+
+#[derive(Resource, Default)]
+struct GlowingTicks(u32);
+
+struct CountTicks;
+
+impl StatusEffectBehavior for CountTicks {
+    fn should_tick(&self, _effect: &ActiveStatusEffect) -> bool {
+        true
+    }
+
+    fn on_tick(&self, world: &mut World, _entity: Entity, _effect: &ActiveStatusEffect) {
+        world.resource_mut::<GlowingTicks>().0 += 1;
+    }
+}
+
+#[test]
+fn custom_behavior() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    app.init_resource::<GlowingTicks>();
+    app.world_mut()
+        .resource_mut::<StatusEffectBehaviors>()
+        .insert(StatusEffect::Glowing, CountTicks);
+
+    apply(
+        &mut app,
+        client,
+        ActiveStatusEffect::from_effect(StatusEffect::Glowing).with_duration(3),
+    );
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    // The behavior runs once for every tick of the effect's duration.
+    assert_eq!(app.world().resource::<GlowingTicks>().0, 3);
+}
@@ -0,0 +1,211 @@
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+use valence_inventory::player_inventory::PlayerInventory;
+use valence_inventory::Inventory;
+use valence_server::enchantment::Enchantment;
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::Flags;
+use valence_server::entity::living::Health;
+use valence_server::entity::player::{Food, Saturation};
+use valence_server::protocol::packets::play::{
+    ClientStatusC2s, DamageTiltS2c, DeathMessageS2c, EntityDamageS2c, PlayerRespawnS2c,
+};
+
+use crate::health::{DamageEvent, DamageSource, Dead, DeathEvent, FireTicks};
+use crate::testing::{health, ScenarioSingleClient};
+use crate::{ident, GameMode, ItemKind, ItemStack};
+
+fn damage(app: &mut App, target: Entity, amount: f32) {
+    app.world_mut().send_event(DamageEvent {
+        target,
+        amount,
+        source: DamageSource::new(ident!("generic")),
+    });
+}
+
+#[test]
+fn damage_reduces_health() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_full_health();
+
+    damage(&mut app, client, 5.0);
+    app.update();
+
+    assert_eq!(health(&app, client), 15.0);
+
+    let frames = helper.collect_received();
+    frames.assert_count::<EntityDamageS2c>(1);
+    // No source position, so there is no tilt.
+    frames.assert_count::<DamageTiltS2c>(0);
+}
+
+#[test]
+fn damage_cooldown_only_applies_excess() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_full_health();
+
+    damage(&mut app, client, 4.0);
+    app.update();
+
+    // Weaker hits during the cooldown are ignored.
+    damage(&mut app, client, 3.0);
+    app.update();
+
+    assert_eq!(health(&app, client), 16.0);
+
+    // Stronger hits only deal the difference and don't play the hurt animation.
+    damage(&mut app, client, 6.0);
+    app.update();
+
+    assert_eq!(health(&app, client), 14.0);
+    helper.collect_received().assert_count::<EntityDamageS2c>(1);
+}
+
+#[test]
+fn armor_reduces_damage() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    app.world_mut()
+        .get_mut::<EntityAttributes>(client)
+        .unwrap()
+        .set_base_value(EntityAttribute::GenericArmor, 20.0);
+
+    // Generic damage bypasses armor, so use a mob attack instead.
+    app.world_mut().send_event(DamageEvent {
+        target: client,
+        amount: 10.0,
+        source: DamageSource::new(ident!("mob_attack")),
+    });
+    app.update();
+
+    // Strong hits pierce some of the armor, so only 60% of the damage is
+    // absorbed instead of 80%.
+    let health = health(&app, client);
+    assert!((health - 16.0).abs() < 1e-4, "health was {health}");
+}
+
+#[test]
+fn protection_enchantments_reduce_damage() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    app.world_mut()
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(
+            PlayerInventory::SLOT_FEET,
+            ItemStack::new(ItemKind::LeatherBoots, 1, None)
+                .with_enchantment(Enchantment::FeatherFalling, 4),
+        );
+
+    // Feather Falling IV gives 12 points of protection against fall damage.
+    app.world_mut().send_event(DamageEvent {
+        target: client,
+        amount: 10.0,
+        source: DamageSource::new(ident!("fall")),
+    });
+    app.update();
+
+    let health = health(&app, client);
+    assert!((health - 14.8).abs() < 1e-4, "health was {health}");
+}
+
+#[test]
+fn burning_deals_damage_every_second() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    app.world_mut()
+        .get_mut::<FireTicks>(client)
+        .unwrap()
+        .ignite(40);
+
+    app.update();
+
+    assert!(app.world().get::<Flags>(client).unwrap().on_fire());
+    assert_eq!(health(&app, client), 19.0);
+
+    for _ in 0..39 {
+        app.update();
+    }
+
+    assert_eq!(health(&app, client), 18.0);
+    assert!(!app.world().get::<Flags>(client).unwrap().on_fire());
+}
+
+#[test]
+fn creative_players_are_invulnerable() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    *app.world_mut().get_mut::<GameMode>(client).unwrap() = GameMode::Creative;
+
+    damage(&mut app, client, 5.0);
+    app.update();
+
+    assert_eq!(health(&app, client), 20.0);
+}
+
+#[test]
+fn death_and_respawn() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_full_health();
+
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 3;
+
+    damage(&mut app, client, 100.0);
+    app.update();
+
+    assert!(app.world().get::<Dead>(client).is_some());
+    assert_eq!(app.world().resource::<Events<DeathEvent>>().len(), 1);
+    helper.collect_received().assert_count::<DeathMessageS2c>(1);
+
+    // Dead players can't be damaged any further.
+    damage(&mut app, client, 1.0);
+    app.update();
+    assert_eq!(health(&app, client), 0.0);
+
+    helper.send(&ClientStatusC2s::PerformRespawn);
+    app.update();
+
+    assert!(app.world().get::<Dead>(client).is_none());
+    assert_eq!(health(&app, client), 20.0);
+    assert_eq!(app.world().get::<Food>(client).unwrap().0, 20);
+    assert_eq!(app.world().get::<Saturation>(client).unwrap().0, 5.0);
+    helper
+        .collect_received()
+        .assert_count::<PlayerRespawnS2c>(1);
+}
+
+#[test]
+fn natural_regeneration() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_full_health();
+
+    app.world_mut().get_mut::<Health>(client).unwrap().0 = 10.0;
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 18;
+
+    for _ in 0..80 {
+        app.update();
+    }
+
+    assert_eq!(health(&app, client), 11.0);
+}
@@ -0,0 +1,286 @@
+use bevy_ecs::prelude::*;
+use valence_server::entity::pig::PigEntityBundle;
+use valence_server::entity::{EntityId, EntityLayerId, Position};
+use valence_server::interact_block::InteractBlockEvent;
+use valence_server::interact_entity::{EntityInteraction, InteractEntityEvent};
+use valence_server::interaction::{
+    InteractionSettings, InteractionTarget, InteractionViolation, InteractionViolationEvent,
+};
+use valence_server::math::Vec3;
+use valence_server::protocol::packets::play::{
+    BlockUpdateS2c, PlayerInteractBlockC2s, PlayerInteractEntityC2s,
+};
+use valence_server::protocol::VarInt;
+use valence_server::{BlockPos, BlockState, ChunkLayer, Direction, EntityLayer, Hand, Server};
+
+use crate::layer::chunk::UnloadedChunk;
+use crate::testing::ScenarioSingleClient;
+
+/// Creates a client standing at the origin with the given settings.
+fn prepare(settings: InteractionSettings) -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<ChunkLayer>(scenario.layer)
+        .unwrap()
+        .insert_chunk([0, 0], UnloadedChunk::new());
+
+    scenario.app.insert_resource(settings);
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn set_block(scenario: &mut ScenarioSingleClient, pos: BlockPos, block: BlockState) {
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<ChunkLayer>(scenario.layer)
+        .unwrap()
+        .set_block(pos, block);
+    scenario.app.update();
+    scenario.helper.clear_received();
+}
+
+fn send_click(scenario: &mut ScenarioSingleClient, pos: BlockPos, face: Direction, cursor: Vec3) {
+    scenario.helper.send(&PlayerInteractBlockC2s {
+        hand: Hand::Main,
+        position: pos,
+        face,
+        cursor_pos: cursor,
+        head_inside_block: false,
+        sequence: VarInt(0),
+    });
+}
+
+fn interact_block(scenario: &mut ScenarioSingleClient, pos: BlockPos, face: Direction) {
+    send_click(scenario, pos, face, Vec3::new(0.5, 1.0, 0.5));
+    scenario.app.update();
+}
+
+fn interacted(scenario: &ScenarioSingleClient) -> bool {
+    scenario
+        .app
+        .world()
+        .resource::<Events<InteractBlockEvent>>()
+        .iter_current_update_events()
+        .next()
+        .is_some()
+}
+
+#[test]
+fn interactions_are_unchecked_by_default() {
+    let mut scenario = prepare(InteractionSettings::default());
+
+    interact_block(&mut scenario, BlockPos::new(100, -1, 100), Direction::Up);
+
+    assert!(interacted(&scenario));
+    assert!(scenario
+        .violations::<InteractionViolationEvent>()
+        .is_empty());
+}
+
+#[test]
+fn out_of_reach_block_is_resynced() {
+    let mut scenario = prepare(InteractionSettings {
+        max_block_reach: Some(4.5),
+        ..Default::default()
+    });
+
+    let pos = BlockPos::new(2, -1, 2);
+    interact_block(&mut scenario, pos, Direction::Up);
+
+    assert!(interacted(&scenario));
+
+    let pos = BlockPos::new(8, -1, 2);
+    interact_block(&mut scenario, pos, Direction::Up);
+
+    assert!(!interacted(&scenario));
+    assert_eq!(
+        scenario.violations::<InteractionViolationEvent>(),
+        [InteractionViolation::Reach]
+    );
+
+    let event = scenario
+        .app
+        .world()
+        .resource::<Events<InteractionViolationEvent>>()
+        .iter_current_update_events()
+        .next()
+        .copied()
+        .unwrap();
+    assert_eq!(event.target, InteractionTarget::Block(pos));
+
+    // Both the clicked block and the block a placement would have gone into.
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<BlockUpdateS2c>(2);
+}
+
+#[test]
+fn clicking_face_pointing_away_is_rejected() {
+    let mut scenario = prepare(InteractionSettings {
+        check_face: true,
+        ..Default::default()
+    });
+
+    // The client is above the block, so it can't see the bottom face.
+    interact_block(&mut scenario, BlockPos::new(1, -1, 1), Direction::Down);
+
+    assert_eq!(
+        scenario.violations::<InteractionViolationEvent>(),
+        [InteractionViolation::Face]
+    );
+
+    interact_block(&mut scenario, BlockPos::new(1, -1, 1), Direction::Up);
+
+    assert!(interacted(&scenario));
+}
+
+#[test]
+fn interacting_through_blocks_is_rejected() {
+    let mut scenario = prepare(InteractionSettings {
+        check_line_of_sight: true,
+        ..Default::default()
+    });
+
+    let target = BlockPos::new(3, 0, 0);
+    let cursor = Vec3::new(0.0, 0.9, 0.5);
+    set_block(&mut scenario, target, BlockState::STONE);
+
+    send_click(&mut scenario, target, Direction::West, cursor);
+    scenario.app.update();
+
+    assert!(interacted(&scenario));
+
+    // A wall between the client and the target.
+    set_block(&mut scenario, BlockPos::new(1, 1, 0), BlockState::STONE);
+    set_block(&mut scenario, BlockPos::new(1, 0, 0), BlockState::STONE);
+
+    send_click(&mut scenario, target, Direction::West, cursor);
+    scenario.app.update();
+
+    assert_eq!(
+        scenario.violations::<InteractionViolationEvent>(),
+        [InteractionViolation::LineOfSight]
+    );
+
+    // Blocks without collision don't obstruct.
+    set_block(&mut scenario, BlockPos::new(1, 1, 0), BlockState::AIR);
+    set_block(&mut scenario, BlockPos::new(1, 0, 0), BlockState::TORCH);
+
+    send_click(&mut scenario, target, Direction::West, cursor);
+    scenario.app.update();
+
+    assert!(interacted(&scenario));
+}
+
+#[test]
+fn interactions_are_rate_limited() {
+    let mut scenario = prepare(InteractionSettings {
+        max_interactions: Some(2),
+        rate_limit_window: 5,
+        ..Default::default()
+    });
+
+    let pos = BlockPos::new(1, -1, 1);
+
+    for _ in 0..3 {
+        send_click(&mut scenario, pos, Direction::Up, Vec3::new(0.5, 1.0, 0.5));
+    }
+    scenario.app.update();
+
+    assert_eq!(
+        scenario.violations::<InteractionViolationEvent>(),
+        [InteractionViolation::RateLimit]
+    );
+
+    for _ in 0..5 {
+        scenario.app.update();
+    }
+
+    interact_block(&mut scenario, pos, Direction::Up);
+
+    assert!(interacted(&scenario));
+}
+
+#[test]
+fn out_of_reach_entity_is_rejected() {
+    let mut scenario = prepare(InteractionSettings {
+        max_entity_reach: Some(6.0),
+        ..Default::default()
+    });
+
+    let interact = |scenario: &mut ScenarioSingleClient, pos: [f64; 3]| {
+        let pig = scenario
+            .app
+            .world_mut()
+            .spawn(PigEntityBundle {
+                layer: EntityLayerId(scenario.layer),
+                position: Position(pos.into()),
+                ..Default::default()
+            })
+            .id();
+        scenario.app.update();
+
+        let entity_id = scenario.app.world().get::<EntityId>(pig).unwrap().get();
+        scenario.helper.send(&PlayerInteractEntityC2s {
+            entity_id: VarInt(entity_id),
+            interact: EntityInteraction::Interact(Hand::Main),
+            sneaking: false,
+        });
+        scenario.app.update();
+
+        scenario
+            .app
+            .world()
+            .resource::<Events<InteractEntityEvent>>()
+            .iter_current_update_events()
+            .any(|event| event.entity == pig)
+    };
+
+    assert!(interact(&mut scenario, [3.0, 0.0, 0.0]));
+    assert!(!interact(&mut scenario, [10.0, 0.0, 0.0]));
+    assert_eq!(
+        scenario.violations::<InteractionViolationEvent>(),
+        [InteractionViolation::Reach]
+    );
+}
+
+#[test]
+fn entity_in_hidden_layer_is_rejected() {
+    let mut scenario = prepare(InteractionSettings::default());
+
+    let hidden_layer = EntityLayer::new(scenario.app.world().resource::<Server>());
+    let hidden_layer = scenario.app.world_mut().spawn(hidden_layer).id();
+
+    let pig = scenario
+        .app
+        .world_mut()
+        .spawn(PigEntityBundle {
+            layer: EntityLayerId(hidden_layer),
+            ..Default::default()
+        })
+        .id();
+    scenario.app.update();
+
+    let entity_id = scenario.app.world().get::<EntityId>(pig).unwrap().get();
+    scenario.helper.send(&PlayerInteractEntityC2s {
+        entity_id: VarInt(entity_id),
+        interact: EntityInteraction::Attack,
+        sneaking: false,
+    });
+    scenario.app.update();
+
+    assert!(scenario
+        .app
+        .world()
+        .resource::<Events<InteractEntityEvent>>()
+        .is_empty());
+    assert_eq!(
+        scenario.violations::<InteractionViolationEvent>(),
+        [InteractionViolation::NotVisible]
+    );
+}
@@ -0,0 +1,150 @@
+use bevy_ecs::prelude::*;
+use valence_server::interact_block::InteractBlockEvent;
+use valence_server::interact_item::InteractItemEvent;
+use valence_server::item_cooldown::ItemCooldowns;
+use valence_server::math::Vec3;
+use valence_server::protocol::packets::play::{
+    CooldownUpdateS2c, PlayerInteractBlockC2s, PlayerInteractItemC2s,
+};
+use valence_server::protocol::VarInt;
+
+use crate::inventory::Inventory;
+use crate::testing::ScenarioSingleClient;
+use crate::{BlockPos, Direction, Hand, ItemKind, ItemStack};
+
+fn prepare() -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario.app.update();
+
+    let mut inventory = scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap();
+
+    // The first hotbar slot is held.
+    inventory.set_slot(36, ItemStack::new(ItemKind::EnderPearl, 16, None));
+    inventory.set_slot(45, ItemStack::new(ItemKind::Snowball, 16, None));
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn cooldowns(scenario: &mut ScenarioSingleClient) -> Mut<'_, ItemCooldowns> {
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<ItemCooldowns>(scenario.client)
+        .unwrap()
+}
+
+fn use_item(scenario: &mut ScenarioSingleClient, hand: Hand) -> bool {
+    scenario.helper.send(&PlayerInteractItemC2s {
+        hand,
+        sequence: VarInt(0),
+    });
+    scenario.app.update();
+
+    scenario
+        .app
+        .world()
+        .resource::<Events<InteractItemEvent>>()
+        .iter_current_update_events()
+        .any(|event| event.hand == hand)
+}
+
+#[test]
+fn cooldown_is_sent_and_ticks_down() {
+    let mut scenario = prepare();
+
+    cooldowns(&mut scenario).set(ItemKind::EnderPearl, 20);
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<CooldownUpdateS2c>(1);
+
+    let pkt = frames.first::<CooldownUpdateS2c>();
+    assert_eq!(pkt.item_id, ItemKind::EnderPearl);
+    assert_eq!(pkt.cooldown_ticks.0, 20);
+
+    for _ in 0..9 {
+        scenario.app.update();
+    }
+
+    assert_eq!(cooldowns(&mut scenario).get(ItemKind::EnderPearl), 10);
+
+    for _ in 0..10 {
+        scenario.app.update();
+    }
+
+    assert!(!cooldowns(&mut scenario).is_cooling_down(ItemKind::EnderPearl));
+
+    // The client removes the overlay on its own.
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<CooldownUpdateS2c>(0);
+}
+
+#[test]
+fn clearing_cooldown_is_sent() {
+    let mut scenario = prepare();
+
+    cooldowns(&mut scenario).set(ItemKind::EnderPearl, 20);
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    cooldowns(&mut scenario).clear(ItemKind::EnderPearl);
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<CooldownUpdateS2c>(1);
+    assert_eq!(frames.first::<CooldownUpdateS2c>().cooldown_ticks.0, 0);
+}
+
+#[test]
+fn item_on_cooldown_cannot_be_used() {
+    let mut scenario = prepare();
+
+    assert!(use_item(&mut scenario, Hand::Main));
+
+    cooldowns(&mut scenario).set(ItemKind::EnderPearl, 20);
+    scenario.app.update();
+
+    assert!(!use_item(&mut scenario, Hand::Main));
+
+    // The snowball in the off hand isn't affected.
+    assert!(use_item(&mut scenario, Hand::Off));
+
+    scenario.helper.send(&PlayerInteractBlockC2s {
+        hand: Hand::Main,
+        position: BlockPos::new(0, -1, 0),
+        face: Direction::Up,
+        cursor_pos: Vec3::new(0.5, 1.0, 0.5),
+        head_inside_block: false,
+        sequence: VarInt(0),
+    });
+    scenario.app.update();
+
+    assert!(scenario
+        .app
+        .world()
+        .resource::<Events<InteractBlockEvent>>()
+        .iter_current_update_events()
+        .next()
+        .is_none());
+
+    // Switching to another item makes the main hand usable again.
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::Stick, 1, None));
+    scenario.app.update();
+
+    assert!(use_item(&mut scenario, Hand::Main));
+}
@@ -0,0 +1,207 @@
+use valence_map::*;
+
+use crate::entity::item_frame::{self, ItemFrameEntityBundle};
+use crate::entity::EntityLayerId;
+use crate::inventory::Inventory;
+use crate::protocol::packets::play::MapUpdateS2c;
+use crate::testing::ScenarioSingleClient;
+use crate::ItemStack;
+
+fn prepare() -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn hold(scenario: &mut ScenarioSingleClient, stack: ItemStack) {
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .set_slot(36, stack);
+}
+
+#[test]
+fn held_map_is_sent_then_patched() {
+    let mut scenario = prepare();
+
+    let mut canvas = MapCanvas::new();
+    canvas.fill(MapColor::WHITE);
+
+    let map = scenario
+        .app
+        .world_mut()
+        .spawn(MapBundle {
+            id: MapId(1),
+            canvas,
+            ..Default::default()
+        })
+        .id();
+
+    scenario.app.update();
+
+    // Nobody is holding the map yet.
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<MapUpdateS2c>(0);
+
+    hold(&mut scenario, MapId(1).item_stack());
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<MapUpdateS2c>(1);
+
+    let pkt = frames.first::<MapUpdateS2c>();
+    assert_eq!(pkt.map_id.0, 1);
+    assert_eq!(pkt.icons, Some(vec![]));
+
+    let data = pkt.data.unwrap();
+    assert_eq!([data.columns, data.rows], [128, 128]);
+    assert!(data.data.iter().all(|&c| c == MapColor::WHITE.0));
+
+    // Only the changed area is sent.
+    let mut canvas = scenario.app.world_mut().get_mut::<MapCanvas>(map).unwrap();
+    canvas.set_pixel(5, 6, MapColor::RED);
+    canvas.set_pixel(7, 6, MapColor::RED);
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<MapUpdateS2c>(1);
+
+    let pkt = frames.first::<MapUpdateS2c>();
+    assert_eq!(pkt.icons, None);
+
+    let data = pkt.data.unwrap();
+    assert_eq!([data.columns, data.rows], [3, 1]);
+    assert_eq!(data.position, [5, 6]);
+    assert_eq!(
+        data.data,
+        [MapColor::RED.0, MapColor::WHITE.0, MapColor::RED.0]
+    );
+
+    // Changes while the map isn't held are sent in full when it is held again.
+    hold(&mut scenario, ItemStack::EMPTY);
+    scenario.app.update();
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<MapCanvas>(map)
+        .unwrap()
+        .set_pixel(0, 0, MapColor::BLACK);
+    scenario.app.update();
+
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<MapUpdateS2c>(0);
+
+    hold(&mut scenario, MapId(1).item_stack());
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<MapUpdateS2c>(1);
+    assert_eq!(frames.first::<MapUpdateS2c>().data.unwrap().columns, 128);
+}
+
+#[test]
+fn map_in_item_frame_is_sent() {
+    let mut scenario = prepare();
+
+    let map = scenario
+        .app
+        .world_mut()
+        .spawn(MapBundle {
+            id: MapId(2),
+            ..Default::default()
+        })
+        .id();
+
+    scenario.app.world_mut().spawn(ItemFrameEntityBundle {
+        item_frame_item_stack: item_frame::ItemStack(MapId(2).item_stack()),
+        layer: EntityLayerId(scenario.layer),
+        ..Default::default()
+    });
+
+    scenario.app.update();
+
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<MapUpdateS2c>(1);
+
+    let mut icon = MapIcon::new(IconType::RedPointer, [-10, 20]);
+    icon.rotation = 4;
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<MapIcons>(map)
+        .unwrap()
+        .push(icon);
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<MapUpdateS2c>(1);
+
+    let pkt = frames.first::<MapUpdateS2c>();
+    assert_eq!(pkt.data, None);
+
+    let icons = pkt.icons.unwrap();
+    assert_eq!(icons.len(), 1);
+    assert_eq!(icons[0].position, [-10, 20]);
+    assert_eq!(icons[0].direction, 4);
+}
+
+#[test]
+fn canvas_drawing() {
+    let mut canvas = MapCanvas::new();
+
+    canvas.draw_line([0, 0], [3, 3], MapColor::RED);
+    assert!((0..4).all(|i| canvas.pixel(i, i) == Some(MapColor::RED)));
+    assert_eq!(canvas.pixel(1, 0), Some(MapColor::TRANSPARENT));
+
+    // Lines are clipped before they are drawn, even with extreme ends.
+    canvas.draw_line([-100, 50], [i32::MAX, 50], MapColor::RED);
+    assert!((0..128).all(|x| canvas.pixel(x, 50) == Some(MapColor::RED)));
+    canvas.draw_line([i32::MIN, i32::MIN], [-1, 200], MapColor::RED);
+
+    // Shapes are clipped to the canvas.
+    canvas.fill_rect([120, -5], [20, 10], MapColor::BLUE);
+    assert_eq!(canvas.pixel(127, 4), Some(MapColor::BLUE));
+    assert_eq!(canvas.pixel(127, 5), Some(MapColor::TRANSPARENT));
+    assert_eq!(canvas.pixel(128, 0), None);
+
+    canvas.draw_rect([10, 10], [5, 4], MapColor::GREEN);
+    assert_eq!(canvas.pixel(14, 13), Some(MapColor::GREEN));
+    assert_eq!(canvas.pixel(12, 12), Some(MapColor::TRANSPARENT));
+
+    assert_eq!(MapCanvas::text_size("ab\nc"), [11, 16]);
+    assert_eq!(canvas.draw_text([20, 20], "I", MapColor::BLACK), [5, 7]);
+    // The middle column of the "I" glyph.
+    assert!((0..7).all(|y| canvas.pixel(22, 20 + y) == Some(MapColor::BLACK)));
+
+    // Images are quantized to the palette and transparent pixels are skipped.
+    canvas.draw_image(
+        [40, 40],
+        2,
+        &[
+            [255, 255, 255, 255],
+            [0, 0, 0, 0],
+            [25, 25, 25, 255],
+            [1, 2, 3, 200],
+        ],
+    );
+    assert_eq!(canvas.pixel(40, 40), Some(MapColor::WHITE));
+    assert_eq!(canvas.pixel(41, 40), Some(MapColor::TRANSPARENT));
+    assert_eq!(canvas.pixel(40, 41), Some(MapColor::BLACK));
+    assert!(!canvas.pixel(41, 41).unwrap().is_transparent());
+
+    assert_eq!(MapColor::WHITE.to_rgb(), Some([255, 255, 255]));
+    assert_eq!(MapColor::TRANSPARENT.to_rgb(), None);
+}
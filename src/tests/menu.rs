@@ -0,0 +1,177 @@
+use bevy_ecs::prelude::*;
+
+use crate::inventory::menu::{Menu, MenuButton, MenuClickEvent};
+use crate::inventory::{
+    ClickMode, ClientInventoryState, Inventory, InventoryKind, OpenInventory, SlotChange,
+};
+use crate::protocol::packets::play::{ClickSlotC2s, InventoryS2c, ScreenHandlerSlotUpdateS2c};
+use crate::protocol::VarInt;
+use crate::testing::ScenarioSingleClient;
+use crate::{ItemKind, ItemStack};
+
+fn wool(count: usize) -> Vec<MenuButton> {
+    (0..count)
+        .map(|_| MenuButton::new(ItemStack::new(ItemKind::RedWool, 1, None)))
+        .collect()
+}
+
+fn open_menu(scenario: &mut ScenarioSingleClient, kind: InventoryKind, menu: Menu) -> Entity {
+    let menu = scenario
+        .app
+        .world_mut()
+        .spawn((Inventory::new(kind), menu))
+        .id();
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(OpenInventory::new(menu));
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    menu
+}
+
+fn click(scenario: &mut ScenarioSingleClient, slot_idx: i16, slot_changes: Vec<SlotChange>) {
+    let inv_state = scenario
+        .app
+        .world()
+        .get::<ClientInventoryState>(scenario.client)
+        .unwrap();
+
+    let window_id = inv_state.window_id();
+    let state_id = inv_state.state_id().0;
+
+    scenario.helper.send(&ClickSlotC2s {
+        window_id,
+        state_id: VarInt(state_id),
+        slot_idx,
+        button: 0,
+        mode: ClickMode::Click,
+        slot_changes: slot_changes.into(),
+        carried_item: ItemStack::EMPTY,
+    });
+    scenario.app.update();
+}
+
+#[test]
+fn clicking_button_sends_event_and_moves_nothing() {
+    let mut scenario = ScenarioSingleClient::new();
+    scenario.app.update();
+
+    let menu = open_menu(&mut scenario, InventoryKind::Generic9x1, Menu::new(wool(2)));
+
+    // The client predicts picking up the button.
+    click(
+        &mut scenario,
+        1,
+        vec![SlotChange {
+            idx: 1,
+            stack: ItemStack::EMPTY,
+        }],
+    );
+
+    let events = scenario.read_events::<MenuClickEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].menu, menu);
+    assert_eq!(events[0].button, 1);
+
+    let menu_inv = scenario.app.world().get::<Inventory>(menu).unwrap();
+    assert_eq!(menu_inv.slot(1).item, ItemKind::RedWool);
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<InventoryS2c>(1);
+
+    let resync = frames.first::<InventoryS2c>();
+    assert_eq!(resync.slots.len(), 9 + 36);
+    assert_eq!(resync.slots[1].item, ItemKind::RedWool);
+    assert!(resync.carried_item.is_empty());
+}
+
+#[test]
+fn buttons_are_paginated() {
+    let mut scenario = ScenarioSingleClient::new();
+    scenario.app.update();
+
+    let mut buttons = wool(30);
+    buttons[19].stack = ItemStack::new(ItemKind::BlueWool, 1, None);
+
+    let menu = open_menu(&mut scenario, InventoryKind::Generic9x3, Menu::new(buttons));
+
+    let menu_inv = scenario.app.world().get::<Inventory>(menu).unwrap();
+    assert!(menu_inv.slot(18).is_empty());
+    assert_eq!(menu_inv.slot(26).item, ItemKind::Arrow);
+
+    click(&mut scenario, 26, vec![]);
+
+    let menu_ref = scenario.app.world().get::<Menu>(menu).unwrap();
+    assert_eq!(menu_ref.page(), 1);
+    assert_eq!(menu_ref.page_count(InventoryKind::Generic9x3), 2);
+
+    let menu_inv = scenario.app.world().get::<Inventory>(menu).unwrap();
+    assert_eq!(menu_inv.slot(0).item, ItemKind::RedWool);
+    assert_eq!(menu_inv.slot(1).item, ItemKind::BlueWool);
+    assert_eq!(menu_inv.slot(11).item, ItemKind::RedWool);
+    assert!(menu_inv.slot(12).is_empty());
+    assert_eq!(menu_inv.slot(18).item, ItemKind::Arrow);
+    assert!(menu_inv.slot(26).is_empty());
+
+    click(&mut scenario, 1, vec![]);
+
+    let events = scenario.read_events::<MenuClickEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].button, 19);
+}
+
+#[test]
+fn changing_menu_updates_clients() {
+    let mut scenario = ScenarioSingleClient::new();
+    scenario.app.update();
+
+    let menu = open_menu(&mut scenario, InventoryKind::Generic9x1, Menu::new(wool(2)));
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Menu>(menu)
+        .unwrap()
+        .buttons[0]
+        .stack = ItemStack::new(ItemKind::GreenWool, 1, None);
+
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<ScreenHandlerSlotUpdateS2c>(1);
+
+    let update = frames.first::<ScreenHandlerSlotUpdateS2c>();
+    assert_eq!(update.slot_idx, 0);
+    assert_eq!(update.slot_data.item, ItemKind::GreenWool);
+}
+
+#[derive(Resource, Default)]
+struct Clicked(Vec<usize>);
+
+#[test]
+fn clicking_button_runs_callback() {
+    let mut scenario = ScenarioSingleClient::new();
+    scenario.app.init_resource::<Clicked>();
+    scenario.app.update();
+
+    let system = scenario.app.world_mut().register_system(
+        |In(event): In<MenuClickEvent>, mut clicked: ResMut<Clicked>| {
+            clicked.0.push(event.button);
+        },
+    );
+
+    let mut buttons = wool(2);
+    buttons[1] = buttons[1].clone().on_click(system);
+
+    open_menu(&mut scenario, InventoryKind::Generic9x1, Menu::new(buttons));
+
+    click(&mut scenario, 0, vec![]);
+    click(&mut scenario, 1, vec![]);
+
+    assert_eq!(scenario.app.world().resource::<Clicked>().0, [1]);
+}
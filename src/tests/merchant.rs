@@ -0,0 +1,170 @@
+use bevy_ecs::prelude::*;
+
+use crate::inventory::merchant::{Merchant, MerchantOffer, TradeEvent};
+use crate::inventory::{
+    ClickMode, ClientInventoryState, CursorItem, Inventory, InventoryKind, OpenInventory,
+    SlotChange,
+};
+use crate::protocol::packets::play::{
+    ClickSlotC2s, InventoryS2c, OpenScreenS2c, SelectMerchantTradeC2s, SetTradeOffersS2c,
+};
+use crate::protocol::VarInt;
+use crate::testing::ScenarioSingleClient;
+use crate::{ItemKind, ItemStack};
+
+fn bread_offer() -> MerchantOffer {
+    MerchantOffer::new(
+        ItemStack::new(ItemKind::Emerald, 3, None),
+        ItemStack::EMPTY,
+        ItemStack::new(ItemKind::Bread, 6, None),
+    )
+}
+
+fn open_merchant(scenario: &mut ScenarioSingleClient) -> Entity {
+    let merchant = scenario
+        .app
+        .world_mut()
+        .spawn((
+            Inventory::new(InventoryKind::Merchant),
+            Merchant::new(vec![bread_offer()]),
+        ))
+        .id();
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(OpenInventory::new(merchant));
+
+    merchant
+}
+
+#[test]
+fn offers_are_sent_after_opening() {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    open_merchant(&mut scenario);
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<SetTradeOffersS2c>(1);
+    frames.assert_order::<(OpenScreenS2c, SetTradeOffersS2c)>();
+
+    let offers = frames.first::<SetTradeOffersS2c>();
+    assert_eq!(offers.trades.len(), 1);
+    assert_eq!(
+        offers.trades[0].output_item,
+        ItemStack::new(ItemKind::Bread, 6, None)
+    );
+}
+
+#[test]
+fn selecting_offer_fills_input_slots() {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario.app.update();
+
+    let mut player_inv = scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap();
+    player_inv.set_slot(20, ItemStack::new(ItemKind::Emerald, 10, None));
+
+    let merchant = open_merchant(&mut scenario);
+    scenario.app.update();
+
+    scenario.helper.send(&SelectMerchantTradeC2s {
+        selected_slot: VarInt(0),
+    });
+    scenario.app.update();
+
+    let merchant_inv = scenario.app.world().get::<Inventory>(merchant).unwrap();
+    assert_eq!(
+        merchant_inv.slot(0),
+        &ItemStack::new(ItemKind::Emerald, 10, None)
+    );
+    assert_eq!(
+        merchant_inv.slot(2),
+        &ItemStack::new(ItemKind::Bread, 6, None)
+    );
+
+    let player_inv = scenario
+        .app
+        .world()
+        .get::<Inventory>(scenario.client)
+        .unwrap();
+    assert!(player_inv.slot(20).is_empty());
+}
+
+#[test]
+fn clicking_result_executes_trade() {
+    let mut scenario = ScenarioSingleClient::new();
+    let merchant = open_merchant(&mut scenario);
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(merchant)
+        .unwrap()
+        .set_slot(0, ItemStack::new(ItemKind::Emerald, 5, None));
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    let inv_state = scenario
+        .app
+        .world()
+        .get::<ClientInventoryState>(scenario.client)
+        .unwrap();
+    let window_id = inv_state.window_id();
+    let state_id = inv_state.state_id();
+
+    // The client claims to get more bread than the trade makes.
+    scenario.helper.send(&ClickSlotC2s {
+        window_id,
+        state_id: VarInt(state_id.0),
+        slot_idx: 2,
+        button: 0,
+        mode: ClickMode::Click,
+        slot_changes: vec![SlotChange {
+            idx: 2,
+            stack: ItemStack::EMPTY,
+        }]
+        .into(),
+        carried_item: ItemStack::new(ItemKind::Bread, 64, None),
+    });
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<InventoryS2c>(1);
+
+    // The offers are resent with the new uses.
+    let offers = frames.first::<SetTradeOffersS2c>();
+    assert_eq!(offers.trades[0].number_of_trade_uses, 1);
+
+    let cursor = scenario
+        .app
+        .world()
+        .get::<CursorItem>(scenario.client)
+        .unwrap();
+    assert_eq!(cursor.0, ItemStack::new(ItemKind::Bread, 6, None));
+
+    let merchant_inv = scenario.app.world().get::<Inventory>(merchant).unwrap();
+    assert_eq!(
+        merchant_inv.slot(0),
+        &ItemStack::new(ItemKind::Emerald, 2, None)
+    );
+    // Two emeralds aren't enough for another trade.
+    assert!(merchant_inv.slot(2).is_empty());
+
+    let merchant = scenario.app.world().get::<Merchant>(merchant).unwrap();
+    assert_eq!(merchant.offers[0].uses, 1);
+
+    let events = scenario.read_events::<TradeEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].offer, 0);
+}
@@ -0,0 +1,206 @@
+use crate::entity::Position;
+use crate::layer::chunk::UnloadedChunk;
+use crate::layer::ChunkLayer;
+use crate::math::DVec3;
+use crate::movement::{MovementSettings, MovementViolation, MovementViolationEvent};
+use crate::protocol::packets::play::{PlayerPositionLookS2c, PositionAndOnGroundC2s};
+use crate::testing::ScenarioSingleClient;
+use crate::BlockState;
+
+/// Puts the client on a stone floor at y = 64 with the given settings.
+fn prepare(settings: MovementSettings) -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario.app.insert_resource(settings);
+
+    let mut layer = scenario
+        .app
+        .world_mut()
+        .get_mut::<ChunkLayer>(scenario.layer)
+        .unwrap();
+
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+
+    for z in 0..16 {
+        for x in 0..16 {
+            layer.set_block([x, 63, z], BlockState::STONE);
+        }
+    }
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Position>(scenario.client)
+        .unwrap()
+        .set([0.5, 64.0, 0.5]);
+
+    scenario.app.update();
+    scenario.helper.confirm_initial_pending_teleports();
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn move_to(scenario: &mut ScenarioSingleClient, pos: [f64; 3], on_ground: bool) {
+    scenario.helper.send(&PositionAndOnGroundC2s {
+        position: pos.into(),
+        on_ground,
+    });
+    scenario.app.update();
+}
+
+fn position(scenario: &ScenarioSingleClient) -> DVec3 {
+    scenario
+        .app
+        .world()
+        .get::<Position>(scenario.client)
+        .unwrap()
+        .0
+}
+
+#[test]
+fn checks_are_disabled_by_default() {
+    let mut scenario = prepare(MovementSettings::default());
+
+    move_to(&mut scenario, [8.5, 80.0, 8.5], true);
+
+    assert!(scenario.violations::<MovementViolationEvent>().is_empty());
+    assert_eq!(position(&scenario), DVec3::new(8.5, 80.0, 8.5));
+}
+
+#[test]
+fn speed_check_rubber_bands() {
+    let mut scenario = prepare(MovementSettings {
+        check_speed: true,
+        ..Default::default()
+    });
+
+    // Walking is fine.
+    move_to(&mut scenario, [0.7, 64.0, 0.5], true);
+
+    assert!(scenario.violations::<MovementViolationEvent>().is_empty());
+    assert_eq!(position(&scenario), DVec3::new(0.7, 64.0, 0.5));
+
+    move_to(&mut scenario, [4.0, 64.0, 0.5], true);
+
+    assert_eq!(
+        scenario.violations::<MovementViolationEvent>(),
+        [MovementViolation::Speed]
+    );
+    assert_eq!(position(&scenario), DVec3::new(0.7, 64.0, 0.5));
+
+    // The client is teleported back.
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<PlayerPositionLookS2c>(1);
+    assert_eq!(
+        frames.first::<PlayerPositionLookS2c>().position,
+        DVec3::new(0.7, 64.0, 0.5)
+    );
+}
+
+#[test]
+fn teleport_check_without_rubber_banding() {
+    let mut scenario = prepare(MovementSettings {
+        max_move_distance: Some(10.0),
+        rubber_band: false,
+        ..Default::default()
+    });
+
+    move_to(&mut scenario, [0.5, 64.0, 15.5], true);
+
+    assert_eq!(
+        scenario.violations::<MovementViolationEvent>(),
+        [MovementViolation::Teleport]
+    );
+    assert_eq!(position(&scenario), DVec3::new(0.5, 64.0, 15.5));
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(0);
+}
+
+#[test]
+fn collision_check() {
+    let mut scenario = prepare(MovementSettings {
+        check_collision: true,
+        ..Default::default()
+    });
+
+    let mut layer = scenario
+        .app
+        .world_mut()
+        .get_mut::<ChunkLayer>(scenario.layer)
+        .unwrap();
+
+    for z in 0..16 {
+        layer.set_block([2, 64, z], BlockState::STONE);
+        layer.set_block([2, 65, z], BlockState::STONE);
+    }
+
+    move_to(&mut scenario, [1.5, 64.0, 0.5], true);
+
+    assert!(scenario.violations::<MovementViolationEvent>().is_empty());
+
+    // Moving through the wall.
+    move_to(&mut scenario, [3.5, 64.0, 0.5], true);
+
+    assert_eq!(
+        scenario.violations::<MovementViolationEvent>(),
+        [MovementViolation::Collision]
+    );
+    assert_eq!(position(&scenario), DVec3::new(1.5, 64.0, 0.5));
+}
+
+#[test]
+fn on_ground_check() {
+    let mut scenario = prepare(MovementSettings {
+        check_on_ground: true,
+        rubber_band: false,
+        ..Default::default()
+    });
+
+    move_to(&mut scenario, [0.5, 64.0, 1.5], true);
+
+    assert!(scenario.violations::<MovementViolationEvent>().is_empty());
+
+    move_to(&mut scenario, [0.5, 65.0, 1.5], true);
+
+    assert_eq!(
+        scenario.violations::<MovementViolationEvent>(),
+        [MovementViolation::OnGround]
+    );
+
+    move_to(&mut scenario, [0.5, 65.0, 1.5], false);
+
+    assert!(scenario.violations::<MovementViolationEvent>().is_empty());
+}
+
+#[test]
+fn flight_check() {
+    let mut scenario = prepare(MovementSettings {
+        check_flight: true,
+        max_air_ticks: 5,
+        rubber_band: false,
+        ..Default::default()
+    });
+
+    for i in 1..=5 {
+        move_to(&mut scenario, [0.5, 64.0 + f64::from(i) * 0.1, 0.5], false);
+
+        assert!(scenario.violations::<MovementViolationEvent>().is_empty());
+    }
+
+    // Hovering.
+    move_to(&mut scenario, [0.5, 64.5, 0.5], false);
+
+    assert_eq!(
+        scenario.violations::<MovementViolationEvent>(),
+        [MovementViolation::Flight]
+    );
+
+    // Falling is fine.
+    move_to(&mut scenario, [0.5, 64.3, 0.5], false);
+
+    assert!(scenario.violations::<MovementViolationEvent>().is_empty());
+}
@@ -0,0 +1,378 @@
+use bevy_ecs::prelude::*;
+use valence_recipe::*;
+
+use crate::event_loop::EventLoopUpdate;
+use crate::inventory::transaction::{CancelTransactionEvent, ItemDroppedFromSlotEvent};
+use crate::inventory::{
+    ClickMode, ClientInventoryState, CursorItem, DropItemStackEvent, Inventory, InventoryKind,
+    OpenInventory, SlotChange,
+};
+use crate::protocol::packets::play::unlock_recipes_s2c::UpdateRecipeBookAction;
+use crate::protocol::packets::play::{
+    ClickSlotC2s, CloseHandledScreenC2s, CraftFailedResponseS2c, CraftRequestC2s, InventoryS2c,
+    SynchronizeRecipesS2c, UnlockRecipesS2c,
+};
+use crate::protocol::VarInt;
+use crate::registry::tags::TagsRegistry;
+use crate::testing::ScenarioSingleClient;
+use crate::{ident, ItemKind, ItemStack};
+
+fn stick_recipe() -> Recipe {
+    Recipe::Shaped(ShapedRecipe::new(
+        &["#", "#"],
+        &[('#', ItemKind::OakPlanks.into())],
+        ItemStack::new(ItemKind::Stick, 4, None),
+    ))
+}
+
+fn prepare() -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario
+        .app
+        .world_mut()
+        .resource_mut::<RecipeRegistry>()
+        .insert(ident!("stick"), stick_recipe());
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn player_inventory(scenario: &mut ScenarioSingleClient) -> &mut Inventory {
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .into_inner()
+}
+
+#[test]
+fn player_grid_result_is_crafted_on_click() {
+    let mut scenario = prepare();
+
+    let inv = player_inventory(&mut scenario);
+    inv.set_slot(1, ItemStack::new(ItemKind::OakPlanks, 2, None));
+    inv.set_slot(3, ItemStack::new(ItemKind::OakPlanks, 1, None));
+
+    scenario.app.update();
+
+    assert_eq!(
+        player_inventory(&mut scenario).slot(0),
+        &ItemStack::new(ItemKind::Stick, 4, None)
+    );
+
+    scenario.helper.clear_received();
+
+    let state_id = scenario
+        .app
+        .world()
+        .get::<ClientInventoryState>(scenario.client)
+        .unwrap()
+        .state_id();
+
+    // The client predicts the craft, but the server decides what happens.
+    scenario.helper.send(&ClickSlotC2s {
+        window_id: 0,
+        state_id: VarInt(state_id.0),
+        slot_idx: 0,
+        button: 0,
+        mode: ClickMode::Click,
+        slot_changes: vec![SlotChange {
+            idx: 0,
+            stack: ItemStack::EMPTY,
+        }]
+        .into(),
+        carried_item: ItemStack::new(ItemKind::Stick, 4, None),
+    });
+
+    scenario.app.update();
+
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<InventoryS2c>(1);
+
+    let cursor = scenario
+        .app
+        .world()
+        .get::<CursorItem>(scenario.client)
+        .unwrap();
+    assert_eq!(cursor.0, ItemStack::new(ItemKind::Stick, 4, None));
+
+    let inv = player_inventory(&mut scenario);
+    assert_eq!(inv.slot(1), &ItemStack::new(ItemKind::OakPlanks, 1, None));
+    assert!(inv.slot(3).is_empty());
+    // One plank isn't enough for another craft.
+    assert!(inv.slot(0).is_empty());
+
+    let events = scenario.read_events::<CraftEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].recipe, ident!("stick"));
+}
+
+#[test]
+fn cancelled_result_drop_undoes_craft() {
+    let mut scenario = prepare();
+
+    scenario.app.add_systems(
+        EventLoopUpdate,
+        |mut events: EventReader<ItemDroppedFromSlotEvent>,
+         mut cancel: EventWriter<CancelTransactionEvent>| {
+            for event in events.read() {
+                cancel.send(CancelTransactionEvent(event.transaction));
+            }
+        },
+    );
+
+    let inv = player_inventory(&mut scenario);
+    inv.set_slot(1, ItemStack::new(ItemKind::OakPlanks, 1, None));
+    inv.set_slot(3, ItemStack::new(ItemKind::OakPlanks, 1, None));
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    let state_id = scenario
+        .app
+        .world()
+        .get::<ClientInventoryState>(scenario.client)
+        .unwrap()
+        .state_id();
+
+    scenario.helper.send(&ClickSlotC2s {
+        window_id: 0,
+        state_id: VarInt(state_id.0),
+        slot_idx: 0,
+        button: 0,
+        mode: ClickMode::DropKey,
+        slot_changes: vec![].into(),
+        carried_item: ItemStack::EMPTY,
+    });
+
+    scenario.app.update();
+
+    assert_eq!(scenario.read_events::<ItemDroppedFromSlotEvent>().len(), 1);
+    assert!(scenario.read_events::<DropItemStackEvent>().is_empty());
+
+    let inv = player_inventory(&mut scenario);
+    assert_eq!(inv.slot(1), &ItemStack::new(ItemKind::OakPlanks, 1, None));
+    assert_eq!(inv.slot(3), &ItemStack::new(ItemKind::OakPlanks, 1, None));
+    assert_eq!(inv.slot(0), &ItemStack::new(ItemKind::Stick, 4, None));
+}
+
+#[test]
+fn recipe_book_is_synced() {
+    let mut scenario = ScenarioSingleClient::new();
+
+    let mut registry = scenario.app.world_mut().resource_mut::<RecipeRegistry>();
+    registry.insert(ident!("stick"), stick_recipe());
+    registry.insert(
+        ident!("oak_planks"),
+        Recipe::Shapeless(ShapelessRecipe::new(
+            vec![ItemKind::OakLog.into()],
+            ItemStack::new(ItemKind::OakPlanks, 4, None),
+        )),
+    );
+
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<SynchronizeRecipesS2c>(1);
+    frames.assert_order::<(SynchronizeRecipesS2c, UnlockRecipesS2c)>();
+
+    let sync = frames.first::<SynchronizeRecipesS2c>();
+    assert_eq!(sync.recipes.len(), 2);
+    assert_eq!(sync.recipes[0].recipe_id, ident!("stick"));
+
+    let unlock = frames.first::<UnlockRecipesS2c>();
+    assert!(matches!(unlock.action, UpdateRecipeBookAction::Init { .. }));
+    assert!(unlock.recipe_ids.is_empty());
+
+    let mut book = scenario
+        .app
+        .world_mut()
+        .get_mut::<RecipeBook>(scenario.client)
+        .unwrap();
+    book.unlock(ident!("stick"));
+    book.unlock(ident!("oak_planks"));
+
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<SynchronizeRecipesS2c>(0);
+    frames.assert_count::<UnlockRecipesS2c>(1);
+
+    let unlock = frames.first::<UnlockRecipesS2c>();
+    assert_eq!(unlock.action, UpdateRecipeBookAction::Add);
+    assert_eq!(
+        unlock.recipe_ids,
+        vec![ident!("oak_planks"), ident!("stick")]
+    );
+
+    // Only the changes are sent.
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<RecipeBook>(scenario.client)
+        .unwrap()
+        .lock("minecraft:stick");
+
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<UnlockRecipesS2c>(1);
+
+    let unlock = frames.first::<UnlockRecipesS2c>();
+    assert_eq!(unlock.action, UpdateRecipeBookAction::Remove);
+    assert_eq!(unlock.recipe_ids, vec![ident!("stick")]);
+}
+
+#[test]
+fn craft_request_fills_grid() {
+    let mut scenario = prepare();
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<RecipeBook>(scenario.client)
+        .unwrap()
+        .unlock(ident!("stick"));
+
+    let request = CraftRequestC2s {
+        window_id: 0,
+        recipe: ident!("stick").into(),
+        make_all: false,
+    };
+
+    // Without planks only the recipe is shown.
+    scenario.helper.send(&request);
+    scenario.app.update();
+
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<CraftFailedResponseS2c>(1);
+
+    player_inventory(&mut scenario).set_slot(20, ItemStack::new(ItemKind::OakPlanks, 5, None));
+
+    scenario.helper.send(&request);
+    scenario.app.update();
+
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<CraftFailedResponseS2c>(0);
+
+    let inv = player_inventory(&mut scenario);
+    assert_eq!(inv.slot(1), &ItemStack::new(ItemKind::OakPlanks, 1, None));
+    assert_eq!(inv.slot(3), &ItemStack::new(ItemKind::OakPlanks, 1, None));
+    assert_eq!(inv.slot(20), &ItemStack::new(ItemKind::OakPlanks, 3, None));
+    assert_eq!(inv.slot(0), &ItemStack::new(ItemKind::Stick, 4, None));
+}
+
+#[test]
+fn closing_crafting_table_returns_grid_items() {
+    let mut scenario = prepare();
+
+    let mut table = Inventory::new(InventoryKind::Crafting);
+    table.set_slot(5, ItemStack::new(ItemKind::OakPlanks, 3, None));
+    let table = scenario.app.world_mut().spawn(table).id();
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(OpenInventory::new(table));
+
+    scenario.app.update();
+
+    let window_id = scenario
+        .app
+        .world()
+        .get::<ClientInventoryState>(scenario.client)
+        .unwrap()
+        .window_id();
+
+    scenario.helper.send(&CloseHandledScreenC2s {
+        window_id: window_id as i8,
+    });
+
+    scenario.app.update();
+
+    let table = scenario.app.world().get::<Inventory>(table).unwrap();
+    assert!(table.slot(5).is_empty());
+
+    let inv = player_inventory(&mut scenario);
+    assert_eq!(inv.slot(44), &ItemStack::new(ItemKind::OakPlanks, 3, None));
+}
+
+#[test]
+fn load_json_recipes() {
+    let scenario = prepare();
+    let tags = scenario.app.world().resource::<TagsRegistry>();
+    let mut registry = RecipeRegistry::default();
+
+    registry
+        .insert_json(
+            ident!("stick"),
+            r##"{
+                "type": "minecraft:crafting_shaped",
+                "category": "misc",
+                "group": "sticks",
+                "key": { "#": { "tag": "minecraft:planks" } },
+                "pattern": ["#", "#"],
+                "result": { "count": 4, "item": "minecraft:stick" }
+            }"##,
+            tags,
+        )
+        .unwrap();
+
+    registry
+        .insert_json(
+            ident!("iron_ingot_from_smelting_raw_iron"),
+            r#"{
+                "type": "minecraft:smelting",
+                "category": "misc",
+                "cookingtime": 200,
+                "experience": 0.7,
+                "ingredient": { "item": "minecraft:raw_iron" },
+                "result": "minecraft:iron_ingot"
+            }"#,
+            tags,
+        )
+        .unwrap();
+
+    assert!(registry
+        .insert_json(
+            ident!("armor_dye"),
+            r#"{ "type": "minecraft:crafting_special_armordye", "category": "misc" }"#,
+            tags,
+        )
+        .is_err());
+
+    assert_eq!(registry.len(), 2);
+
+    let Some(Recipe::Shaped(stick)) = registry.get("minecraft:stick") else {
+        panic!("stick recipe must be shaped");
+    };
+    assert_eq!(stick.group, "sticks");
+    assert!(stick.ingredients[0].0.contains(&ItemKind::BirchPlanks));
+
+    // The pattern can be placed anywhere in the grid.
+    let grid = [
+        ItemStack::EMPTY,
+        ItemStack::new(ItemKind::BirchPlanks, 1, None),
+        ItemStack::EMPTY,
+        ItemStack::new(ItemKind::BirchPlanks, 1, None),
+    ];
+    assert!(registry.find_crafting_recipe(&grid, 2).is_some());
+
+    let Some(Recipe::Cooking(iron)) = registry.get("minecraft:iron_ingot_from_smelting_raw_iron")
+    else {
+        panic!("iron recipe must be a cooking recipe");
+    };
+    assert_eq!(iron.kind, CookingKind::Smelting);
+    assert_eq!(iron.result, ItemStack::new(ItemKind::IronIngot, 1, None));
+}
@@ -3,12 +3,14 @@ use valence_scoreboard::*;
 use crate::client::VisibleEntityLayers;
 use crate::entity::EntityLayerId;
 use crate::layer::EntityLayer;
+use crate::protocol::packets::play::team_s2c::Mode;
 use crate::protocol::packets::play::{
-    ScoreboardDisplayS2c, ScoreboardObjectiveUpdateS2c, ScoreboardPlayerUpdateS2c,
+    ScoreboardDisplayS2c, ScoreboardObjectiveUpdateS2c, ScoreboardPlayerUpdateS2c, TeamS2c,
 };
-use crate::testing::ScenarioSingleClient;
+use crate::protocol::Packet;
+use crate::testing::{MockClientHelper, ScenarioSingleClient};
 use crate::text::IntoText;
-use crate::Server;
+use crate::{Despawned, Server};
 
 #[test]
 fn show_scoreboard_when_added_to_layer() {
@@ -191,3 +193,323 @@ fn should_only_update_score_diff() {
         recvd.assert_count::<ScoreboardPlayerUpdateS2c>(1);
     }
 }
+
+/// Returns the modes of the team packets received by the client.
+fn team_modes(helper: &mut MockClientHelper) -> Vec<String> {
+    helper
+        .collect_received()
+        .0
+        .iter()
+        .filter_map(|frame| frame.decode::<TeamS2c>().ok())
+        .map(|pkt| match pkt.mode {
+            Mode::CreateTeam { entities, .. } => format!("create {}", entities.join(",")),
+            Mode::RemoveTeam => "remove".to_owned(),
+            Mode::UpdateTeamInfo { .. } => "update".to_owned(),
+            Mode::AddEntities { entities } => format!("add {}", entities.join(",")),
+            Mode::RemoveEntities { entities } => format!("remove {}", entities.join(",")),
+        })
+        .collect()
+}
+
+#[test]
+fn team_is_created_updated_and_removed() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        layer,
+        ..
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    let team = app
+        .world_mut()
+        .spawn(TeamBundle {
+            name: Team::new("red"),
+            color: TeamColor::Red,
+            members: TeamMembers::from_iter(["test"]),
+            layer: EntityLayerId(layer),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+
+    assert_eq!(team_modes(&mut helper), ["create test"]);
+
+    app.world_mut().get_mut::<TeamPrefix>(team).unwrap().0 = "[Red] ".into_text();
+
+    app.update();
+
+    assert_eq!(team_modes(&mut helper), ["update"]);
+
+    app.world_mut().entity_mut(team).insert(Despawned);
+    app.update();
+
+    assert_eq!(team_modes(&mut helper), ["remove"]);
+    assert!(app.world().get_entity(team).is_none());
+}
+
+#[test]
+fn team_members_are_diffed() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        layer,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let team = app
+        .world_mut()
+        .spawn(TeamBundle {
+            name: Team::new("blue"),
+            members: TeamMembers::from_iter(["alice", "bob"]),
+            layer: EntityLayerId(layer),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+    helper.clear_received();
+
+    let uuid = crate::uuid::Uuid::from_u128(1);
+
+    let mut members = app.world_mut().get_mut::<TeamMembers>(team).unwrap();
+    members.remove("alice");
+    members.insert("carol");
+    members.insert_entity(uuid);
+
+    app.update();
+
+    assert_eq!(
+        team_modes(&mut helper),
+        [format!("add {uuid},carol"), "remove alice".to_owned()]
+    );
+
+    // Nothing is sent when the members don't change.
+    app.world_mut()
+        .get_mut::<TeamMembers>(team)
+        .unwrap()
+        .insert("bob");
+    app.update();
+
+    assert!(team_modes(&mut helper).is_empty());
+}
+
+#[test]
+fn team_follows_visible_layers() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let server = app.world().get_resource::<Server>().unwrap().clone();
+    let team_layer = app.world_mut().spawn(EntityLayer::new(&server)).id();
+
+    app.world_mut().spawn(TeamBundle {
+        name: Team::new("green"),
+        members: TeamMembers::from_iter(["alice"]),
+        layer: EntityLayerId(team_layer),
+        ..Default::default()
+    });
+
+    app.update();
+
+    // The client can't see the team's layer.
+    assert!(team_modes(&mut helper).is_empty());
+
+    app.world_mut()
+        .get_mut::<VisibleEntityLayers>(client)
+        .unwrap()
+        .0
+        .insert(team_layer);
+    app.update();
+
+    assert_eq!(team_modes(&mut helper), ["create alice"]);
+
+    app.world_mut()
+        .get_mut::<VisibleEntityLayers>(client)
+        .unwrap()
+        .0
+        .remove(&team_layer);
+    app.update();
+
+    assert_eq!(team_modes(&mut helper), ["remove"]);
+}
+
+#[test]
+fn client_sidebar_only_sends_changed_lines() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    app.world_mut()
+        .entity_mut(client)
+        .insert(Sidebar::new("Title").with_lines(["a", "b", "c"]));
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<ScoreboardObjectiveUpdateS2c>(1);
+    frames.assert_count::<ScoreboardDisplayS2c>(1);
+    frames.assert_count::<TeamS2c>(Sidebar::MAX_LINES);
+    frames.assert_count::<ScoreboardPlayerUpdateS2c>(3);
+
+    app.world_mut()
+        .get_mut::<Sidebar>(client)
+        .unwrap()
+        .set_line(1, "B");
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<ScoreboardObjectiveUpdateS2c>(0);
+    frames.assert_count::<ScoreboardPlayerUpdateS2c>(0);
+
+    let team = frames.first::<TeamS2c>();
+    assert_eq!(team.team_name, format!("sb.{}.1", client.index()));
+    assert!(matches!(
+        team.mode,
+        Mode::UpdateTeamInfo { team_prefix, .. } if team_prefix.as_ref() == &"B".into_text()
+    ));
+
+    // Removing a line only removes its score.
+    app.world_mut()
+        .get_mut::<Sidebar>(client)
+        .unwrap()
+        .truncate(2);
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<TeamS2c>(0);
+    frames.assert_count::<ScoreboardPlayerUpdateS2c>(1);
+
+    app.world_mut().entity_mut(client).remove::<Sidebar>();
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<ScoreboardObjectiveUpdateS2c>(1);
+    frames.assert_count::<TeamS2c>(Sidebar::MAX_LINES);
+}
+
+#[test]
+fn layer_sidebar_follows_visible_layers() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let server = app.world().get_resource::<Server>().unwrap().clone();
+    let sidebar_layer = app.world_mut().spawn(EntityLayer::new(&server)).id();
+
+    let sidebar = app
+        .world_mut()
+        .spawn((
+            Sidebar::new("Title").with_lines(["a"]),
+            EntityLayerId(sidebar_layer),
+        ))
+        .id();
+
+    app.update();
+
+    // The client can't see the sidebar's layer.
+    helper
+        .collect_received()
+        .assert_count::<ScoreboardObjectiveUpdateS2c>(0);
+
+    app.world_mut()
+        .get_mut::<VisibleEntityLayers>(client)
+        .unwrap()
+        .0
+        .insert(sidebar_layer);
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<ScoreboardObjectiveUpdateS2c>(1);
+    frames.assert_count::<TeamS2c>(Sidebar::MAX_LINES);
+    frames.assert_count::<ScoreboardPlayerUpdateS2c>(1);
+
+    app.world_mut()
+        .get_mut::<Sidebar>(sidebar)
+        .unwrap()
+        .set_title("New Title");
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<ScoreboardObjectiveUpdateS2c>(1);
+    frames.assert_count::<TeamS2c>(0);
+
+    app.world_mut().entity_mut(sidebar).insert(Despawned);
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<ScoreboardObjectiveUpdateS2c>(1);
+    frames.assert_count::<TeamS2c>(Sidebar::MAX_LINES);
+    assert!(app.world().get_entity(sidebar).is_none());
+}
+
+#[test]
+fn client_and_layer_sidebars_have_separate_lines() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    app.world_mut()
+        .entity_mut(client)
+        .insert(Sidebar::new("Client").with_lines(["a", "b"]));
+    let sidebar = app
+        .world_mut()
+        .spawn((
+            Sidebar::new("Layer").with_lines(["c", "d"]),
+            EntityLayerId(layer),
+        ))
+        .id();
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<ScoreboardObjectiveUpdateS2c>(2);
+    frames.assert_count::<TeamS2c>(Sidebar::MAX_LINES * 2);
+
+    // No team takes a line from the other sidebar.
+    let mut entries: Vec<_> = frames
+        .0
+        .iter()
+        .filter(|frame| frame.id == TeamS2c::ID)
+        .flat_map(|frame| match frame.decode::<TeamS2c>().unwrap().mode {
+            Mode::CreateTeam { entities, .. } => entities,
+            _ => vec![],
+        })
+        .map(str::to_owned)
+        .collect();
+    entries.sort();
+    entries.dedup();
+    assert_eq!(entries.len(), Sidebar::MAX_LINES * 2);
+
+    // Removing the layer sidebar leaves the client sidebar's teams alone.
+    app.world_mut().entity_mut(sidebar).insert(Despawned);
+    app.update();
+
+    let frames = helper.collect_received();
+    frames.assert_count::<TeamS2c>(Sidebar::MAX_LINES);
+    for frame in frames.0.iter().filter(|frame| frame.id == TeamS2c::ID) {
+        let team = frame.decode::<TeamS2c>().unwrap();
+        assert!(team
+            .team_name
+            .starts_with(&format!("sb.{}.", sidebar.index())));
+    }
+}
@@ -0,0 +1,151 @@
+use bevy_ecs::prelude::*;
+
+use crate::inventory::screen::anvil::{Anvil, RenameItemEvent};
+use crate::inventory::screen::furnace::Furnace;
+use crate::inventory::screen::stonecutter::{SelectStonecutterRecipeEvent, Stonecutter};
+use crate::inventory::{ClientInventoryState, Inventory, InventoryKind, OpenInventory};
+use crate::protocol::packets::play::{
+    ButtonClickC2s, OpenScreenS2c, RenameItemC2s, ScreenHandlerPropertyUpdateS2c,
+};
+use crate::protocol::Packet;
+use crate::testing::ScenarioSingleClient;
+
+fn open_screen<T: Component>(
+    scenario: &mut ScenarioSingleClient,
+    kind: InventoryKind,
+    screen: T,
+) -> Entity {
+    let inventory = scenario
+        .app
+        .world_mut()
+        .spawn((Inventory::new(kind), screen))
+        .id();
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(OpenInventory::new(inventory));
+
+    inventory
+}
+
+#[test]
+fn furnace_properties_are_sent() {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    let furnace = open_screen(
+        &mut scenario,
+        InventoryKind::Furnace,
+        Furnace {
+            fuel_left: 800,
+            fuel_time: 1600,
+            cook_progress: 0,
+            cook_time: 200,
+        },
+    );
+
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<ScreenHandlerPropertyUpdateS2c>(4);
+    frames.assert_order::<(OpenScreenS2c, ScreenHandlerPropertyUpdateS2c)>();
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Furnace>(furnace)
+        .unwrap()
+        .cook_progress = 100;
+
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<ScreenHandlerPropertyUpdateS2c>(4);
+
+    let cook_progress = frames
+        .0
+        .iter()
+        .filter(|frame| frame.id == ScreenHandlerPropertyUpdateS2c::ID)
+        .map(|frame| frame.decode::<ScreenHandlerPropertyUpdateS2c>().unwrap())
+        .find(|pkt| pkt.property == 2)
+        .unwrap();
+    assert_eq!(cook_progress.value, 100);
+}
+
+#[test]
+fn anvil_rename_sends_event() {
+    let mut scenario = ScenarioSingleClient::new();
+    let anvil = open_screen(&mut scenario, InventoryKind::Anvil, Anvil::default());
+
+    scenario.app.update();
+
+    scenario.helper.send(&RenameItemC2s {
+        item_name: "Excalibur",
+    });
+    // Too long for vanilla clients.
+    scenario.helper.send(&RenameItemC2s {
+        item_name: &"a".repeat(51),
+    });
+
+    scenario.app.update();
+
+    let events = scenario.read_events::<RenameItemEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].name, "Excalibur");
+
+    let anvil = scenario.app.world().get::<Anvil>(anvil).unwrap();
+    assert_eq!(anvil.item_name(), "Excalibur");
+}
+
+#[test]
+fn stonecutter_selection_is_stored() {
+    let mut scenario = ScenarioSingleClient::new();
+    let stonecutter = open_screen(
+        &mut scenario,
+        InventoryKind::Stonecutter,
+        Stonecutter::default(),
+    );
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    let window_id = scenario
+        .app
+        .world()
+        .get::<ClientInventoryState>(scenario.client)
+        .unwrap()
+        .window_id();
+
+    scenario.helper.send(&ButtonClickC2s {
+        window_id: window_id as i8,
+        button_id: 3,
+    });
+    // Clicks in other windows are ignored.
+    scenario.helper.send(&ButtonClickC2s {
+        window_id: window_id as i8 + 1,
+        button_id: 5,
+    });
+
+    scenario.app.update();
+
+    let events = scenario.read_events::<SelectStonecutterRecipeEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].recipe, 3);
+
+    let stonecutter = scenario
+        .app
+        .world()
+        .get::<Stonecutter>(stonecutter)
+        .unwrap();
+    assert_eq!(stonecutter.selected_recipe, Some(3));
+
+    // The client already knows what it selected.
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<ScreenHandlerPropertyUpdateS2c>(0);
+}
@@ -0,0 +1,146 @@
+use crate::layer::chunk::UnloadedChunk;
+use crate::protocol::packets::play::{
+    BlockEntityUpdateS2c, BlockUpdateS2c, SignEditorOpenS2c, UpdateSignC2s,
+};
+use crate::sign::{DyeColor, OpenSignEditorEvent, SignData, SignEditEvent, SignText};
+use crate::testing::ScenarioSingleClient;
+use crate::text::IntoText;
+use crate::{BlockPos, BlockState, ChunkLayer};
+
+const POS: BlockPos = BlockPos::new(1, 63, 1);
+
+fn setup_sign(data: Option<SignData>) -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    let mut layer = scenario
+        .app
+        .world_mut()
+        .get_mut::<ChunkLayer>(scenario.layer)
+        .unwrap();
+
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+
+    if let Some(data) = data {
+        layer.set_block(POS, BlockState::OAK_SIGN);
+        layer.set_sign(POS, &data);
+    }
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn edit_sign(scenario: &mut ScenarioSingleClient, is_front_text: bool, lines: [&str; 4]) {
+    scenario.helper.send(&UpdateSignC2s {
+        position: POS,
+        is_front_text,
+        lines: lines.map(Into::into),
+    });
+    scenario.app.update();
+}
+
+#[test]
+fn sign_data_round_trip() {
+    let data = SignData {
+        front: SignText {
+            lines: ["a".into(), "b".bold().into_text(), "".into(), "d".into()],
+            color: DyeColor::LightBlue,
+            glowing: true,
+        },
+        back: SignText::default(),
+        waxed: true,
+    };
+
+    assert_eq!(SignData::from_nbt(&data.to_nbt()), data);
+}
+
+#[test]
+fn editing_sign_updates_text() {
+    let mut scenario = setup_sign(Some(SignData::default()));
+
+    scenario.app.world_mut().send_event(OpenSignEditorEvent {
+        client: scenario.client,
+        position: POS,
+        is_front_text: true,
+        fake_sign: None,
+    });
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<SignEditorOpenS2c>(1);
+    frames.assert_count::<BlockUpdateS2c>(0);
+
+    edit_sign(&mut scenario, true, ["§cHello", "world", "", ""]);
+
+    let events = scenario.read_events::<SignEditEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].lines, ["Hello", "world", "", ""]);
+    assert!(!events[0].fake);
+
+    let data = scenario
+        .app
+        .world()
+        .get::<ChunkLayer>(scenario.layer)
+        .unwrap()
+        .sign(POS)
+        .unwrap();
+    assert_eq!(data.front.lines[0], "Hello".into());
+    assert_eq!(data.back, SignText::default());
+}
+
+#[test]
+fn edit_without_open_editor_is_rejected() {
+    let mut scenario = setup_sign(Some(SignData::default()));
+
+    edit_sign(&mut scenario, true, ["Griefed", "", "", ""]);
+
+    assert!(scenario.read_events::<SignEditEvent>().is_empty());
+
+    let data = scenario
+        .app
+        .world()
+        .get::<ChunkLayer>(scenario.layer)
+        .unwrap()
+        .sign(POS)
+        .unwrap();
+    assert_eq!(data, SignData::default());
+
+    // The client's copy of the sign is reverted.
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<BlockUpdateS2c>(1);
+    frames.assert_count::<BlockEntityUpdateS2c>(1);
+}
+
+#[test]
+fn fake_sign_is_restored() {
+    let mut scenario = setup_sign(None);
+
+    scenario.app.world_mut().send_event(OpenSignEditorEvent {
+        client: scenario.client,
+        position: POS,
+        is_front_text: true,
+        fake_sign: Some(SignData::default()),
+    });
+    scenario.app.update();
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_order::<(BlockUpdateS2c, BlockEntityUpdateS2c, SignEditorOpenS2c)>();
+
+    edit_sign(&mut scenario, true, ["Steve", "", "", ""]);
+
+    let events = scenario.read_events::<SignEditEvent>();
+    assert_eq!(events.len(), 1);
+    assert!(events[0].fake);
+
+    // The real block is sent back and nothing is placed in the world.
+    let frames = scenario.helper.collect_received();
+    assert_eq!(frames.first::<BlockUpdateS2c>().block_id, BlockState::AIR);
+
+    let layer = scenario
+        .app
+        .world()
+        .get::<ChunkLayer>(scenario.layer)
+        .unwrap();
+    assert!(layer.sign(POS).is_none());
+}
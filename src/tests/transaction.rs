@@ -0,0 +1,344 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+
+use crate::event_loop::EventLoopUpdate;
+use crate::inventory::transaction::{
+    CancelTransactionEvent, ItemDroppedFromSlotEvent, ItemLocation, ItemMovedEvent,
+    ItemPickedUpToCursorEvent, ShiftClickTransferEvent,
+};
+use crate::inventory::{
+    ClickMode, ClientInventoryState, CursorItem, DropItemStackEvent, Inventory, InventoryKind,
+    OpenInventory, SlotChange,
+};
+use crate::protocol::packets::play::{
+    ClickSlotC2s, CreativeInventoryActionC2s, ScreenHandlerSlotUpdateS2c,
+};
+use crate::protocol::VarInt;
+use crate::testing::ScenarioSingleClient;
+use crate::{GameMode, ItemKind, ItemStack};
+
+fn diamonds() -> ItemStack {
+    ItemStack::new(ItemKind::Diamond, 10, None)
+}
+
+/// Puts diamonds in slot 20 of the player's inventory.
+fn setup() -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario.app.update();
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<Inventory>(scenario.client)
+        .unwrap()
+        .set_slot(20, diamonds());
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn click(
+    scenario: &mut ScenarioSingleClient,
+    mode: ClickMode,
+    button: i8,
+    slot_idx: i16,
+    slot_changes: Vec<SlotChange>,
+    carried_item: ItemStack,
+) {
+    let inv_state = scenario
+        .app
+        .world()
+        .get::<ClientInventoryState>(scenario.client)
+        .unwrap();
+
+    let window_id = inv_state.window_id();
+    let state_id = inv_state.state_id().0;
+
+    scenario.helper.send(&ClickSlotC2s {
+        window_id,
+        state_id: VarInt(state_id),
+        slot_idx,
+        button,
+        mode,
+        slot_changes: slot_changes.into(),
+        carried_item,
+    });
+    scenario.app.update();
+}
+
+#[test]
+fn picking_up_sends_event() {
+    let mut scenario = setup();
+
+    click(
+        &mut scenario,
+        ClickMode::Click,
+        0,
+        20,
+        vec![SlotChange {
+            idx: 20,
+            stack: ItemStack::EMPTY,
+        }],
+        diamonds(),
+    );
+
+    let events = scenario.read_events::<ItemPickedUpToCursorEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].from,
+        ItemLocation::Slot {
+            inventory: scenario.client,
+            slot: 20
+        }
+    );
+    assert_eq!(events[0].stack, diamonds());
+}
+
+#[test]
+fn shift_click_sends_event() {
+    let mut scenario = setup();
+
+    let chest = scenario
+        .app
+        .world_mut()
+        .spawn(Inventory::new(InventoryKind::Generic9x1))
+        .id();
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.client)
+        .insert(OpenInventory::new(chest));
+    scenario.app.update();
+
+    // Player slot 20 is slot 20 of the window too since the chest has 9 slots.
+    click(
+        &mut scenario,
+        ClickMode::ShiftClick,
+        0,
+        20,
+        vec![
+            SlotChange {
+                idx: 20,
+                stack: ItemStack::EMPTY,
+            },
+            SlotChange {
+                idx: 0,
+                stack: diamonds(),
+            },
+        ],
+        ItemStack::EMPTY,
+    );
+
+    let events = scenario.read_events::<ShiftClickTransferEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].to,
+        [ItemLocation::Slot {
+            inventory: chest,
+            slot: 0
+        }]
+    );
+    assert_eq!(events[0].stack, diamonds());
+
+    let chest = scenario.app.world().get::<Inventory>(chest).unwrap();
+    assert_eq!(chest.slot(0), &diamonds());
+}
+
+#[derive(Resource, Default)]
+struct DropsInUpdate(usize);
+
+#[test]
+fn drop_key_sends_event() {
+    let mut scenario = setup();
+
+    scenario.app.init_resource::<DropsInUpdate>().add_systems(
+        Update,
+        |mut events: EventReader<DropItemStackEvent>, mut drops: ResMut<DropsInUpdate>| {
+            drops.0 += events.read().count();
+        },
+    );
+
+    click(
+        &mut scenario,
+        ClickMode::DropKey,
+        0,
+        20,
+        vec![SlotChange {
+            idx: 20,
+            stack: diamonds().with_count(9),
+        }],
+        ItemStack::EMPTY,
+    );
+
+    let events = scenario.read_events::<ItemDroppedFromSlotEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].stack, diamonds().with_count(1));
+
+    let drops = scenario.read_events::<DropItemStackEvent>();
+    assert_eq!(drops.len(), 1);
+    assert_eq!(drops[0].from_slot, Some(20));
+
+    // The drop is sent in the same tick, before `Update`.
+    assert_eq!(scenario.app.world().resource::<DropsInUpdate>().0, 1);
+}
+
+#[test]
+fn cancelled_drop_drops_nothing() {
+    let mut scenario = setup();
+
+    scenario.app.add_systems(
+        EventLoopUpdate,
+        |mut events: EventReader<ItemDroppedFromSlotEvent>,
+         mut cancel: EventWriter<CancelTransactionEvent>| {
+            for event in events.read() {
+                cancel.send(CancelTransactionEvent(event.transaction));
+            }
+        },
+    );
+
+    click(
+        &mut scenario,
+        ClickMode::DropKey,
+        1,
+        20,
+        vec![SlotChange {
+            idx: 20,
+            stack: ItemStack::EMPTY,
+        }],
+        ItemStack::EMPTY,
+    );
+
+    let inventory = scenario
+        .app
+        .world()
+        .get::<Inventory>(scenario.client)
+        .unwrap();
+    assert_eq!(inventory.slot(20), &diamonds());
+
+    assert!(scenario.read_events::<DropItemStackEvent>().is_empty());
+}
+
+#[test]
+fn cancelled_transaction_is_reverted() {
+    let mut scenario = setup();
+
+    scenario.app.add_systems(
+        EventLoopUpdate,
+        |mut events: EventReader<ItemPickedUpToCursorEvent>,
+         mut cancel: EventWriter<CancelTransactionEvent>| {
+            for event in events.read() {
+                cancel.send(CancelTransactionEvent(event.transaction));
+            }
+        },
+    );
+
+    click(
+        &mut scenario,
+        ClickMode::Click,
+        0,
+        20,
+        vec![SlotChange {
+            idx: 20,
+            stack: ItemStack::EMPTY,
+        }],
+        diamonds(),
+    );
+
+    let inventory = scenario
+        .app
+        .world()
+        .get::<Inventory>(scenario.client)
+        .unwrap();
+    assert_eq!(inventory.slot(20), &diamonds());
+
+    let cursor = scenario
+        .app
+        .world()
+        .get::<CursorItem>(scenario.client)
+        .unwrap();
+    assert!(cursor.0.is_empty());
+
+    // The slot and the cursor are sent to the client again.
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<ScreenHandlerSlotUpdateS2c>(2);
+}
+
+#[test]
+fn creative_action_sends_event_and_can_be_cancelled() {
+    let mut scenario = setup();
+
+    *scenario
+        .app
+        .world_mut()
+        .get_mut::<GameMode>(scenario.client)
+        .unwrap() = GameMode::Creative;
+
+    scenario.app.add_systems(
+        EventLoopUpdate,
+        |mut events: EventReader<ItemMovedEvent>,
+         mut cancel: EventWriter<CancelTransactionEvent>| {
+            for event in events.read() {
+                if event.stack.item == ItemKind::Bedrock {
+                    cancel.send(CancelTransactionEvent(event.transaction));
+                }
+            }
+        },
+    );
+
+    // Clearing the slot destroys the diamonds.
+    scenario.helper.send(&CreativeInventoryActionC2s {
+        slot: 20,
+        clicked_item: ItemStack::EMPTY,
+    });
+    scenario.app.update();
+
+    let events = scenario.read_events::<ItemMovedEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].to, ItemLocation::Creative);
+    assert_eq!(events[0].stack, diamonds());
+
+    scenario.helper.clear_received();
+
+    scenario.helper.send(&CreativeInventoryActionC2s {
+        slot: 21,
+        clicked_item: ItemStack::new(ItemKind::Bedrock, 1, None),
+    });
+    scenario.app.update();
+
+    let inventory = scenario
+        .app
+        .world()
+        .get::<Inventory>(scenario.client)
+        .unwrap();
+    assert!(inventory.slot(21).is_empty());
+
+    // The slot is set by the client and then reverted.
+    scenario
+        .helper
+        .collect_received()
+        .assert_count::<ScreenHandlerSlotUpdateS2c>(2);
+
+    // Cancelled creative drops are not dropped either.
+    scenario.app.add_systems(
+        EventLoopUpdate,
+        |mut events: EventReader<ItemDroppedFromSlotEvent>,
+         mut cancel: EventWriter<CancelTransactionEvent>| {
+            for event in events.read() {
+                cancel.send(CancelTransactionEvent(event.transaction));
+            }
+        },
+    );
+
+    scenario.helper.send(&CreativeInventoryActionC2s {
+        slot: -1,
+        clicked_item: ItemStack::new(ItemKind::Bedrock, 1, None),
+    });
+    scenario.app.update();
+
+    assert!(scenario.read_events::<DropItemStackEvent>().is_empty());
+}
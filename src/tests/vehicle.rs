@@ -0,0 +1,240 @@
+use bevy_ecs::prelude::*;
+use valence_server::entity::{EntityId, Position};
+use valence_server::interact_entity::EntityInteraction;
+use valence_server::math::DVec3;
+use valence_server::protocol::packets::play::player_input_c2s::PlayerInputFlags;
+use valence_server::protocol::packets::play::{
+    EntityPassengersSetS2c, PlayerInputC2s, PlayerInteractEntityC2s, PlayerPositionLookS2c,
+    VehicleMoveC2s, VehicleMoveS2c,
+};
+use valence_server::protocol::{Packet, VarInt};
+use valence_server::vehicle::{
+    DismountEvent, Passengers, Rideable, Vehicle, VehicleControl, VehicleMoveEvent,
+};
+use valence_server::Despawned;
+
+use crate::testing::ScenarioSingleClient;
+use crate::Hand;
+
+fn mount(scenario: &mut ScenarioSingleClient, pig: Entity) {
+    let entity_id = scenario.app.world().get::<EntityId>(pig).unwrap().get();
+
+    scenario.helper.send(&PlayerInteractEntityC2s {
+        entity_id: VarInt(entity_id),
+        interact: EntityInteraction::Interact(Hand::Main),
+        sneaking: false,
+    });
+
+    scenario.app.update();
+}
+
+#[test]
+fn interacting_mounts_rideable_entity() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([1.0, 0.0, 0.0], Rideable::default());
+
+    mount(&mut scenario, pig);
+
+    let ScenarioSingleClient {
+        app,
+        client,
+        mut helper,
+        ..
+    } = scenario;
+
+    assert_eq!(app.world().get::<Vehicle>(client), Some(&Vehicle(pig)));
+    assert_eq!(app.world().get::<Passengers>(pig).unwrap().get(), &[client]);
+
+    // The client is following the pig.
+    assert_eq!(
+        app.world().get::<Position>(client).unwrap().0,
+        DVec3::new(1.0, 0.0, 0.0)
+    );
+
+    // The last packet uses the client's own entity ID.
+    let frames = helper.collect_received();
+    let pig_id = app.world().get::<EntityId>(pig).unwrap().get();
+    let pkt = frames
+        .0
+        .iter()
+        .rev()
+        .find(|f| f.id == EntityPassengersSetS2c::ID)
+        .unwrap()
+        .decode::<EntityPassengersSetS2c>()
+        .unwrap();
+
+    assert_eq!(pkt.entity_id, VarInt(pig_id));
+    assert_eq!(&*pkt.passengers, &[VarInt(0)]);
+
+    frames.assert_count::<PlayerPositionLookS2c>(0);
+}
+
+#[test]
+fn full_vehicle_cannot_be_mounted() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([1.0, 0.0, 0.0], Rideable::default());
+
+    let other = scenario.spawn_pig([1.0, 0.0, 0.0], Rideable::default());
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(other)
+        .insert(Vehicle(pig));
+    scenario.app.update();
+
+    mount(&mut scenario, pig);
+
+    assert!(scenario
+        .app
+        .world()
+        .get::<Vehicle>(scenario.client)
+        .is_none());
+}
+
+#[test]
+fn sneaking_dismounts() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([1.0, 0.0, 0.0], Rideable::default());
+
+    mount(&mut scenario, pig);
+
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = scenario;
+
+    helper.clear_received();
+
+    helper.send(&PlayerInputC2s {
+        sideways: 0.0,
+        forward: 0.0,
+        flags: PlayerInputFlags::new().with_unmount(true),
+    });
+    app.update();
+
+    assert!(app.world().get::<Vehicle>(client).is_none());
+    assert!(app.world().get::<Passengers>(pig).unwrap().is_empty());
+    assert_eq!(app.world().resource::<Events<DismountEvent>>().len(), 1);
+
+    // The client is moved on top of the pig.
+    let pos = app.world().get::<Position>(client).unwrap().0;
+    assert!(pos.y > 0.5, "client was not moved on top: {pos}");
+    helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(1);
+}
+
+#[test]
+fn client_moves_vehicle() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([1.0, 0.0, 0.0], Rideable::default());
+
+    mount(&mut scenario, pig);
+
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = scenario;
+
+    helper.clear_received();
+
+    helper.send(&VehicleMoveC2s {
+        position: DVec3::new(3.0, 0.0, 0.0),
+        yaw: 90.0,
+        pitch: 0.0,
+    });
+    app.update();
+
+    assert_eq!(app.world().get::<Position>(pig).unwrap().0.x, 3.0);
+    assert_eq!(app.world().get::<Position>(client).unwrap().0.x, 3.0);
+    assert_eq!(app.world().resource::<Events<VehicleMoveEvent>>().len(), 1);
+
+    let frames = helper.collect_received();
+    frames.assert_count::<VehicleMoveS2c>(0);
+    frames.assert_count::<PlayerPositionLookS2c>(0);
+}
+
+#[test]
+fn server_controlled_vehicle_rejects_client_movement() {
+    let mut scenario = ScenarioSingleClient::new();
+    let pig = scenario.spawn_pig([1.0, 0.0, 0.0], Rideable::default());
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(pig)
+        .insert(VehicleControl::Server);
+
+    mount(&mut scenario, pig);
+
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = scenario;
+
+    helper.clear_received();
+
+    helper.send(&VehicleMoveC2s {
+        position: DVec3::new(3.0, 0.0, 0.0),
+        yaw: 0.0,
+        pitch: 0.0,
+    });
+    app.update();
+
+    assert_eq!(app.world().get::<Position>(pig).unwrap().0.x, 1.0);
+    helper.collect_received().assert_count::<VehicleMoveS2c>(1);
+
+    // Moves made by the server are sent to the client.
+    app.world_mut().get_mut::<Position>(pig).unwrap().0.x = 2.0;
+    app.update();
+
+    helper.collect_received().assert_count::<VehicleMoveS2c>(1);
+}
+
+#[test]
+fn despawning_vehicle_dismounts_passengers() {
+    let mut scenario = ScenarioSingleClient::new();
+    let vehicle = scenario.spawn_pig([1.0, 0.0, 0.0], Rideable::default());
+    let passenger = scenario.spawn_pig([2.0, 0.0, 0.0], Rideable::default());
+
+    let app = &mut scenario.app;
+
+    app.world_mut()
+        .entity_mut(passenger)
+        .insert(Vehicle(vehicle));
+    app.update();
+
+    assert!(app
+        .world()
+        .get::<Passengers>(vehicle)
+        .unwrap()
+        .contains(passenger));
+
+    app.world_mut().entity_mut(vehicle).insert(Despawned);
+    app.update();
+
+    assert!(app.world().get::<Vehicle>(passenger).is_none());
+}
+
+#[test]
+fn cannot_ride_own_passenger() {
+    let mut scenario = ScenarioSingleClient::new();
+    let a = scenario.spawn_pig([1.0, 0.0, 0.0], Rideable::default());
+    let b = scenario.spawn_pig([2.0, 0.0, 0.0], Rideable::default());
+
+    let app = &mut scenario.app;
+
+    app.world_mut().entity_mut(b).insert(Vehicle(a));
+    app.update();
+
+    app.world_mut().entity_mut(a).insert(Vehicle(b));
+    app.update();
+
+    assert!(app.world().get::<Vehicle>(a).is_none());
+    assert_eq!(app.world().get::<Vehicle>(b), Some(&Vehicle(a)));
+}
@@ -0,0 +1,141 @@
+use crate::protocol::packets::play::WorldTimeUpdateS2c;
+use crate::testing::*;
+use crate::world_time::WorldTime;
+
+fn prepare() -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.layer)
+        .insert(WorldTime::new(1000));
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn last_time(helper: &mut MockClientHelper) -> Option<WorldTimeUpdateS2c> {
+    helper
+        .collect_received()
+        .0
+        .iter()
+        .rev()
+        .find_map(|frame| frame.decode::<WorldTimeUpdateS2c>().ok())
+}
+
+#[test]
+fn time_is_sent_on_join() {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(scenario.layer)
+        .insert(WorldTime::new(1000));
+
+    scenario.app.update();
+
+    let pkt = last_time(&mut scenario.helper).unwrap();
+    assert_eq!(pkt.time_of_day, 1001);
+    assert_eq!(pkt.world_age, 1);
+}
+
+#[test]
+fn time_advances_and_is_sent_periodically() {
+    let ScenarioSingleClient {
+        mut app,
+        layer,
+        mut helper,
+        ..
+    } = prepare();
+
+    for _ in 0..40 {
+        app.update();
+    }
+
+    helper
+        .collect_received()
+        .assert_count::<WorldTimeUpdateS2c>(2);
+
+    let time = app.world().get::<WorldTime>(layer).unwrap();
+    assert_eq!(time.time_of_day, 1041);
+    assert_eq!(time.world_age, 41);
+}
+
+#[test]
+fn changed_time_is_sent_immediately() {
+    let ScenarioSingleClient {
+        mut app,
+        layer,
+        mut helper,
+        ..
+    } = prepare();
+
+    app.world_mut()
+        .get_mut::<WorldTime>(layer)
+        .unwrap()
+        .time_of_day = 18000;
+    app.update();
+
+    assert_eq!(last_time(&mut helper).unwrap().time_of_day, 18001);
+}
+
+#[test]
+fn disabled_daylight_cycle_freezes_time() {
+    let ScenarioSingleClient {
+        mut app,
+        layer,
+        mut helper,
+        ..
+    } = prepare();
+
+    let mut time = app.world_mut().get_mut::<WorldTime>(layer).unwrap();
+    time.time_of_day = 6000;
+    time.daylight_cycle = false;
+
+    app.update();
+
+    // A negative time of day tells the client not to advance it.
+    assert_eq!(last_time(&mut helper).unwrap().time_of_day, -6000);
+
+    app.update();
+
+    assert_eq!(
+        app.world().get::<WorldTime>(layer).unwrap().time_of_day,
+        6000
+    );
+}
+
+#[test]
+fn client_override() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = prepare();
+
+    app.world_mut().entity_mut(client).insert(WorldTime {
+        world_age: 0,
+        time_of_day: 13000,
+        daylight_cycle: false,
+    });
+    app.update();
+
+    assert_eq!(last_time(&mut helper).unwrap().time_of_day, -13000);
+
+    // Periodic updates use the override too.
+    for _ in 0..20 {
+        app.update();
+    }
+
+    assert_eq!(last_time(&mut helper).unwrap().time_of_day, -13000);
+
+    app.world_mut().entity_mut(client).remove::<WorldTime>();
+    app.update();
+
+    assert!(last_time(&mut helper).unwrap().time_of_day > 1000);
+}